@@ -1,11 +1,43 @@
 use anchor_lang::prelude::*;
 
+use crate::pyth::MAX_CONFIDENCE_RATIO;
+
 pub const MAGIC_PROGRAM_ID: Pubkey = pubkey!("Magic11111111111111111111111111111111111111");
 
 pub const MAGIC_CONTEXT_ID: Pubkey = pubkey!("MagicContext1111111111111111111111111111111");
 
 pub const DELEGATION_PROGRAM_ID: Pubkey = pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
 
-pub const DRIFT_PROGRAM_ID: Pubkey = pubkey!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+pub use crate::drift_cpi::DRIFT_PROGRAM_ID;
+
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+pub const GHOST_CRANK_PROGRAM_ID: Pubkey = pubkey!("7VvD7j99AE7q9PC9atpJeMEUeEzZ5ZYH7WqSzGdmvsqv");
+
+/// Anchor discriminator of ghost-crank's `MarketRegistry` account
+/// (`sha256("account:MarketRegistry")[..8]`).
+pub const MARKET_REGISTRY_DISCRIMINATOR: [u8; 8] = [200, 118, 217, 126, 179, 169, 172, 104];
+
+/// Default budget for the Drift call handler of a Magic Action.
+pub const DRIFT_EXECUTE_COMPUTE_UNITS: u32 = 200_000;
+
+/// Solana's per-transaction compute limit; a larger handler budget could
+/// never be granted.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Budget for the redelegation call handler.
+pub const DELEGATE_COMPUTE_UNITS: u32 = 50_000;
+
+/// Confidence band, in bps of price, past which a whole feed is marked
+/// degraded and its orders stop executing; the same 2% a single read allows.
+pub const FEED_DEGRADED_CONFIDENCE_BPS: u64 = 10_000 / MAX_CONFIDENCE_RATIO;
+
+/// Largest gap, in basis points, tolerated between a keeper's claimed
+/// price and the oracle read that has to corroborate it.
+pub const MAX_PRICE_CLAIM_DEVIATION_BPS: u64 = 50;
+
+/// Drift base asset precision (1e9).
+pub const BASE_ASSET_PRECISION: u64 = 1_000_000_000;
 
-pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+/// Ceiling on the protocol's execution fee (0.5%).
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 50;
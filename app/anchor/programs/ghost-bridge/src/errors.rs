@@ -8,7 +8,7 @@ pub enum GhostBridgeError {
     #[msg("Executor authority not found")]
     ExecutorNotFound,
 
-    #[msg("Maximum orders per executor reached (16)")]
+    #[msg("Maximum orders per executor reached")]
     MaxOrdersReached,
 
     #[msg("Order hash already exists")]
@@ -70,4 +70,94 @@ pub enum GhostBridgeError {
 
     #[msg("Invalid Pyth price feed account")]
     InvalidPriceFeed,
+
+    #[msg("Pyth price feed is stale")]
+    PriceFeedStale,
+
+    #[msg("Executor order capacity is at its maximum")]
+    MaxCapacityReached,
+
+    #[msg("Executor authorization expiry must be in the future")]
+    InvalidExecutorExpiry,
+
+    #[msg("Order has not expired yet")]
+    OrderNotExpired,
+
+    #[msg("Drift user account does not belong to the order owner")]
+    DriftUserMismatch,
+
+    #[msg("Drift authority is not the order owner")]
+    DriftAuthorityMismatch,
+
+    #[msg("Market type must be 0 (spot) or 1 (perp)")]
+    InvalidMarketType,
+
+    #[msg("Spot orders require the quote spot market account")]
+    QuoteSpotMarketMissing,
+
+    #[msg("Ciphertext commitment does not match the stored ciphertext and revealed params")]
+    CiphertextCommitmentMismatch,
+
+    #[msg("Order feed does not match the feed registered for its market")]
+    FeedMarketMismatch,
+
+    #[msg("Market has no registered feed")]
+    MarketNotRegistered,
+
+    #[msg("Account is not ghost-crank's market registry")]
+    InvalidMarketRegistry,
+
+    #[msg("Only the bridge admin can change the config")]
+    NotConfigAdmin,
+
+    #[msg("Protocol fee exceeds MAX_PROTOCOL_FEE_BPS")]
+    ProtocolFeeTooHigh,
+
+    #[msg("A non-zero protocol fee needs a fee vault")]
+    FeeVaultMissing,
+
+    #[msg("Executors other than the owner must pass the order's price feed")]
+    PriceAttestationRequired,
+
+    #[msg("Too many orders for one batch")]
+    BatchTooLarge,
+
+    #[msg("An order account for this hash is still open; close it before reusing the hash")]
+    OrderAccountExists,
+
+    #[msg("Order market is not on the executor's allowed market list")]
+    MarketNotAllowed,
+
+    #[msg("Allowed market list exceeds MAX_ALLOWED_MARKETS")]
+    TooManyAllowedMarkets,
+
+    #[msg("Order notional exceeds the executor's max_order_notional")]
+    ExceedsMaxNotional,
+
+    #[msg("Only a committed order can be revealed")]
+    OrderNotCommitted,
+
+    #[msg("A committed order has no ciphertext until it is revealed")]
+    OrderNotRevealed,
+
+    #[msg("Price feed is degraded by a wide confidence band; executions are paused")]
+    FeedDegraded,
+
+    #[msg("Account is not at a layout version this program can migrate")]
+    UnsupportedAccountVersion,
+
+    #[msg("Only the program upgrade authority can initialize the bridge config")]
+    NotUpgradeAuthority,
+
+    #[msg("Protocol fees are owed; settle them before placing new orders")]
+    ProtocolFeesOwed,
+
+    #[msg("Fee vault does not match the bridge config")]
+    FeeVaultMismatch,
+
+    #[msg("Compute units exceed MAX_COMPUTE_UNITS")]
+    ComputeUnitsTooHigh,
+
+    #[msg("Order size is below the market's minimum")]
+    OrderBelowMinSize,
 }
@@ -6,13 +6,19 @@ use crate::errors::GhostBridgeError;
 pub struct AuthorizeExecutorArgs {
     pub executor: Pubkey,
     pub authorize: bool,
+    /// Unix timestamp the grant expires at; 0 for no expiry
+    pub expiry: i64,
 }
 
 pub fn handler(ctx: Context<AuthorizeExecutor>, args: AuthorizeExecutorArgs) -> Result<()> {
     let executor_authority = &mut ctx.accounts.executor_authority;
 
     if args.authorize {
-        executor_authority.add_authorized_executor(args.executor)?;
+        require!(
+            args.expiry == 0 || args.expiry > Clock::get()?.unix_timestamp,
+            GhostBridgeError::InvalidExecutorExpiry
+        );
+        executor_authority.add_authorized_executor(args.executor, args.expiry)?;
         msg!("Authorized executor: {}", args.executor);
     } else {
         executor_authority.remove_authorized_executor(args.executor)?;
@@ -28,6 +34,36 @@ pub fn handler(ctx: Context<AuthorizeExecutor>, args: AuthorizeExecutorArgs) ->
     Ok(())
 }
 
+pub fn batch_handler(ctx: Context<AuthorizeExecutor>, entries: Vec<AuthorizeExecutorArgs>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        entries
+            .iter()
+            .all(|entry| !entry.authorize || entry.expiry == 0 || entry.expiry > now),
+        GhostBridgeError::InvalidExecutorExpiry
+    );
+
+    let changes: Vec<(Pubkey, bool, i64)> = entries
+        .iter()
+        .map(|entry| (entry.executor, entry.authorize, entry.expiry))
+        .collect();
+
+    ctx.accounts.executor_authority.apply_executor_batch(&changes)?;
+
+    let owner = ctx.accounts.owner.key();
+    for (executor, authorized, _) in changes {
+        emit!(ExecutorAuthorizationChanged {
+            owner,
+            executor,
+            authorized,
+        });
+    }
+
+    msg!("Applied {} executor authorization changes", entries.len());
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct AuthorizeExecutor<'info> {
     #[account(mut)]
@@ -37,7 +73,8 @@ pub struct AuthorizeExecutor<'info> {
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
         bump = executor_authority.bump,
-        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 }
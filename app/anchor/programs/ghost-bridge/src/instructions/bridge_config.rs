@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use crate::constants::SPL_TOKEN_PROGRAM_ID;
+use crate::errors::GhostBridgeError;
+use crate::program::GhostBridge;
+use crate::state::{BridgeConfig, ExecutorAuthority};
+
+/// SPL Token `Transfer` instruction tag.
+const TOKEN_TRANSFER_TAG: u8 = 3;
+
+pub fn init_config_handler(ctx: Context<InitBridgeConfig>, admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.bridge_config;
+    config.admin = admin;
+    config.protocol_fee_bps = 0;
+    config.fee_vault = Pubkey::default();
+    config.bump = ctx.bumps.bridge_config;
+
+    msg!("Bridge config initialized: admin={}", admin);
+
+    Ok(())
+}
+
+pub fn set_protocol_fee_handler(
+    ctx: Context<SetProtocolFee>,
+    protocol_fee_bps: u16,
+    fee_vault: Pubkey,
+) -> Result<()> {
+    let config = &mut ctx.accounts.bridge_config;
+    config.set_protocol_fee(protocol_fee_bps, fee_vault)?;
+
+    msg!("Protocol fee set: {} bps to {}", protocol_fee_bps, fee_vault);
+
+    emit!(ProtocolFeeSet {
+        admin: ctx.accounts.admin.key(),
+        protocol_fee_bps,
+        fee_vault,
+    });
+
+    Ok(())
+}
+
+/// Only the program's upgrade authority may create the config, so nobody
+/// can claim the admin seat ahead of the deployer.
+#[derive(Accounts)]
+pub struct InitBridgeConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, GhostBridge>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ GhostBridgeError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = BridgeConfig::LEN,
+        seeds = [BridgeConfig::SEED_PREFIX],
+        bump
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Pays the fees executions accrued on the owner's authority from the
+/// owner's quote token account into the config's `fee_vault`.
+pub fn settle_protocol_fees_handler(ctx: Context<SettleProtocolFees>) -> Result<()> {
+    let executor = &mut ctx.accounts.executor_authority;
+    let amount = executor.take_protocol_fees_owed();
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let mut data = Vec::with_capacity(9);
+    data.push(TOKEN_TRANSFER_TAG);
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let transfer_ix = Instruction {
+        program_id: SPL_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(ctx.accounts.owner_quote_account.key(), false),
+            AccountMeta::new(ctx.accounts.fee_vault.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.owner.key(), true),
+        ],
+        data,
+    };
+    invoke(
+        &transfer_ix,
+        &[
+            ctx.accounts.owner_quote_account.to_account_info(),
+            ctx.accounts.fee_vault.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+    )?;
+
+    msg!("Protocol fees settled: {} to {}", amount, ctx.accounts.fee_vault.key());
+
+    emit!(ProtocolFeesSettled {
+        owner: ctx.accounts.owner.key(),
+        amount,
+        fee_vault: ctx.accounts.fee_vault.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BridgeConfig::SEED_PREFIX],
+        bump = bridge_config.bump,
+        constraint = bridge_config.is_admin(&admin.key()) @ GhostBridgeError::NotConfigAdmin
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleProtocolFees<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+
+    #[account(
+        seeds = [BridgeConfig::SEED_PREFIX],
+        bump = bridge_config.bump
+    )]
+    pub bridge_config: Account<'info, BridgeConfig>,
+
+    /// CHECK: Owner's quote token account; the token program checks it and the owner's signature
+    #[account(mut)]
+    pub owner_quote_account: UncheckedAccount<'info>,
+
+    /// CHECK: Quote token account the fees are owed to
+    #[account(
+        mut,
+        address = bridge_config.fee_vault @ GhostBridgeError::FeeVaultMismatch
+    )]
+    pub fee_vault: UncheckedAccount<'info>,
+
+    /// CHECK: SPL Token program
+    #[account(address = SPL_TOKEN_PROGRAM_ID)]
+    pub token_program: UncheckedAccount<'info>,
+}
+
+#[event]
+pub struct ProtocolFeeSet {
+    pub admin: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub fee_vault: Pubkey,
+}
+
+#[event]
+pub struct ProtocolFeesSettled {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub fee_vault: Pubkey,
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::state::{CompressedOrderParams, ExecutorAuthority};
+use crate::errors::GhostBridgeError;
+
+pub fn handler(ctx: Context<CancelCompressedOrder>, params: CompressedOrderParams) -> Result<()> {
+    let executor = &mut ctx.accounts.executor_authority;
+
+    let order = params.to_order(ctx.accounts.owner.key())?;
+    let order_hash = executor
+        .find_order_hash(&order)
+        .ok_or(GhostBridgeError::OrderHashNotFound)?;
+
+    executor.remove_order_hash(order_hash)?;
+
+    msg!(
+        "Compressed ghost order cancelled: order_id={}, hash={:?}",
+        params.order_id,
+        &order_hash[..8]
+    );
+
+    emit!(CompressedOrderCancelled {
+        owner: ctx.accounts.owner.key(),
+        order_id: params.order_id,
+        order_hash,
+    });
+
+    Ok(())
+}
+
+/// Cancels every listed order still held by the executor authority.
+/// Params for orders that are already gone are skipped rather than
+/// failing the batch.
+pub fn cancel_all_handler(
+    ctx: Context<CancelCompressedOrder>,
+    params: Vec<CompressedOrderParams>,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let orders = params
+        .iter()
+        .map(|p| p.to_order(owner))
+        .collect::<Result<Vec<_>>>()?;
+
+    let removed = ctx.accounts.executor_authority.remove_orders(&orders);
+    for &(order_id, order_hash) in &removed {
+        emit!(CompressedOrderCancelled {
+            owner,
+            order_id,
+            order_hash,
+        });
+    }
+
+    msg!(
+        "Bulk cancel: removed {} of {} compressed orders",
+        removed.len(),
+        orders.len()
+    );
+
+    emit!(BulkCancelled {
+        owner,
+        requested: orders.len() as u32,
+        cancelled: removed.len() as u32,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelCompressedOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+}
+
+#[event]
+pub struct CompressedOrderCancelled {
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub order_hash: [u8; 32],
+}
+
+#[event]
+pub struct BulkCancelled {
+    pub owner: Pubkey,
+    pub requested: u32,
+    pub cancelled: u32,
+}
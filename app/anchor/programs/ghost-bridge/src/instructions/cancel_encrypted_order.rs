@@ -6,8 +6,12 @@ pub fn handler(ctx: Context<CancelEncryptedOrder>) -> Result<()> {
     let encrypted_order = &mut ctx.accounts.encrypted_order;
     let executor = &mut ctx.accounts.executor_authority;
 
+    // A committed order that was never revealed can be withdrawn too
     require!(
-        encrypted_order.status == EncryptedOrderStatus::Active,
+        matches!(
+            encrypted_order.status,
+            EncryptedOrderStatus::Active | EncryptedOrderStatus::Committed
+        ),
         GhostBridgeError::InvalidOrderData
     );
 
@@ -36,7 +40,11 @@ pub struct CancelEncryptedOrder<'info> {
     #[account(
         mut,
         constraint = encrypted_order.owner == owner.key() @ GhostBridgeError::Unauthorized,
-        constraint = encrypted_order.status == EncryptedOrderStatus::Active @ GhostBridgeError::InvalidOrderData
+        constraint = matches!(
+            encrypted_order.status,
+            EncryptedOrderStatus::Active | EncryptedOrderStatus::Committed
+        ) @ GhostBridgeError::InvalidOrderData,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub encrypted_order: Account<'info, EncryptedOrder>,
 
@@ -44,7 +52,8 @@ pub struct CancelEncryptedOrder<'info> {
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
         bump = executor_authority.bump,
-        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 }
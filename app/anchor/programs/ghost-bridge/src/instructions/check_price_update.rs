@@ -1,72 +1,266 @@
 use anchor_lang::prelude::*;
-use crate::state::{EncryptedOrder, EncryptedOrderStatus};
+use crate::state::{
+    EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority, FeedHealth, MAX_BATCH_MONITORED_ORDERS,
+};
+use crate::errors::GhostBridgeError;
+use crate::instructions::feed_health::FeedHealthChanged;
+use crate::oracle::{read_confidence_bps, read_price, unless_stale, OracleSource};
+
+/// Records the feed's confidence on its `FeedHealth`, when the feed is
+/// tracked. Returns whether the feed is degraded or stale, in which case the
+/// read must not be used; the wide band is persisted instead of failing the
+/// transaction.
+fn record_feed_health(
+    feed_health_info: &AccountInfo,
+    source: OracleSource,
+    price_feed: &AccountInfo,
+    clock: &Clock,
+) -> Result<bool> {
+    let Some(mut feed_health) = FeedHealth::load_tracked(feed_health_info)? else {
+        return Ok(false);
+    };
+
+    let Some(confidence_bps) =
+        unless_stale(read_confidence_bps(source, price_feed, &feed_health.feed_id, clock))?
+    else {
+        msg!("Price feed stale, skipping");
+        return Ok(true);
+    };
+    if feed_health.record_confidence(confidence_bps, clock) {
+        msg!(
+            "Feed health changed: degraded={}, confidence_bps={}",
+            feed_health.degraded,
+            confidence_bps
+        );
+        emit!(FeedHealthChanged::for_feed(&feed_health));
+    }
+    feed_health.store(feed_health_info)?;
+    Ok(feed_health.degraded)
+}
 
 pub fn handler(ctx: Context<CheckPriceUpdate>) -> Result<()> {
-    let encrypted_order = &ctx.accounts.encrypted_order;
+    let clock = Clock::get()?;
+    let encrypted_order = &mut ctx.accounts.encrypted_order;
 
     if encrypted_order.status != EncryptedOrderStatus::Active {
         return Ok(());
     }
 
-    let current_price = read_pyth_price(&ctx.accounts.price_feed)?;
+    if encrypted_order.expire_if_stale(&clock) {
+        msg!("Encrypted order expired: hash={:?}", &encrypted_order.order_hash[..8]);
+        emit!(EncryptedOrderExpired {
+            owner: encrypted_order.owner,
+            order_hash: encrypted_order.order_hash,
+            expiry: encrypted_order.expiry,
+            cancelled_at: clock.unix_timestamp,
+        });
+        return Ok(());
+    }
+
+    let source = encrypted_order.oracle_source;
+    if record_feed_health(&ctx.accounts.feed_health, source, &ctx.accounts.price_feed, &clock)? {
+        return Ok(());
+    }
+    let Some(current_price) =
+        unless_stale(read_price(source, &ctx.accounts.price_feed, &encrypted_order.feed_id, &clock))?
+    else {
+        msg!("Price feed stale, skipping");
+        return Ok(());
+    };
+
+    if !encrypted_order.accept_observed_price(current_price) {
+        msg!(
+            "Price jump rejected: last={}, now={}",
+            encrypted_order.last_observed_price,
+            current_price
+        );
+        emit!(PriceJumpRejected {
+            order_hash: encrypted_order.order_hash,
+            last_observed_price: encrypted_order.last_observed_price,
+            current_price,
+        });
+        return Ok(());
+    }
+
+    let secondary_price = if encrypted_order.has_secondary_feed() {
+        let price_feed_b = ctx
+            .accounts
+            .price_feed_b
+            .as_ref()
+            .ok_or(GhostBridgeError::InvalidPriceFeed)?;
+        match unless_stale(read_price(source, price_feed_b, &encrypted_order.feed_id_b, &clock))? {
+            Some(price) => Some(price),
+            None => {
+                msg!("Secondary price feed stale, skipping");
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+    let observed_price = encrypted_order.observed_price(current_price, secondary_price)?;
 
     emit!(PriceUpdateChecked {
         order_hash: encrypted_order.order_hash,
         feed_id: encrypted_order.feed_id,
         current_price,
+        feed_id_b: encrypted_order.feed_id_b,
+        secondary_price: secondary_price.unwrap_or(0),
+        observed_price,
     });
 
     Ok(())
 }
 
-fn read_pyth_price(price_feed: &AccountInfo) -> Result<i64> {
-    use crate::constants::PYTH_RECEIVER_ID;
-    use crate::errors::GhostBridgeError;
+/// Checks every order of one executor on `feed_id`, passed writable in
+/// `remaining_accounts`, against a single read of `price_feed`. Orders no
+/// longer in the batch (executed, cancelled, other feed) are skipped, as are
+/// orders on a different oracle source than the first one read.
+pub fn check_batch_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CheckPriceUpdateBatch<'info>>,
+    feed_id: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let executor_key = ctx.accounts.executor_authority.key();
 
-    if price_feed.owner != &PYTH_RECEIVER_ID {
-        msg!(
-            "Invalid price feed owner: expected {}, got {}",
-            PYTH_RECEIVER_ID,
-            price_feed.owner
+    require!(
+        ctx.remaining_accounts.len() <= MAX_BATCH_MONITORED_ORDERS,
+        GhostBridgeError::BatchTooLarge
+    );
+
+    let mut price = None;
+    let mut checked = 0u8;
+
+    for order_info in ctx.remaining_accounts {
+        require!(order_info.is_writable, GhostBridgeError::InvalidOrderData);
+        let mut encrypted_order = Account::<EncryptedOrder>::try_from(order_info)?;
+        require!(
+            encrypted_order.is_current_version(),
+            GhostBridgeError::UnsupportedAccountVersion
         );
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
 
-    let data = price_feed.try_borrow_data()?;
+        if !encrypted_order.in_monitoring_batch(
+            &executor_key,
+            &ctx.accounts.executor_authority,
+            &feed_id,
+        ) {
+            continue;
+        }
 
-    if data.len() < 64 {
-        msg!("Price feed data too short: {} bytes", data.len());
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
+        if encrypted_order.expire_if_stale(&clock) {
+            emit!(EncryptedOrderExpired {
+                owner: encrypted_order.owner,
+                order_hash: encrypted_order.order_hash,
+                expiry: encrypted_order.expiry,
+                cancelled_at: clock.unix_timestamp,
+            });
+            encrypted_order.exit(ctx.program_id)?;
+            continue;
+        }
 
-    let magic = &data[0..4];
-    if magic != b"PYTH" && magic != [0x50, 0x32, 0x55, 0x56] {
-        msg!("Invalid price feed magic bytes");
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
+        // The first order to need a price fixes the source for the batch
+        let (source, current_price) = match price {
+            Some(read) => read,
+            None => {
+                let source = encrypted_order.oracle_source;
+                if record_feed_health(
+                    &ctx.accounts.feed_health,
+                    source,
+                    &ctx.accounts.price_feed,
+                    &clock,
+                )? {
+                    return Ok(());
+                }
+                let Some(current_price) =
+                    unless_stale(read_price(source, &ctx.accounts.price_feed, &feed_id, &clock))?
+                else {
+                    msg!("Price feed stale, skipping batch");
+                    return Ok(());
+                };
+                let read = (source, current_price);
+                price = Some(read);
+                read
+            }
+        };
+        if encrypted_order.oracle_source != source {
+            msg!(
+                "Order {:?} uses a different oracle source, skipping",
+                &encrypted_order.order_hash[..8]
+            );
+            continue;
+        }
+
+        if !encrypted_order.accept_observed_price(current_price) {
+            emit!(PriceJumpRejected {
+                order_hash: encrypted_order.order_hash,
+                last_observed_price: encrypted_order.last_observed_price,
+                current_price,
+            });
+            continue;
+        }
 
-    let price_offset = 32 + 8;
-    if data.len() < price_offset + 8 {
-        msg!("Price feed missing price data at expected offset");
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
+        emit!(PriceUpdateChecked {
+            order_hash: encrypted_order.order_hash,
+            feed_id: encrypted_order.feed_id,
+            current_price,
+            feed_id_b: encrypted_order.feed_id_b,
+            secondary_price: 0,
+            observed_price: current_price,
+        });
+        encrypted_order.exit(ctx.program_id)?;
+        checked += 1;
     }
 
-    let price_bytes: [u8; 8] = data[price_offset..price_offset + 8]
-        .try_into()
-        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+    msg!("Batch price check: orders={}, checked={}", ctx.remaining_accounts.len(), checked);
 
-    Ok(i64::from_le_bytes(price_bytes))
+    Ok(())
 }
 
 #[derive(Accounts)]
 pub struct CheckPriceUpdate<'info> {
     #[account(
-        constraint = encrypted_order.status == EncryptedOrderStatus::Active
+        mut,
+        constraint = encrypted_order.status == EncryptedOrderStatus::Active,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub encrypted_order: Account<'info, EncryptedOrder>,
 
-    /// CHECK: Pyth price feed account
+    /// CHECK: Price feed for `feed_id`; owner checked against the order's oracle source
+    pub price_feed: AccountInfo<'info>,
+
+    /// CHECK: Price feed for `feed_id_b`, required when the order has one
+    pub price_feed_b: Option<AccountInfo<'info>>,
+
+    /// CHECK: The order feed's `FeedHealth`; may not exist yet, loaded by `FeedHealth::load_tracked`
+    #[account(
+        mut,
+        seeds = [FeedHealth::SEED_PREFIX, encrypted_order.feed_id.as_ref()],
+        bump
+    )]
+    pub feed_health: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct CheckPriceUpdateBatch<'info> {
+    #[account(
+        seeds = [ExecutorAuthority::SEED_PREFIX, executor_authority.owner.as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+
+    /// CHECK: Price feed for `feed_id`, shared by every order in the batch;
+    /// owner checked against the orders' oracle source
     pub price_feed: AccountInfo<'info>,
+
+    /// CHECK: The `feed_id` `FeedHealth`; may not exist yet, loaded by `FeedHealth::load_tracked`
+    #[account(
+        mut,
+        seeds = [FeedHealth::SEED_PREFIX, feed_id.as_ref()],
+        bump
+    )]
+    pub feed_health: UncheckedAccount<'info>,
 }
 
 #[event]
@@ -74,4 +268,23 @@ pub struct PriceUpdateChecked {
     pub order_hash: [u8; 32],
     pub feed_id: [u8; 32],
     pub current_price: i64,
+    pub feed_id_b: [u8; 32],
+    pub secondary_price: i64,
+    /// Value compared against the trigger: `current_price`, or the spread for two-feed orders.
+    pub observed_price: i64,
+}
+
+#[event]
+pub struct PriceJumpRejected {
+    pub order_hash: [u8; 32],
+    pub last_observed_price: i64,
+    pub current_price: i64,
+}
+
+#[event]
+pub struct EncryptedOrderExpired {
+    pub owner: Pubkey,
+    pub order_hash: [u8; 32],
+    pub expiry: i64,
+    pub cancelled_at: i64,
 }
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{EncryptedOrder, EncryptedOrderStatus};
+use crate::state::{EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority};
 use crate::errors::GhostBridgeError;
 
 pub fn handler(ctx: Context<CloseEncryptedOrder>) -> Result<()> {
@@ -11,6 +11,14 @@ pub fn handler(ctx: Context<CloseEncryptedOrder>) -> Result<()> {
         GhostBridgeError::InvalidOrderData
     );
 
+    // Orders cancelled on expiry still hold their hash; free it so the
+    // hash can be used again once this account is gone
+    if let Some(executor) = ctx.accounts.executor_authority.as_mut() {
+        if executor.has_order_hash(&encrypted_order.order_hash) {
+            executor.remove_order_hash(encrypted_order.order_hash)?;
+        }
+    }
+
     msg!(
         "Encrypted order closed: hash={:?}, rent returned to owner",
         &encrypted_order.order_hash[..8]
@@ -36,9 +44,21 @@ pub struct CloseEncryptedOrder<'info> {
         constraint = (
             encrypted_order.status == EncryptedOrderStatus::Executed ||
             encrypted_order.status == EncryptedOrderStatus::Cancelled
-        ) @ GhostBridgeError::InvalidOrderData
+        ) @ GhostBridgeError::InvalidOrderData,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub encrypted_order: Account<'info, EncryptedOrder>,
+
+    /// Frees a hash the order still holds; clients built before it was
+    /// added close without it
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Option<Account<'info, ExecutorAuthority>>,
 }
 
 #[event]
@@ -1,21 +1,24 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::commit;
 use ephemeral_rollups_sdk::ephem::{
-    CallHandler, CommitAndUndelegate, CommitType, MagicAction, MagicInstructionBuilder,
+    CommitAndUndelegate, CommitType, MagicAction, MagicInstructionBuilder,
     UndelegateType,
 };
-use ephemeral_rollups_sdk::{ActionArgs, ShortAccountMeta};
-use crate::state::{CompressedGhostOrder, ExecutorAuthority, TriggerCondition, OrderSide};
+use ephemeral_rollups_sdk::ShortAccountMeta;
+use crate::state::{
+    BridgeConfig, CompressedGhostOrder, ExecutorAuthority, FeedHealth, TriggerCondition, OrderSide,
+};
 use crate::errors::GhostBridgeError;
-use crate::constants::DRIFT_PROGRAM_ID;
-use crate::drift_cpi::build_drift_place_perp_order;
-
-pub const DRIFT_EXECUTE_COMPUTE_UNITS: u32 = 200_000;
+use crate::constants::DRIFT_EXECUTE_COMPUTE_UNITS;
+use crate::drift_cpi::{build_drift_place_order, drift_call_handler, DriftMarketType};
+use crate::oracle::{corroborate_price, read_price, OracleSource};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ConsumeAndExecuteArgs {
     pub order_id: u64,
     pub market_index: u16,
+    /// Drift market type: 0 = spot, 1 = perp
+    pub market_type: u8,
     pub trigger_price: i64,
     pub trigger_condition: u8,
     pub order_side: u8,
@@ -24,8 +27,12 @@ pub struct ConsumeAndExecuteArgs {
     pub expiry: i64,
     pub feed_id: [u8; 32],
     pub salt: [u8; 16],
+    /// Keeper's price; with `price_feed` it is only a hint the oracle must corroborate
     pub current_price: i64,
     pub keep_delegated: bool,
+    /// Network `price_feed` is read from. Left out of the order hash: a
+    /// `feed_id` only names a feed under one source
+    pub oracle_source: OracleSource,
 }
 
 pub fn handler<'info>(
@@ -46,12 +53,27 @@ pub fn handler<'info>(
         _ => return Err(GhostBridgeError::InvalidOrderData.into()),
     };
 
+    let market_type = DriftMarketType::from_u8(args.market_type)
+        .ok_or(GhostBridgeError::InvalidMarketType)?;
+
     let owner = ctx.accounts.executor_authority.owner;
 
+    require!(
+        ctx.accounts.executor_authority.is_authorized_executor(&ctx.accounts.payer.key(), &clock)
+            || owner == ctx.accounts.payer.key(),
+        GhostBridgeError::ExecutorNotAuthorized
+    );
+    ctx.accounts.executor_authority.verify_drift_accounts(
+        &ctx.accounts.drift_user.key(),
+        &ctx.accounts.drift_authority.key(),
+    )?;
+    ctx.accounts.executor_authority.require_market_allowed(market_type, args.market_index)?;
+
     let order = CompressedGhostOrder {
         owner,
         order_id: args.order_id,
         market_index: args.market_index,
+        market_type,
         trigger_price: args.trigger_price,
         trigger_condition,
         order_side,
@@ -61,45 +83,71 @@ pub fn handler<'info>(
         feed_id: args.feed_id,
         salt: args.salt,
     };
+    ctx.accounts.executor_authority.require_within_max_notional(&order)?;
 
-    let order_hash = order.compute_hash();
-
-    require!(
-        ctx.accounts.executor_authority.has_order_hash(&order_hash),
-        GhostBridgeError::OrderHashNotFound
-    );
+    let order_hash = ctx
+        .accounts
+        .executor_authority
+        .find_order_hash(&order)
+        .ok_or(GhostBridgeError::OrderHashNotFound)?;
 
     require!(
-        !order.is_expired(clock.unix_timestamp),
+        !order.is_expired(&clock),
         GhostBridgeError::OrderExpired
     );
+    FeedHealth::check_account(&ctx.accounts.feed_health)?;
+
+    // A delegated keeper's claim has to be backed by the order's own feed;
+    // only the owner may trigger on a price it reports itself
+    let current_price = match ctx.accounts.price_feed.as_ref() {
+        Some(price_feed) => corroborate_price(
+            args.current_price,
+            read_price(args.oracle_source, price_feed, &order.feed_id, &clock)?,
+        )?,
+        None => {
+            require_keys_eq!(
+                ctx.accounts.payer.key(),
+                owner,
+                GhostBridgeError::PriceAttestationRequired
+            );
+            args.current_price
+        }
+    };
 
     require!(
-        order.check_trigger(args.current_price),
+        order.check_trigger(current_price),
         GhostBridgeError::TriggerConditionNotMet
     );
 
     ctx.accounts.executor_authority.remove_order_hash(order_hash)?;
 
+    let (protocol_fee, fee_vault) = BridgeConfig::load_initialized(&ctx.accounts.bridge_config)?
+        .map_or((0, Pubkey::default()), |config| {
+            (config.protocol_fee(args.base_asset_amount, current_price), config.fee_vault)
+        });
+    ctx.accounts.executor_authority.accrue_protocol_fee(protocol_fee);
+
     if !args.keep_delegated {
         ctx.accounts.executor_authority.is_delegated = false;
     }
 
     msg!(
-        "Order hash verified and removed. Executing Drift order: market={}, side={:?}, amount={}",
+        "Order hash verified and removed. Executing Drift order: market={}, type={:?}, side={:?}, amount={}",
         args.market_index,
+        market_type,
         order_side,
         args.base_asset_amount
     );
 
-    let drift_ix_data = build_drift_place_perp_order(
+    let drift_ix_data = build_drift_place_order(
+        market_type,
         args.market_index,
         order_side,
         args.base_asset_amount,
         args.reduce_only,
     );
 
-    let drift_accounts = build_drift_short_account_metas(
+    let mut drift_accounts = build_drift_short_account_metas(
         ctx.accounts.drift_state.key(),
         ctx.accounts.drift_user.key(),
         ctx.accounts.drift_user_stats.key(),
@@ -108,13 +156,26 @@ pub fn handler<'info>(
         ctx.accounts.oracle.key(),
     );
 
-    let drift_call_handler = CallHandler {
-        destination_program: DRIFT_PROGRAM_ID,
-        accounts: drift_accounts,
-        args: ActionArgs::new(drift_ix_data),
-        escrow_authority: ctx.accounts.payer.to_account_info(),
-        compute_units: DRIFT_EXECUTE_COMPUTE_UNITS,
-    };
+    // Spot orders also move the quote balance, so Drift needs the quote
+    // spot market writable alongside the base market
+    if market_type == DriftMarketType::Spot {
+        let quote_spot_market = ctx
+            .accounts
+            .quote_spot_market
+            .as_ref()
+            .ok_or(GhostBridgeError::QuoteSpotMarketMissing)?;
+        drift_accounts.push(ShortAccountMeta {
+            pubkey: quote_spot_market.key(),
+            is_writable: true,
+        });
+    }
+
+    let drift_call_handler = drift_call_handler(
+        ctx.accounts.payer.to_account_info(),
+        drift_accounts,
+        drift_ix_data,
+        DRIFT_EXECUTE_COMPUTE_UNITS,
+    );
 
     let executor_account_info = ctx.accounts.executor_authority.to_account_info();
 
@@ -147,14 +208,18 @@ pub fn handler<'info>(
         order_id: args.order_id,
         order_hash,
         market_index: args.market_index,
-        execution_price: args.current_price,
+        trigger_price: args.trigger_price,
+        execution_price: current_price,
+        realized_slippage_bps: order.realized_slippage_bps(current_price),
+        protocol_fee,
+        fee_vault,
     });
 
     msg!(
         "Ghost order executed via Magic Action: order_id={}, market={}, price={}",
         args.order_id,
         args.market_index,
-        args.current_price
+        current_price
     );
 
     Ok(())
@@ -199,6 +264,7 @@ fn build_drift_short_account_metas(
 
 #[commit]
 #[derive(Accounts)]
+#[instruction(args: ConsumeAndExecuteArgs)]
 pub struct ConsumeAndExecute<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -206,14 +272,15 @@ pub struct ConsumeAndExecute<'info> {
     #[account(
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, executor_authority.owner.as_ref()],
-        bump = executor_authority.bump
+        bump = executor_authority.bump,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 
     /// CHECK: Drift program state account
     pub drift_state: AccountInfo<'info>,
 
-    /// CHECK: Drift user account for the ghost order owner
+    /// CHECK: Drift user account; must match `executor_authority.drift_user`
     #[account(mut)]
     pub drift_user: AccountInfo<'info>,
 
@@ -221,10 +288,10 @@ pub struct ConsumeAndExecute<'info> {
     #[account(mut)]
     pub drift_user_stats: AccountInfo<'info>,
 
-    /// CHECK: Authority for the Drift user (ghost order owner)
+    /// CHECK: Authority for the Drift user; must be the executor owner
     pub drift_authority: AccountInfo<'info>,
 
-    /// CHECK: Perp market account for the order
+    /// CHECK: Market account for the order; the spot market for spot orders
     #[account(mut)]
     pub perp_market: AccountInfo<'info>,
 
@@ -236,6 +303,28 @@ pub struct ConsumeAndExecute<'info> {
 
     /// CHECK: Magic program for ER operations
     pub magic_program: AccountInfo<'info>,
+
+    /// CHECK: Drift quote spot market (USDC), required for spot orders
+    #[account(mut)]
+    pub quote_spot_market: Option<AccountInfo<'info>>,
+
+    /// CHECK: Bridge config; may not be initialized yet, read by `BridgeConfig::load_initialized`
+    #[account(
+        seeds = [BridgeConfig::SEED_PREFIX],
+        bump
+    )]
+    pub bridge_config: UncheckedAccount<'info>,
+
+    /// CHECK: Price feed for the order's `feed_id`; owner and feed checked on read.
+    /// Required unless the owner executes
+    pub price_feed: Option<AccountInfo<'info>>,
+
+    /// CHECK: The order feed's `FeedHealth`; may not exist yet, read by `FeedHealth::check_account`
+    #[account(
+        seeds = [FeedHealth::SEED_PREFIX, args.feed_id.as_ref()],
+        bump
+    )]
+    pub feed_health: UncheckedAccount<'info>,
 }
 
 #[event]
@@ -244,5 +333,11 @@ pub struct GhostOrderExecuted {
     pub order_id: u64,
     pub order_hash: [u8; 32],
     pub market_index: u16,
+    pub trigger_price: i64,
     pub execution_price: i64,
+    /// Basis points of `trigger_price`; positive is adverse for the side, negative favorable
+    pub realized_slippage_bps: i32,
+    /// Accrued on the executor authority for `fee_vault`, in quote micro-units (0 when the fee is off)
+    pub protocol_fee: u64,
+    pub fee_vault: Pubkey,
 }
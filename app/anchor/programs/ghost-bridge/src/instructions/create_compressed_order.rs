@@ -1,11 +1,15 @@
 use anchor_lang::prelude::*;
-use crate::state::{CompressedGhostOrder, ExecutorAuthority, TriggerCondition, OrderSide};
+use crate::state::{CompressedGhostOrder, ExecutorAuthority, MarketRegistry, TriggerCondition, OrderSide};
 use crate::errors::GhostBridgeError;
+use crate::drift_cpi::DriftMarketType;
+use crate::order_size::DEFAULT_MIN_BASE_ASSET_AMOUNT;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct CreateCompressedOrderArgs {
     pub order_id: u64,
     pub market_index: u16,
+    /// Drift market type: 0 = spot, 1 = perp
+    pub market_type: u8,
     pub trigger_price: i64,
     pub trigger_condition: u8,
     pub order_side: u8,
@@ -32,6 +36,9 @@ pub fn handler(ctx: Context<CreateCompressedOrder>, args: CreateCompressedOrderA
         _ => return Err(GhostBridgeError::InvalidOrderData.into()),
     };
 
+    let market_type = DriftMarketType::from_u8(args.market_type)
+        .ok_or(GhostBridgeError::InvalidMarketType)?;
+
     let expiry = if args.expiry_seconds > 0 {
         clock.unix_timestamp + args.expiry_seconds
     } else {
@@ -42,6 +49,7 @@ pub fn handler(ctx: Context<CreateCompressedOrder>, args: CreateCompressedOrderA
         owner: ctx.accounts.owner.key(),
         order_id: args.order_id,
         market_index: args.market_index,
+        market_type,
         trigger_price: args.trigger_price,
         trigger_condition,
         order_side,
@@ -52,6 +60,12 @@ pub fn handler(ctx: Context<CreateCompressedOrder>, args: CreateCompressedOrderA
         salt: args.salt,
     };
 
+    let min_base_asset_amount = match &ctx.accounts.market_registry {
+        Some(registry) => MarketRegistry::load(registry)?.min_base_asset_amount(market_type, args.market_index),
+        None => DEFAULT_MIN_BASE_ASSET_AMOUNT,
+    };
+    order.validate_size(min_base_asset_amount)?;
+
     let order_hash = order.compute_hash();
 
     executor.add_order_hash(order_hash)?;
@@ -82,11 +96,17 @@ pub struct CreateCompressedOrder<'info> {
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
         bump = executor_authority.bump,
-        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: ghost-crank's market registry, for the market's minimum order
+    /// size; owner and discriminator checked on load. Without it the
+    /// default minimum applies.
+    pub market_registry: Option<UncheckedAccount<'info>>,
 }
 
 #[event]
@@ -1,60 +1,220 @@
 use anchor_lang::prelude::*;
-use crate::state::{EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority, MAX_ENCRYPTED_DATA_LEN};
+use anchor_lang::system_program::{
+    allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+};
+use crate::account_version::ACCOUNT_VERSION;
+use crate::state::{
+    is_vacant_order_account, validate_compute_units, EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority,
+};
 use crate::errors::GhostBridgeError;
+use crate::oracle::OracleSource;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct CreateEncryptedOrderArgs {
     pub order_hash: [u8; 32],
     pub encrypted_data: Vec<u8>,
     pub feed_id: [u8; 32],
+    /// Secondary feed for spread orders; all zeros for single-feed orders.
+    pub feed_id_b: [u8; 32],
+    pub relation: u8,
+    /// Oracle network both feed accounts are read from
+    pub oracle_source: OracleSource,
+    /// Unix timestamp after which monitoring cancels the order; 0 never expires
+    pub expiry: i64,
+    /// Ephemeral public key and nonce the executor needs to decrypt `encrypted_data`
+    pub ephemeral_pubkey: [u8; 32],
+    pub nonce: [u8; 12],
+    /// `blake3(encrypted_data || order_hash || blake3(commitment_key))`, checked again at execution
+    pub ciphertext_commitment: [u8; 32],
+    /// Compute budget for the Drift call handler; 0 uses the default
+    pub compute_units: u32,
 }
 
 pub fn handler(ctx: Context<CreateEncryptedOrder>, args: CreateEncryptedOrderArgs) -> Result<()> {
     let clock = Clock::get()?;
+    let data_len = args.encrypted_data.len();
 
     require!(
-        args.encrypted_data.len() <= MAX_ENCRYPTED_DATA_LEN,
-        GhostBridgeError::InvalidOrderData
+        args.expiry == 0 || args.expiry > clock.unix_timestamp,
+        GhostBridgeError::OrderExpired
     );
+    validate_compute_units(args.compute_units)?;
 
+    // An order from an earlier use of this hash that was never closed still
+    // occupies the address; `init` would fail on it with an opaque system error
     require!(
-        args.encrypted_data.len() > 0,
-        GhostBridgeError::InvalidOrderData
+        is_vacant_order_account(&ctx.accounts.encrypted_order),
+        GhostBridgeError::OrderAccountExists
     );
 
     let executor = &mut ctx.accounts.executor_authority;
     executor.add_order_hash(args.order_hash)?;
 
-    let encrypted_order = &mut ctx.accounts.encrypted_order;
-    encrypted_order.owner = ctx.accounts.owner.key();
-    encrypted_order.order_hash = args.order_hash;
-    encrypted_order.executor_authority = ctx.accounts.executor_authority.key();
-    encrypted_order.data_len = args.encrypted_data.len() as u16;
-    encrypted_order.encrypted_data[..args.encrypted_data.len()].copy_from_slice(&args.encrypted_data);
-    encrypted_order.feed_id = args.feed_id;
-    encrypted_order.created_at = clock.unix_timestamp;
-    encrypted_order.triggered_at = 0;
-    encrypted_order.execution_price = 0;
-    encrypted_order.status = EncryptedOrderStatus::Active;
-    encrypted_order.bump = ctx.bumps.encrypted_order;
+    let mut encrypted_order = EncryptedOrder {
+        version: ACCOUNT_VERSION,
+        owner: ctx.accounts.owner.key(),
+        order_hash: args.order_hash,
+        executor_authority: ctx.accounts.executor_authority.key(),
+        feed_id: args.feed_id,
+        oracle_source: args.oracle_source,
+        expiry: args.expiry,
+        ephemeral_pubkey: args.ephemeral_pubkey,
+        nonce: args.nonce,
+        ciphertext_commitment: args.ciphertext_commitment,
+        compute_units: args.compute_units,
+        created_at: clock.unix_timestamp,
+        status: EncryptedOrderStatus::Active,
+        bump: ctx.bumps.encrypted_order,
+        ..EncryptedOrder::default()
+    };
+    encrypted_order.set_encrypted_data(args.encrypted_data)?;
+    encrypted_order.set_secondary_feed(args.feed_id_b, args.relation)?;
+
+    create_order_account(
+        &ctx.accounts.owner,
+        &ctx.accounts.encrypted_order,
+        &ctx.accounts.system_program,
+        &encrypted_order,
+    )?;
 
     msg!(
         "Encrypted order created: hash={:?}, feed={:?}, data_len={}",
         &args.order_hash[..8],
         &args.feed_id[..8],
-        args.encrypted_data.len()
+        data_len
     );
 
-    emit!(EncryptedOrderCreated {
+    emit!(EncryptedOrderCreated::for_order(&encrypted_order));
+
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CommitEncryptedOrderArgs {
+    pub order_hash: [u8; 32],
+    /// `blake3(encrypted_data || order_hash || blake3(commitment_key))` the later reveal must match
+    pub ciphertext_commitment: [u8; 32],
+}
+
+/// First half of a two-phase creation: registers the order hash and the
+/// ciphertext commitment without the ciphertext, so the payload can be
+/// attached by `reveal_encrypted_order` at a later, unrelated time. The
+/// order is not monitored or executable until then.
+pub fn commit_handler(ctx: Context<CommitEncryptedOrder>, args: CommitEncryptedOrderArgs) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        is_vacant_order_account(&ctx.accounts.encrypted_order),
+        GhostBridgeError::OrderAccountExists
+    );
+
+    let executor = &mut ctx.accounts.executor_authority;
+    executor.add_order_hash(args.order_hash)?;
+
+    let encrypted_order = EncryptedOrder {
+        version: ACCOUNT_VERSION,
         owner: ctx.accounts.owner.key(),
         order_hash: args.order_hash,
-        feed_id: args.feed_id,
+        executor_authority: ctx.accounts.executor_authority.key(),
+        ciphertext_commitment: args.ciphertext_commitment,
         created_at: clock.unix_timestamp,
+        status: EncryptedOrderStatus::Committed,
+        bump: ctx.bumps.encrypted_order,
+        ..EncryptedOrder::default()
+    };
+
+    create_order_account(
+        &ctx.accounts.owner,
+        &ctx.accounts.encrypted_order,
+        &ctx.accounts.system_program,
+        &encrypted_order,
+    )?;
+
+    msg!("Encrypted order committed: hash={:?}", &args.order_hash[..8]);
+
+    emit!(EncryptedOrderCommitted {
+        owner: encrypted_order.owner,
+        order_hash: encrypted_order.order_hash,
+        committed_at: encrypted_order.created_at,
     });
 
     Ok(())
 }
 
+/// Allocates the order PDA as Anchor's `init` would, topping up instead of
+/// failing when someone has already sent lamports to the address, and
+/// writes `order` into it.
+fn create_order_account<'info>(
+    payer: &Signer<'info>,
+    account: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    order: &EncryptedOrder,
+) -> Result<()> {
+    let space = EncryptedOrder::space_for(order.encrypted_data.len());
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        EncryptedOrder::SEED_PREFIX,
+        order.owner.as_ref(),
+        &order.order_hash,
+        &[order.bump],
+    ]];
+    let system_program = system_program.to_account_info();
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+
+    if current_lamports == 0 {
+        create_account(
+            CpiContext::new_with_signer(
+                system_program,
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(current_lamports);
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: account.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Allocate {
+                    account_to_allocate: account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        assign(
+            CpiContext::new_with_signer(
+                system_program,
+                Assign {
+                    account_to_assign: account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    order.try_serialize(&mut &mut data[..])
+}
+
 #[derive(Accounts)]
 #[instruction(args: CreateEncryptedOrderArgs)]
 pub struct CreateEncryptedOrder<'info> {
@@ -65,26 +225,74 @@ pub struct CreateEncryptedOrder<'info> {
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
         bump = executor_authority.bump,
-        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 
+    /// CHECK: Created by the handler, which requires the address to be vacant
     #[account(
-        init,
-        payer = owner,
-        space = EncryptedOrder::LEN,
+        mut,
+        seeds = [EncryptedOrder::SEED_PREFIX, owner.key().as_ref(), &args.order_hash],
+        bump
+    )]
+    pub encrypted_order: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: CommitEncryptedOrderArgs)]
+pub struct CommitEncryptedOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+
+    /// CHECK: Created by the handler, which requires the address to be vacant
+    #[account(
+        mut,
         seeds = [EncryptedOrder::SEED_PREFIX, owner.key().as_ref(), &args.order_hash],
         bump
     )]
-    pub encrypted_order: Account<'info, EncryptedOrder>,
+    pub encrypted_order: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+#[event]
+pub struct EncryptedOrderCommitted {
+    pub owner: Pubkey,
+    pub order_hash: [u8; 32],
+    pub committed_at: i64,
+}
+
 #[event]
 pub struct EncryptedOrderCreated {
     pub owner: Pubkey,
     pub order_hash: [u8; 32],
     pub feed_id: [u8; 32],
     pub created_at: i64,
+    pub ephemeral_pubkey: [u8; 32],
+    pub nonce: [u8; 12],
+}
+
+impl EncryptedOrderCreated {
+    pub fn for_order(order: &EncryptedOrder) -> Self {
+        Self {
+            owner: order.owner,
+            order_hash: order.order_hash,
+            feed_id: order.feed_id,
+            created_at: order.created_at,
+            ephemeral_pubkey: order.ephemeral_pubkey,
+            nonce: order.nonce,
+        }
+    }
 }
@@ -51,7 +51,8 @@ pub struct DelegateEncryptedOrder<'info> {
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, payer.key().as_ref()],
         bump = executor_authority.bump,
-        constraint = executor_authority.owner == payer.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority.owner == payer.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 }
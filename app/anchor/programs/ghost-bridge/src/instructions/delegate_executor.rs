@@ -43,7 +43,8 @@ pub struct DelegateExecutor<'info> {
     #[account(
         seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
         bump = executor_authority_data.bump,
-        constraint = executor_authority_data.owner == owner.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority_data.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority_data.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority_data: Account<'info, ExecutorAuthority>,
 
@@ -66,7 +67,8 @@ pub struct PostDelegateExecutor<'info> {
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
         bump = executor_authority.bump,
-        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 }
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::{CompressedOrderParams, ExecutorAuthority};
+use crate::errors::GhostBridgeError;
+
+/// Permissionless: anyone holding the order params can reclaim the slot of an
+/// expired compressed order.
+pub fn handler(ctx: Context<ExpireCompressedOrder>, params: CompressedOrderParams) -> Result<()> {
+    let clock = Clock::get()?;
+    let executor = &mut ctx.accounts.executor_authority;
+
+    let order = params.to_order(executor.owner)?;
+    let order_hash = executor.reclaim_expired_order(&order, &clock)?;
+
+    msg!(
+        "Expired compressed order reclaimed: order_id={}, hash={:?}",
+        params.order_id,
+        &order_hash[..8]
+    );
+
+    emit!(OrderExpired {
+        owner: executor.owner,
+        order_id: params.order_id,
+        order_hash,
+        reclaimed_by: ctx.accounts.payer.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExpireCompressedOrder<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, executor_authority.owner.as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+}
+
+#[event]
+pub struct OrderExpired {
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub order_hash: [u8; 32],
+    pub reclaimed_by: Pubkey,
+}
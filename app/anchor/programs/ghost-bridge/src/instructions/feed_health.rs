@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::state::FeedHealth;
+
+/// Starts tracking `feed_id`'s confidence. Permissionless: the account only
+/// ever records what `check_price_update` reads from the feed itself.
+pub fn init_feed_health_handler(ctx: Context<InitFeedHealth>, feed_id: [u8; 32]) -> Result<()> {
+    let feed_health = &mut ctx.accounts.feed_health;
+    feed_health.feed_id = feed_id;
+    feed_health.confidence_bps = 0;
+    feed_health.degraded = false;
+    feed_health.updated_at = Clock::get()?.unix_timestamp;
+    feed_health.bump = ctx.bumps.feed_health;
+
+    msg!("Feed health tracking started: feed={:?}", &feed_id[..8]);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct InitFeedHealth<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = FeedHealth::LEN,
+        seeds = [FeedHealth::SEED_PREFIX, feed_id.as_ref()],
+        bump
+    )]
+    pub feed_health: Account<'info, FeedHealth>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct FeedHealthChanged {
+    pub feed_id: [u8; 32],
+    pub degraded: bool,
+    pub confidence_bps: u64,
+    pub updated_at: i64,
+}
+
+impl FeedHealthChanged {
+    pub fn for_feed(feed_health: &FeedHealth) -> Self {
+        Self {
+            feed_id: feed_health.feed_id,
+            degraded: feed_health.degraded,
+            confidence_bps: feed_health.confidence_bps,
+            updated_at: feed_health.updated_at,
+        }
+    }
+}
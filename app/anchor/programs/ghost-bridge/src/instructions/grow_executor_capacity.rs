@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::{ExecutorAuthority, MAX_ORDERS_PER_EXECUTOR};
+use crate::errors::GhostBridgeError;
+
+pub fn handler(ctx: Context<GrowExecutorCapacity>) -> Result<()> {
+    let executor_authority = &mut ctx.accounts.executor_authority;
+
+    executor_authority.grow_capacity()?;
+
+    msg!(
+        "Executor order capacity grown to {}",
+        executor_authority.capacity
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct GrowExecutorCapacity<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = !executor_authority.is_delegated @ GhostBridgeError::ExecutorDelegated,
+        realloc = ExecutorAuthority::space_for(executor_authority.capacity as usize + MAX_ORDERS_PER_EXECUTOR),
+        realloc::payer = owner,
+        realloc::zero = false,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+
+    pub system_program: Program<'info, System>,
+}
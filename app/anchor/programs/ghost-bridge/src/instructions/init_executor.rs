@@ -1,15 +1,28 @@
 use anchor_lang::prelude::*;
-use crate::state::ExecutorAuthority;
+use crate::account_version::ACCOUNT_VERSION;
+use crate::state::{
+    AllowedMarket, ExecutorAuthority, MAX_ALLOWED_MARKETS, MAX_AUTHORIZED_EXECUTORS,
+    MAX_ORDERS_PER_EXECUTOR,
+};
 
-pub fn handler(ctx: Context<InitExecutor>) -> Result<()> {
+pub fn handler(ctx: Context<InitExecutor>, drift_user: Pubkey) -> Result<()> {
     let executor = &mut ctx.accounts.executor_authority;
 
+    executor.version = ACCOUNT_VERSION;
     executor.owner = ctx.accounts.owner.key();
     executor.order_count = 0;
     executor.is_delegated = false;
     executor.bump = ctx.bumps.executor_authority;
-    executor.order_hashes = [[0u8; 32]; 16];
+    executor.order_hashes = Vec::new();
     executor.order_hash_count = 0;
+    executor.capacity = MAX_ORDERS_PER_EXECUTOR as u16;
+    executor.executor_expires_at = [0; MAX_AUTHORIZED_EXECUTORS];
+    executor.drift_user = drift_user;
+    executor.previous_owner = Pubkey::default();
+    executor.allowed_markets = [AllowedMarket::default(); MAX_ALLOWED_MARKETS];
+    executor.allowed_market_count = 0;
+    executor.max_order_notional = 0;
+    executor.protocol_fees_owed = 0;
 
     msg!(
         "ExecutorAuthority initialized for owner: {}",
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::account_version::{rewrite_account, ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+use crate::errors::GhostBridgeError;
+use crate::state::{EncryptedOrder, EncryptedOrderV1, ExecutorAuthority, ExecutorAuthorityV1};
+
+/// Rewrites an encrypted order or executor authority at the current layout
+/// version, resized to fit. Stored fields keep their values and new ones
+/// take their defaults, so anyone may pay to migrate any account; delegated
+/// accounts have to be back on the base layer first.
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account = &ctx.accounts.account;
+    let (is_order, version) = {
+        let data = account.try_borrow_data()?;
+        let is_order = data.starts_with(EncryptedOrder::DISCRIMINATOR);
+        require!(
+            is_order || data.starts_with(ExecutorAuthority::DISCRIMINATOR),
+            GhostBridgeError::UnsupportedAccountVersion
+        );
+        let stored_version = if is_order {
+            EncryptedOrder::stored_version
+        } else {
+            ExecutorAuthority::stored_version
+        };
+        let version = stored_version(&account.key(), &data)
+            .ok_or(GhostBridgeError::UnsupportedAccountVersion)?;
+        (is_order, version)
+    };
+
+    match version {
+        ACCOUNT_VERSION => {
+            msg!("Account already at version {}", version);
+            return Ok(());
+        }
+        LEGACY_ACCOUNT_VERSION => {
+            let account_info = account.to_account_info();
+            let payer = ctx.accounts.payer.to_account_info();
+            let system_program = ctx.accounts.system_program.to_account_info();
+            if is_order {
+                let order = EncryptedOrderV1::from_account_data(&account.try_borrow_data()?)?.upgrade();
+                let space = EncryptedOrder::space_for(order.encrypted_data.len());
+                rewrite_account(&account_info, &payer, &system_program, &order, space)?;
+            } else {
+                let executor = ExecutorAuthorityV1::from_account_data(&account.try_borrow_data()?)?.upgrade();
+                let space = ExecutorAuthority::space_for(executor.capacity as usize);
+                rewrite_account(&account_info, &payer, &system_program, &executor, space)?;
+            }
+        }
+        _ => return err!(GhostBridgeError::UnsupportedAccountVersion),
+    }
+
+    emit!(AccountMigrated {
+        account: account.key(),
+        from_version: version,
+        to_version: ACCOUNT_VERSION,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: An encrypted order or executor authority at any layout version;
+    /// the handler checks its discriminator and that its seeds derive this address
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
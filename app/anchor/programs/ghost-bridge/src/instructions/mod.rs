@@ -1,31 +1,55 @@
 pub mod init_executor;
 pub mod delegate_executor;
 pub mod undelegate_executor;
+pub mod grow_executor_capacity;
+pub mod rotate_executor_owner;
 pub mod create_compressed_order;
 pub mod consume_and_execute;
+pub mod cancel_compressed_order;
+pub mod expire_compressed_order;
 
 pub mod create_encrypted_order;
 pub mod delegate_encrypted_order;
 pub mod trigger_and_execute;
 pub mod cancel_encrypted_order;
 pub mod close_encrypted_order;
+pub mod update_encrypted_data;
+pub mod reveal_encrypted_order;
+pub mod reveal_encrypted_data;
 
 pub mod schedule_encrypted_monitoring;
 pub mod check_price_update;
 pub mod authorize_executor;
+pub mod set_allowed_markets;
+pub mod set_max_order_notional;
+pub mod bridge_config;
+pub mod feed_health;
+pub mod migrate_account;
 
 pub use init_executor::*;
 pub use delegate_executor::*;
 pub use undelegate_executor::*;
+pub use grow_executor_capacity::*;
+pub use rotate_executor_owner::*;
 pub use create_compressed_order::*;
 pub use consume_and_execute::*;
+pub use cancel_compressed_order::*;
+pub use expire_compressed_order::*;
 
 pub use create_encrypted_order::*;
 pub use delegate_encrypted_order::*;
 pub use trigger_and_execute::*;
 pub use cancel_encrypted_order::*;
 pub use close_encrypted_order::*;
+pub use update_encrypted_data::*;
+pub use reveal_encrypted_order::*;
+pub use reveal_encrypted_data::*;
 
 pub use schedule_encrypted_monitoring::*;
 pub use check_price_update::*;
 pub use authorize_executor::*;
+pub use set_allowed_markets::*;
+pub use set_max_order_notional::*;
+pub use bridge_config::*;
+pub use feed_health::*;
+pub use migrate_account::*;
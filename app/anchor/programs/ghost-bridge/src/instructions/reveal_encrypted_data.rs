@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::{EncryptedOrder, EncryptedOrderStatus};
+use crate::errors::GhostBridgeError;
+
+/// Read-only: emits the stored ciphertext and the key material needed to
+/// decrypt it, so the owner's client can take it from the transaction logs
+/// instead of parsing the raw account. Nothing is decrypted on-chain.
+pub fn handler(ctx: Context<RevealEncryptedData>) -> Result<()> {
+    let encrypted_order = &ctx.accounts.encrypted_order;
+
+    msg!(
+        "Encrypted order data: hash={:?}, data_len={}",
+        &encrypted_order.order_hash[..8],
+        encrypted_order.data_len
+    );
+
+    emit!(EncryptedOrderData::for_order(encrypted_order));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevealEncryptedData<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [EncryptedOrder::SEED_PREFIX, owner.key().as_ref(), &encrypted_order.order_hash],
+        bump = encrypted_order.bump,
+        constraint = encrypted_order.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = encrypted_order.status != EncryptedOrderStatus::Committed @ GhostBridgeError::OrderNotRevealed,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub encrypted_order: Account<'info, EncryptedOrder>,
+}
+
+#[event]
+pub struct EncryptedOrderData {
+    pub owner: Pubkey,
+    pub order_hash: [u8; 32],
+    pub encrypted_data: Vec<u8>,
+    pub feed_id: [u8; 32],
+    pub feed_id_b: [u8; 32],
+    pub ephemeral_pubkey: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext_commitment: [u8; 32],
+}
+
+impl EncryptedOrderData {
+    pub fn for_order(order: &EncryptedOrder) -> Self {
+        Self {
+            owner: order.owner,
+            order_hash: order.order_hash,
+            encrypted_data: order.get_encrypted_data().to_vec(),
+            feed_id: order.feed_id,
+            feed_id_b: order.feed_id_b,
+            ephemeral_pubkey: order.ephemeral_pubkey,
+            nonce: order.nonce,
+            ciphertext_commitment: order.ciphertext_commitment,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::state::{validate_compute_units, EncryptedOrder, EncryptedOrderStatus};
+use crate::errors::GhostBridgeError;
+use crate::instructions::create_encrypted_order::EncryptedOrderCreated;
+use crate::oracle::OracleSource;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RevealEncryptedOrderArgs {
+    /// Must match the ciphertext commitment made at commit time
+    pub encrypted_data: Vec<u8>,
+    pub feed_id: [u8; 32],
+    /// Secondary feed for spread orders; all zeros for single-feed orders.
+    pub feed_id_b: [u8; 32],
+    pub relation: u8,
+    pub oracle_source: OracleSource,
+    /// Unix timestamp after which monitoring cancels the order; 0 never expires
+    pub expiry: i64,
+    pub ephemeral_pubkey: [u8; 32],
+    pub nonce: [u8; 12],
+    /// Compute budget for the Drift call handler; 0 uses the default
+    pub compute_units: u32,
+    /// `blake3(commitment_key)`; the key stays sealed in the ciphertext
+    pub commitment_key_hash: [u8; 32],
+}
+
+/// Second half of a two-phase creation: attaches the ciphertext to a
+/// committed order and makes it active.
+pub fn handler(ctx: Context<RevealEncryptedOrder>, args: RevealEncryptedOrderArgs) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        args.expiry == 0 || args.expiry > clock.unix_timestamp,
+        GhostBridgeError::OrderExpired
+    );
+    validate_compute_units(args.compute_units)?;
+
+    let encrypted_order = &mut ctx.accounts.encrypted_order;
+    encrypted_order.reveal(args.encrypted_data, &args.commitment_key_hash)?;
+    encrypted_order.feed_id = args.feed_id;
+    encrypted_order.set_secondary_feed(args.feed_id_b, args.relation)?;
+    encrypted_order.oracle_source = args.oracle_source;
+    encrypted_order.expiry = args.expiry;
+    encrypted_order.ephemeral_pubkey = args.ephemeral_pubkey;
+    encrypted_order.nonce = args.nonce;
+    encrypted_order.compute_units = args.compute_units;
+
+    msg!(
+        "Encrypted order revealed: hash={:?}, feed={:?}, data_len={}",
+        &encrypted_order.order_hash[..8],
+        &args.feed_id[..8],
+        encrypted_order.data_len
+    );
+
+    emit!(EncryptedOrderCreated::for_order(encrypted_order));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(args: RevealEncryptedOrderArgs)]
+pub struct RevealEncryptedOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [EncryptedOrder::SEED_PREFIX, owner.key().as_ref(), &encrypted_order.order_hash],
+        bump = encrypted_order.bump,
+        constraint = encrypted_order.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = encrypted_order.status == EncryptedOrderStatus::Committed @ GhostBridgeError::OrderNotCommitted,
+        realloc = EncryptedOrder::space_for(args.encrypted_data.len()),
+        realloc::payer = owner,
+        realloc::zero = false,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub encrypted_order: Account<'info, EncryptedOrder>,
+
+    pub system_program: Program<'info, System>,
+}
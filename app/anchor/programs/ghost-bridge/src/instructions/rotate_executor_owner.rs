@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::ExecutorAuthority;
+use crate::errors::GhostBridgeError;
+
+/// Moves an executor authority to the PDA of `new_owner`: the new account is
+/// created with the old one's capacity, takes a copy of its state and the
+/// old account is closed to the current owner. Encrypted orders are keyed by
+/// the old owner and have to be recreated.
+pub fn handler(ctx: Context<RotateExecutorOwner>, drift_user: Pubkey) -> Result<()> {
+    let new_owner = ctx.accounts.new_owner.key();
+    let rotated = ctx.accounts.old_executor_authority.rotated(
+        new_owner,
+        drift_user,
+        ctx.bumps.new_executor_authority,
+    );
+    ctx.accounts.new_executor_authority.set_inner(rotated);
+
+    msg!(
+        "ExecutorAuthority rotated: {} -> {}, order_hashes={}",
+        ctx.accounts.owner.key(),
+        new_owner,
+        ctx.accounts.new_executor_authority.order_hash_count
+    );
+
+    emit!(ExecutorOwnerRotated {
+        previous_owner: ctx.accounts.owner.key(),
+        new_owner,
+        executor_authority: ctx.accounts.new_executor_authority.key(),
+        order_hash_count: ctx.accounts.new_executor_authority.order_hash_count,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RotateExecutorOwner<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Must sign so the authority cannot be pushed onto a key nobody controls
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = old_executor_authority.bump,
+        constraint = old_executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = !old_executor_authority.is_delegated @ GhostBridgeError::ExecutorDelegated,
+        constraint = old_executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub old_executor_authority: Account<'info, ExecutorAuthority>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ExecutorAuthority::space_for(old_executor_authority.capacity as usize),
+        seeds = [ExecutorAuthority::SEED_PREFIX, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_executor_authority: Account<'info, ExecutorAuthority>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct ExecutorOwnerRotated {
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub executor_authority: Pubkey,
+    pub order_hash_count: u8,
+}
@@ -2,7 +2,9 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_lang::solana_program::program::invoke;
 use magicblock_magic_program_api::{args::ScheduleTaskArgs, instruction::MagicBlockInstruction};
-use crate::state::{EncryptedOrder, EncryptedOrderStatus};
+use crate::state::{
+    EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority, MAX_BATCH_MONITORED_ORDERS,
+};
 use crate::errors::GhostBridgeError;
 use crate::constants::MAGIC_PROGRAM_ID;
 
@@ -24,9 +26,16 @@ pub fn handler(
         GhostBridgeError::InvalidOrderData
     );
 
+    let price_feed_b = ctx.accounts.price_feed_b.as_ref().map(|feed| feed.key());
+    require!(
+        !encrypted_order.has_secondary_feed() || price_feed_b.is_some(),
+        GhostBridgeError::InvalidPriceFeed
+    );
+
     let check_trigger_ix = build_check_encrypted_trigger_instruction(
         &encrypted_order.key(),
         &ctx.accounts.price_feed.key(),
+        price_feed_b.as_ref(),
     );
 
     let schedule_args = ScheduleTaskArgs {
@@ -39,25 +48,25 @@ pub fn handler(
     let schedule_ix_data = bincode::serialize(&MagicBlockInstruction::ScheduleTask(schedule_args))
         .map_err(|_| GhostBridgeError::MagicActionFailed)?;
 
-    let schedule_ix = Instruction::new_with_bytes(
-        MAGIC_PROGRAM_ID,
-        &schedule_ix_data,
-        vec![
-            AccountMeta::new(ctx.accounts.payer.key(), true),
-            AccountMeta::new(ctx.accounts.encrypted_order.key(), false),
-            AccountMeta::new_readonly(ctx.accounts.price_feed.key(), false),
-        ],
-    );
+    let mut schedule_metas = vec![
+        AccountMeta::new(ctx.accounts.payer.key(), true),
+        AccountMeta::new(ctx.accounts.encrypted_order.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.price_feed.key(), false),
+    ];
+    let mut schedule_infos = vec![
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.encrypted_order.to_account_info(),
+        ctx.accounts.price_feed.to_account_info(),
+    ];
+    if let Some(feed) = ctx.accounts.price_feed_b.as_ref() {
+        schedule_metas.push(AccountMeta::new_readonly(feed.key(), false));
+        schedule_infos.push(feed.to_account_info());
+    }
+    schedule_infos.push(ctx.accounts.magic_program.to_account_info());
 
-    invoke(
-        &schedule_ix,
-        &[
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.encrypted_order.to_account_info(),
-            ctx.accounts.price_feed.to_account_info(),
-            ctx.accounts.magic_program.to_account_info(),
-        ],
-    )?;
+    let schedule_ix = Instruction::new_with_bytes(MAGIC_PROGRAM_ID, &schedule_ix_data, schedule_metas);
+
+    invoke(&schedule_ix, &schedule_infos)?;
 
     msg!(
         "Encrypted order monitoring scheduled: task_id={}, interval={}ms, iterations={}",
@@ -76,15 +85,139 @@ pub fn handler(
     Ok(())
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ScheduleBatchMonitoringArgs {
+    pub task_id: i64,
+    pub check_interval_millis: i64,
+    pub max_iterations: i64,
+    /// Feed every order in the batch watches
+    pub feed_id: [u8; 32],
+}
+
+/// Schedules one recurring task that price-checks all of the owner's active
+/// single-feed orders on `feed_id`, passed in `remaining_accounts`, instead
+/// of one task per order.
+pub fn schedule_batch_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ScheduleBatchMonitoring<'info>>,
+    args: ScheduleBatchMonitoringArgs,
+) -> Result<()> {
+    let executor_key = ctx.accounts.executor_authority.key();
+    let order_infos = ctx.remaining_accounts;
+
+    require!(!order_infos.is_empty(), GhostBridgeError::InvalidOrderData);
+    require!(
+        order_infos.len() <= MAX_BATCH_MONITORED_ORDERS,
+        GhostBridgeError::BatchTooLarge
+    );
+    for order_info in order_infos {
+        let encrypted_order = Account::<EncryptedOrder>::try_from(order_info)?;
+        require!(
+            encrypted_order.is_current_version(),
+            GhostBridgeError::UnsupportedAccountVersion
+        );
+        require!(
+            encrypted_order.in_monitoring_batch(
+                &executor_key,
+                &ctx.accounts.executor_authority,
+                &args.feed_id,
+            ),
+            GhostBridgeError::InvalidOrderData
+        );
+    }
+
+    let order_keys: Vec<Pubkey> = order_infos.iter().map(|info| info.key()).collect();
+    let check_batch_ix = build_check_price_update_batch_instruction(
+        &executor_key,
+        &ctx.accounts.price_feed.key(),
+        &order_keys,
+        args.feed_id,
+    );
+
+    let schedule_args = ScheduleTaskArgs {
+        task_id: args.task_id,
+        execution_interval_millis: args.check_interval_millis,
+        iterations: args.max_iterations,
+        instructions: vec![check_batch_ix],
+    };
+
+    let schedule_ix_data = bincode::serialize(&MagicBlockInstruction::ScheduleTask(schedule_args))
+        .map_err(|_| GhostBridgeError::MagicActionFailed)?;
+
+    let mut schedule_metas = vec![
+        AccountMeta::new(ctx.accounts.owner.key(), true),
+        AccountMeta::new_readonly(executor_key, false),
+        AccountMeta::new_readonly(ctx.accounts.price_feed.key(), false),
+    ];
+    let mut schedule_infos = vec![
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.executor_authority.to_account_info(),
+        ctx.accounts.price_feed.to_account_info(),
+    ];
+    for order_info in order_infos {
+        schedule_metas.push(AccountMeta::new(order_info.key(), false));
+        schedule_infos.push(order_info.clone());
+    }
+    schedule_infos.push(ctx.accounts.magic_program.to_account_info());
+
+    let schedule_ix = Instruction::new_with_bytes(MAGIC_PROGRAM_ID, &schedule_ix_data, schedule_metas);
+
+    invoke(&schedule_ix, &schedule_infos)?;
+
+    msg!(
+        "Batch monitoring scheduled: task_id={}, orders={}, interval={}ms, iterations={}",
+        args.task_id,
+        order_keys.len(),
+        args.check_interval_millis,
+        args.max_iterations
+    );
+
+    emit!(BatchMonitoringScheduled {
+        owner: ctx.accounts.owner.key(),
+        feed_id: args.feed_id,
+        order_count: order_keys.len() as u8,
+        task_id: args.task_id,
+        check_interval_millis: args.check_interval_millis,
+        max_iterations: args.max_iterations,
+    });
+
+    Ok(())
+}
+
+fn build_check_price_update_batch_instruction(
+    executor_authority: &Pubkey,
+    price_feed: &Pubkey,
+    orders: &[Pubkey],
+    feed_id: [u8; 32],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*executor_authority, false),
+        AccountMeta::new_readonly(*price_feed, false),
+    ];
+    accounts.extend(orders.iter().map(|order| AccountMeta::new(*order, false)));
+
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: anchor_lang::InstructionData::data(&crate::instruction::CheckPriceUpdateBatch {
+            feed_id,
+        }),
+    }
+}
+
 fn build_check_encrypted_trigger_instruction(
     encrypted_order: &Pubkey,
     price_feed: &Pubkey,
+    price_feed_b: Option<&Pubkey>,
 ) -> Instruction {
+    // Anchor reads the program id in an optional slot as `None`.
+    let secondary = price_feed_b.copied().unwrap_or(crate::ID);
+
     Instruction {
         program_id: crate::ID,
         accounts: vec![
             AccountMeta::new(*encrypted_order, false),
             AccountMeta::new_readonly(*price_feed, false),
+            AccountMeta::new_readonly(secondary, false),
         ],
         data: anchor_lang::InstructionData::data(&crate::instruction::CheckPriceUpdate {}),
     }
@@ -98,7 +231,8 @@ pub struct ScheduleEncryptedMonitoring<'info> {
 
     #[account(
         mut,
-        constraint = encrypted_order.status == EncryptedOrderStatus::Active @ GhostBridgeError::InvalidOrderData
+        constraint = encrypted_order.status == EncryptedOrderStatus::Active @ GhostBridgeError::InvalidOrderData,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub encrypted_order: Account<'info, EncryptedOrder>,
 
@@ -108,6 +242,40 @@ pub struct ScheduleEncryptedMonitoring<'info> {
     /// CHECK: Magic Program for scheduling
     #[account(address = MAGIC_PROGRAM_ID)]
     pub magic_program: AccountInfo<'info>,
+
+    /// CHECK: Secondary Pyth price feed for two-feed orders
+    pub price_feed_b: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ScheduleBatchMonitoring<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+
+    /// CHECK: Price feed shared by the batch; owner checked on each read
+    pub price_feed: AccountInfo<'info>,
+
+    /// CHECK: Magic Program for scheduling
+    #[account(address = MAGIC_PROGRAM_ID)]
+    pub magic_program: AccountInfo<'info>,
+}
+
+#[event]
+pub struct BatchMonitoringScheduled {
+    pub owner: Pubkey,
+    pub feed_id: [u8; 32],
+    pub order_count: u8,
+    pub task_id: i64,
+    pub check_interval_millis: i64,
+    pub max_iterations: i64,
 }
 
 #[event]
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::{AllowedMarket, ExecutorAuthority};
+use crate::errors::GhostBridgeError;
+
+/// Replaces the markets executors may trade for this owner. An empty list
+/// lifts the restriction.
+pub fn handler(ctx: Context<SetAllowedMarkets>, markets: Vec<AllowedMarket>) -> Result<()> {
+    ctx.accounts.executor_authority.set_allowed_markets(&markets)?;
+
+    msg!("Allowed markets set: {:?}", markets);
+
+    emit!(AllowedMarketsUpdated {
+        owner: ctx.accounts.owner.key(),
+        markets,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAllowedMarkets<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+}
+
+#[event]
+pub struct AllowedMarketsUpdated {
+    pub owner: Pubkey,
+    pub markets: Vec<AllowedMarket>,
+}
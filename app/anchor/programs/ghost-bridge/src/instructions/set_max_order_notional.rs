@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::ExecutorAuthority;
+use crate::errors::GhostBridgeError;
+
+/// Caps the notional of any single order executors may place for this
+/// owner, in quote micro-units. Zero removes the cap.
+pub fn handler(ctx: Context<SetMaxOrderNotional>, max_order_notional: u64) -> Result<()> {
+    ctx.accounts.executor_authority.max_order_notional = max_order_notional;
+
+    msg!("Max order notional set: {}", max_order_notional);
+
+    emit!(MaxOrderNotionalUpdated {
+        owner: ctx.accounts.owner.key(),
+        max_order_notional,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMaxOrderNotional<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ExecutorAuthority::SEED_PREFIX, owner.key().as_ref()],
+        bump = executor_authority.bump,
+        constraint = executor_authority.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub executor_authority: Account<'info, ExecutorAuthority>,
+}
+
+#[event]
+pub struct MaxOrderNotionalUpdated {
+    pub owner: Pubkey,
+    pub max_order_notional: u64,
+}
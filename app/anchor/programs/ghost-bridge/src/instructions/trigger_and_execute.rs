@@ -6,15 +6,15 @@ use ephemeral_rollups_sdk::ephem::{
 };
 use ephemeral_rollups_sdk::{ActionArgs, ShortAccountMeta};
 use crate::state::{
-    EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority,
-    CompressedGhostOrder, TriggerCondition, OrderSide,
+    BridgeConfig, EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority, FeedHealth,
+    CompressedGhostOrder, MarketRegistry, TriggerCondition, OrderSide,
 };
 use crate::errors::GhostBridgeError;
-use crate::constants::{DRIFT_PROGRAM_ID, DELEGATION_PROGRAM_ID};
-use crate::drift_cpi::build_drift_place_perp_order;
-
-pub const DRIFT_EXECUTE_COMPUTE_UNITS: u32 = 200_000;
-pub const DELEGATE_COMPUTE_UNITS: u32 = 50_000;
+use crate::constants::{DELEGATE_COMPUTE_UNITS, DELEGATION_PROGRAM_ID};
+use crate::delegation::is_delegation_accounts;
+use crate::drift_cpi::{build_drift_place_perp_order, drift_call_handler, DriftMarketType};
+use crate::instructions::check_price_update::PriceJumpRejected;
+use crate::oracle::{read_price, unless_stale};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct TriggerAndExecuteArgs {
@@ -28,6 +28,9 @@ pub struct TriggerAndExecuteArgs {
     pub reduce_only: bool,
     pub expiry: i64,
     pub redelegate_after: bool,
+    /// Secret sealed in the ciphertext, proving the executor decrypted it;
+    /// see `EncryptedOrder::verify_ciphertext_commitment`
+    pub commitment_key: [u8; 32],
 }
 
 pub fn handler<'info>(
@@ -59,15 +62,22 @@ pub fn handler<'info>(
     );
 
     require!(
-        ctx.accounts.executor_authority.is_authorized_executor(&ctx.accounts.payer.key())
+        ctx.accounts.executor_authority.is_authorized_executor(&ctx.accounts.payer.key(), &clock)
             || ctx.accounts.executor_authority.owner == ctx.accounts.payer.key(),
         GhostBridgeError::ExecutorNotAuthorized
     );
+    ctx.accounts.executor_authority.verify_drift_accounts(
+        &ctx.accounts.drift_user.key(),
+        &ctx.accounts.drift_authority.key(),
+    )?;
+    ctx.accounts.executor_authority.require_market_allowed(DriftMarketType::Perp, args.market_index)?;
 
     let order = CompressedGhostOrder {
         owner,
         order_id: args.order_id,
         market_index: args.market_index,
+        // Encrypted orders only trade perps
+        market_type: DriftMarketType::Perp,
         trigger_price: args.trigger_price,
         trigger_condition,
         order_side,
@@ -83,23 +93,80 @@ pub fn handler<'info>(
         computed_hash == stored_hash,
         GhostBridgeError::OrderHashMismatch
     );
+    ctx.accounts.executor_authority.require_within_max_notional(&order)?;
+    ctx.accounts
+        .encrypted_order
+        .verify_ciphertext_commitment(&args.commitment_key, &order)?;
 
-    if order.is_expired(clock.unix_timestamp) {
+    if order.is_expired(&clock) || ctx.accounts.encrypted_order.is_expired(&clock) {
         ctx.accounts.encrypted_order.status = EncryptedOrderStatus::Cancelled;
         msg!("Order expired: hash={:?}", &stored_hash[..8]);
         return Ok(());
     }
 
-    let current_price = read_pyth_price(&ctx.accounts.price_feed)?;
+    MarketRegistry::load(&ctx.accounts.market_registry)?.verify_feed(
+        order.market_type,
+        order.market_index,
+        &order.feed_id,
+    )?;
+
+    FeedHealth::check_account(&ctx.accounts.feed_health)?;
+
+    let source = ctx.accounts.encrypted_order.oracle_source;
+    let Some(current_price) =
+        unless_stale(read_price(source, &ctx.accounts.price_feed, &feed_id, &clock))?
+    else {
+        msg!("Price feed stale, skipping execution");
+        return Ok(());
+    };
+    if !ctx.accounts.encrypted_order.accept_observed_price(current_price) {
+        msg!(
+            "Price jump rejected: last={}, now={}, skipping execution",
+            ctx.accounts.encrypted_order.last_observed_price,
+            current_price
+        );
+        emit!(PriceJumpRejected {
+            order_hash: stored_hash,
+            last_observed_price: ctx.accounts.encrypted_order.last_observed_price,
+            current_price,
+        });
+        return Ok(());
+    }
+    let secondary_price = if ctx.accounts.encrypted_order.has_secondary_feed() {
+        let price_feed_b = ctx
+            .accounts
+            .price_feed_b
+            .as_ref()
+            .ok_or(GhostBridgeError::InvalidPriceFeed)?;
+        match unless_stale(read_price(
+            source,
+            price_feed_b,
+            &ctx.accounts.encrypted_order.feed_id_b,
+            &clock,
+        ))? {
+            Some(price) => Some(price),
+            None => {
+                msg!("Secondary price feed stale, skipping execution");
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+    let observed_price = ctx
+        .accounts
+        .encrypted_order
+        .observed_price(current_price, secondary_price)?;
 
     msg!(
-        "Checking trigger: current={}, trigger={}, condition={:?}",
+        "Checking trigger: current={}, observed={}, trigger={}, condition={:?}",
         current_price,
+        observed_price,
         args.trigger_price,
         trigger_condition
     );
 
-    if !order.check_trigger(current_price) {
+    if !order.check_trigger(observed_price) {
         msg!("Trigger condition not met, skipping execution");
         return Ok(());
     }
@@ -116,6 +183,14 @@ pub fn handler<'info>(
     ctx.accounts.encrypted_order.triggered_at = clock.unix_timestamp;
     ctx.accounts.encrypted_order.execution_price = current_price;
 
+    // Accrued on the authority and settled into `fee_vault` off the ER; the
+    // Drift fill itself is left untouched
+    let (protocol_fee, fee_vault) = BridgeConfig::load_initialized(&ctx.accounts.bridge_config)?
+        .map_or((0, Pubkey::default()), |config| {
+            (config.protocol_fee(args.base_asset_amount, current_price), config.fee_vault)
+        });
+    ctx.accounts.executor_authority.accrue_protocol_fee(protocol_fee);
+
     let drift_ix_data = build_drift_place_perp_order(
         args.market_index,
         order_side,
@@ -132,13 +207,12 @@ pub fn handler<'info>(
         ctx.accounts.oracle.key(),
     );
 
-    let drift_call_handler = CallHandler {
-        destination_program: DRIFT_PROGRAM_ID,
-        accounts: drift_accounts,
-        args: ActionArgs::new(drift_ix_data),
-        escrow_authority: ctx.accounts.payer.to_account_info(),
-        compute_units: DRIFT_EXECUTE_COMPUTE_UNITS,
-    };
+    let drift_call_handler = drift_call_handler(
+        ctx.accounts.payer.to_account_info(),
+        drift_accounts,
+        drift_ix_data,
+        ctx.accounts.encrypted_order.drift_compute_units(),
+    );
 
     let call_handlers = if args.redelegate_after {
         let redelegate_handler = build_redelegate_handler(
@@ -180,8 +254,11 @@ pub fn handler<'info>(
         base_asset_amount: args.base_asset_amount,
         trigger_price: args.trigger_price,
         execution_price: current_price,
+        realized_slippage_bps: order.realized_slippage_bps(current_price),
         executed_at: clock.unix_timestamp,
         redelegated: args.redelegate_after,
+        protocol_fee,
+        fee_vault,
     });
 
     msg!(
@@ -204,6 +281,15 @@ fn build_redelegate_handler<'info>(
         msg!("Skipping redelegation: missing delegation PDAs");
         return Err(GhostBridgeError::MagicActionFailed.into());
     }
+    if !is_delegation_accounts(
+        remaining_accounts,
+        &encrypted_order_key,
+        &crate::ID,
+        &DELEGATION_PROGRAM_ID,
+    ) {
+        msg!("Redelegation accounts are not the delegation PDAs of the order");
+        return Err(GhostBridgeError::MagicActionFailed.into());
+    }
 
     let buffer_pda = &remaining_accounts[0];
     let record_pda = &remaining_accounts[1];
@@ -292,67 +378,40 @@ fn build_drift_short_account_metas(
     ]
 }
 
-fn read_pyth_price(price_feed: &AccountInfo) -> Result<i64> {
-    use crate::constants::PYTH_RECEIVER_ID;
-
-    if price_feed.owner != &PYTH_RECEIVER_ID {
-        msg!(
-            "Invalid price feed owner: expected {}, got {}",
-            PYTH_RECEIVER_ID,
-            price_feed.owner
-        );
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
-
-    let data = price_feed.try_borrow_data()?;
-
-    if data.len() < 64 {
-        msg!("Price feed data too short: {} bytes", data.len());
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
-
-    let magic = &data[0..4];
-    if magic != b"PYTH" && magic != [0x50, 0x32, 0x55, 0x56] {
-        msg!("Invalid price feed magic bytes");
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
-
-    let price_offset = 32 + 8;
-    if data.len() < price_offset + 8 {
-        msg!("Price feed missing price data at expected offset");
-        return Err(GhostBridgeError::InvalidPriceFeed.into());
-    }
-
-    let price_bytes: [u8; 8] = data[price_offset..price_offset + 8]
-        .try_into()
-        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
-
-    Ok(i64::from_le_bytes(price_bytes))
-}
-
 #[commit]
 #[derive(Accounts)]
 pub struct TriggerAndExecute<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
     pub encrypted_order: Account<'info, EncryptedOrder>,
 
     #[account(
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, encrypted_order.owner.as_ref()],
-        bump = executor_authority.bump
+        bump = executor_authority.bump,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 
-    /// CHECK: Pyth price feed for trigger comparison
+    /// CHECK: Price feed for `feed_id`, compared against the trigger; owner
+    /// checked against the order's oracle source
     pub price_feed: AccountInfo<'info>,
 
+    /// CHECK: Price feed for `feed_id_b`, required when the order has one
+    pub price_feed_b: Option<AccountInfo<'info>>,
+
+    /// CHECK: ghost-crank's market registry; owner and discriminator checked on load
+    pub market_registry: AccountInfo<'info>,
+
     /// CHECK: Drift program state account
     pub drift_state: AccountInfo<'info>,
 
-    /// CHECK: Drift user account for the order owner
+    /// CHECK: Drift user account; must match `executor_authority.drift_user`
     #[account(mut)]
     pub drift_user: AccountInfo<'info>,
 
@@ -360,7 +419,7 @@ pub struct TriggerAndExecute<'info> {
     #[account(mut)]
     pub drift_user_stats: AccountInfo<'info>,
 
-    /// CHECK: Authority for the Drift user (order owner)
+    /// CHECK: Authority for the Drift user; must be the executor owner
     pub drift_authority: AccountInfo<'info>,
 
     /// CHECK: Perp market account for the order
@@ -375,6 +434,20 @@ pub struct TriggerAndExecute<'info> {
 
     /// CHECK: Magic program for ER operations
     pub magic_program: AccountInfo<'info>,
+
+    /// CHECK: Bridge config; may not be initialized yet, read by `BridgeConfig::load_initialized`
+    #[account(
+        seeds = [BridgeConfig::SEED_PREFIX],
+        bump
+    )]
+    pub bridge_config: UncheckedAccount<'info>,
+
+    /// CHECK: The order feed's `FeedHealth`; may not exist yet, read by `FeedHealth::check_account`
+    #[account(
+        seeds = [FeedHealth::SEED_PREFIX, encrypted_order.feed_id.as_ref()],
+        bump
+    )]
+    pub feed_health: UncheckedAccount<'info>,
 }
 
 #[event]
@@ -386,6 +459,11 @@ pub struct OrderTriggeredAndExecuted {
     pub base_asset_amount: u64,
     pub trigger_price: i64,
     pub execution_price: i64,
+    /// Basis points of `trigger_price`; positive is adverse for the side, negative favorable
+    pub realized_slippage_bps: i32,
     pub executed_at: i64,
     pub redelegated: bool,
+    /// Accrued on the executor authority for `fee_vault`, in quote micro-units (0 when the fee is off)
+    pub protocol_fee: u64,
+    pub fee_vault: Pubkey,
 }
@@ -44,7 +44,8 @@ pub struct UndelegateExecutor<'info> {
     #[account(
         mut,
         seeds = [ExecutorAuthority::SEED_PREFIX, executor_authority.owner.as_ref()],
-        bump = executor_authority.bump
+        bump = executor_authority.bump,
+        constraint = executor_authority.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
     )]
     pub executor_authority: Account<'info, ExecutorAuthority>,
 
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::{EncryptedOrder, EncryptedOrderStatus};
+use crate::errors::GhostBridgeError;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct UpdateEncryptedDataArgs {
+    pub encrypted_data: Vec<u8>,
+    /// Commitment over the new ciphertext and the unchanged order hash
+    pub ciphertext_commitment: [u8; 32],
+}
+
+pub fn handler(ctx: Context<UpdateEncryptedData>, args: UpdateEncryptedDataArgs) -> Result<()> {
+    let encrypted_order = &mut ctx.accounts.encrypted_order;
+
+    encrypted_order.set_encrypted_data(args.encrypted_data)?;
+    encrypted_order.ciphertext_commitment = args.ciphertext_commitment;
+
+    msg!(
+        "Encrypted order re-encrypted: hash={:?}, data_len={}",
+        &encrypted_order.order_hash[..8],
+        encrypted_order.data_len
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdateEncryptedDataArgs)]
+pub struct UpdateEncryptedData<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [EncryptedOrder::SEED_PREFIX, owner.key().as_ref(), &encrypted_order.order_hash],
+        bump = encrypted_order.bump,
+        constraint = encrypted_order.owner == owner.key() @ GhostBridgeError::Unauthorized,
+        constraint = encrypted_order.status == EncryptedOrderStatus::Active @ GhostBridgeError::OrderNotActive,
+        realloc = EncryptedOrder::space_for(args.encrypted_data.len()),
+        realloc::payer = owner,
+        realloc::zero = false,
+        constraint = encrypted_order.is_current_version() @ GhostBridgeError::UnsupportedAccountVersion,
+    )]
+    pub encrypted_order: Account<'info, EncryptedOrder>,
+
+    pub system_program: Program<'info, System>,
+}
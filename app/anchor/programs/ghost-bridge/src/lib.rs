@@ -2,15 +2,32 @@ use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::ephemeral;
 
 pub mod constants;
-pub mod drift_cpi;
 pub mod errors;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
+#[path = "../../../shared/account_version.rs"]
+pub mod account_version;
+#[path = "../../../shared/delegation.rs"]
+pub mod delegation;
+#[path = "../../../shared/drift_cpi.rs"]
+pub mod drift_cpi;
+#[path = "../../../shared/order_size.rs"]
+pub mod order_size;
+#[path = "../../../shared/pyth.rs"]
+pub mod pyth;
+#[path = "../../../shared/switchboard.rs"]
+pub mod switchboard;
+#[path = "../../../shared/time.rs"]
+pub mod time;
+#[path = "../../../shared/trigger.rs"]
+pub mod trigger;
 
 #[cfg(test)]
 mod tests;
 
 use instructions::*;
+use state::{AllowedMarket, CompressedOrderParams};
 
 declare_id!("8w95bQ7UzKHKa4NYvyVeAVGN3dMgwshJhhTinPfabMLA");
 
@@ -19,8 +36,8 @@ declare_id!("8w95bQ7UzKHKa4NYvyVeAVGN3dMgwshJhhTinPfabMLA");
 pub mod ghost_bridge {
     use super::*;
 
-    pub fn init_executor(ctx: Context<InitExecutor>) -> Result<()> {
-        instructions::init_executor::handler(ctx)
+    pub fn init_executor(ctx: Context<InitExecutor>, drift_user: Pubkey) -> Result<()> {
+        instructions::init_executor::handler(ctx, drift_user)
     }
 
     pub fn delegate_executor(ctx: Context<DelegateExecutor>) -> Result<()> {
@@ -31,6 +48,17 @@ pub mod ghost_bridge {
         instructions::undelegate_executor::handler(ctx)
     }
 
+    pub fn grow_executor_capacity(ctx: Context<GrowExecutorCapacity>) -> Result<()> {
+        instructions::grow_executor_capacity::handler(ctx)
+    }
+
+    pub fn rotate_executor_owner(
+        ctx: Context<RotateExecutorOwner>,
+        drift_user: Pubkey,
+    ) -> Result<()> {
+        instructions::rotate_executor_owner::handler(ctx, drift_user)
+    }
+
     pub fn create_compressed_order(
         ctx: Context<CreateCompressedOrder>,
         args: CreateCompressedOrderArgs,
@@ -45,6 +73,27 @@ pub mod ghost_bridge {
         instructions::consume_and_execute::handler(ctx, args)
     }
 
+    pub fn cancel_compressed_order(
+        ctx: Context<CancelCompressedOrder>,
+        params: CompressedOrderParams,
+    ) -> Result<()> {
+        instructions::cancel_compressed_order::handler(ctx, params)
+    }
+
+    pub fn cancel_all_orders(
+        ctx: Context<CancelCompressedOrder>,
+        params: Vec<CompressedOrderParams>,
+    ) -> Result<()> {
+        instructions::cancel_compressed_order::cancel_all_handler(ctx, params)
+    }
+
+    pub fn expire_compressed_order(
+        ctx: Context<ExpireCompressedOrder>,
+        params: CompressedOrderParams,
+    ) -> Result<()> {
+        instructions::expire_compressed_order::handler(ctx, params)
+    }
+
     pub fn create_encrypted_order(
         ctx: Context<CreateEncryptedOrder>,
         args: CreateEncryptedOrderArgs,
@@ -52,6 +101,20 @@ pub mod ghost_bridge {
         instructions::create_encrypted_order::handler(ctx, args)
     }
 
+    pub fn commit_encrypted_order(
+        ctx: Context<CommitEncryptedOrder>,
+        args: CommitEncryptedOrderArgs,
+    ) -> Result<()> {
+        instructions::create_encrypted_order::commit_handler(ctx, args)
+    }
+
+    pub fn reveal_encrypted_order(
+        ctx: Context<RevealEncryptedOrder>,
+        args: RevealEncryptedOrderArgs,
+    ) -> Result<()> {
+        instructions::reveal_encrypted_order::handler(ctx, args)
+    }
+
     pub fn delegate_encrypted_order(
         ctx: Context<DelegateEncryptedOrder>,
         args: DelegateEncryptedOrderArgs,
@@ -74,6 +137,17 @@ pub mod ghost_bridge {
         instructions::close_encrypted_order::handler(ctx)
     }
 
+    pub fn update_encrypted_data(
+        ctx: Context<UpdateEncryptedData>,
+        args: UpdateEncryptedDataArgs,
+    ) -> Result<()> {
+        instructions::update_encrypted_data::handler(ctx, args)
+    }
+
+    pub fn reveal_encrypted_data(ctx: Context<RevealEncryptedData>) -> Result<()> {
+        instructions::reveal_encrypted_data::handler(ctx)
+    }
+
     pub fn authorize_executor(
         ctx: Context<AuthorizeExecutor>,
         args: AuthorizeExecutorArgs,
@@ -81,6 +155,27 @@ pub mod ghost_bridge {
         instructions::authorize_executor::handler(ctx, args)
     }
 
+    pub fn authorize_executors_batch(
+        ctx: Context<AuthorizeExecutor>,
+        entries: Vec<AuthorizeExecutorArgs>,
+    ) -> Result<()> {
+        instructions::authorize_executor::batch_handler(ctx, entries)
+    }
+
+    pub fn set_allowed_markets(
+        ctx: Context<SetAllowedMarkets>,
+        markets: Vec<AllowedMarket>,
+    ) -> Result<()> {
+        instructions::set_allowed_markets::handler(ctx, markets)
+    }
+
+    pub fn set_max_order_notional(
+        ctx: Context<SetMaxOrderNotional>,
+        max_order_notional: u64,
+    ) -> Result<()> {
+        instructions::set_max_order_notional::handler(ctx, max_order_notional)
+    }
+
     pub fn schedule_encrypted_monitoring(
         ctx: Context<ScheduleEncryptedMonitoring>,
         args: ScheduleEncryptedMonitoringArgs,
@@ -88,7 +183,45 @@ pub mod ghost_bridge {
         instructions::schedule_encrypted_monitoring::handler(ctx, args)
     }
 
+    pub fn schedule_batch_monitoring<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ScheduleBatchMonitoring<'info>>,
+        args: ScheduleBatchMonitoringArgs,
+    ) -> Result<()> {
+        instructions::schedule_encrypted_monitoring::schedule_batch_handler(ctx, args)
+    }
+
     pub fn check_price_update(ctx: Context<CheckPriceUpdate>) -> Result<()> {
         instructions::check_price_update::handler(ctx)
     }
+
+    pub fn check_price_update_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckPriceUpdateBatch<'info>>,
+        feed_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::check_price_update::check_batch_handler(ctx, feed_id)
+    }
+
+    pub fn init_feed_health(ctx: Context<InitFeedHealth>, feed_id: [u8; 32]) -> Result<()> {
+        instructions::feed_health::init_feed_health_handler(ctx, feed_id)
+    }
+
+    pub fn init_bridge_config(ctx: Context<InitBridgeConfig>, admin: Pubkey) -> Result<()> {
+        instructions::bridge_config::init_config_handler(ctx, admin)
+    }
+
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        protocol_fee_bps: u16,
+        fee_vault: Pubkey,
+    ) -> Result<()> {
+        instructions::bridge_config::set_protocol_fee_handler(ctx, protocol_fee_bps, fee_vault)
+    }
+
+    pub fn settle_protocol_fees(ctx: Context<SettleProtocolFees>) -> Result<()> {
+        instructions::bridge_config::settle_protocol_fees_handler(ctx)
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account::handler(ctx)
+    }
 }
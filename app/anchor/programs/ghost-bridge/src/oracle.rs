@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_PRICE_CLAIM_DEVIATION_BPS;
+use crate::errors::GhostBridgeError;
+use crate::pyth::{check_pyth_owner, parse_pyth_price, read_pyth_price_for_feed};
+use crate::state::confidence_bps;
+use crate::switchboard::{
+    check_switchboard_owner, parse_switchboard_price, read_switchboard_price_for_feed,
+};
+use crate::time::TimeSource;
+
+/// Errors the shared `pyth` and `switchboard` parsers raise.
+pub use crate::errors::GhostBridgeError as OracleError;
+
+/// Oracle network an order's feed accounts belong to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum OracleSource {
+    #[default]
+    Pyth = 0,
+    Switchboard = 1,
+}
+
+/// Reads `feed` with the parser for `source`, returning a price at
+/// `PRICE_TARGET_EXPONENT`. Each parser checks the account owner, and the
+/// read is rejected unless `feed` is the one `feed_id` names: the Pyth feed
+/// id of the update, or a Switchboard pull feed's address.
+pub fn read_price(
+    source: OracleSource,
+    feed: &AccountInfo,
+    feed_id: &[u8; 32],
+    now: &impl TimeSource,
+) -> Result<i64> {
+    match source {
+        OracleSource::Pyth => read_pyth_price_for_feed(feed, feed_id, now),
+        OracleSource::Switchboard => read_switchboard_price_for_feed(feed, feed_id, now),
+    }
+}
+
+/// Turns a stale-feed error from `read_price` or `read_confidence_bps` into
+/// `None`, so keeper instructions can skip the order and try again on a
+/// fresh update instead of failing. Every other error is kept.
+pub fn unless_stale<T>(read: Result<T>) -> Result<Option<T>> {
+    match read {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err == OracleError::PriceFeedStale.into() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Confidence band of `feed` in bps of its price, for `FeedHealth`.
+/// Freshness, owner and `feed_id` are checked as in `read_price`; a wide
+/// band is returned, not rejected.
+pub fn read_confidence_bps(
+    source: OracleSource,
+    feed: &AccountInfo,
+    feed_id: &[u8; 32],
+    now: &impl TimeSource,
+) -> Result<u64> {
+    match source {
+        OracleSource::Pyth => {
+            check_pyth_owner(feed)?;
+            let pyth_price = parse_pyth_price(&feed.try_borrow_data()?)?;
+            require!(pyth_price.feed_id == *feed_id, OracleError::InvalidPriceFeed);
+            pyth_price.validate_freshness(now)?;
+            Ok(confidence_bps(pyth_price.price as i128, pyth_price.confidence as i128))
+        }
+        OracleSource::Switchboard => {
+            check_switchboard_owner(feed)?;
+            require!(feed.key.to_bytes() == *feed_id, OracleError::InvalidPriceFeed);
+            let price = parse_switchboard_price(&feed.try_borrow_data()?)?;
+            price.validate_freshness(now)?;
+            Ok(confidence_bps(price.value, price.std_dev))
+        }
+    }
+}
+
+/// Checks a keeper's `claimed` price against the oracle read, returning the
+/// oracle price. Claims further than `MAX_PRICE_CLAIM_DEVIATION_BPS` off are
+/// rejected, so a keeper cannot force a trigger by misreporting.
+pub fn corroborate_price(claimed: i64, oracle_price: i64) -> Result<i64> {
+    let gap = (claimed as i128 - oracle_price as i128).unsigned_abs();
+    let tolerance = oracle_price.unsigned_abs() as u128 * MAX_PRICE_CLAIM_DEVIATION_BPS as u128;
+    if claimed <= 0 || gap * 10_000 > tolerance {
+        msg!("Claimed price {} not corroborated by oracle {}", claimed, oracle_price);
+        return Err(GhostBridgeError::InvalidPriceFeed.into());
+    }
+    Ok(oracle_price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::FEED_DEGRADED_CONFIDENCE_BPS;
+    use crate::pyth::PYTH_RECEIVER_ID;
+    use crate::switchboard::{
+        LAST_UPDATE_TIMESTAMP_OFFSET, PULL_FEED_DISCRIMINATOR, RESULT_STD_DEV_OFFSET,
+        RESULT_VALUE_OFFSET, SWITCHBOARD_ON_DEMAND_ID,
+    };
+    use crate::time::FixedTime;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn pull_feed(value: i128) -> Vec<u8> {
+        let mut data = vec![0u8; 3208];
+        data[..8].copy_from_slice(&PULL_FEED_DISCRIMINATOR);
+        data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&NOW.to_le_bytes());
+        data[RESULT_VALUE_OFFSET..RESULT_VALUE_OFFSET + 16].copy_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_dispatch_reads_switchboard_feed() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &SWITCHBOARD_ON_DEMAND_ID, false, 0,
+        );
+
+        let price =
+            read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &FixedTime::at(NOW)).unwrap();
+        assert_eq!(price, 42_500000);
+    }
+
+    #[test]
+    fn test_dispatch_rejects_owner_of_other_source() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &PYTH_RECEIVER_ID, false, 0,
+        );
+
+        let now = FixedTime::at(NOW);
+
+        assert!(read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &now).is_err());
+        assert!(read_price(OracleSource::Pyth, &feed, &key.to_bytes(), &now).is_err());
+    }
+
+    #[test]
+    fn test_batch_feed_must_be_the_requested_one() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &SWITCHBOARD_ON_DEMAND_ID, false, 0,
+        );
+        let now = FixedTime::at(NOW);
+
+        let price = read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &now);
+        assert_eq!(price.unwrap(), 42_500000);
+
+        // A healthy feed, but not the one the batch names
+        let other = Pubkey::new_unique().to_bytes();
+        assert_eq!(
+            read_price(OracleSource::Switchboard, &feed, &other, &now).unwrap_err(),
+            GhostBridgeError::InvalidPriceFeed.into()
+        );
+    }
+
+    #[test]
+    fn test_stale_feed_reads_as_none() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &SWITCHBOARD_ON_DEMAND_ID, false, 0,
+        );
+        let an_hour_later = FixedTime::at(NOW + 3_600);
+
+        let price = read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &an_hour_later);
+        assert_eq!(unless_stale(price).unwrap(), None);
+        let bps = read_confidence_bps(OracleSource::Switchboard, &feed, &key.to_bytes(), &an_hour_later);
+        assert_eq!(unless_stale(bps).unwrap(), None);
+
+        // Other read failures still fail the call
+        let other = Pubkey::new_unique().to_bytes();
+        let price = read_price(OracleSource::Switchboard, &feed, &other, &FixedTime::at(NOW));
+        assert!(unless_stale(price).is_err());
+    }
+
+    #[test]
+    fn test_wide_band_is_measured_not_rejected() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let value: i128 = 42_500_000_000_000_000_000;
+        let mut data = pull_feed(value);
+        // 5% std-dev: too wide to trade on, but still a reading worth recording
+        data[RESULT_STD_DEV_OFFSET..RESULT_STD_DEV_OFFSET + 16]
+            .copy_from_slice(&(value / 20).to_le_bytes());
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &SWITCHBOARD_ON_DEMAND_ID, false, 0,
+        );
+        let now = FixedTime::at(NOW);
+
+        assert!(read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &now).is_err());
+        let bps = read_confidence_bps(OracleSource::Switchboard, &feed, &key.to_bytes(), &now).unwrap();
+        assert_eq!(bps, 500);
+        assert!(bps > FEED_DEGRADED_CONFIDENCE_BPS);
+    }
+
+    #[test]
+    fn test_lying_keeper_price_rejected() {
+        let oracle_price = 150_000_000;
+
+        // Within tolerance the oracle price is what gets used
+        assert_eq!(corroborate_price(150_500_000, oracle_price).unwrap(), oracle_price);
+        assert_eq!(corroborate_price(149_250_000, oracle_price).unwrap(), oracle_price);
+
+        // A keeper claiming a crash (or a spike) to force a trigger
+        for claimed in [140_000_000, 149_000_000, 151_000_000, 0, -150_000_000] {
+            assert_eq!(
+                corroborate_price(claimed, oracle_price).unwrap_err(),
+                GhostBridgeError::InvalidPriceFeed.into()
+            );
+        }
+    }
+}
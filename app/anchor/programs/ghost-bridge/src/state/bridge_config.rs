@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{BASE_ASSET_PRECISION, MAX_PROTOCOL_FEE_BPS};
+use crate::errors::GhostBridgeError;
+
+/// Program-wide settings the admin controls: the protocol's cut of each
+/// executed order and where it is owed.
+#[account]
+pub struct BridgeConfig {
+    pub admin: Pubkey,
+    /// Fee on order notional, in bps (0 = no fee)
+    pub protocol_fee_bps: u16,
+    /// Quote account the recorded fees are settled into
+    pub fee_vault: Pubkey,
+    pub bump: u8,
+}
+
+impl BridgeConfig {
+    pub const SEED_PREFIX: &'static [u8] = b"bridge_config";
+
+    pub const LEN: usize = 8 +  // discriminator
+        32 +                    // admin
+        2 +                     // protocol_fee_bps
+        32 +                    // fee_vault
+        1;                      // bump
+
+    /// The config stored at `info`, or `None` until `init_bridge_config`
+    /// has run. Executions before then charge no fee.
+    pub fn load_initialized(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+        let data = info.try_borrow_data()?;
+        Ok(Some(BridgeConfig::try_deserialize(&mut &data[..])?))
+    }
+
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admin == *key
+    }
+
+    /// Sets the fee, capped at `MAX_PROTOCOL_FEE_BPS`. A non-zero fee needs
+    /// somewhere to go.
+    pub fn set_protocol_fee(&mut self, protocol_fee_bps: u16, fee_vault: Pubkey) -> Result<()> {
+        require!(
+            protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            GhostBridgeError::ProtocolFeeTooHigh
+        );
+        require!(
+            protocol_fee_bps == 0 || fee_vault != Pubkey::default(),
+            GhostBridgeError::FeeVaultMissing
+        );
+
+        self.protocol_fee_bps = protocol_fee_bps;
+        self.fee_vault = fee_vault;
+        Ok(())
+    }
+
+    /// Fee owed on an order of `base_asset_amount` (base precision) filled
+    /// at `price` (micro-units), in quote micro-units. Rounds down.
+    pub fn protocol_fee(&self, base_asset_amount: u64, price: i64) -> u64 {
+        if self.protocol_fee_bps == 0 || price <= 0 {
+            return 0;
+        }
+
+        let notional = base_asset_amount as u128 * price as u128 / BASE_ASSET_PRECISION as u128;
+        let fee = notional * self.protocol_fee_bps as u128 / 10_000;
+        u64::try_from(fee).unwrap_or(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_config() -> BridgeConfig {
+        BridgeConfig {
+            admin: Pubkey::new_unique(),
+            protocol_fee_bps: 0,
+            fee_vault: Pubkey::default(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_bridge_config_size() {
+        let config = create_test_config();
+        assert_eq!(BridgeConfig::LEN, 8 + config.try_to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn test_configured_fee_on_notional() {
+        let mut config = create_test_config();
+        config.set_protocol_fee(10, Pubkey::new_unique()).unwrap();
+
+        // 2 SOL at $150: $300 notional, 10 bps = $0.30
+        assert_eq!(config.protocol_fee(2_000_000_000, 150_000_000), 300_000);
+        // Rounds down on dust
+        assert_eq!(config.protocol_fee(1, 150_000_000), 0);
+    }
+
+    #[test]
+    fn test_uninitialized_config_loads_as_none() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut empty = [];
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut empty, &crate::ID, false, 0);
+        assert!(BridgeConfig::load_initialized(&info).unwrap().is_none());
+
+        let mut config = create_test_config();
+        config.set_protocol_fee(10, Pubkey::new_unique()).unwrap();
+        let mut data = vec![0u8; BridgeConfig::LEN];
+        config.try_serialize(&mut data.as_mut_slice()).unwrap();
+        let mut lamports = 1;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &crate::ID, false, 0);
+        let loaded = BridgeConfig::load_initialized(&info).unwrap().unwrap();
+        assert_eq!(loaded.protocol_fee_bps, 10);
+        assert_eq!(loaded.fee_vault, config.fee_vault);
+    }
+
+    #[test]
+    fn test_zero_fee_charges_nothing() {
+        let config = create_test_config();
+        assert_eq!(config.protocol_fee(2_000_000_000, 150_000_000), 0);
+    }
+
+    #[test]
+    fn test_fee_is_bounded() {
+        let mut config = create_test_config();
+        let fee_vault = Pubkey::new_unique();
+
+        assert_eq!(
+            config.set_protocol_fee(MAX_PROTOCOL_FEE_BPS + 1, fee_vault).unwrap_err(),
+            GhostBridgeError::ProtocolFeeTooHigh.into()
+        );
+        assert_eq!(
+            config.set_protocol_fee(MAX_PROTOCOL_FEE_BPS, Pubkey::default()).unwrap_err(),
+            GhostBridgeError::FeeVaultMissing.into()
+        );
+        assert_eq!(config.protocol_fee_bps, 0);
+
+        config.set_protocol_fee(MAX_PROTOCOL_FEE_BPS, fee_vault).unwrap();
+        assert_eq!(config.protocol_fee(1_000_000_000, 100_000_000), 500_000);
+
+        // Disabling needs no vault
+        config.set_protocol_fee(0, Pubkey::default()).unwrap();
+        assert_eq!(config.protocol_fee(1_000_000_000, 100_000_000), 0);
+    }
+}
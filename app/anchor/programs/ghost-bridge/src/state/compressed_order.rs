@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::BASE_ASSET_PRECISION;
+use crate::drift_cpi::DriftMarketType;
+use crate::time::TimeSource;
+use crate::trigger::{evaluate_trigger, is_past_expiry};
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u8)]
 pub enum TriggerCondition {
@@ -31,6 +36,7 @@ pub struct CompressedGhostOrder {
     pub owner: Pubkey,
     pub order_id: u64,
     pub market_index: u16,
+    pub market_type: DriftMarketType,
     pub trigger_price: i64,
     pub trigger_condition: TriggerCondition,
     pub order_side: OrderSide,
@@ -41,13 +47,70 @@ pub struct CompressedGhostOrder {
     pub salt: [u8; 16],
 }
 
+/// Full compressed order parameters as submitted by a client, used to
+/// recompute the stored hash for an order that only exists off-chain.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CompressedOrderParams {
+    pub order_id: u64,
+    pub market_index: u16,
+    pub market_type: u8,
+    pub trigger_price: i64,
+    pub trigger_condition: u8,
+    pub order_side: u8,
+    pub base_asset_amount: u64,
+    pub reduce_only: bool,
+    pub expiry: i64,
+    pub feed_id: [u8; 32],
+    pub salt: [u8; 16],
+}
+
+impl CompressedOrderParams {
+    pub fn to_order(&self, owner: Pubkey) -> Result<CompressedGhostOrder> {
+        let trigger_condition = match self.trigger_condition {
+            0 => TriggerCondition::Above,
+            1 => TriggerCondition::Below,
+            _ => return Err(crate::errors::GhostBridgeError::InvalidTriggerCondition.into()),
+        };
+
+        let order_side = match self.order_side {
+            0 => OrderSide::Long,
+            1 => OrderSide::Short,
+            _ => return Err(crate::errors::GhostBridgeError::InvalidOrderData.into()),
+        };
+
+        let market_type = DriftMarketType::from_u8(self.market_type)
+            .ok_or(crate::errors::GhostBridgeError::InvalidMarketType)?;
+
+        Ok(CompressedGhostOrder {
+            owner,
+            order_id: self.order_id,
+            market_index: self.market_index,
+            market_type,
+            trigger_price: self.trigger_price,
+            trigger_condition,
+            order_side,
+            base_asset_amount: self.base_asset_amount,
+            reduce_only: self.reduce_only,
+            expiry: self.expiry,
+            feed_id: self.feed_id,
+            salt: self.salt,
+        })
+    }
+}
+
 impl CompressedGhostOrder {
+    /// Perp orders hash exactly as they did before spot support, so
+    /// commitments made by older clients stay valid; spot orders add a
+    /// market-type byte after the market index.
     pub fn compute_hash(&self) -> [u8; 32] {
-        let mut data = Vec::with_capacity(144);
+        let mut data = Vec::with_capacity(145);
 
         data.extend_from_slice(self.owner.as_ref());
         data.extend_from_slice(&self.order_id.to_le_bytes());
         data.extend_from_slice(&self.market_index.to_le_bytes());
+        if self.market_type != DriftMarketType::Perp {
+            data.push(self.market_type as u8);
+        }
         data.extend_from_slice(&self.trigger_price.to_le_bytes());
         data.push(self.trigger_condition as u8);
         data.push(self.order_side as u8);
@@ -60,21 +123,50 @@ impl CompressedGhostOrder {
         *blake3::hash(&data).as_bytes()
     }
 
+    /// Rejects dust orders that would waste compute and fail on Drift.
+    pub fn validate_size(&self, min_base_asset_amount: u64) -> Result<()> {
+        require!(
+            self.base_asset_amount >= min_base_asset_amount,
+            crate::errors::GhostBridgeError::OrderBelowMinSize
+        );
+        Ok(())
+    }
+
     pub fn check_trigger(&self, current_price: i64) -> bool {
-        match self.trigger_condition {
-            TriggerCondition::Above => current_price >= self.trigger_price,
-            TriggerCondition::Below => current_price <= self.trigger_price,
+        evaluate_trigger(self.trigger_condition, self.trigger_price, current_price)
+    }
+
+    /// Execution price relative to the trigger, in basis points of the
+    /// trigger: positive when the fill was worse for the order's side
+    /// (a long paying more, a short receiving less), negative when better.
+    pub fn realized_slippage_bps(&self, execution_price: i64) -> i32 {
+        if self.trigger_price <= 0 {
+            return 0;
         }
+        let moved = execution_price as i128 - self.trigger_price as i128;
+        let adverse = match self.order_side {
+            OrderSide::Long => moved,
+            OrderSide::Short => -moved,
+        };
+        let bps = adverse * 10_000 / self.trigger_price as i128;
+        bps.clamp(i32::MIN as i128, i32::MAX as i128) as i32
+    }
+
+    /// Order size valued at its trigger price, in quote micro-units.
+    pub fn notional(&self) -> u128 {
+        self.base_asset_amount as u128 * self.trigger_price.max(0) as u128
+            / BASE_ASSET_PRECISION as u128
     }
 
-    pub fn is_expired(&self, current_time: i64) -> bool {
-        self.expiry > 0 && current_time > self.expiry
+    pub fn is_expired(&self, now: &impl TimeSource) -> bool {
+        is_past_expiry(self.expiry, now.unix_timestamp())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::account_version::ACCOUNT_VERSION;
 
     #[test]
     fn test_hash_deterministic() {
@@ -82,6 +174,7 @@ mod tests {
             owner: Pubkey::new_unique(),
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 50000_000000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Short,
@@ -104,6 +197,7 @@ mod tests {
             owner: Pubkey::new_unique(),
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 180_000_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Short,
@@ -144,4 +238,179 @@ mod tests {
         assert!(order_above.check_trigger(50000));
         assert!(order_above.check_trigger(51000));
     }
+
+    #[test]
+    fn test_trigger_matches_shared_table() {
+        for &(trigger_condition, trigger_price, current_price, fires) in crate::trigger::TRIGGER_CASES {
+            let order = CompressedGhostOrder {
+                trigger_price,
+                trigger_condition,
+                ..Default::default()
+            };
+            assert_eq!(order.check_trigger(current_price), fires);
+        }
+    }
+
+    #[test]
+    fn test_cancelled_order_cannot_be_consumed() {
+        use crate::state::{
+            AllowedMarket, ExecutorAuthority, MAX_ALLOWED_MARKETS, MAX_AUTHORIZED_EXECUTORS,
+            MAX_ORDERS_PER_EXECUTOR,
+        };
+
+        let owner = Pubkey::new_unique();
+        let mut executor = ExecutorAuthority {
+            version: ACCOUNT_VERSION,
+            owner,
+            order_count: 0,
+            is_delegated: false,
+            bump: 255,
+            order_hashes: Vec::new(),
+            order_hash_count: 0,
+            authorized_executors: [Pubkey::default(); MAX_AUTHORIZED_EXECUTORS],
+            executor_count: 0,
+            capacity: MAX_ORDERS_PER_EXECUTOR as u16,
+            executor_expires_at: [0; MAX_AUTHORIZED_EXECUTORS],
+            drift_user: Pubkey::default(),
+            previous_owner: Pubkey::default(),
+            allowed_markets: [AllowedMarket::default(); MAX_ALLOWED_MARKETS],
+            allowed_market_count: 0,
+            max_order_notional: 0,
+            protocol_fees_owed: 0,
+        };
+
+        let params = CompressedOrderParams {
+            order_id: 7,
+            market_index: 0,
+            market_type: 1,
+            trigger_price: 180_000_000,
+            trigger_condition: 1,
+            order_side: 0,
+            base_asset_amount: 1_000_000,
+            reduce_only: false,
+            expiry: 0,
+            feed_id: [0u8; 32],
+            salt: [9u8; 16],
+        };
+
+        // create_compressed_order
+        let hash = params.to_order(owner).unwrap().compute_hash();
+        executor.add_order_hash(hash).unwrap();
+
+        // cancel_compressed_order recomputes the same hash from the params
+        let cancel_hash = params.to_order(owner).unwrap().compute_hash();
+        assert!(executor.has_order_hash(&cancel_hash));
+        executor.remove_order_hash(cancel_hash).unwrap();
+
+        // consume_and_execute requires the hash to be present
+        assert!(!executor.has_order_hash(&hash));
+        assert_eq!(
+            executor.remove_order_hash(hash).unwrap_err(),
+            crate::errors::GhostBridgeError::OrderHashNotFound.into()
+        );
+    }
+
+    #[test]
+    fn test_params_reject_invalid_enums() {
+        let params = CompressedOrderParams {
+            order_id: 1,
+            market_index: 0,
+            market_type: 1,
+            trigger_price: 1,
+            trigger_condition: 2,
+            order_side: 0,
+            base_asset_amount: 1,
+            reduce_only: false,
+            expiry: 0,
+            feed_id: [0u8; 32],
+            salt: [0u8; 16],
+        };
+
+        assert!(params.to_order(Pubkey::new_unique()).is_err());
+
+        let bad_market = CompressedOrderParams {
+            trigger_condition: 0,
+            market_type: 2,
+            ..params
+        };
+        assert!(bad_market.to_order(Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_min_order_size() {
+        const MIN_BASE_ASSET_AMOUNT: u64 = 1_000_000;
+
+        let at_minimum = CompressedGhostOrder {
+            base_asset_amount: MIN_BASE_ASSET_AMOUNT,
+            ..Default::default()
+        };
+        assert!(at_minimum.validate_size(MIN_BASE_ASSET_AMOUNT).is_ok());
+
+        let dust = CompressedGhostOrder {
+            base_asset_amount: MIN_BASE_ASSET_AMOUNT - 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            dust.validate_size(MIN_BASE_ASSET_AMOUNT).unwrap_err(),
+            crate::errors::GhostBridgeError::OrderBelowMinSize.into()
+        );
+    }
+
+    #[test]
+    fn test_market_type_affects_hash() {
+        let perp = CompressedGhostOrder {
+            owner: Pubkey::new_unique(),
+            order_id: 1,
+            base_asset_amount: 1_000000,
+            salt: [3u8; 16],
+            ..Default::default()
+        };
+        let spot = CompressedGhostOrder {
+            market_type: DriftMarketType::Spot,
+            ..perp.clone()
+        };
+
+        assert_ne!(perp.compute_hash(), spot.compute_hash());
+    }
+
+    #[test]
+    fn test_expiry_tolerates_clock_drift() {
+        use crate::time::FixedTime;
+        use crate::trigger::EXPIRY_GRACE_SECONDS;
+
+        let order = CompressedGhostOrder {
+            expiry: 1_700_000_000,
+            ..Default::default()
+        };
+
+        assert!(!order.is_expired(&FixedTime::at(1_700_000_000 - EXPIRY_GRACE_SECONDS)));
+        assert!(!order.is_expired(&FixedTime::at(1_700_000_000)));
+        assert!(!order.is_expired(&FixedTime::at(1_700_000_000 + EXPIRY_GRACE_SECONDS)));
+        assert!(order.is_expired(&FixedTime::at(1_700_000_000 + EXPIRY_GRACE_SECONDS + 1)));
+    }
+
+    #[test]
+    fn test_realized_slippage_sign_follows_side() {
+        let long = CompressedGhostOrder {
+            trigger_price: 100_000_000,
+            order_side: OrderSide::Long,
+            ..Default::default()
+        };
+        let short = CompressedGhostOrder {
+            order_side: OrderSide::Short,
+            ..long.clone()
+        };
+
+        // Long filled 0.5% above its trigger: adverse
+        assert_eq!(long.realized_slippage_bps(100_500_000), 50);
+        // Long filled 1.2% below: favorable
+        assert_eq!(long.realized_slippage_bps(98_800_000), -120);
+        // Short filled below its trigger: adverse; above: favorable
+        assert_eq!(short.realized_slippage_bps(99_750_000), 25);
+        assert_eq!(short.realized_slippage_bps(101_000_000), -100);
+        assert_eq!(long.realized_slippage_bps(100_000_000), 0);
+
+        let unpriced = CompressedGhostOrder::default();
+        assert_eq!(unpriced.realized_slippage_bps(100_000_000), 0);
+    }
 }
@@ -1,6 +1,36 @@
 use anchor_lang::prelude::*;
 
-pub const MAX_ENCRYPTED_DATA_LEN: usize = 256;
+use crate::account_version;
+use crate::oracle::OracleSource;
+use crate::state::{CompressedGhostOrder, ExecutorAuthority, MAX_ORDERS_PER_EXECUTOR};
+use crate::time::TimeSource;
+use crate::trigger::{accept_observed_price, is_past_expiry};
+
+/// Upper bound on ciphertext size; accounts are sized to the actual payload.
+pub const MAX_ENCRYPTED_DATA_LEN: usize = 1024;
+
+/// Whether an encrypted order can be created at `account`: it was never
+/// initialized, or the order that held it has been closed.
+pub fn is_vacant_order_account(account: &AccountInfo) -> bool {
+    account.owner == &anchor_lang::system_program::ID && account.data_is_empty()
+}
+
+/// Accepts 0 (use the default) or a budget a transaction can actually grant.
+pub fn validate_compute_units(compute_units: u32) -> Result<()> {
+    require!(
+        compute_units <= crate::constants::MAX_COMPUTE_UNITS,
+        crate::errors::GhostBridgeError::ComputeUnitsTooHigh
+    );
+    Ok(())
+}
+
+/// Orders one scheduled batch check may cover; fits a single transaction.
+pub const MAX_BATCH_MONITORED_ORDERS: usize = MAX_ORDERS_PER_EXECUTOR;
+
+/// Trigger on the primary feed price alone.
+pub const FEED_RELATION_SINGLE: u8 = 0;
+/// Trigger on the spread `feed_id - feed_id_b`.
+pub const FEED_RELATION_SPREAD: u8 = 1;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
 #[repr(u8)]
@@ -10,14 +40,18 @@ pub enum EncryptedOrderStatus {
     Triggered = 1,
     Executed = 2,
     Cancelled = 3,
+    /// Hash registered by `commit_encrypted_order`; waiting on its ciphertext
+    Committed = 4,
 }
 
 #[account]
 pub struct EncryptedOrder {
+    /// Layout version the account was written with; see `account_version`
+    pub version: u8,
     pub owner: Pubkey,
     pub order_hash: [u8; 32],
     pub executor_authority: Pubkey,
-    pub encrypted_data: [u8; MAX_ENCRYPTED_DATA_LEN],
+    pub encrypted_data: Vec<u8>,
     pub data_len: u16,
     pub feed_id: [u8; 32],
     pub created_at: i64,
@@ -26,16 +60,55 @@ pub struct EncryptedOrder {
     pub status: EncryptedOrderStatus,
     pub is_delegated: bool,
     pub bump: u8,
+    pub feed_id_b: [u8; 32],
+    pub relation: u8,
+    pub oracle_source: OracleSource,
+    /// Unix timestamp after which the order is cancelled; 0 never expires
+    pub expiry: i64,
+    /// Sender's ephemeral X25519 key; with the executor's key it derives the
+    /// shared secret that decrypts `encrypted_data` off-chain
+    pub ephemeral_pubkey: [u8; 32],
+    /// AEAD nonce the ciphertext was sealed with
+    pub nonce: [u8; 12],
+    /// `blake3(encrypted_data || order_hash || blake3(commitment_key))`, where
+    /// `commitment_key` is a secret sealed inside the ciphertext; executors
+    /// reveal the key to show they decrypted it
+    pub ciphertext_commitment: [u8; 32],
+    /// Compute budget for the Drift call handler; 0 uses `DRIFT_EXECUTE_COMPUTE_UNITS`
+    pub compute_units: u32,
+    /// Last primary feed price accepted by a check (0 = none yet)
+    pub last_observed_price: i64,
+    /// Reads rejected as price jumps since `last_observed_price` was accepted
+    pub rejected_jumps: u8,
 }
 
-impl EncryptedOrder {
-    pub const SEED_PREFIX: &'static [u8] = b"encrypted_order";
+/// Ciphertext capacity of a version 1 order, stored as a fixed array.
+pub const V1_ENCRYPTED_DATA_LEN: usize = 256;
+
+/// `EncryptedOrder` as stored at layout version 1, before the version byte
+/// and every field after `bump` were added and the ciphertext became a vec.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EncryptedOrderV1 {
+    pub owner: Pubkey,
+    pub order_hash: [u8; 32],
+    pub executor_authority: Pubkey,
+    pub encrypted_data: [u8; V1_ENCRYPTED_DATA_LEN],
+    pub data_len: u16,
+    pub feed_id: [u8; 32],
+    pub created_at: i64,
+    pub triggered_at: i64,
+    pub execution_price: i64,
+    pub status: EncryptedOrderStatus,
+    pub is_delegated: bool,
+    pub bump: u8,
+}
 
+impl EncryptedOrderV1 {
     pub const LEN: usize = 8 +          // discriminator
         32 +                             // owner
         32 +                             // order_hash
         32 +                             // executor_authority
-        MAX_ENCRYPTED_DATA_LEN +         // encrypted_data
+        V1_ENCRYPTED_DATA_LEN +          // encrypted_data
         2 +                              // data_len
         32 +                             // feed_id
         8 +                              // created_at
@@ -45,27 +118,471 @@ impl EncryptedOrder {
         1 +                              // is_delegated
         1;                               // bump
 
+    /// Reads a version 1 order from its raw account data.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(EncryptedOrder::DISCRIMINATOR),
+            crate::errors::GhostBridgeError::UnsupportedAccountVersion
+        );
+        Self::deserialize(&mut &data[account_version::VERSION_OFFSET..])
+            .map_err(|_| error!(crate::errors::GhostBridgeError::UnsupportedAccountVersion))
+    }
+
+    /// The order at the current layout, holding only the used part of the
+    /// ciphertext. The rest starts as for a single Pyth feed that never
+    /// expires. Version 1 stored no ciphertext commitment, so executors
+    /// cannot prove a decryption until the owner re-seals the order with
+    /// `update_encrypted_data`.
+    pub fn upgrade(self) -> EncryptedOrder {
+        let data_len = (self.data_len as usize).min(V1_ENCRYPTED_DATA_LEN);
+
+        EncryptedOrder {
+            version: account_version::ACCOUNT_VERSION,
+            owner: self.owner,
+            order_hash: self.order_hash,
+            executor_authority: self.executor_authority,
+            encrypted_data: self.encrypted_data[..data_len].to_vec(),
+            data_len: data_len as u16,
+            feed_id: self.feed_id,
+            created_at: self.created_at,
+            triggered_at: self.triggered_at,
+            execution_price: self.execution_price,
+            status: self.status,
+            is_delegated: self.is_delegated,
+            bump: self.bump,
+            feed_id_b: [0u8; 32],
+            relation: FEED_RELATION_SINGLE,
+            oracle_source: OracleSource::Pyth,
+            expiry: 0,
+            ephemeral_pubkey: [0u8; 32],
+            nonce: [0u8; 12],
+            ciphertext_commitment: [0u8; 32],
+            compute_units: 0,
+            last_observed_price: 0,
+            rejected_jumps: 0,
+        }
+    }
+}
+
+impl EncryptedOrder {
+    pub const SEED_PREFIX: &'static [u8] = b"encrypted_order";
+
+    /// Account size excluding the ciphertext bytes themselves.
+    pub const HEADER_LEN: usize = 8 +   // discriminator
+        1 +                              // version
+        32 +                             // owner
+        32 +                             // order_hash
+        32 +                             // executor_authority
+        4 +                              // encrypted_data (vec prefix)
+        2 +                              // data_len
+        32 +                             // feed_id
+        8 +                              // created_at
+        8 +                              // triggered_at
+        8 +                              // execution_price
+        1 +                              // status
+        1 +                              // is_delegated
+        1 +                              // bump
+        32 +                             // feed_id_b
+        1 +                              // relation
+        1 +                              // oracle_source
+        8 +                              // expiry
+        32 +                             // ephemeral_pubkey
+        12 +                             // nonce
+        32 +                             // ciphertext_commitment
+        4 +                              // compute_units
+        8 +                              // last_observed_price
+        1;                               // rejected_jumps
+
+    pub const fn space_for(data_len: usize) -> usize {
+        Self::HEADER_LEN + data_len
+    }
+
+    /// Whether the order was written at the layout this program reads;
+    /// older ones go through `migrate_account` first.
+    pub fn is_current_version(&self) -> bool {
+        self.version == account_version::ACCOUNT_VERSION
+    }
+
+    /// Layout version of the raw order `data` stored at `address`.
+    pub fn stored_version(address: &Pubkey, data: &[u8]) -> Option<u8> {
+        account_version::stored_version(data, |fields| {
+            // owner, then order_hash
+            fields.len() >= 64
+                && Pubkey::find_program_address(
+                    &[Self::SEED_PREFIX, &fields[..32], &fields[32..64]],
+                    &crate::ID,
+                )
+                .0 == *address
+        })
+    }
+
     pub fn is_active(&self) -> bool {
         self.status == EncryptedOrderStatus::Active
     }
 
+    /// Attaches the ciphertext to a committed order and activates it. The
+    /// ciphertext must match the commitment made with the hash; only the
+    /// key's hash is needed, so the key itself stays sealed.
+    pub fn reveal(&mut self, encrypted_data: Vec<u8>, commitment_key_hash: &[u8; 32]) -> Result<()> {
+        require!(
+            self.status == EncryptedOrderStatus::Committed,
+            crate::errors::GhostBridgeError::OrderNotCommitted
+        );
+        require!(
+            Self::compute_ciphertext_commitment(&encrypted_data, &self.order_hash, commitment_key_hash)
+                == self.ciphertext_commitment,
+            crate::errors::GhostBridgeError::CiphertextCommitmentMismatch
+        );
+
+        self.set_encrypted_data(encrypted_data)?;
+        self.status = EncryptedOrderStatus::Active;
+        Ok(())
+    }
+
     pub fn is_triggered(&self) -> bool {
         self.status == EncryptedOrderStatus::Triggered
     }
 
+    pub fn is_expired(&self, now: &impl TimeSource) -> bool {
+        is_past_expiry(self.expiry, now.unix_timestamp())
+    }
+
+    /// Cancels an active order that has passed its expiry. Returns whether
+    /// the order was cancelled.
+    pub fn expire_if_stale(&mut self, now: &impl TimeSource) -> bool {
+        if !self.is_active() || !self.is_expired(now) {
+            return false;
+        }
+        self.status = EncryptedOrderStatus::Cancelled;
+        true
+    }
+
+    /// Whether a batch check of `feed_id` for the executor at `executor_key`
+    /// covers this order: active, single-feed, on that feed, and still held
+    /// by the executor. Spread orders keep their own monitoring task.
+    pub fn in_monitoring_batch(
+        &self,
+        executor_key: &Pubkey,
+        executor: &ExecutorAuthority,
+        feed_id: &[u8; 32],
+    ) -> bool {
+        self.is_active()
+            && !self.has_secondary_feed()
+            && self.feed_id == *feed_id
+            && self.executor_authority == *executor_key
+            && executor.has_order_hash(&self.order_hash)
+    }
+
+    /// An all-zero `feed_id_b` means the order watches a single feed.
+    pub fn has_secondary_feed(&self) -> bool {
+        self.feed_id_b != [0u8; 32]
+    }
+
+    pub fn set_secondary_feed(&mut self, feed_id_b: [u8; 32], relation: u8) -> Result<()> {
+        let expected = if feed_id_b == [0u8; 32] {
+            FEED_RELATION_SINGLE
+        } else {
+            FEED_RELATION_SPREAD
+        };
+        require!(relation == expected, crate::errors::GhostBridgeError::InvalidOrderData);
+
+        self.feed_id_b = feed_id_b;
+        self.relation = relation;
+        Ok(())
+    }
+
+    /// Combines the feed prices into the value compared against the trigger price.
+    pub fn observed_price(&self, price_a: i64, price_b: Option<i64>) -> Result<i64> {
+        if !self.has_secondary_feed() {
+            return Ok(price_a);
+        }
+
+        let price_b = price_b.ok_or(crate::errors::GhostBridgeError::InvalidPriceFeed)?;
+        match self.relation {
+            FEED_RELATION_SPREAD => price_a
+                .checked_sub(price_b)
+                .ok_or(crate::errors::GhostBridgeError::InvalidPriceFeed.into()),
+            _ => Err(crate::errors::GhostBridgeError::InvalidOrderData.into()),
+        }
+    }
+
     pub fn get_encrypted_data(&self) -> &[u8] {
-        &self.encrypted_data[..self.data_len as usize]
+        &self.encrypted_data
+    }
+
+    pub fn set_encrypted_data(&mut self, data: Vec<u8>) -> Result<()> {
+        require!(!data.is_empty(), crate::errors::GhostBridgeError::InvalidOrderData);
+        require!(
+            data.len() <= MAX_ENCRYPTED_DATA_LEN,
+            crate::errors::GhostBridgeError::EncryptedDataTooLong
+        );
+
+        self.data_len = data.len() as u16;
+        self.encrypted_data = data;
+        Ok(())
+    }
+
+    /// Records `price` as the latest primary feed read unless it jumped more
+    /// than `MAX_PRICE_JUMP_BPS` from the last accepted one, for at most
+    /// `MAX_REJECTED_JUMPS` reads in a row. Returns whether the read was
+    /// accepted; a rejected read must not be checked.
+    pub fn accept_observed_price(&mut self, price: i64) -> bool {
+        accept_observed_price(&mut self.last_observed_price, &mut self.rejected_jumps, price)
+    }
+
+    pub fn drift_compute_units(&self) -> u32 {
+        if self.compute_units > 0 {
+            self.compute_units
+        } else {
+            crate::constants::DRIFT_EXECUTE_COMPUTE_UNITS
+        }
+    }
+
+    pub fn commitment_key_hash(commitment_key: &[u8; 32]) -> [u8; 32] {
+        *blake3::hash(commitment_key).as_bytes()
+    }
+
+    /// Binds a ciphertext to the hash of the params it encrypts and to the
+    /// hash of the secret key sealed inside it.
+    pub fn compute_ciphertext_commitment(
+        encrypted_data: &[u8],
+        order_hash: &[u8; 32],
+        commitment_key_hash: &[u8; 32],
+    ) -> [u8; 32] {
+        let mut data = Vec::with_capacity(encrypted_data.len() + 64);
+        data.extend_from_slice(encrypted_data);
+        data.extend_from_slice(order_hash);
+        data.extend_from_slice(commitment_key_hash);
+        *blake3::hash(&data).as_bytes()
+    }
+
+    /// Checks the executor's revealed `commitment_key` against the commitment
+    /// stored at creation, over this order's ciphertext and the revealed
+    /// params. Everything else in it is public, so only a party that
+    /// decrypted the ciphertext can pass.
+    pub fn verify_ciphertext_commitment(
+        &self,
+        commitment_key: &[u8; 32],
+        revealed: &CompressedGhostOrder,
+    ) -> Result<()> {
+        let expected = Self::compute_ciphertext_commitment(
+            &self.encrypted_data,
+            &revealed.compute_hash(),
+            &Self::commitment_key_hash(commitment_key),
+        );
+        require!(
+            expected == self.ciphertext_commitment,
+            crate::errors::GhostBridgeError::CiphertextCommitmentMismatch
+        );
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::account_version::{ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+    use crate::trigger::EXPIRY_GRACE_SECONDS;
+
+    const COMMITMENT_KEY: [u8; 32] = [9u8; 32];
+
+    fn commitment(encrypted_data: &[u8], order_hash: &[u8; 32]) -> [u8; 32] {
+        EncryptedOrder::compute_ciphertext_commitment(
+            encrypted_data,
+            order_hash,
+            &EncryptedOrder::commitment_key_hash(&COMMITMENT_KEY),
+        )
+    }
+
+    fn serialized_len(order: &EncryptedOrder) -> usize {
+        let mut buf = Vec::new();
+        order.try_serialize(&mut buf).unwrap();
+        buf.len()
+    }
 
     #[test]
     fn test_account_size() {
-        assert_eq!(EncryptedOrder::LEN, 8 + 32 + 32 + 32 + 256 + 2 + 32 + 8 + 8 + 8 + 1 + 1 + 1);
-        assert_eq!(EncryptedOrder::LEN, 421);
+        assert_eq!(
+            EncryptedOrder::HEADER_LEN,
+            8 + 1 + 32 + 32 + 32 + 4 + 2 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 32 + 1 + 1 + 8 + 32 + 12 + 32 + 4 + 8 + 1
+        );
+        assert_eq!(EncryptedOrder::HEADER_LEN, 301);
+    }
+
+    #[test]
+    fn test_account_sized_to_payload() {
+        let rent = Rent::default();
+
+        let mut small = EncryptedOrder::default();
+        small.set_encrypted_data(vec![7u8; 10]).unwrap();
+        assert_eq!(serialized_len(&small), EncryptedOrder::space_for(10));
+        assert_eq!(small.get_encrypted_data(), &[7u8; 10]);
+
+        let mut large = EncryptedOrder::default();
+        large.set_encrypted_data(vec![9u8; 300]).unwrap();
+        assert_eq!(serialized_len(&large), EncryptedOrder::space_for(300));
+        assert_eq!(large.data_len, 300);
+
+        assert!(
+            rent.minimum_balance(EncryptedOrder::space_for(10))
+                < rent.minimum_balance(EncryptedOrder::space_for(300))
+        );
+    }
+
+    #[test]
+    fn test_compute_units_capped_at_transaction_limit() {
+        use crate::constants::{DRIFT_EXECUTE_COMPUTE_UNITS, MAX_COMPUTE_UNITS};
+
+        let mut order = EncryptedOrder::default();
+        assert_eq!(order.drift_compute_units(), DRIFT_EXECUTE_COMPUTE_UNITS);
+        order.compute_units = MAX_COMPUTE_UNITS;
+        assert_eq!(order.drift_compute_units(), MAX_COMPUTE_UNITS);
+
+        assert!(validate_compute_units(0).is_ok());
+        assert!(validate_compute_units(MAX_COMPUTE_UNITS).is_ok());
+        assert_eq!(
+            validate_compute_units(MAX_COMPUTE_UNITS + 1).unwrap_err(),
+            crate::errors::GhostBridgeError::ComputeUnitsTooHigh.into()
+        );
+    }
+
+    #[test]
+    fn test_price_spike_is_ignored() {
+        let mut order = EncryptedOrder::default();
+
+        assert!(order.accept_observed_price(150_000_000));
+        assert!(order.accept_observed_price(152_000_000));
+        assert_eq!(order.last_observed_price, 152_000_000);
+
+        // 50% spike: dropped, the last good price stays
+        assert!(!order.accept_observed_price(228_000_000));
+        assert_eq!(order.last_observed_price, 152_000_000);
+    }
+
+    #[test]
+    fn test_encryption_metadata_round_trips() {
+        use crate::instructions::create_encrypted_order::EncryptedOrderCreated;
+
+        let mut order = EncryptedOrder {
+            owner: Pubkey::new_unique(),
+            ephemeral_pubkey: [5u8; 32],
+            nonce: [6u8; 12],
+            ..Default::default()
+        };
+        order.set_encrypted_data(vec![1u8; 48]).unwrap();
+
+        let mut buf = Vec::new();
+        order.try_serialize(&mut buf).unwrap();
+        let decoded = EncryptedOrder::try_deserialize(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded.ephemeral_pubkey, [5u8; 32]);
+        assert_eq!(decoded.nonce, [6u8; 12]);
+        assert_eq!(decoded.get_encrypted_data(), &[1u8; 48]);
+
+        let event = EncryptedOrderCreated::for_order(&decoded);
+        assert_eq!(event.owner, order.owner);
+        assert_eq!(event.ephemeral_pubkey, [5u8; 32]);
+        assert_eq!(event.nonce, [6u8; 12]);
+    }
+
+    #[test]
+    fn test_data_event_carries_stored_ciphertext() {
+        use crate::instructions::reveal_encrypted_data::EncryptedOrderData;
+
+        let ciphertext: Vec<u8> = (0..200u16).map(|i| (i % 251) as u8).collect();
+        let mut order = EncryptedOrder {
+            owner: Pubkey::new_unique(),
+            order_hash: [3u8; 32],
+            feed_id: [4u8; 32],
+            ephemeral_pubkey: [5u8; 32],
+            nonce: [6u8; 12],
+            ..Default::default()
+        };
+        order.set_encrypted_data(ciphertext.clone()).unwrap();
+        order.ciphertext_commitment = commitment(&ciphertext, &order.order_hash);
+
+        let mut buf = Vec::new();
+        order.try_serialize(&mut buf).unwrap();
+        let stored = EncryptedOrder::try_deserialize(&mut buf.as_slice()).unwrap();
+
+        let event = EncryptedOrderData::for_order(&stored);
+        assert_eq!(event.encrypted_data, ciphertext);
+        assert_eq!(event.encrypted_data.len(), stored.data_len as usize);
+        assert_eq!(event.owner, order.owner);
+        assert_eq!(event.order_hash, [3u8; 32]);
+        assert_eq!(event.feed_id, [4u8; 32]);
+        assert_eq!(event.ephemeral_pubkey, [5u8; 32]);
+        assert_eq!(event.nonce, [6u8; 12]);
+        assert_eq!(
+            event.ciphertext_commitment,
+            commitment(&event.encrypted_data, &event.order_hash)
+        );
+    }
+
+    #[test]
+    fn test_ciphertext_commitment_accepts_correct_reveal() {
+        let revealed = CompressedGhostOrder {
+            owner: Pubkey::new_unique(),
+            order_id: 3,
+            base_asset_amount: 100_000_000,
+            salt: [4u8; 16],
+            ..Default::default()
+        };
+        let ciphertext = vec![0xabu8; 96];
+        let mut order = EncryptedOrder {
+            order_hash: revealed.compute_hash(),
+            ciphertext_commitment: commitment(&ciphertext, &revealed.compute_hash()),
+            ..Default::default()
+        };
+        order.set_encrypted_data(ciphertext).unwrap();
+
+        order.verify_ciphertext_commitment(&COMMITMENT_KEY, &revealed).unwrap();
+    }
+
+    #[test]
+    fn test_ciphertext_commitment_rejects_forged_key() {
+        let revealed = CompressedGhostOrder {
+            owner: Pubkey::new_unique(),
+            order_id: 3,
+            base_asset_amount: 100_000_000,
+            salt: [4u8; 16],
+            ..Default::default()
+        };
+        let ciphertext = vec![0xabu8; 96];
+        let mut order = EncryptedOrder {
+            order_hash: revealed.compute_hash(),
+            ciphertext_commitment: commitment(&ciphertext, &revealed.compute_hash()),
+            ..Default::default()
+        };
+        order.set_encrypted_data(ciphertext).unwrap();
+
+        // Correct params and everything public on the account, but not the
+        // key sealed in the ciphertext
+        let stored = order.ciphertext_commitment;
+        assert_eq!(
+            order.verify_ciphertext_commitment(&stored, &revealed).unwrap_err(),
+            crate::errors::GhostBridgeError::CiphertextCommitmentMismatch.into()
+        );
+        let forged_key = EncryptedOrder::commitment_key_hash(&COMMITMENT_KEY);
+        assert!(order.verify_ciphertext_commitment(&forged_key, &revealed).is_err());
+
+        // The right key replayed with different params
+        let other = CompressedGhostOrder {
+            salt: [5u8; 16],
+            ..revealed
+        };
+        assert!(order.verify_ciphertext_commitment(&COMMITMENT_KEY, &other).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_data_bounds() {
+        let mut order = EncryptedOrder::default();
+
+        assert!(order.set_encrypted_data(Vec::new()).is_err());
+        assert_eq!(
+            order.set_encrypted_data(vec![0u8; MAX_ENCRYPTED_DATA_LEN + 1]).unwrap_err(),
+            crate::errors::GhostBridgeError::EncryptedDataTooLong.into()
+        );
+        order.set_encrypted_data(vec![0u8; MAX_ENCRYPTED_DATA_LEN]).unwrap();
     }
 
     #[test]
@@ -77,15 +594,316 @@ mod tests {
         assert!(order.is_active());
         assert!(!order.is_triggered());
     }
+
+    #[test]
+    fn test_expiry_cancels_active_order() {
+        use crate::time::FixedTime;
+
+        let mut order = EncryptedOrder {
+            expiry: 1_700_000_000,
+            ..Default::default()
+        };
+
+        assert!(!order.expire_if_stale(&FixedTime::at(1_700_000_000)));
+        // Still live within the clock-drift grace
+        assert!(!order.expire_if_stale(&FixedTime::at(1_700_000_000 + EXPIRY_GRACE_SECONDS)));
+        assert!(order.is_active());
+
+        let past_grace = 1_700_000_000 + EXPIRY_GRACE_SECONDS + 1;
+        assert!(order.expire_if_stale(&FixedTime::at(past_grace)));
+        assert_eq!(order.status, EncryptedOrderStatus::Cancelled);
+        assert!(!order.expire_if_stale(&FixedTime::at(past_grace + 1)));
+    }
+
+    #[test]
+    fn test_zero_expiry_never_expires() {
+        use crate::time::FixedTime;
+
+        let mut order = EncryptedOrder::default();
+        assert!(!order.expire_if_stale(&FixedTime::at(i64::MAX)));
+        assert!(order.is_active());
+
+        order.status = EncryptedOrderStatus::Executed;
+        order.expiry = 1;
+        assert!(!order.expire_if_stale(&FixedTime::at(i64::MAX)));
+        assert_eq!(order.status, EncryptedOrderStatus::Executed);
+    }
+
+    #[test]
+    fn test_single_feed_ignores_secondary_price() {
+        let order = EncryptedOrder::default();
+
+        assert!(!order.has_secondary_feed());
+        assert_eq!(order.observed_price(150_000_000, None).unwrap(), 150_000_000);
+        assert_eq!(order.observed_price(150_000_000, Some(1)).unwrap(), 150_000_000);
+    }
+
+    #[test]
+    fn test_secondary_feed_relation_validation() {
+        let mut order = EncryptedOrder::default();
+
+        assert!(order.set_secondary_feed([0u8; 32], FEED_RELATION_SPREAD).is_err());
+        assert!(order.set_secondary_feed([2u8; 32], FEED_RELATION_SINGLE).is_err());
+        assert!(order.set_secondary_feed([2u8; 32], 7).is_err());
+
+        order.set_secondary_feed([0u8; 32], FEED_RELATION_SINGLE).unwrap();
+        assert!(!order.has_secondary_feed());
+
+        order.set_secondary_feed([2u8; 32], FEED_RELATION_SPREAD).unwrap();
+        assert!(order.has_secondary_feed());
+    }
+
+    #[test]
+    fn test_spread_trigger() {
+        use crate::state::{CompressedGhostOrder, TriggerCondition};
+
+        let mut order = EncryptedOrder::default();
+        order.set_secondary_feed([2u8; 32], FEED_RELATION_SPREAD).unwrap();
+
+        // Fire when feed A trades more than 5_000 above feed B.
+        let widening = CompressedGhostOrder {
+            trigger_price: 5_000,
+            trigger_condition: TriggerCondition::Above,
+            ..Default::default()
+        };
+        let spread = order.observed_price(105_000, Some(101_000)).unwrap();
+        assert_eq!(spread, 4_000);
+        assert!(!widening.check_trigger(spread));
+        let spread = order.observed_price(107_000, Some(101_000)).unwrap();
+        assert!(widening.check_trigger(spread));
+
+        // Negative spreads compare naturally against a negative threshold.
+        let inverted = CompressedGhostOrder {
+            trigger_price: -2_000,
+            trigger_condition: TriggerCondition::Below,
+            ..Default::default()
+        };
+        let spread = order.observed_price(99_000, Some(100_000)).unwrap();
+        assert!(!inverted.check_trigger(spread));
+        let spread = order.observed_price(97_000, Some(100_000)).unwrap();
+        assert!(inverted.check_trigger(spread));
+
+        assert!(order.observed_price(100_000, None).is_err());
+    }
+
+    fn test_executor() -> ExecutorAuthority {
+        ExecutorAuthority {
+            version: ACCOUNT_VERSION,
+            owner: Pubkey::new_unique(),
+            order_count: 0,
+            is_delegated: false,
+            bump: 255,
+            order_hashes: Vec::new(),
+            order_hash_count: 0,
+            authorized_executors: [Pubkey::default(); 4],
+            executor_count: 0,
+            capacity: MAX_ORDERS_PER_EXECUTOR as u16,
+            executor_expires_at: [0; 4],
+            drift_user: Pubkey::new_unique(),
+            previous_owner: Pubkey::default(),
+            allowed_markets: [crate::state::AllowedMarket::default(); crate::state::MAX_ALLOWED_MARKETS],
+            allowed_market_count: 0,
+            max_order_notional: 0,
+            protocol_fees_owed: 0,
+        }
+    }
+
+    #[test]
+    fn test_one_batch_covers_orders_on_feed() {
+        let executor_key = Pubkey::new_unique();
+        let mut executor = test_executor();
+        let feed = [7u8; 32];
+        let order = |hash: u8, feed_id: [u8; 32]| EncryptedOrder {
+            order_hash: [hash; 32],
+            executor_authority: executor_key,
+            feed_id,
+            ..Default::default()
+        };
+
+        let mut orders = vec![order(1, feed), order(2, feed), order(3, feed), order(4, [8u8; 32])];
+        let mut spread = order(5, feed);
+        spread.set_secondary_feed([9u8; 32], FEED_RELATION_SPREAD).unwrap();
+        let mut cancelled = order(6, feed);
+        cancelled.status = EncryptedOrderStatus::Cancelled;
+        orders.extend([spread, cancelled]);
+        for o in &orders {
+            executor.add_order_hash(o.order_hash).unwrap();
+        }
+        // Executed and removed from the executor, but still Active on the account
+        orders.push(order(7, feed));
+
+        let covered: Vec<u8> = orders
+            .iter()
+            .filter(|o| o.in_monitoring_batch(&executor_key, &executor, &feed))
+            .map(|o| o.order_hash[0])
+            .collect();
+        assert_eq!(covered, vec![1, 2, 3]);
+
+        // Another executor's task never picks these orders up
+        assert!(!orders[0].in_monitoring_batch(&Pubkey::new_unique(), &executor, &feed));
+    }
+
+    #[test]
+    fn test_only_vacant_addresses_take_new_orders() {
+        let key = Pubkey::new_unique();
+        let system_program = anchor_lang::system_program::ID;
+
+        let mut lamports = 0;
+        let mut data = [];
+        let fresh = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &system_program, false, 0);
+        assert!(is_vacant_order_account(&fresh));
+
+        // Closed, then pre-funded by someone else
+        let mut lamports = 5_000;
+        let mut data = [];
+        let closed = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &system_program, false, 0);
+        assert!(is_vacant_order_account(&closed));
+
+        // Still holding an earlier order with the same hash
+        let mut lamports = 5_000;
+        let mut data = vec![0u8; EncryptedOrder::space_for(64)];
+        let open = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert!(!is_vacant_order_account(&open));
+    }
+
+    #[test]
+    fn test_closed_order_hash_can_be_recreated() {
+        use crate::time::FixedTime;
+
+        let mut executor = test_executor();
+        let mut order = EncryptedOrder {
+            order_hash: [1u8; 32],
+            expiry: 1_700_000_000,
+            ..Default::default()
+        };
+        executor.add_order_hash(order.order_hash).unwrap();
+
+        // Expiry cancels the order but leaves its hash registered
+        assert!(order.expire_if_stale(&FixedTime::at(1_700_000_100)));
+        assert_eq!(
+            executor.add_order_hash(order.order_hash).unwrap_err(),
+            crate::errors::GhostBridgeError::OrderHashExists.into()
+        );
+
+        // Closing frees it, as `close_encrypted_order` does
+        assert!(executor.has_order_hash(&order.order_hash));
+        executor.remove_order_hash(order.order_hash).unwrap();
+        executor.add_order_hash(order.order_hash).unwrap();
+        assert_eq!(executor.order_hash_count, 1);
+    }
+
+    #[test]
+    fn test_commit_then_reveal() {
+        let ciphertext = vec![0x5au8; 80];
+        let order_hash = [3u8; 32];
+        let mut order = EncryptedOrder {
+            order_hash,
+            ciphertext_commitment: commitment(&ciphertext, &order_hash),
+            status: EncryptedOrderStatus::Committed,
+            ..Default::default()
+        };
+
+        // Committed orders hold no payload and are not monitored
+        assert_eq!(order.data_len, 0);
+        assert!(!order.is_active());
+
+        let key_hash = EncryptedOrder::commitment_key_hash(&COMMITMENT_KEY);
+        order.reveal(ciphertext.clone(), &key_hash).unwrap();
+        assert!(order.is_active());
+        assert_eq!(order.encrypted_data, ciphertext);
+        assert_eq!(order.data_len, 80);
+
+        // Revealing happens once
+        assert_eq!(
+            order.reveal(ciphertext, &key_hash).unwrap_err(),
+            crate::errors::GhostBridgeError::OrderNotCommitted.into()
+        );
+    }
+
+    #[test]
+    fn test_reveal_rejects_uncommitted_ciphertext() {
+        let order_hash = [3u8; 32];
+        let mut order = EncryptedOrder {
+            order_hash,
+            ciphertext_commitment: commitment(&[0x5au8; 80], &order_hash),
+            status: EncryptedOrderStatus::Committed,
+            ..Default::default()
+        };
+
+        let key_hash = EncryptedOrder::commitment_key_hash(&COMMITMENT_KEY);
+        assert_eq!(
+            order.reveal(vec![0x5bu8; 80], &key_hash).unwrap_err(),
+            crate::errors::GhostBridgeError::CiphertextCommitmentMismatch.into()
+        );
+        assert_eq!(order.status, EncryptedOrderStatus::Committed);
+        assert!(order.encrypted_data.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_order_detected_and_upgraded() {
+        let mut encrypted_data = [0u8; V1_ENCRYPTED_DATA_LEN];
+        encrypted_data[..40].copy_from_slice(&[6u8; 40]);
+        let legacy = EncryptedOrderV1 {
+            owner: Pubkey::new_unique(),
+            order_hash: [4u8; 32],
+            executor_authority: Pubkey::new_unique(),
+            encrypted_data,
+            data_len: 40,
+            feed_id: [5u8; 32],
+            created_at: 1_700_000_000,
+            triggered_at: 0,
+            execution_price: 0,
+            status: EncryptedOrderStatus::Active,
+            is_delegated: false,
+            bump: 251,
+        };
+        let (address, _) = Pubkey::find_program_address(
+            &[EncryptedOrder::SEED_PREFIX, legacy.owner.as_ref(), &legacy.order_hash],
+            &crate::ID,
+        );
+
+        // Serialized with the version 1 layout
+        let mut data = EncryptedOrder::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), EncryptedOrderV1::LEN);
+        assert_eq!(EncryptedOrder::stored_version(&address, &data), Some(LEGACY_ACCOUNT_VERSION));
+
+        let mut upgraded = vec![0u8; EncryptedOrder::space_for(40)];
+        EncryptedOrderV1::from_account_data(&data)
+            .unwrap()
+            .upgrade()
+            .try_serialize(&mut upgraded.as_mut_slice())
+            .unwrap();
+        assert_eq!(EncryptedOrder::stored_version(&address, &upgraded), Some(ACCOUNT_VERSION));
+        assert_eq!(EncryptedOrder::stored_version(&Pubkey::new_unique(), &upgraded), None);
+
+        let order = EncryptedOrder::try_deserialize(&mut upgraded.as_slice()).unwrap();
+        assert!(order.is_current_version());
+        assert_eq!(order.owner, legacy.owner);
+        assert_eq!(order.order_hash, [4u8; 32]);
+        assert_eq!(order.executor_authority, legacy.executor_authority);
+        assert_eq!(order.get_encrypted_data(), &[6u8; 40]);
+        assert_eq!(order.feed_id, [5u8; 32]);
+        assert_eq!(order.created_at, 1_700_000_000);
+        assert_eq!(order.status, EncryptedOrderStatus::Active);
+        assert_eq!(order.bump, 251);
+        assert_eq!(order.relation, FEED_RELATION_SINGLE);
+
+        // No commitment was stored, so no key can prove a decryption yet
+        assert!(order
+            .verify_ciphertext_commitment(&[0u8; 32], &CompressedGhostOrder::default())
+            .is_err());
+    }
 }
 
 impl Default for EncryptedOrder {
     fn default() -> Self {
         Self {
+            version: account_version::ACCOUNT_VERSION,
             owner: Pubkey::default(),
             order_hash: [0u8; 32],
             executor_authority: Pubkey::default(),
-            encrypted_data: [0u8; MAX_ENCRYPTED_DATA_LEN],
+            encrypted_data: Vec::new(),
             data_len: 0,
             feed_id: [0u8; 32],
             created_at: 0,
@@ -94,6 +912,16 @@ impl Default for EncryptedOrder {
             status: EncryptedOrderStatus::Active,
             is_delegated: false,
             bump: 0,
+            feed_id_b: [0u8; 32],
+            relation: FEED_RELATION_SINGLE,
+            oracle_source: OracleSource::Pyth,
+            expiry: 0,
+            ephemeral_pubkey: [0u8; 32],
+            nonce: [0u8; 12],
+            ciphertext_commitment: [0u8; 32],
+            compute_units: 0,
+            last_observed_price: 0,
+            rejected_jumps: 0,
         }
     }
 }
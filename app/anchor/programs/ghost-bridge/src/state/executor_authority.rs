@@ -1,24 +1,80 @@
 use anchor_lang::prelude::*;
 
+use crate::account_version;
+use crate::drift_cpi::DriftMarketType;
+use crate::state::CompressedGhostOrder;
+use crate::time::TimeSource;
+
+/// Initial order hash capacity; also the chunk size `grow_capacity` adds.
 pub const MAX_ORDERS_PER_EXECUTOR: usize = 16;
 
+/// Hard ceiling on order hash capacity after growth.
+pub const MAX_ORDER_CAPACITY: usize = 128;
+
 pub const MAX_AUTHORIZED_EXECUTORS: usize = 4;
 
+/// Most Drift markets an owner can restrict executors to.
+pub const MAX_ALLOWED_MARKETS: usize = 8;
+
+/// A Drift market. Spot and perp markets are indexed separately, so an
+/// index alone does not name one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct AllowedMarket {
+    pub market_type: DriftMarketType,
+    pub market_index: u16,
+}
+
+impl AllowedMarket {
+    pub const LEN: usize = 1 + 2;
+}
+
 #[account]
 pub struct ExecutorAuthority {
+    /// Layout version the account was written with; see `account_version`
+    pub version: u8,
     pub owner: Pubkey,
     pub order_count: u64,
     pub is_delegated: bool,
     pub bump: u8,
-    pub order_hashes: [[u8; 32]; MAX_ORDERS_PER_EXECUTOR],
+    /// Kept sorted so lookups can binary-search.
+    pub order_hashes: Vec<[u8; 32]>,
     pub order_hash_count: u8,
     pub authorized_executors: [Pubkey; MAX_AUTHORIZED_EXECUTORS],
     pub executor_count: u8,
+    pub capacity: u16,
+    /// Expiry per `authorized_executors` slot; 0 means the grant never expires.
+    pub executor_expires_at: [i64; MAX_AUTHORIZED_EXECUTORS],
+    /// Drift user account that orders from this executor must trade against.
+    pub drift_user: Pubkey,
+    /// Owner the authority was rotated away from; hashes it committed to
+    /// still resolve. Default when never rotated.
+    pub previous_owner: Pubkey,
+    /// Markets executors may trade; none set allows every market.
+    pub allowed_markets: [AllowedMarket; MAX_ALLOWED_MARKETS],
+    pub allowed_market_count: u8,
+    /// Largest order notional executors may place, in quote micro-units; 0 is unlimited.
+    pub max_order_notional: u64,
+    /// Protocol fees accrued by executions and not yet paid to the fee vault,
+    /// in quote micro-units. No new orders are taken while any is owed.
+    pub protocol_fees_owed: u64,
 }
 
-impl ExecutorAuthority {
-    pub const SEED_PREFIX: &'static [u8] = b"executor";
+/// `ExecutorAuthority` as stored at layout version 1, before the version
+/// byte and every field after `executor_count` were added and the order
+/// hashes became a sorted vec.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ExecutorAuthorityV1 {
+    pub owner: Pubkey,
+    pub order_count: u64,
+    pub is_delegated: bool,
+    pub bump: u8,
+    pub order_hashes: [[u8; 32]; MAX_ORDERS_PER_EXECUTOR],
+    pub order_hash_count: u8,
+    pub authorized_executors: [Pubkey; MAX_AUTHORIZED_EXECUTORS],
+    pub executor_count: u8,
+}
 
+impl ExecutorAuthorityV1 {
     pub const LEN: usize = 8 +      // discriminator
         32 +                         // owner
         8 +                          // order_count
@@ -29,19 +85,119 @@ impl ExecutorAuthority {
         (32 * MAX_AUTHORIZED_EXECUTORS) + // authorized_executors (4 * 32 = 128)
         1;                           // executor_count
 
+    /// Reads a version 1 authority from its raw account data.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(ExecutorAuthority::DISCRIMINATOR),
+            crate::errors::GhostBridgeError::UnsupportedAccountVersion
+        );
+        Self::deserialize(&mut &data[account_version::VERSION_OFFSET..])
+            .map_err(|_| error!(crate::errors::GhostBridgeError::UnsupportedAccountVersion))
+    }
+
+    /// The authority at the current layout, with its stored hashes sorted
+    /// and room for the version 1 maximum. Grants never expire and every
+    /// market is allowed, as before; executions must trade against the
+    /// owner's first Drift sub-account.
+    pub fn upgrade(self) -> ExecutorAuthority {
+        let hash_count = (self.order_hash_count as usize).min(MAX_ORDERS_PER_EXECUTOR);
+        let mut order_hashes = self.order_hashes[..hash_count].to_vec();
+        order_hashes.sort();
+        let (drift_user, _) = Pubkey::find_program_address(
+            &[b"user", self.owner.as_ref(), &0u16.to_le_bytes()],
+            &crate::drift_cpi::DRIFT_PROGRAM_ID,
+        );
+
+        ExecutorAuthority {
+            version: account_version::ACCOUNT_VERSION,
+            owner: self.owner,
+            order_count: self.order_count,
+            is_delegated: self.is_delegated,
+            bump: self.bump,
+            order_hashes,
+            order_hash_count: hash_count as u8,
+            authorized_executors: self.authorized_executors,
+            executor_count: self.executor_count,
+            capacity: MAX_ORDERS_PER_EXECUTOR as u16,
+            executor_expires_at: [0; MAX_AUTHORIZED_EXECUTORS],
+            drift_user,
+            previous_owner: Pubkey::default(),
+            allowed_markets: [AllowedMarket::default(); MAX_ALLOWED_MARKETS],
+            allowed_market_count: 0,
+            max_order_notional: 0,
+            protocol_fees_owed: 0,
+        }
+    }
+}
+
+impl ExecutorAuthority {
+    pub const SEED_PREFIX: &'static [u8] = b"executor";
+
+    pub const LEN: usize = Self::space_for(MAX_ORDERS_PER_EXECUTOR);
+
+    /// Whether the authority was written at the layout this program reads;
+    /// older ones go through `migrate_account` first.
+    pub fn is_current_version(&self) -> bool {
+        self.version == account_version::ACCOUNT_VERSION
+    }
+
+    /// Layout version of the raw authority `data` stored at `address`.
+    pub fn stored_version(address: &Pubkey, data: &[u8]) -> Option<u8> {
+        account_version::stored_version(data, |fields| {
+            fields.len() >= 32
+                && Pubkey::find_program_address(&[Self::SEED_PREFIX, &fields[..32]], &crate::ID).0
+                    == *address
+        })
+    }
+
+    pub const fn space_for(capacity: usize) -> usize {
+        8 +                          // discriminator
+        1 +                          // version
+        32 +                         // owner
+        8 +                          // order_count
+        1 +                          // is_delegated
+        1 +                          // bump
+        4 + (32 * capacity) +        // order_hashes (vec prefix + capacity * 32)
+        1 +                          // order_hash_count
+        (32 * MAX_AUTHORIZED_EXECUTORS) + // authorized_executors (4 * 32 = 128)
+        1 +                          // executor_count
+        2 +                          // capacity
+        (8 * MAX_AUTHORIZED_EXECUTORS) + // executor_expires_at (4 * 8 = 32)
+        32 +                         // drift_user
+        32 +                         // previous_owner
+        (AllowedMarket::LEN * MAX_ALLOWED_MARKETS) + // allowed_markets (8 * 3 = 24)
+        1 +                          // allowed_market_count
+        8 +                          // max_order_notional
+        8                            // protocol_fees_owed
+    }
+
+    pub fn grow_capacity(&mut self) -> Result<()> {
+        let new_capacity = self.capacity as usize + MAX_ORDERS_PER_EXECUTOR;
+        require!(
+            new_capacity <= MAX_ORDER_CAPACITY,
+            crate::errors::GhostBridgeError::MaxCapacityReached
+        );
+
+        self.capacity = new_capacity as u16;
+        Ok(())
+    }
+
     pub fn add_order_hash(&mut self, hash: [u8; 32]) -> Result<()> {
         require!(
-            (self.order_hash_count as usize) < MAX_ORDERS_PER_EXECUTOR,
+            self.protocol_fees_owed == 0,
+            crate::errors::GhostBridgeError::ProtocolFeesOwed
+        );
+        require!(
+            (self.order_hash_count as usize) < self.capacity as usize,
             crate::errors::GhostBridgeError::MaxOrdersReached
         );
 
-        for i in 0..self.order_hash_count as usize {
-            if self.order_hashes[i] == hash {
-                return Err(crate::errors::GhostBridgeError::OrderHashExists.into());
-            }
-        }
+        let idx = match self.order_hashes.binary_search(&hash) {
+            Ok(_) => return Err(crate::errors::GhostBridgeError::OrderHashExists.into()),
+            Err(idx) => idx,
+        };
 
-        self.order_hashes[self.order_hash_count as usize] = hash;
+        self.order_hashes.insert(idx, hash);
         self.order_hash_count += 1;
         self.order_count += 1;
 
@@ -49,67 +205,209 @@ impl ExecutorAuthority {
     }
 
     pub fn remove_order_hash(&mut self, hash: [u8; 32]) -> Result<()> {
-        let mut found_index: Option<usize> = None;
-
-        for i in 0..self.order_hash_count as usize {
-            if self.order_hashes[i] == hash {
-                found_index = Some(i);
-                break;
-            }
-        }
-
-        match found_index {
-            Some(idx) => {
-                for i in idx..(self.order_hash_count as usize - 1) {
-                    self.order_hashes[i] = self.order_hashes[i + 1];
-                }
-                self.order_hashes[self.order_hash_count as usize - 1] = [0u8; 32];
+        match self.order_hashes.binary_search(&hash) {
+            Ok(idx) => {
+                self.order_hashes.remove(idx);
                 self.order_hash_count -= 1;
                 Ok(())
             }
-            None => Err(crate::errors::GhostBridgeError::OrderHashNotFound.into()),
+            Err(_) => Err(crate::errors::GhostBridgeError::OrderHashNotFound.into()),
         }
     }
 
     pub fn has_order_hash(&self, hash: &[u8; 32]) -> bool {
-        for i in 0..self.order_hash_count as usize {
-            if &self.order_hashes[i] == hash {
-                return true;
+        self.order_hashes.binary_search(hash).is_ok()
+    }
+
+    /// Hash under which `order` is stored: committed to its own owner, or,
+    /// for orders carried over by a rotation, to `previous_owner`.
+    pub fn find_order_hash(&self, order: &CompressedGhostOrder) -> Option<[u8; 32]> {
+        let hash = order.compute_hash();
+        if self.has_order_hash(&hash) {
+            return Some(hash);
+        }
+        if self.previous_owner == Pubkey::default() {
+            return None;
+        }
+
+        let migrated = CompressedGhostOrder {
+            owner: self.previous_owner,
+            ..order.clone()
+        };
+        let hash = migrated.compute_hash();
+        self.has_order_hash(&hash).then_some(hash)
+    }
+
+    /// Copy of this authority handed to `new_owner`, for the PDA derived from
+    /// the new key. Order hashes and executor grants carry over; only the
+    /// most recent previous owner's hashes stay resolvable.
+    pub fn rotated(&self, new_owner: Pubkey, drift_user: Pubkey, bump: u8) -> Self {
+        Self {
+            owner: new_owner,
+            bump,
+            drift_user,
+            previous_owner: self.owner,
+            ..self.clone()
+        }
+    }
+
+    /// Removes every order in `orders` this authority still tracks, returning
+    /// the order id and hash of each one removed. Orders that were never
+    /// stored, or are already gone, are skipped.
+    pub fn remove_orders(&mut self, orders: &[CompressedGhostOrder]) -> Vec<(u64, [u8; 32])> {
+        let mut removed = Vec::new();
+        for order in orders {
+            if let Some(order_hash) = self.find_order_hash(order) {
+                if self.remove_order_hash(order_hash).is_ok() {
+                    removed.push((order.order_id, order_hash));
+                }
             }
         }
-        false
+        removed
+    }
+
+    /// Removes the hash of an expired compressed order, returning it.
+    pub fn reclaim_expired_order(
+        &mut self,
+        order: &CompressedGhostOrder,
+        now: &impl TimeSource,
+    ) -> Result<[u8; 32]> {
+        require!(
+            order.is_expired(now),
+            crate::errors::GhostBridgeError::OrderNotExpired
+        );
+
+        let order_hash = self
+            .find_order_hash(order)
+            .ok_or(crate::errors::GhostBridgeError::OrderHashNotFound)?;
+        self.remove_order_hash(order_hash)?;
+        Ok(order_hash)
+    }
+
+    pub fn accrue_protocol_fee(&mut self, fee: u64) {
+        self.protocol_fees_owed = self.protocol_fees_owed.saturating_add(fee);
+    }
+
+    /// Clears the owed fees once paid, returning the amount to transfer.
+    pub fn take_protocol_fees_owed(&mut self) -> u64 {
+        std::mem::take(&mut self.protocol_fees_owed)
+    }
+
+    /// Replaces the market allowlist; an empty slice allows every market.
+    pub fn set_allowed_markets(&mut self, markets: &[AllowedMarket]) -> Result<()> {
+        require!(
+            markets.len() <= MAX_ALLOWED_MARKETS,
+            crate::errors::GhostBridgeError::TooManyAllowedMarkets
+        );
+
+        self.allowed_markets = [AllowedMarket::default(); MAX_ALLOWED_MARKETS];
+        self.allowed_markets[..markets.len()].copy_from_slice(markets);
+        self.allowed_market_count = markets.len() as u8;
+        Ok(())
+    }
+
+    pub fn is_market_allowed(&self, market_type: DriftMarketType, market_index: u16) -> bool {
+        let market = AllowedMarket { market_type, market_index };
+        self.allowed_market_count == 0
+            || self.allowed_markets[..self.allowed_market_count as usize].contains(&market)
+    }
+
+    /// Keeps executors, owner included, to the markets the owner allowed.
+    pub fn require_market_allowed(&self, market_type: DriftMarketType, market_index: u16) -> Result<()> {
+        require!(
+            self.is_market_allowed(market_type, market_index),
+            crate::errors::GhostBridgeError::MarketNotAllowed
+        );
+        Ok(())
+    }
+
+    pub fn require_within_max_notional(&self, order: &CompressedGhostOrder) -> Result<()> {
+        require!(
+            self.max_order_notional == 0 || order.notional() <= self.max_order_notional as u128,
+            crate::errors::GhostBridgeError::ExceedsMaxNotional
+        );
+        Ok(())
+    }
+
+    /// Rejects Drift accounts that belong to anyone other than the owner.
+    pub fn verify_drift_accounts(&self, drift_user: &Pubkey, drift_authority: &Pubkey) -> Result<()> {
+        require_keys_eq!(
+            *drift_authority,
+            self.owner,
+            crate::errors::GhostBridgeError::DriftAuthorityMismatch
+        );
+        require_keys_eq!(
+            *drift_user,
+            self.drift_user,
+            crate::errors::GhostBridgeError::DriftUserMismatch
+        );
+        Ok(())
     }
 
     pub fn is_empty(&self) -> bool {
         self.order_hash_count == 0
     }
 
-    pub fn is_authorized_executor(&self, executor: &Pubkey) -> bool {
+    pub fn is_authorized_executor(&self, executor: &Pubkey, now: &impl TimeSource) -> bool {
         for i in 0..self.executor_count as usize {
             if &self.authorized_executors[i] == executor {
-                return true;
+                let expires_at = self.executor_expires_at[i];
+                return expires_at == 0 || now.unix_timestamp() < expires_at;
             }
         }
         false
     }
 
-    pub fn add_authorized_executor(&mut self, executor: Pubkey) -> Result<()> {
-        require!(
-            (self.executor_count as usize) < MAX_AUTHORIZED_EXECUTORS,
-            crate::errors::GhostBridgeError::MaxExecutorsReached
-        );
-
+    /// Authorizes `executor` until `expires_at` (0 = no expiry). Re-authorizing
+    /// an existing executor replaces its expiry.
+    pub fn add_authorized_executor(&mut self, executor: Pubkey, expires_at: i64) -> Result<()> {
         for i in 0..self.executor_count as usize {
             if self.authorized_executors[i] == executor {
+                self.executor_expires_at[i] = expires_at;
                 return Ok(());
             }
         }
 
+        require!(
+            (self.executor_count as usize) < MAX_AUTHORIZED_EXECUTORS,
+            crate::errors::GhostBridgeError::MaxExecutorsReached
+        );
+
         self.authorized_executors[self.executor_count as usize] = executor;
+        self.executor_expires_at[self.executor_count as usize] = expires_at;
         self.executor_count += 1;
         Ok(())
     }
 
+    /// Applies `(executor, authorize, expires_at)` changes in order. The whole
+    /// batch is checked against `MAX_AUTHORIZED_EXECUTORS` before anything is modified.
+    pub fn apply_executor_batch(&mut self, changes: &[(Pubkey, bool, i64)]) -> Result<()> {
+        let mut staged = self.authorized_executors[..self.executor_count as usize].to_vec();
+        for (executor, authorize, _) in changes {
+            if *authorize {
+                if !staged.contains(executor) {
+                    require!(
+                        staged.len() < MAX_AUTHORIZED_EXECUTORS,
+                        crate::errors::GhostBridgeError::MaxExecutorsReached
+                    );
+                    staged.push(*executor);
+                }
+            } else {
+                staged.retain(|e| e != executor);
+            }
+        }
+
+        for (executor, authorize, expires_at) in changes {
+            if *authorize {
+                self.add_authorized_executor(*executor, *expires_at)?;
+            } else {
+                self.remove_authorized_executor(*executor)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn remove_authorized_executor(&mut self, executor: Pubkey) -> Result<()> {
         let mut found_index: Option<usize> = None;
 
@@ -123,8 +421,10 @@ impl ExecutorAuthority {
         if let Some(idx) = found_index {
             for i in idx..(self.executor_count as usize - 1) {
                 self.authorized_executors[i] = self.authorized_executors[i + 1];
+                self.executor_expires_at[i] = self.executor_expires_at[i + 1];
             }
             self.authorized_executors[self.executor_count as usize - 1] = Pubkey::default();
+            self.executor_expires_at[self.executor_count as usize - 1] = 0;
             self.executor_count -= 1;
         }
 
@@ -135,17 +435,28 @@ impl ExecutorAuthority {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::account_version::{ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+    use crate::time::FixedTime;
 
     fn create_test_executor() -> ExecutorAuthority {
         ExecutorAuthority {
+            version: ACCOUNT_VERSION,
             owner: Pubkey::new_unique(),
             order_count: 0,
             is_delegated: false,
             bump: 255,
-            order_hashes: [[0u8; 32]; MAX_ORDERS_PER_EXECUTOR],
+            order_hashes: Vec::new(),
             order_hash_count: 0,
             authorized_executors: [Pubkey::default(); MAX_AUTHORIZED_EXECUTORS],
             executor_count: 0,
+            capacity: MAX_ORDERS_PER_EXECUTOR as u16,
+            executor_expires_at: [0; MAX_AUTHORIZED_EXECUTORS],
+            drift_user: Pubkey::new_unique(),
+            previous_owner: Pubkey::default(),
+            allowed_markets: [AllowedMarket::default(); MAX_ALLOWED_MARKETS],
+            allowed_market_count: 0,
+            max_order_notional: 0,
+            protocol_fees_owed: 0,
         }
     }
 
@@ -182,15 +493,170 @@ mod tests {
         assert!(executor.add_order_hash(overflow_hash).is_err());
     }
 
+    #[test]
+    fn test_grow_capacity_and_fill() {
+        let mut executor = create_test_executor();
+
+        executor.grow_capacity().unwrap();
+        assert_eq!(executor.capacity, 32);
+
+        for i in 0..32 {
+            let mut hash = [0u8; 32];
+            hash[0] = i as u8;
+            executor.add_order_hash(hash).unwrap();
+        }
+
+        assert_eq!(executor.order_hash_count, 32);
+        assert!(executor.add_order_hash([255u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_grow_capacity_ceiling() {
+        let mut executor = create_test_executor();
+
+        while (executor.capacity as usize) < MAX_ORDER_CAPACITY {
+            executor.grow_capacity().unwrap();
+        }
+
+        assert!(executor.grow_capacity().is_err());
+        assert_eq!(executor.capacity as usize, MAX_ORDER_CAPACITY);
+    }
+
+    /// Reference model with the original linear-scan semantics.
+    struct NaiveHashes {
+        hashes: Vec<[u8; 32]>,
+        capacity: usize,
+    }
+
+    impl NaiveHashes {
+        fn add(&mut self, hash: [u8; 32]) -> bool {
+            if self.hashes.len() >= self.capacity || self.hashes.iter().any(|h| h == &hash) {
+                return false;
+            }
+            self.hashes.push(hash);
+            true
+        }
+
+        fn remove(&mut self, hash: [u8; 32]) -> bool {
+            match self.hashes.iter().position(|h| h == &hash) {
+                Some(idx) => {
+                    self.hashes.remove(idx);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn has(&self, hash: &[u8; 32]) -> bool {
+            self.hashes.iter().any(|h| h == hash)
+        }
+    }
+
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    fn random_hash(state: &mut u64) -> [u8; 32] {
+        // Small key space so duplicates and hits on removal are common
+        let mut hash = [0u8; 32];
+        hash[0] = (xorshift(state) % 24) as u8;
+        hash[31] = (xorshift(state) % 2) as u8;
+        hash
+    }
+
+    #[test]
+    fn test_sorted_hashes_match_naive_model() {
+        for seed in 1..=32u64 {
+            let mut rng = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            let mut executor = create_test_executor();
+            let mut model = NaiveHashes {
+                hashes: Vec::new(),
+                capacity: MAX_ORDERS_PER_EXECUTOR,
+            };
+
+            for _ in 0..200 {
+                let hash = random_hash(&mut rng);
+                match xorshift(&mut rng) % 3 {
+                    0 => assert_eq!(executor.add_order_hash(hash).is_ok(), model.add(hash)),
+                    1 => assert_eq!(executor.remove_order_hash(hash).is_ok(), model.remove(hash)),
+                    _ => assert_eq!(executor.has_order_hash(&hash), model.has(&hash)),
+                }
+
+                assert_eq!(executor.order_hash_count as usize, model.hashes.len());
+                assert!(executor.order_hashes.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sorted_insert_out_of_order() {
+        let mut executor = create_test_executor();
+
+        executor.add_order_hash([3u8; 32]).unwrap();
+        executor.add_order_hash([1u8; 32]).unwrap();
+        executor.add_order_hash([2u8; 32]).unwrap();
+
+        assert_eq!(executor.order_hashes, vec![[1u8; 32], [2u8; 32], [3u8; 32]]);
+        assert!(executor.add_order_hash([2u8; 32]).is_err());
+        assert!(executor.remove_order_hash([4u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_reclaim_expired_order() {
+        let mut executor = create_test_executor();
+        let order = CompressedGhostOrder {
+            owner: executor.owner,
+            expiry: 1_700_000_000,
+            ..Default::default()
+        };
+        executor.add_order_hash(order.compute_hash()).unwrap();
+
+        let hash = executor
+            .reclaim_expired_order(&order, &FixedTime::at(1_700_000_003))
+            .unwrap();
+
+        assert_eq!(hash, order.compute_hash());
+        assert!(executor.is_empty());
+    }
+
+    #[test]
+    fn test_reclaim_rejects_live_order() {
+        let mut executor = create_test_executor();
+        let live = CompressedGhostOrder {
+            owner: executor.owner,
+            expiry: 1_700_000_000,
+            ..Default::default()
+        };
+        let no_expiry = CompressedGhostOrder {
+            owner: executor.owner,
+            order_id: 2,
+            ..Default::default()
+        };
+        executor.add_order_hash(live.compute_hash()).unwrap();
+        executor.add_order_hash(no_expiry.compute_hash()).unwrap();
+
+        // Within the clock-drift grace the order is still live
+        let now = FixedTime::at(1_700_000_002);
+        assert_eq!(
+            executor.reclaim_expired_order(&live, &now).unwrap_err(),
+            crate::errors::GhostBridgeError::OrderNotExpired.into()
+        );
+        assert!(executor.reclaim_expired_order(&no_expiry, &now).is_err());
+        assert_eq!(executor.order_hash_count, 2);
+    }
+
     #[test]
     fn test_add_authorized_executor() {
         let mut executor = create_test_executor();
         let tee_pubkey = Pubkey::new_unique();
 
-        executor.add_authorized_executor(tee_pubkey).unwrap();
+        executor.add_authorized_executor(tee_pubkey, 0).unwrap();
 
         assert_eq!(executor.executor_count, 1);
-        assert!(executor.is_authorized_executor(&tee_pubkey));
+        assert!(executor.is_authorized_executor(&tee_pubkey, &FixedTime::at(0)));
     }
 
     #[test]
@@ -198,11 +664,11 @@ mod tests {
         let mut executor = create_test_executor();
         let tee_pubkey = Pubkey::new_unique();
 
-        executor.add_authorized_executor(tee_pubkey).unwrap();
-        assert!(executor.is_authorized_executor(&tee_pubkey));
+        executor.add_authorized_executor(tee_pubkey, 0).unwrap();
+        assert!(executor.is_authorized_executor(&tee_pubkey, &FixedTime::at(0)));
 
         executor.remove_authorized_executor(tee_pubkey).unwrap();
-        assert!(!executor.is_authorized_executor(&tee_pubkey));
+        assert!(!executor.is_authorized_executor(&tee_pubkey, &FixedTime::at(0)));
         assert_eq!(executor.executor_count, 0);
     }
 
@@ -211,11 +677,69 @@ mod tests {
         let mut executor = create_test_executor();
 
         for _ in 0..MAX_AUTHORIZED_EXECUTORS {
-            executor.add_authorized_executor(Pubkey::new_unique()).unwrap();
+            executor.add_authorized_executor(Pubkey::new_unique(), 0).unwrap();
         }
 
         assert_eq!(executor.executor_count, MAX_AUTHORIZED_EXECUTORS as u8);
-        assert!(executor.add_authorized_executor(Pubkey::new_unique()).is_err());
+        assert!(executor.add_authorized_executor(Pubkey::new_unique(), 0).is_err());
+    }
+
+    #[test]
+    fn test_executor_batch_authorizes_four() {
+        let mut executor = create_test_executor();
+        let batch: Vec<(Pubkey, bool, i64)> = (0..4).map(|_| (Pubkey::new_unique(), true, 0)).collect();
+
+        executor.apply_executor_batch(&batch).unwrap();
+
+        assert_eq!(executor.executor_count, 4);
+        for (key, _, _) in &batch {
+            assert!(executor.is_authorized_executor(key, &FixedTime::at(0)));
+        }
+    }
+
+    #[test]
+    fn test_executor_batch_overflow_is_atomic() {
+        let mut executor = create_test_executor();
+        let existing = Pubkey::new_unique();
+        executor.add_authorized_executor(existing, 0).unwrap();
+
+        let batch: Vec<(Pubkey, bool, i64)> = (0..4).map(|_| (Pubkey::new_unique(), true, 0)).collect();
+
+        assert!(executor.apply_executor_batch(&batch).is_err());
+        assert_eq!(executor.executor_count, 1);
+        assert!(executor.is_authorized_executor(&existing, &FixedTime::at(0)));
+        assert!(!executor.is_authorized_executor(&batch[0].0, &FixedTime::at(0)));
+    }
+
+    #[test]
+    fn test_executor_batch_revoke_frees_slot() {
+        let mut executor = create_test_executor();
+        let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        for key in &keys[..4] {
+            executor.add_authorized_executor(*key, 0).unwrap();
+        }
+
+        executor.apply_executor_batch(&[(keys[0], false, 0), (keys[4], true, 0)]).unwrap();
+
+        assert_eq!(executor.executor_count, 4);
+        assert!(!executor.is_authorized_executor(&keys[0], &FixedTime::at(0)));
+        assert!(executor.is_authorized_executor(&keys[4], &FixedTime::at(0)));
+    }
+
+    #[test]
+    fn test_executor_authorization_expires() {
+        let mut executor = create_test_executor();
+        let keeper = Pubkey::new_unique();
+
+        executor.add_authorized_executor(keeper, 1_700_000_060).unwrap();
+
+        assert!(executor.is_authorized_executor(&keeper, &FixedTime::at(1_700_000_059)));
+        assert!(!executor.is_authorized_executor(&keeper, &FixedTime::at(1_700_000_060)));
+
+        // Re-authorizing extends the grant without taking a new slot
+        executor.add_authorized_executor(keeper, 0).unwrap();
+        assert_eq!(executor.executor_count, 1);
+        assert!(executor.is_authorized_executor(&keeper, &FixedTime::at(1_800_000_000)));
     }
 
     #[test]
@@ -223,9 +747,256 @@ mod tests {
         let mut executor = create_test_executor();
         let tee_pubkey = Pubkey::new_unique();
 
-        executor.add_authorized_executor(tee_pubkey).unwrap();
-        executor.add_authorized_executor(tee_pubkey).unwrap();
+        executor.add_authorized_executor(tee_pubkey, 0).unwrap();
+        executor.add_authorized_executor(tee_pubkey, 0).unwrap();
 
         assert_eq!(executor.executor_count, 1);
     }
+
+    #[test]
+    fn test_verify_drift_accounts_rejects_other_users() {
+        let executor = create_test_executor();
+
+        assert!(executor
+            .verify_drift_accounts(&executor.drift_user, &executor.owner)
+            .is_ok());
+        assert_eq!(
+            executor
+                .verify_drift_accounts(&Pubkey::new_unique(), &executor.owner)
+                .unwrap_err(),
+            crate::errors::GhostBridgeError::DriftUserMismatch.into()
+        );
+        assert_eq!(
+            executor
+                .verify_drift_accounts(&executor.drift_user, &Pubkey::new_unique())
+                .unwrap_err(),
+            crate::errors::GhostBridgeError::DriftAuthorityMismatch.into()
+        );
+    }
+
+    fn create_test_order(owner: Pubkey) -> CompressedGhostOrder {
+        CompressedGhostOrder {
+            owner,
+            order_id: 1,
+            market_index: 0,
+            market_type: crate::drift_cpi::DriftMarketType::Perp,
+            trigger_price: 150_000_000,
+            trigger_condition: crate::state::TriggerCondition::Below,
+            order_side: crate::state::OrderSide::Long,
+            base_asset_amount: 1_000_000_000,
+            reduce_only: false,
+            expiry: 0,
+            feed_id: [1u8; 32],
+            salt: [2u8; 16],
+        }
+    }
+
+    #[test]
+    fn test_rotation_carries_active_hashes() {
+        let mut old = create_test_executor();
+        let tee = Pubkey::new_unique();
+        old.add_authorized_executor(tee, 0).unwrap();
+
+        let order = create_test_order(old.owner);
+        let order_hash = order.compute_hash();
+        old.add_order_hash(order_hash).unwrap();
+        old.add_order_hash([9u8; 32]).unwrap();
+
+        let new_owner = Pubkey::new_unique();
+        let new_drift_user = Pubkey::new_unique();
+        let mut rotated = old.rotated(new_owner, new_drift_user, 254);
+
+        assert_eq!(rotated.owner, new_owner);
+        assert_eq!(rotated.previous_owner, old.owner);
+        assert_eq!(rotated.drift_user, new_drift_user);
+        assert_eq!(rotated.bump, 254);
+        assert_eq!(rotated.order_hashes, old.order_hashes);
+        assert_eq!(rotated.order_hash_count, 2);
+        assert_eq!(rotated.capacity, old.capacity);
+        assert!(rotated.is_authorized_executor(&tee, &FixedTime::at(0)));
+
+        // The order is now revealed under the new owner but still resolves
+        // to the hash committed by the old one
+        let revealed = CompressedGhostOrder { owner: new_owner, ..order.clone() };
+        assert_eq!(rotated.find_order_hash(&revealed), Some(order_hash));
+        rotated.remove_order_hash(order_hash).unwrap();
+        assert_eq!(rotated.find_order_hash(&revealed), None);
+
+        let mut buf = Vec::new();
+        rotated.try_serialize(&mut buf).unwrap();
+        assert!(buf.len() <= ExecutorAuthority::space_for(rotated.capacity as usize));
+    }
+
+    #[test]
+    fn test_unrotated_authority_ignores_other_owners() {
+        let executor = create_test_executor();
+        let order = create_test_order(Pubkey::new_unique());
+        assert_eq!(executor.find_order_hash(&order), None);
+    }
+
+    #[test]
+    fn test_bulk_cancel_skips_absent_orders() {
+        let mut executor = create_test_executor();
+        let orders: Vec<CompressedGhostOrder> = (0..4)
+            .map(|i| CompressedGhostOrder {
+                owner: executor.owner,
+                order_id: i,
+                ..Default::default()
+            })
+            .collect();
+        executor.add_order_hash(orders[0].compute_hash()).unwrap();
+        executor.add_order_hash(orders[2].compute_hash()).unwrap();
+        executor.add_order_hash([9u8; 32]).unwrap();
+
+        // Orders 1 and 3 were never stored; order 0 is listed twice
+        let mut batch = orders.clone();
+        batch.push(orders[0].clone());
+        let removed = executor.remove_orders(&batch);
+
+        assert_eq!(removed, vec![(0, orders[0].compute_hash()), (2, orders[2].compute_hash())]);
+        assert_eq!(executor.order_hash_count, 1);
+        assert!(executor.has_order_hash(&[9u8; 32]));
+        assert!(executor.remove_orders(&batch).is_empty());
+    }
+
+    #[test]
+    fn test_allowed_markets_restrict_execution() {
+        let mut executor = create_test_executor();
+
+        let perp = |market_index| AllowedMarket { market_type: DriftMarketType::Perp, market_index };
+
+        // No list: every market is open
+        assert!(executor.require_market_allowed(DriftMarketType::Perp, 42).is_ok());
+
+        executor.set_allowed_markets(&[perp(0), perp(1)]).unwrap();
+        assert!(executor.require_market_allowed(DriftMarketType::Perp, 0).is_ok());
+        assert!(executor.require_market_allowed(DriftMarketType::Perp, 1).is_ok());
+        assert_eq!(
+            executor.require_market_allowed(DriftMarketType::Perp, 2).unwrap_err(),
+            crate::errors::GhostBridgeError::MarketNotAllowed.into()
+        );
+
+        // Narrowing the list drops the markets left out
+        executor.set_allowed_markets(&[perp(1)]).unwrap();
+        assert!(!executor.is_market_allowed(DriftMarketType::Perp, 0));
+
+        executor.set_allowed_markets(&[]).unwrap();
+        assert!(executor.is_market_allowed(DriftMarketType::Perp, 2));
+
+        let too_many: Vec<AllowedMarket> = (0..=MAX_ALLOWED_MARKETS as u16).map(perp).collect();
+        assert_eq!(
+            executor.set_allowed_markets(&too_many).unwrap_err(),
+            crate::errors::GhostBridgeError::TooManyAllowedMarkets.into()
+        );
+        assert!(executor.is_market_allowed(DriftMarketType::Perp, 2));
+    }
+
+    #[test]
+    fn test_allowed_market_keyed_on_type_and_index() {
+        let mut executor = create_test_executor();
+
+        // Perp 1 (BTC-PERP) allowed does not open spot market 1
+        executor
+            .set_allowed_markets(&[AllowedMarket { market_type: DriftMarketType::Perp, market_index: 1 }])
+            .unwrap();
+        assert!(executor.is_market_allowed(DriftMarketType::Perp, 1));
+        assert_eq!(
+            executor.require_market_allowed(DriftMarketType::Spot, 1).unwrap_err(),
+            crate::errors::GhostBridgeError::MarketNotAllowed.into()
+        );
+    }
+
+    #[test]
+    fn test_max_order_notional_rejects_over_cap_order() {
+        let mut executor = create_test_executor();
+        // 1 SOL at $150
+        let order = create_test_order(executor.owner);
+        assert_eq!(order.notional(), 150_000_000);
+
+        // Zero leaves size unlimited
+        let huge = CompressedGhostOrder { base_asset_amount: u64::MAX, ..order.clone() };
+        assert!(executor.require_within_max_notional(&huge).is_ok());
+
+        executor.max_order_notional = 1_000_000_000;
+        assert!(executor.require_within_max_notional(&order).is_ok());
+
+        // 10 SOL at $150 is $1,500 against a $1,000 cap
+        let over = CompressedGhostOrder { base_asset_amount: 10_000_000_000, ..order.clone() };
+        assert_eq!(
+            executor.require_within_max_notional(&over).unwrap_err(),
+            crate::errors::GhostBridgeError::ExceedsMaxNotional.into()
+        );
+        assert!(executor.require_within_max_notional(&huge).is_err());
+    }
+
+    #[test]
+    fn test_owed_protocol_fees_block_new_orders_until_settled() {
+        let mut executor = create_test_executor();
+        executor.add_order_hash([1u8; 32]).unwrap();
+
+        executor.accrue_protocol_fee(300_000);
+        executor.accrue_protocol_fee(200_000);
+        assert_eq!(executor.protocol_fees_owed, 500_000);
+        assert_eq!(
+            executor.add_order_hash([2u8; 32]).unwrap_err(),
+            crate::errors::GhostBridgeError::ProtocolFeesOwed.into()
+        );
+
+        assert_eq!(executor.take_protocol_fees_owed(), 500_000);
+        assert_eq!(executor.protocol_fees_owed, 0);
+        executor.add_order_hash([2u8; 32]).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_executor_detected_and_upgraded() {
+        let owner = Pubkey::new_unique();
+        let executor = Pubkey::new_unique();
+        let mut order_hashes = [[0u8; 32]; MAX_ORDERS_PER_EXECUTOR];
+        order_hashes[0] = [7u8; 32];
+        order_hashes[1] = [3u8; 32];
+        let mut authorized_executors = [Pubkey::default(); MAX_AUTHORIZED_EXECUTORS];
+        authorized_executors[0] = executor;
+        let legacy = ExecutorAuthorityV1 {
+            owner,
+            order_count: 5,
+            is_delegated: false,
+            bump: 254,
+            order_hashes,
+            order_hash_count: 2,
+            authorized_executors,
+            executor_count: 1,
+        };
+        let (address, _) =
+            Pubkey::find_program_address(&[ExecutorAuthority::SEED_PREFIX, owner.as_ref()], &crate::ID);
+
+        // An authority written with the version 1 layout
+        let mut data = ExecutorAuthority::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ExecutorAuthorityV1::LEN);
+        assert_eq!(
+            ExecutorAuthority::stored_version(&address, &data),
+            Some(LEGACY_ACCOUNT_VERSION)
+        );
+        assert!(ExecutorAuthority::try_deserialize(&mut data.as_slice()).is_err());
+
+        let upgraded = ExecutorAuthorityV1::from_account_data(&data).unwrap().upgrade();
+        let mut stored = vec![0u8; ExecutorAuthority::space_for(upgraded.capacity as usize)];
+        upgraded.try_serialize(&mut stored.as_mut_slice()).unwrap();
+        assert_eq!(ExecutorAuthority::stored_version(&address, &stored), Some(ACCOUNT_VERSION));
+
+        let decoded = ExecutorAuthority::try_deserialize(&mut stored.as_slice()).unwrap();
+        assert!(decoded.is_current_version());
+        assert_eq!(decoded.owner, owner);
+        assert_eq!(decoded.order_count, 5);
+        assert_eq!(decoded.bump, 254);
+        assert_eq!(decoded.order_hashes, vec![[3u8; 32], [7u8; 32]]);
+        assert!(decoded.has_order_hash(&[7u8; 32]));
+        assert_eq!(decoded.capacity as usize, MAX_ORDERS_PER_EXECUTOR);
+        assert!(decoded.is_authorized_executor(&executor, &FixedTime::at(0)));
+        let (drift_user, _) = Pubkey::find_program_address(
+            &[b"user", owner.as_ref(), &0u16.to_le_bytes()],
+            &crate::drift_cpi::DRIFT_PROGRAM_ID,
+        );
+        assert_eq!(decoded.drift_user, drift_user);
+    }
 }
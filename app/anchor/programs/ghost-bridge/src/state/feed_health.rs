@@ -0,0 +1,207 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::FEED_DEGRADED_CONFIDENCE_BPS;
+use crate::errors::GhostBridgeError;
+use crate::time::TimeSource;
+
+/// Program-wide kill switch for one price feed. Monitoring records the
+/// confidence band of every read; while the band is wider than
+/// `FEED_DEGRADED_CONFIDENCE_BPS` the feed is degraded and no order on it
+/// executes, whoever the executor is.
+#[account]
+#[derive(Default, Debug)]
+pub struct FeedHealth {
+    pub feed_id: [u8; 32],
+    /// Confidence band of the latest read, in bps of its price
+    pub confidence_bps: u64,
+    pub degraded: bool,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl FeedHealth {
+    pub const SEED_PREFIX: &'static [u8] = b"feed_health";
+
+    pub const LEN: usize = 8 +  // discriminator
+        32 +                    // feed_id
+        8 +                     // confidence_bps
+        1 +                     // degraded
+        8 +                     // updated_at
+        1;                      // bump
+
+    /// Records one read. Returns `true` when it flipped the feed between
+    /// healthy and degraded.
+    pub fn record_confidence(&mut self, confidence_bps: u64, now: &impl TimeSource) -> bool {
+        let was_degraded = self.degraded;
+        self.confidence_bps = confidence_bps;
+        self.degraded = confidence_bps > FEED_DEGRADED_CONFIDENCE_BPS;
+        self.updated_at = now.unix_timestamp();
+        self.degraded != was_degraded
+    }
+
+    pub fn require_healthy(&self) -> Result<()> {
+        require!(!self.degraded, GhostBridgeError::FeedDegraded);
+        Ok(())
+    }
+
+    /// The feed's health PDA, or `None` when nobody has started tracking
+    /// the feed and the account does not exist yet.
+    pub fn load_tracked(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+
+        let data = info.try_borrow_data()?;
+        Ok(Some(FeedHealth::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Writes the record back to the PDA it was loaded from.
+    pub fn store(&self, info: &AccountInfo) -> Result<()> {
+        let mut data = info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        self.try_serialize(&mut writer)
+    }
+
+    /// Execution-side check on the feed's health PDA. A feed nobody has
+    /// started tracking counts as healthy.
+    pub fn check_account(info: &AccountInfo) -> Result<()> {
+        match FeedHealth::load_tracked(info)? {
+            Some(feed_health) => feed_health.require_healthy(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Width of a confidence band in bps of `price`; a non-positive price has
+/// no meaningful band and reads as infinitely wide.
+pub fn confidence_bps(price: i128, confidence: i128) -> u64 {
+    if price <= 0 {
+        return u64::MAX;
+    }
+    let bps = confidence.max(0).saturating_mul(10_000) / price;
+    u64::try_from(bps).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::FixedTime;
+
+    fn health_account<'a>(
+        key: &'a Pubkey,
+        lamports: &'a mut u64,
+        data: &'a mut [u8],
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, false, lamports, data, &crate::ID, false, 0)
+    }
+
+    #[test]
+    fn test_feed_health_size() {
+        let health = FeedHealth::default();
+        assert_eq!(FeedHealth::LEN, 8 + health.try_to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn test_confidence_bps() {
+        assert_eq!(confidence_bps(100_000_000, 1_000_000), 100);
+        assert_eq!(confidence_bps(100_000_000, 0), 0);
+        assert_eq!(confidence_bps(0, 1), u64::MAX);
+        assert_eq!(confidence_bps(-5, 1), u64::MAX);
+    }
+
+    #[test]
+    fn test_wide_read_degrades_until_tight_read_clears() {
+        let mut health = FeedHealth::default();
+        let now = FixedTime::at(1_700_000_000);
+
+        assert!(!health.record_confidence(50, &now));
+        assert!(health.require_healthy().is_ok());
+
+        // Band wider than the threshold: flips to degraded and blocks
+        assert!(health.record_confidence(FEED_DEGRADED_CONFIDENCE_BPS + 1, &now));
+        assert!(health.degraded);
+        assert!(health.require_healthy().is_err());
+
+        // Still wide: stays degraded, no flip
+        assert!(!health.record_confidence(FEED_DEGRADED_CONFIDENCE_BPS * 3, &now));
+        assert!(health.require_healthy().is_err());
+
+        let later = FixedTime::at(1_700_000_060);
+        assert!(health.record_confidence(FEED_DEGRADED_CONFIDENCE_BPS, &later));
+        assert!(!health.degraded);
+        assert_eq!(health.updated_at, 1_700_000_060);
+        assert!(health.require_healthy().is_ok());
+    }
+
+    #[test]
+    fn test_recorded_wide_read_blocks_execution() {
+        use crate::oracle::{read_confidence_bps, OracleSource};
+        use crate::switchboard::{
+            LAST_UPDATE_TIMESTAMP_OFFSET, PULL_FEED_DISCRIMINATOR, RESULT_STD_DEV_OFFSET,
+            RESULT_VALUE_OFFSET, SWITCHBOARD_ON_DEMAND_ID,
+        };
+
+        let now = FixedTime::at(1_700_000_000);
+        let value: i128 = 42_500_000_000_000_000_000;
+        let mut feed_data = vec![0u8; 3208];
+        feed_data[..8].copy_from_slice(&PULL_FEED_DISCRIMINATOR);
+        feed_data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        feed_data[RESULT_VALUE_OFFSET..RESULT_VALUE_OFFSET + 16].copy_from_slice(&value.to_le_bytes());
+        feed_data[RESULT_STD_DEV_OFFSET..RESULT_STD_DEV_OFFSET + 16]
+            .copy_from_slice(&(value / 20).to_le_bytes());
+        let feed_key = Pubkey::new_unique();
+        let mut feed_lamports = 0u64;
+        let feed = AccountInfo::new(
+            &feed_key, false, false, &mut feed_lamports, &mut feed_data, &SWITCHBOARD_ON_DEMAND_ID,
+            false, 0,
+        );
+
+        let health = FeedHealth { feed_id: feed_key.to_bytes(), ..Default::default() };
+        let mut data = Vec::new();
+        health.try_serialize(&mut data).unwrap();
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000u64;
+        let info = health_account(&key, &mut lamports, &mut data);
+        assert!(FeedHealth::check_account(&info).is_ok());
+
+        // The monitor records the wide read the way check_price_update does
+        let mut tracked = FeedHealth::load_tracked(&info).unwrap().unwrap();
+        let bps =
+            read_confidence_bps(OracleSource::Switchboard, &feed, &tracked.feed_id, &now).unwrap();
+        assert!(tracked.record_confidence(bps, &now));
+        tracked.store(&info).unwrap();
+
+        // Execution re-reads the account and refuses
+        assert_eq!(
+            FeedHealth::check_account(&info).unwrap_err(),
+            GhostBridgeError::FeedDegraded.into()
+        );
+
+        // A feed other than the tracked one cannot be recorded on it
+        let other = FeedHealth { feed_id: Pubkey::new_unique().to_bytes(), ..Default::default() };
+        assert!(read_confidence_bps(OracleSource::Switchboard, &feed, &other.feed_id, &now).is_err());
+    }
+
+    #[test]
+    fn test_check_account_blocks_only_degraded_feeds() {
+        let key = Pubkey::new_unique();
+
+        let (mut lamports, mut empty) = (0u64, [0u8; 0]);
+        assert!(FeedHealth::check_account(&health_account(&key, &mut lamports, &mut empty)).is_ok());
+
+        let mut health = FeedHealth::default();
+        health.record_confidence(FEED_DEGRADED_CONFIDENCE_BPS + 1, &FixedTime::at(1_700_000_000));
+        let mut data = Vec::new();
+        health.try_serialize(&mut data).unwrap();
+        let mut lamports = 1_000_000u64;
+        assert!(FeedHealth::check_account(&health_account(&key, &mut lamports, &mut data)).is_err());
+
+        health.record_confidence(10, &FixedTime::at(1_700_000_060));
+        let mut data = Vec::new();
+        health.try_serialize(&mut data).unwrap();
+        let mut lamports = 1_000_000u64;
+        assert!(FeedHealth::check_account(&health_account(&key, &mut lamports, &mut data)).is_ok());
+    }
+}
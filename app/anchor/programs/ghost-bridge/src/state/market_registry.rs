@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{GHOST_CRANK_PROGRAM_ID, MARKET_REGISTRY_DISCRIMINATOR};
+use crate::drift_cpi::DriftMarketType;
+use crate::errors::GhostBridgeError;
+use crate::order_size::{min_base_asset_amount, MarketMinOrderSize};
+
+// Mirrors ghost-crank's `MarketRegistry` account, which the crank admin
+// maintains; the bridge only reads it.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MarketFeed {
+    pub market_type: DriftMarketType,
+    pub market_index: u16,
+    pub feed_id: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct MarketRegistry {
+    pub bump: u8,
+    pub markets: Vec<MarketFeed>,
+    pub min_order_sizes: Vec<MarketMinOrderSize>,
+}
+
+impl MarketRegistry {
+    /// Deserializes the crank's registry, rejecting accounts it does not own.
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *info.owner,
+            GHOST_CRANK_PROGRAM_ID,
+            GhostBridgeError::InvalidMarketRegistry
+        );
+
+        let data = info.try_borrow_data()?;
+        Self::parse(&data)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= MARKET_REGISTRY_DISCRIMINATOR.len()
+                && data[..MARKET_REGISTRY_DISCRIMINATOR.len()] == MARKET_REGISTRY_DISCRIMINATOR,
+            GhostBridgeError::InvalidMarketRegistry
+        );
+
+        Self::deserialize(&mut &data[MARKET_REGISTRY_DISCRIMINATOR.len()..])
+            .map_err(|_| error!(GhostBridgeError::InvalidMarketRegistry))
+    }
+
+    /// Rejects an order whose `feed_id` is not the one registered for its market.
+    pub fn verify_feed(
+        &self,
+        market_type: DriftMarketType,
+        market_index: u16,
+        feed_id: &[u8; 32],
+    ) -> Result<()> {
+        let expected = self
+            .markets
+            .iter()
+            .find(|m| m.market_type == market_type && m.market_index == market_index)
+            .map(|m| m.feed_id)
+            .ok_or(GhostBridgeError::MarketNotRegistered)?;
+        require!(expected == *feed_id, GhostBridgeError::FeedMarketMismatch);
+        Ok(())
+    }
+
+    pub fn min_base_asset_amount(&self, market_type: DriftMarketType, market_index: u16) -> u64 {
+        min_base_asset_amount(&self.min_order_sizes, market_type, market_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL_FEED: [u8; 32] = [1u8; 32];
+    const BTC_FEED: [u8; 32] = [2u8; 32];
+
+    fn registry_account_data() -> Vec<u8> {
+        let registry = MarketRegistry {
+            bump: 254,
+            markets: vec![
+                MarketFeed { market_type: DriftMarketType::Perp, market_index: 0, feed_id: SOL_FEED },
+                MarketFeed { market_type: DriftMarketType::Perp, market_index: 1, feed_id: BTC_FEED },
+            ],
+            min_order_sizes: vec![MarketMinOrderSize {
+                market_type: DriftMarketType::Perp,
+                market_index: 1,
+                min_base_asset_amount: 1_000_000,
+            }],
+        };
+        let mut data = MARKET_REGISTRY_DISCRIMINATOR.to_vec();
+        registry.serialize(&mut data).unwrap();
+        // The crank allocates room for the full registry up front
+        data.resize(1_489, 0);
+        data
+    }
+
+    #[test]
+    fn test_reads_registered_min_order_size() {
+        use crate::order_size::DEFAULT_MIN_BASE_ASSET_AMOUNT;
+
+        let registry = MarketRegistry::parse(&registry_account_data()).unwrap();
+        assert_eq!(registry.min_base_asset_amount(DriftMarketType::Perp, 1), 1_000_000);
+        assert_eq!(
+            registry.min_base_asset_amount(DriftMarketType::Perp, 0),
+            DEFAULT_MIN_BASE_ASSET_AMOUNT
+        );
+
+        // A registry written before minimums existed has zeros where the list goes
+        let mut data = MARKET_REGISTRY_DISCRIMINATOR.to_vec();
+        (254u8, vec![MarketFeed { market_type: DriftMarketType::Perp, market_index: 0, feed_id: SOL_FEED }])
+            .serialize(&mut data)
+            .unwrap();
+        data.resize(1_133, 0);
+        assert!(MarketRegistry::parse(&data).unwrap().min_order_sizes.is_empty());
+    }
+
+    #[test]
+    fn test_parses_crank_registry_account() {
+        let registry = MarketRegistry::parse(&registry_account_data()).unwrap();
+
+        assert_eq!(registry.bump, 254);
+        assert!(registry.verify_feed(DriftMarketType::Perp, 0, &SOL_FEED).is_ok());
+        assert!(registry.verify_feed(DriftMarketType::Perp, 1, &BTC_FEED).is_ok());
+    }
+
+    #[test]
+    fn test_mismatched_feed_rejected() {
+        let registry = MarketRegistry::parse(&registry_account_data()).unwrap();
+
+        assert_eq!(
+            registry.verify_feed(DriftMarketType::Perp, 0, &BTC_FEED).unwrap_err(),
+            GhostBridgeError::FeedMarketMismatch.into()
+        );
+        assert_eq!(
+            registry.verify_feed(DriftMarketType::Perp, 7, &SOL_FEED).unwrap_err(),
+            GhostBridgeError::MarketNotRegistered.into()
+        );
+    }
+
+    #[test]
+    fn test_rejects_foreign_account() {
+        let mut data = registry_account_data();
+        data[0] ^= 0xff;
+        assert!(MarketRegistry::parse(&data).is_err());
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = registry_account_data();
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert_eq!(
+            MarketRegistry::load(&info).unwrap_err(),
+            GhostBridgeError::InvalidMarketRegistry.into()
+        );
+    }
+}
@@ -1,7 +1,13 @@
+pub mod bridge_config;
 pub mod compressed_order;
 pub mod encrypted_order;
 pub mod executor_authority;
+pub mod feed_health;
+pub mod market_registry;
 
+pub use bridge_config::*;
 pub use compressed_order::*;
 pub use encrypted_order::*;
 pub use executor_authority::*;
+pub use feed_health::*;
+pub use market_registry::*;
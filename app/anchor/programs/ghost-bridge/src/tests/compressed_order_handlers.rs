@@ -0,0 +1,91 @@
+//! Compressed order instructions run end to end against the built program.
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::cancel_compressed_order::{BulkCancelled, CompressedOrderCancelled};
+    use crate::instructions::create_compressed_order::CreateCompressedOrderArgs;
+    use crate::state::CompressedOrderParams;
+    use crate::tests::svm::{decode_events, executor_pda, instruction_data, send, setup_executor};
+    use crate::ID as PROGRAM_ID;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signer::Signer,
+        system_program,
+    };
+
+    fn params(order_id: u64) -> CompressedOrderParams {
+        CompressedOrderParams {
+            order_id,
+            market_index: 0,
+            market_type: 1,
+            trigger_price: 150_000_000,
+            trigger_condition: 1,
+            order_side: 0,
+            base_asset_amount: 1_000_000_000,
+            reduce_only: false,
+            expiry: 0,
+            feed_id: [1u8; 32],
+            salt: [order_id as u8; 16],
+        }
+    }
+
+    fn create_ix(owner: &Pubkey, order: &CompressedOrderParams) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(executor_pda(owner), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: instruction_data(
+                "create_compressed_order",
+                CreateCompressedOrderArgs {
+                    order_id: order.order_id,
+                    market_index: order.market_index,
+                    market_type: order.market_type,
+                    trigger_price: order.trigger_price,
+                    trigger_condition: order.trigger_condition,
+                    order_side: order.order_side,
+                    base_asset_amount: order.base_asset_amount,
+                    reduce_only: order.reduce_only,
+                    expiry_seconds: 0,
+                    feed_id: order.feed_id,
+                    salt: order.salt,
+                },
+            ),
+        }
+    }
+
+    #[test]
+    fn test_bulk_cancel_emits_event_per_removed_order() {
+        let (mut svm, owner) = setup_executor(Pubkey::new_unique());
+
+        for order_id in [1, 3] {
+            send(&mut svm, &owner, create_ix(&owner.pubkey(), &params(order_id)))
+                .expect("Create should succeed");
+        }
+
+        // Order 2 was never created
+        let cancel_all_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(executor_pda(&owner.pubkey()), false),
+            ],
+            data: instruction_data("cancel_all_orders", vec![params(1), params(2), params(3)]),
+        };
+        let logs = send(&mut svm, &owner, cancel_all_ix).expect("Bulk cancel should succeed");
+
+        let cancelled: Vec<u64> = decode_events::<CompressedOrderCancelled>(&logs)
+            .iter()
+            .map(|event| event.order_id)
+            .collect();
+        assert_eq!(cancelled, vec![1, 3]);
+
+        let summary = decode_events::<BulkCancelled>(&logs);
+        assert_eq!(summary.len(), 1);
+        assert_eq!((summary[0].requested, summary[0].cancelled), (3, 2));
+    }
+}
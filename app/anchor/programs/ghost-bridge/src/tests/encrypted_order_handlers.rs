@@ -0,0 +1,259 @@
+//! Encrypted order instructions run end to end against the built program.
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::create_encrypted_order::CommitEncryptedOrderArgs;
+    use crate::instructions::reveal_encrypted_order::RevealEncryptedOrderArgs;
+    use crate::oracle::OracleSource;
+    use crate::state::{
+        EncryptedOrder, EncryptedOrderStatus, ExecutorAuthority, FeedHealth, FEED_RELATION_SINGLE,
+    };
+    use crate::switchboard::{
+        LAST_UPDATE_TIMESTAMP_OFFSET, PULL_FEED_DISCRIMINATOR, RESULT_VALUE_OFFSET,
+        SWITCHBOARD_ON_DEMAND_ID,
+    };
+    use crate::tests::svm::{
+        executor_pda, fetch, instruction_data, send, setup_executor, sighash, LAMPORTS_PER_SOL,
+    };
+    use crate::ID as PROGRAM_ID;
+    use anchor_lang::prelude::Clock;
+    use litesvm::LiteSVM;
+    use solana_sdk::{
+        account::Account,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+        system_program,
+    };
+
+    const ORDER_HASH: [u8; 32] = [3u8; 32];
+    const COMMITMENT_KEY: [u8; 32] = [9u8; 32];
+
+    fn order_pda(owner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[EncryptedOrder::SEED_PREFIX, owner.as_ref(), &ORDER_HASH], &PROGRAM_ID).0
+    }
+
+    fn ciphertext_commitment(ciphertext: &[u8]) -> [u8; 32] {
+        EncryptedOrder::compute_ciphertext_commitment(
+            ciphertext,
+            &ORDER_HASH,
+            &EncryptedOrder::commitment_key_hash(&COMMITMENT_KEY),
+        )
+    }
+
+    /// A funded owner with an executor and an order committed to `ciphertext`.
+    fn setup_committed_order(ciphertext: &[u8]) -> (LiteSVM, Keypair) {
+        let (mut svm, owner) = setup_executor(Pubkey::new_unique());
+
+        let commit_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(executor_pda(&owner.pubkey()), false),
+                AccountMeta::new(order_pda(&owner.pubkey()), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data(
+                "commit_encrypted_order",
+                CommitEncryptedOrderArgs {
+                    order_hash: ORDER_HASH,
+                    ciphertext_commitment: ciphertext_commitment(ciphertext),
+                },
+            ),
+        };
+        send(&mut svm, &owner, commit_ix).expect("Commit should succeed");
+
+        (svm, owner)
+    }
+
+    fn reveal_ix(signer: &Pubkey, order: &Pubkey, ciphertext: &[u8]) -> Instruction {
+        reveal_ix_on_feed(signer, order, ciphertext, [1u8; 32], OracleSource::Pyth)
+    }
+
+    fn reveal_ix_on_feed(
+        signer: &Pubkey,
+        order: &Pubkey,
+        ciphertext: &[u8],
+        feed_id: [u8; 32],
+        oracle_source: OracleSource,
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*signer, true),
+                AccountMeta::new(*order, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data(
+                "reveal_encrypted_order",
+                RevealEncryptedOrderArgs {
+                    encrypted_data: ciphertext.to_vec(),
+                    feed_id,
+                    feed_id_b: [0u8; 32],
+                    relation: FEED_RELATION_SINGLE,
+                    oracle_source,
+                    expiry: 0,
+                    ephemeral_pubkey: [2u8; 32],
+                    nonce: [4u8; 12],
+                    compute_units: 0,
+                    commitment_key_hash: EncryptedOrder::commitment_key_hash(&COMMITMENT_KEY),
+                },
+            ),
+        }
+    }
+
+    #[test]
+    fn test_reveal_grows_committed_order_to_payload() {
+        let ciphertext = vec![0x5au8; 200];
+        let (mut svm, owner) = setup_committed_order(&ciphertext);
+        let order_address = order_pda(&owner.pubkey());
+
+        let committed = svm.get_account(&order_address).unwrap();
+        assert_eq!(committed.data.len(), EncryptedOrder::space_for(0));
+
+        send(&mut svm, &owner, reveal_ix(&owner.pubkey(), &order_address, &ciphertext))
+            .expect("Reveal should succeed");
+
+        // Resized to the ciphertext, with the owner covering the extra rent
+        let revealed = svm.get_account(&order_address).unwrap();
+        assert_eq!(revealed.data.len(), EncryptedOrder::space_for(ciphertext.len()));
+        assert!(revealed.lamports >= svm.minimum_balance_for_rent_exemption(revealed.data.len()));
+
+        let order: EncryptedOrder = fetch(&svm, &order_address);
+        assert_eq!(order.status, EncryptedOrderStatus::Active);
+        assert_eq!(order.encrypted_data, ciphertext);
+        assert_eq!(order.feed_id, [1u8; 32]);
+    }
+
+    #[test]
+    fn test_reveal_rejected_for_non_owner() {
+        let ciphertext = vec![0x5au8; 64];
+        let (mut svm, owner) = setup_committed_order(&ciphertext);
+        let order_address = order_pda(&owner.pubkey());
+
+        let intruder = Keypair::new();
+        svm.airdrop(&intruder.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        let result = send(&mut svm, &intruder, reveal_ix(&intruder.pubkey(), &order_address, &ciphertext));
+        assert!(result.is_err(), "Only the owner may reveal");
+
+        let order: EncryptedOrder = fetch(&svm, &order_address);
+        assert_eq!(order.status, EncryptedOrderStatus::Committed);
+        assert!(order.encrypted_data.is_empty());
+    }
+
+    #[test]
+    fn test_committed_order_can_be_cancelled() {
+        let (mut svm, owner) = setup_committed_order(&[0x5au8; 64]);
+        let order_address = order_pda(&owner.pubkey());
+        let executor_address = executor_pda(&owner.pubkey());
+
+        let cancel_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(order_address, false),
+                AccountMeta::new(executor_address, false),
+            ],
+            data: sighash("cancel_encrypted_order").to_vec(),
+        };
+        send(&mut svm, &owner, cancel_ix).expect("Cancelling a committed order should succeed");
+
+        let order: EncryptedOrder = fetch(&svm, &order_address);
+        assert_eq!(order.status, EncryptedOrderStatus::Cancelled);
+
+        let executor: ExecutorAuthority = fetch(&svm, &executor_address);
+        assert!(!executor.has_order_hash(&ORDER_HASH));
+
+        // A cancelled order can no longer be revealed
+        let result = send(&mut svm, &owner, reveal_ix(&owner.pubkey(), &order_address, &[0x5au8; 64]));
+        assert!(result.is_err(), "Reveal after cancel should fail");
+    }
+
+    #[test]
+    fn test_stale_feed_skips_price_check() {
+        let ciphertext = vec![0x5au8; 64];
+        let (mut svm, owner) = setup_committed_order(&ciphertext);
+        let order_address = order_pda(&owner.pubkey());
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp = 1_700_000_000;
+        svm.set_sysvar(&clock);
+
+        // A Switchboard pull feed last updated an hour ago
+        let feed = Pubkey::new_unique();
+        let mut data = vec![0u8; 3208];
+        data[..8].copy_from_slice(&PULL_FEED_DISCRIMINATOR);
+        data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&(clock.unix_timestamp - 3_600).to_le_bytes());
+        data[RESULT_VALUE_OFFSET..RESULT_VALUE_OFFSET + 16]
+            .copy_from_slice(&150_000_000_000_000_000_000i128.to_le_bytes());
+        let feed_account = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: SWITCHBOARD_ON_DEMAND_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(feed, feed_account).unwrap();
+
+        let reveal = reveal_ix_on_feed(
+            &owner.pubkey(),
+            &order_address,
+            &ciphertext,
+            feed.to_bytes(),
+            OracleSource::Switchboard,
+        );
+        send(&mut svm, &owner, reveal).expect("Reveal should succeed");
+
+        let check_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(order_address, false),
+                AccountMeta::new_readonly(feed, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(
+                    Pubkey::find_program_address(&[FeedHealth::SEED_PREFIX, feed.as_ref()], &PROGRAM_ID).0,
+                    false,
+                ),
+            ],
+            data: sighash("check_price_update").to_vec(),
+        };
+        send(&mut svm, &owner, check_ix).expect("A stale feed should skip the order, not fail");
+
+        let order: EncryptedOrder = fetch(&svm, &order_address);
+        assert_eq!(order.status, EncryptedOrderStatus::Active);
+        assert_eq!(order.last_observed_price, 0);
+    }
+
+    #[test]
+    fn test_close_without_executor_account() {
+        let (mut svm, owner) = setup_committed_order(&[0x5au8; 64]);
+        let order_address = order_pda(&owner.pubkey());
+
+        let cancel_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(order_address, false),
+                AccountMeta::new(executor_pda(&owner.pubkey()), false),
+            ],
+            data: sighash("cancel_encrypted_order").to_vec(),
+        };
+        send(&mut svm, &owner, cancel_ix).expect("Cancel should succeed");
+
+        // The account list clients used before the executor was added
+        let close_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(order_address, false),
+            ],
+            data: sighash("close_encrypted_order").to_vec(),
+        };
+        send(&mut svm, &owner, close_ix).expect("Close without the executor should succeed");
+
+        assert!(svm.get_account(&order_address).is_none_or(|account| account.data.is_empty()));
+    }
+}
@@ -0,0 +1,93 @@
+//! Order execution instructions run end to end against the built program.
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::consume_and_execute::ConsumeAndExecuteArgs;
+    use crate::oracle::OracleSource;
+    use crate::state::{BridgeConfig, FeedHealth};
+    use crate::tests::svm::{executor_pda, instruction_data, send, setup_executor};
+    use crate::ID as PROGRAM_ID;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signer::Signer,
+    };
+
+    const FEED_ID: [u8; 32] = [1u8; 32];
+
+    /// An owner-executed `consume_and_execute` for a perp order of
+    /// `base_asset_amount` (1e9 precision) at `trigger_price` (1e6 precision).
+    fn consume_ix(owner: &Pubkey, drift_user: &Pubkey, base_asset_amount: u64, trigger_price: i64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(executor_pda(owner), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new(*drift_user, false),
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(
+                    Pubkey::find_program_address(&[BridgeConfig::SEED_PREFIX], &PROGRAM_ID).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(
+                    Pubkey::find_program_address(&[FeedHealth::SEED_PREFIX, &FEED_ID], &PROGRAM_ID).0,
+                    false,
+                ),
+            ],
+            data: instruction_data(
+                "consume_and_execute",
+                ConsumeAndExecuteArgs {
+                    order_id: 1,
+                    market_index: 0,
+                    market_type: 1,
+                    trigger_price,
+                    trigger_condition: 1,
+                    order_side: 0,
+                    base_asset_amount,
+                    reduce_only: false,
+                    expiry: 0,
+                    feed_id: FEED_ID,
+                    salt: [0u8; 16],
+                    current_price: trigger_price,
+                    keep_delegated: false,
+                    oracle_source: OracleSource::Pyth,
+                },
+            ),
+        }
+    }
+
+    #[test]
+    fn test_execution_rejected_above_max_notional() {
+        let drift_user = Pubkey::new_unique();
+        let (mut svm, owner) = setup_executor(drift_user);
+
+        // Cap single orders at $1,000
+        let set_cap_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(owner.pubkey(), true),
+                AccountMeta::new(executor_pda(&owner.pubkey()), false),
+            ],
+            data: instruction_data("set_max_order_notional", 1_000_000_000u64),
+        };
+        send(&mut svm, &owner, set_cap_ix).expect("Setting the cap should succeed");
+
+        // 10 SOL at $150 is over the cap
+        let logs = send(&mut svm, &owner, consume_ix(&owner.pubkey(), &drift_user, 10_000_000_000, 150_000_000))
+            .unwrap_err();
+        assert!(logs.contains("ExceedsMaxNotional"), "Order over the cap should be rejected: {logs}");
+
+        // 1 SOL at $150 passes the cap and only fails for having no stored hash
+        let logs = send(&mut svm, &owner, consume_ix(&owner.pubkey(), &drift_user, 1_000_000_000, 150_000_000))
+            .unwrap_err();
+        assert!(logs.contains("OrderHashNotFound"), "Order under the cap should pass it: {logs}");
+    }
+}
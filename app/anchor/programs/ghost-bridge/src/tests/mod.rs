@@ -1 +1,5 @@
+mod compressed_order_handlers;
+mod encrypted_order_handlers;
+mod execution_handlers;
 mod privacy_audit;
+mod svm;
@@ -6,9 +6,12 @@
 #[cfg(test)]
 mod tests {
     use crate::state::{
-        CompressedGhostOrder, ExecutorAuthority, OrderSide, TriggerCondition,
-        MAX_ORDERS_PER_EXECUTOR, MAX_AUTHORIZED_EXECUTORS,
+        AllowedMarket, CompressedGhostOrder, ExecutorAuthority, OrderSide, TriggerCondition,
+        MAX_ALLOWED_MARKETS, MAX_ORDERS_PER_EXECUTOR, MAX_AUTHORIZED_EXECUTORS,
     };
+    use crate::account_version::ACCOUNT_VERSION;
+    use crate::drift_cpi::DriftMarketType;
+    use crate::time::FixedTime;
     use anchor_lang::prelude::Pubkey;
 
     /// TEST A: Verify hash is deterministic across multiple computations
@@ -19,6 +22,7 @@ mod tests {
             owner,
             order_id: 12345,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 180_000_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Long,
@@ -44,6 +48,7 @@ mod tests {
             owner,
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 180_000_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Long,
@@ -73,6 +78,7 @@ mod tests {
             owner,
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 50_000_000_000,
             trigger_condition: TriggerCondition::Above,
             order_side: OrderSide::Short,
@@ -94,6 +100,7 @@ mod tests {
             owner: Pubkey::new_unique(),
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 50_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Long,
@@ -115,6 +122,7 @@ mod tests {
             owner: Pubkey::new_unique(),
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 50_000,
             trigger_condition: TriggerCondition::Above,
             order_side: OrderSide::Short,
@@ -133,24 +141,25 @@ mod tests {
     /// TEST E: Verify expiry logic
     #[test]
     fn test_order_expiry() {
-        let now = 1700000000i64;
+        let now = FixedTime::at(1700000000);
 
         let order_expired = CompressedGhostOrder {
             owner: Pubkey::new_unique(),
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 50_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Long,
             base_asset_amount: 1_000_000,
             reduce_only: false,
-            expiry: now - 100,
+            expiry: now.unix_timestamp - 100,
             feed_id: [0u8; 32],
             salt: [6u8; 16],
         };
 
         let order_valid = CompressedGhostOrder {
-            expiry: now + 100,
+            expiry: now.unix_timestamp + 100,
             ..order_expired.clone()
         };
 
@@ -159,9 +168,9 @@ mod tests {
             ..order_expired.clone()
         };
 
-        assert!(order_expired.is_expired(now), "Past expiry should be expired");
-        assert!(!order_valid.is_expired(now), "Future expiry should NOT be expired");
-        assert!(!order_no_expiry.is_expired(now), "Zero expiry should never expire");
+        assert!(order_expired.is_expired(&now), "Past expiry should be expired");
+        assert!(!order_valid.is_expired(&now), "Future expiry should NOT be expired");
+        assert!(!order_no_expiry.is_expired(&now), "Zero expiry should never expire");
     }
 
     /// TEST F: Verify ExecutorAuthority hash management
@@ -240,6 +249,7 @@ mod tests {
             owner,
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 50_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Long,
@@ -264,6 +274,12 @@ mod tests {
         };
         assert_ne!(base_hash, order_different_market.compute_hash(), "market_index affects hash");
 
+        let order_different_market_type = CompressedGhostOrder {
+            market_type: DriftMarketType::Spot,
+            ..base_order.clone()
+        };
+        assert_ne!(base_hash, order_different_market_type.compute_hash(), "market_type affects hash");
+
         let order_different_side = CompressedGhostOrder {
             order_side: OrderSide::Short,
             ..base_order.clone()
@@ -318,6 +334,7 @@ mod tests {
             owner,
             order_id: 1,
             market_index: 0,
+            market_type: DriftMarketType::Perp,
             trigger_price: 180_000_000,
             trigger_condition: TriggerCondition::Below,
             order_side: OrderSide::Long,
@@ -344,14 +361,23 @@ mod tests {
 
     fn create_test_executor(owner: Pubkey) -> ExecutorAuthority {
         ExecutorAuthority {
+            version: ACCOUNT_VERSION,
             owner,
             order_count: 0,
             is_delegated: false,
             bump: 255,
-            order_hashes: [[0u8; 32]; MAX_ORDERS_PER_EXECUTOR],
+            order_hashes: Vec::new(),
             order_hash_count: 0,
             authorized_executors: [Pubkey::default(); MAX_AUTHORIZED_EXECUTORS],
             executor_count: 0,
+            capacity: MAX_ORDERS_PER_EXECUTOR as u16,
+            executor_expires_at: [0; MAX_AUTHORIZED_EXECUTORS],
+            drift_user: Pubkey::default(),
+            previous_owner: Pubkey::default(),
+            allowed_markets: [AllowedMarket::default(); MAX_ALLOWED_MARKETS],
+            allowed_market_count: 0,
+            max_order_notional: 0,
+            protocol_fees_owed: 0,
         }
     }
 }
@@ -0,0 +1,81 @@
+//! Helpers for running ghost-bridge instructions against the built program.
+
+use crate::state::ExecutorAuthority;
+use crate::ID as PROGRAM_ID;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+use base64::Engine;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_program,
+    transaction::Transaction,
+};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+pub fn sighash(name: &str) -> [u8; 8] {
+    let preimage = format!("global:{}", name);
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    sighash
+}
+
+pub fn instruction_data(name: &str, args: impl AnchorSerialize) -> Vec<u8> {
+    let mut data = sighash(name).to_vec();
+    args.serialize(&mut data).unwrap();
+    data
+}
+
+pub fn executor_pda(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ExecutorAuthority::SEED_PREFIX, owner.as_ref()], &PROGRAM_ID).0
+}
+
+/// Sends `ix` signed by `signer`, returning the program logs either way.
+pub fn send(svm: &mut LiteSVM, signer: &Keypair, ix: Instruction) -> Result<Vec<String>, String> {
+    let blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+    svm.send_transaction(tx)
+        .map(|meta| meta.logs)
+        .map_err(|failed| failed.meta.logs.join("\n"))
+}
+
+/// Decodes every `emit!`ed event of type `E` from a transaction's program logs.
+pub fn decode_events<E: AnchorDeserialize + Discriminator>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded).ok())
+        .filter(|data| data.starts_with(E::DISCRIMINATOR))
+        .map(|data| E::deserialize(&mut &data[E::DISCRIMINATOR.len()..]).expect("event should decode"))
+        .collect()
+}
+
+pub fn fetch<T: AccountDeserialize>(svm: &LiteSVM, address: &Pubkey) -> T {
+    let account = svm.get_account(address).expect("account should exist");
+    T::try_deserialize(&mut &account.data[..]).expect("account should decode")
+}
+
+/// A funded owner whose executor authority points at `drift_user`.
+pub fn setup_executor(drift_user: Pubkey) -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    let program_bytes = include_bytes!("../../../../target/deploy/ghost_bridge.so");
+    let _ = svm.add_program(PROGRAM_ID, program_bytes);
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+
+    let init_ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(executor_pda(&owner.pubkey()), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: instruction_data("init_executor", drift_user),
+    };
+    send(&mut svm, &owner, init_ix).expect("Init executor should succeed");
+
+    (svm, owner)
+}
@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+
+use crate::state::OrderSide;
+
+/// Anchor discriminator of Drift's `User` account (`sha256("account:User")[..8]`).
+pub const DRIFT_USER_DISCRIMINATOR: [u8; 8] = [159, 117, 95, 227, 239, 151, 58, 236];
+
+/// Minimal view of Drift's `User` account (zero-copy, `repr(C)`).
+///
+/// # Layout
+/// ```text
+/// [0-7]      discriminator
+/// [8-39]     authority
+/// [40-71]    delegate
+/// [72-103]   name
+/// [104-423]  spot_positions ([SpotPosition; 8], 40 bytes each)
+/// [424-1191] perp_positions ([PerpPosition; 8], 96 bytes each)
+///
+/// PerpPosition
+/// [+8]       base_asset_amount (i64 LE, signed: long > 0, short < 0)
+/// [+92]      market_index (u16 LE)
+/// ```
+pub const DELEGATE_OFFSET: usize = 8 + 32;
+pub const PERP_POSITIONS_OFFSET: usize = 8 + 32 + 32 + 32 + 8 * 40;
+pub const PERP_POSITION_SIZE: usize = 96;
+pub const MAX_PERP_POSITIONS: usize = 8;
+pub const PERP_BASE_ASSET_AMOUNT_OFFSET: usize = 8;
+pub const PERP_MARKET_INDEX_OFFSET: usize = 92;
+pub const MIN_DRIFT_USER_LEN: usize = PERP_POSITIONS_OFFSET + MAX_PERP_POSITIONS * PERP_POSITION_SIZE;
+
+/// Signed base amount the user holds in `market_index`; zero when flat.
+///
+/// Unused position slots are zeroed, so a slot only counts when its base
+/// amount is non-zero (market 0 would otherwise match every empty slot).
+pub fn perp_base_asset_amount(data: &[u8], market_index: u16) -> Result<i64> {
+    check_drift_user(data)?;
+
+    for slot in 0..MAX_PERP_POSITIONS {
+        let position = PERP_POSITIONS_OFFSET + slot * PERP_POSITION_SIZE;
+        let market = u16::from_le_bytes([
+            data[position + PERP_MARKET_INDEX_OFFSET],
+            data[position + PERP_MARKET_INDEX_OFFSET + 1],
+        ]);
+        let base_bytes: [u8; 8] = data
+            [position + PERP_BASE_ASSET_AMOUNT_OFFSET..position + PERP_BASE_ASSET_AMOUNT_OFFSET + 8]
+            .try_into()
+            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+        let base = i64::from_le_bytes(base_bytes);
+
+        if market == market_index && base != 0 {
+            return Ok(base);
+        }
+    }
+
+    Ok(0)
+}
+
+/// Fails unless the user has granted `delegate` as its Drift delegate;
+/// Drift rejects orders signed by anyone else.
+pub fn require_delegate(data: &[u8], delegate: &Pubkey) -> Result<()> {
+    check_drift_user(data)?;
+
+    let granted = Pubkey::try_from(&data[DELEGATE_OFFSET..DELEGATE_OFFSET + 32])
+        .map_err(|_| DriftUserError::InvalidDriftUser)?;
+    if granted != *delegate {
+        msg!("Drift user delegate {} is not {}", granted, delegate);
+        return Err(DriftUserError::DelegateNotAuthorized.into());
+    }
+    Ok(())
+}
+
+fn check_drift_user(data: &[u8]) -> Result<()> {
+    if data.len() < MIN_DRIFT_USER_LEN || data[..8] != DRIFT_USER_DISCRIMINATOR {
+        msg!("Invalid Drift user account: {} bytes", data.len());
+        return Err(DriftUserError::InvalidDriftUser.into());
+    }
+    Ok(())
+}
+
+/// A reduce-only order only does something when it trades against the open position.
+pub fn reduces_position(base_asset_amount: i64, side: OrderSide) -> bool {
+    match side {
+        OrderSide::Long => base_asset_amount < 0,
+        OrderSide::Short => base_asset_amount > 0,
+    }
+}
+
+/// Base amount a reduce-only order should send: never more than the open
+/// position, so an oversized amount means "close everything".
+pub fn reduce_only_amount(requested: u64, position_base: i64) -> u64 {
+    requested.min(position_base.unsigned_abs())
+}
+
+/// Base amount filled in the direction of `side` between two reads of the
+/// same perp position. Movement against `side` counts as no fill.
+pub fn filled_base_asset_amount(before: i64, after: i64, side: OrderSide) -> u64 {
+    let delta = after.saturating_sub(before);
+    let filled = match side {
+        OrderSide::Long => delta,
+        OrderSide::Short => delta.saturating_neg(),
+    };
+    filled.max(0) as u64
+}
+
+#[error_code]
+pub enum DriftUserError {
+    #[msg("Drift user account could not be parsed")]
+    InvalidDriftUser,
+    #[msg("Drift user has not granted the order's delegate PDA")]
+    DelegateNotAuthorized,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_drift_user(positions: &[(u16, i64)]) -> Vec<u8> {
+        let mut data = vec![0u8; 4376];
+        data[..8].copy_from_slice(&DRIFT_USER_DISCRIMINATOR);
+        for (slot, (market, base)) in positions.iter().enumerate() {
+            let position = PERP_POSITIONS_OFFSET + slot * PERP_POSITION_SIZE;
+            data[position + PERP_BASE_ASSET_AMOUNT_OFFSET..position + PERP_BASE_ASSET_AMOUNT_OFFSET + 8]
+                .copy_from_slice(&base.to_le_bytes());
+            data[position + PERP_MARKET_INDEX_OFFSET..position + PERP_MARKET_INDEX_OFFSET + 2]
+                .copy_from_slice(&market.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_short_reduced_by_long_order() {
+        let data = build_drift_user(&[(1, 5_000), (0, -2_000_000)]);
+
+        let base = perp_base_asset_amount(&data, 0).unwrap();
+        assert_eq!(base, -2_000_000);
+        assert!(reduces_position(base, OrderSide::Long));
+        assert!(!reduces_position(base, OrderSide::Short));
+    }
+
+    #[test]
+    fn test_flat_market_has_nothing_to_reduce() {
+        let data = build_drift_user(&[(1, 5_000)]);
+
+        let base = perp_base_asset_amount(&data, 0).unwrap();
+        assert_eq!(base, 0);
+        assert!(!reduces_position(base, OrderSide::Long));
+        assert!(!reduces_position(base, OrderSide::Short));
+    }
+
+    #[test]
+    fn test_same_direction_has_nothing_to_reduce() {
+        let data = build_drift_user(&[(2, 1_000_000)]);
+
+        let base = perp_base_asset_amount(&data, 2).unwrap();
+        assert!(reduces_position(base, OrderSide::Short));
+        assert!(!reduces_position(base, OrderSide::Long));
+    }
+
+    #[test]
+    fn test_invalid_drift_user_rejected() {
+        let mut data = build_drift_user(&[]);
+        assert!(perp_base_asset_amount(&data[..MIN_DRIFT_USER_LEN - 1], 0).is_err());
+
+        data[0] ^= 0xff;
+        assert!(perp_base_asset_amount(&data, 0).is_err());
+    }
+
+    #[test]
+    fn test_delegate_must_match_pda() {
+        let delegate_pda = Pubkey::new_unique();
+        let mut data = build_drift_user(&[]);
+        data[DELEGATE_OFFSET..DELEGATE_OFFSET + 32].copy_from_slice(delegate_pda.as_ref());
+        assert!(require_delegate(&data, &delegate_pda).is_ok());
+
+        let other = Pubkey::new_unique();
+        data[DELEGATE_OFFSET..DELEGATE_OFFSET + 32].copy_from_slice(other.as_ref());
+        assert_eq!(
+            require_delegate(&data, &delegate_pda).unwrap_err(),
+            DriftUserError::DelegateNotAuthorized.into()
+        );
+
+        // A user that never set a delegate has the default key there
+        let unset = build_drift_user(&[]);
+        assert!(require_delegate(&unset, &delegate_pda).is_err());
+    }
+
+    #[test]
+    fn test_reduce_only_clamped_to_position() {
+        let data = build_drift_user(&[(0, -300_000_000)]);
+        let base = perp_base_asset_amount(&data, 0).unwrap();
+
+        // Asking to buy back more than the short closes exactly the short
+        assert_eq!(reduce_only_amount(u64::MAX, base), 300_000_000);
+        assert_eq!(reduce_only_amount(1_000_000_000, base), 300_000_000);
+        // A smaller reduce is left alone
+        assert_eq!(reduce_only_amount(100_000_000, base), 100_000_000);
+        assert_eq!(reduce_only_amount(500_000, i64::MIN), 500_000);
+    }
+
+    #[test]
+    fn test_partial_fill_measured_from_position_change() {
+        let before = build_drift_user(&[(0, -2_000_000)]);
+        let after = build_drift_user(&[(0, -2_400_000)]);
+
+        let filled = filled_base_asset_amount(
+            perp_base_asset_amount(&before, 0).unwrap(),
+            perp_base_asset_amount(&after, 0).unwrap(),
+            OrderSide::Short,
+        );
+        assert_eq!(filled, 400_000);
+
+        // Opening from flat counts the whole new position
+        let flat = build_drift_user(&[]);
+        let long = build_drift_user(&[(0, 1_000_000)]);
+        assert_eq!(
+            filled_base_asset_amount(
+                perp_base_asset_amount(&flat, 0).unwrap(),
+                perp_base_asset_amount(&long, 0).unwrap(),
+                OrderSide::Long,
+            ),
+            1_000_000
+        );
+        assert_eq!(filled_base_asset_amount(0, 1_000_000, OrderSide::Short), 0);
+    }
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::drift_cpi::{build_drift_cancel_order, DRIFT_PROGRAM_ID};
+use crate::state::GhostOrder;
+use crate::instructions::migrate_account::MigrateError;
+
+/// Cancels a Drift order the delegate PDA placed for this ghost order, e.g.
+/// a ladder tranche still resting on the book after the order expired.
+/// `drift_order_id` is the id Drift assigned, read from the user account.
+pub fn handler(ctx: Context<CancelDriftOrder>, drift_order_id: u32) -> Result<()> {
+    let ghost_order = &ctx.accounts.ghost_order;
+    let authority = ctx.accounts.authority.key();
+
+    require!(
+        ghost_order.can_cancel_drift_orders(&authority),
+        CancelDriftOrderError::Unauthorized
+    );
+
+    let cancel_ix = Instruction {
+        program_id: DRIFT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(ctx.accounts.drift_state.key(), false),
+            AccountMeta::new(ctx.accounts.drift_user.key(), false),
+            // Authority is the delegate PDA, which signs via invoke_signed
+            AccountMeta::new_readonly(ctx.accounts.delegate_pda.key(), true),
+        ],
+        data: build_drift_cancel_order(drift_order_id),
+    };
+
+    let owner_key = ghost_order.owner;
+    let delegate_bump = [ghost_order.delegate_bump];
+    let delegate_seeds = GhostOrder::delegate_signer_seeds(&owner_key, &delegate_bump);
+
+    invoke_signed(
+        &cancel_ix,
+        &[
+            ctx.accounts.drift_state.to_account_info(),
+            ctx.accounts.drift_user.to_account_info(),
+            ctx.accounts.delegate_pda.to_account_info(),
+            ctx.accounts.drift_program.to_account_info(),
+        ],
+        &[&delegate_seeds],
+    )?;
+
+    emit!(DriftOrderCancelled {
+        order_id: ghost_order.order_id,
+        drift_order_id,
+        cancelled_by: authority,
+    });
+
+    msg!(
+        "Drift order cancelled: ghost_order={}, drift_order_id={}",
+        ghost_order.order_id,
+        drift_order_id
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelDriftOrder<'info> {
+    /// Order owner or a keeper on the order's allowlist
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
+        bump = ghost_order.bump,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub ghost_order: Account<'info, GhostOrder>,
+
+    /// CHECK: Delegate PDA that acts as authority for Drift CPI
+    #[account(
+        seeds = [GhostOrder::DELEGATE_SEED_PREFIX, ghost_order.owner.as_ref()],
+        bump = ghost_order.delegate_bump,
+    )]
+    pub delegate_pda: AccountInfo<'info>,
+
+    /// CHECK: Drift program state
+    pub drift_state: AccountInfo<'info>,
+
+    /// CHECK: User's Drift account
+    #[account(
+        mut,
+        constraint = drift_user.key() == ghost_order.drift_user @ CancelDriftOrderError::DriftUserMismatch
+    )]
+    pub drift_user: AccountInfo<'info>,
+
+    /// CHECK: Drift program
+    #[account(address = DRIFT_PROGRAM_ID)]
+    pub drift_program: AccountInfo<'info>,
+}
+
+#[event]
+pub struct DriftOrderCancelled {
+    pub order_id: u64,
+    pub drift_order_id: u32,
+    pub cancelled_by: Pubkey,
+}
+
+#[error_code]
+pub enum CancelDriftOrderError {
+    #[msg("Only the owner or one of the order's keepers can cancel Drift orders")]
+    Unauthorized,
+    #[msg("Drift user account mismatch")]
+    DriftUserMismatch,
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use crate::state::GhostOrder;
+use crate::instructions::schedule_monitoring::MAGIC_PROGRAM_ID;
+use crate::instructions::migrate_account::MigrateError;
+
+/// Magic program tag for `CancelTask`, next to `ScheduleTask` (1).
+pub const CANCEL_TASK_DISCRIMINATOR: u8 = 2;
+
+pub fn handler(ctx: Context<CancelMonitoring>) -> Result<()> {
+    require!(
+        ctx.accounts.ghost_order.is_monitored(),
+        CancelMonitoringError::NotMonitored
+    );
+
+    cancel_scheduled_task(
+        &mut ctx.accounts.ghost_order,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.magic_program,
+    )
+}
+
+/// Cancels the order's crank task, if it has one, and clears `crank_task_id`.
+/// `authority` must be the account that scheduled the task.
+pub fn cancel_scheduled_task<'info>(
+    ghost_order: &mut GhostOrder,
+    authority: &AccountInfo<'info>,
+    magic_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if !ghost_order.is_monitored() {
+        return Ok(());
+    }
+
+    let task_id = ghost_order.crank_task_id;
+    let cancel_ix = Instruction {
+        program_id: MAGIC_PROGRAM_ID,
+        accounts: vec![AccountMeta::new(authority.key(), true)],
+        data: build_cancel_task_data(task_id),
+    };
+
+    invoke(&cancel_ix, &[authority.clone(), magic_program.clone()])?;
+
+    ghost_order.clear_monitoring();
+
+    msg!("Ghost order monitoring cancelled: id={}, task_id={}", ghost_order.order_id, task_id);
+
+    Ok(())
+}
+
+pub fn build_cancel_task_data(task_id: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(9);
+    data.push(CANCEL_TASK_DISCRIMINATOR);
+    data.extend(task_id.to_le_bytes());
+    data
+}
+
+#[derive(Accounts)]
+pub struct CancelMonitoring<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &ghost_order.order_id.to_le_bytes()],
+        bump = ghost_order.bump,
+        constraint = ghost_order.owner == owner.key() @ CancelMonitoringError::NotOwner,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub ghost_order: Account<'info, GhostOrder>,
+
+    /// CHECK: Magic Program that owns the scheduled task
+    #[account(address = MAGIC_PROGRAM_ID)]
+    pub magic_program: AccountInfo<'info>,
+}
+
+#[error_code]
+pub enum CancelMonitoringError {
+    #[msg("Only the owner can cancel monitoring")]
+    NotOwner,
+    #[msg("Order has no scheduled monitoring task")]
+    NotMonitored,
+}
@@ -1,18 +1,60 @@
 use anchor_lang::prelude::*;
-use crate::state::{GhostOrder, OrderStatus};
+use crate::state::{CancelReason, GhostOrder, OrderStatus};
+use crate::instructions::link_oco_orders::cancel_linked_sibling;
+use crate::instructions::cancel_monitoring::cancel_scheduled_task;
+use crate::instructions::schedule_monitoring::MAGIC_PROGRAM_ID;
+use crate::instructions::migrate_account::MigrateError;
 
 pub fn handler(ctx: Context<CancelOrder>) -> Result<()> {
     let ghost_order = &mut ctx.accounts.ghost_order;
+    let clock = Clock::get()?;
 
-    require!(
-        ghost_order.status == OrderStatus::Pending ||
-        ghost_order.status == OrderStatus::Active,
-        CancelError::OrderNotCancellable
-    );
+    let new_status = ghost_order.cancel_by_owner(&clock)?;
+    emit!(GhostOrderCancelled::for_order(ghost_order));
 
-    ghost_order.status = OrderStatus::Cancelled;
+    msg!("Ghost order {:?}: id={}", new_status, ghost_order.order_id);
 
-    msg!("Ghost order cancelled: id={}", ghost_order.order_id);
+    cancel_linked_sibling(
+        ghost_order.key(),
+        ghost_order,
+        ctx.accounts.sibling_order.as_mut(),
+        Some(ctx.accounts.owner.to_account_info()),
+    )?;
+
+    // Stop the crank so it does not keep spending compute on a dead order.
+    // Best effort: the cancel stands without the Magic program, and the
+    // task id stays on the order for a later `cancel_monitoring`
+    match ctx.accounts.magic_program.as_ref() {
+        Some(magic_program) => cancel_scheduled_task(
+            &mut ctx.accounts.ghost_order,
+            &ctx.accounts.owner.to_account_info(),
+            magic_program,
+        )?,
+        None if ctx.accounts.ghost_order.is_monitored() => msg!(
+            "Crank task {} left scheduled; no Magic program passed",
+            ctx.accounts.ghost_order.crank_task_id
+        ),
+        None => {}
+    }
+
+    // Nothing can execute an order whose ready window lapsed; hand the
+    // rent and any unpaid keeper fee back to the owner
+    if new_status == OrderStatus::Expired {
+        ctx.accounts
+            .ghost_order
+            .close(ctx.accounts.owner.to_account_info())?;
+    } else {
+        // A cancelled order stays open for `cancel_drift_order`, but no
+        // keeper can earn its fee any more
+        let order_info = ctx.accounts.ghost_order.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(order_info.data_len());
+        let refund = ctx.accounts.ghost_order.refund_keeper_fee(
+            &order_info,
+            &ctx.accounts.owner.to_account_info(),
+            rent_minimum,
+        )?;
+        msg!("Keeper fee refunded: {} lamports", refund);
+    }
 
     Ok(())
 }
@@ -26,9 +68,40 @@ pub struct CancelOrder<'info> {
         mut,
         seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &ghost_order.order_id.to_le_bytes()],
         bump = ghost_order.bump,
-        constraint = ghost_order.owner == owner.key() @ CancelError::NotOwner
+        constraint = ghost_order.owner == owner.key() @ CancelError::NotOwner,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
+
+    /// OCO sibling, required when the order is linked
+    #[account(
+        mut,
+        constraint = sibling_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub sibling_order: Option<Account<'info, GhostOrder>>,
+
+    /// CHECK: Magic Program, to cancel the order's scheduled crank task
+    #[account(address = MAGIC_PROGRAM_ID)]
+    pub magic_program: Option<AccountInfo<'info>>,
+}
+
+#[event]
+pub struct GhostOrderCancelled {
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub status: OrderStatus,
+    pub cancel_reason: CancelReason,
+}
+
+impl GhostOrderCancelled {
+    pub fn for_order(order: &GhostOrder) -> Self {
+        Self {
+            owner: order.owner,
+            order_id: order.order_id,
+            status: order.status,
+            cancel_reason: order.cancel_reason,
+        }
+    }
 }
 
 #[error_code]
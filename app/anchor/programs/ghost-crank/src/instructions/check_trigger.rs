@@ -1,58 +1,162 @@
 use anchor_lang::prelude::*;
-use crate::state::{GhostOrder, OrderStatus};
+use crate::state::{CancelReason, CrankConfig, GhostOrder, MarketRegistry, OrderStatus, TriggerCondition};
+use crate::instructions::cancel_order::GhostOrderCancelled;
+use crate::instructions::crank_config::require_not_paused;
+use crate::oracle::{read_price, rescale_price};
+use crate::instructions::migrate_account::MigrateError;
+
+/// Most orders one `check_triggers_batch` call will walk.
+pub const MAX_BATCH_ORDERS: usize = 16;
 
 pub fn handler(ctx: Context<CheckTrigger>) -> Result<()> {
     let ghost_order = &mut ctx.accounts.ghost_order;
     let clock = Clock::get()?;
 
+    require_not_paused(&ctx.accounts.crank_config)?;
+
     if !ghost_order.is_active() {
         msg!("Order not active, skipping check");
         return Ok(());
     }
 
-    if ghost_order.is_expired(clock.unix_timestamp) {
-        ghost_order.status = OrderStatus::Expired;
+    if ghost_order.is_expired(&clock) {
+        ghost_order.cancel(OrderStatus::Expired, CancelReason::Expired);
+        emit!(GhostOrderCancelled::for_order(ghost_order));
         msg!("Order expired: id={}", ghost_order.order_id);
         return Ok(());
     }
 
-    let current_price = read_pyth_price(&ctx.accounts.price_feed)?;
-
-    msg!("Checking trigger: current_price={}, trigger_price={}, condition={:?}",
-         current_price, ghost_order.trigger_price, ghost_order.trigger_condition);
+    ctx.accounts.market_registry.verify_feed(
+        ghost_order.market_type,
+        ghost_order.market_index,
+        &ghost_order.feed_id,
+    )?;
+
+    let current_price = ghost_order.read_price(&ctx.accounts.price_feed, &clock)?;
+
+    if !ghost_order.accept_observed_price(current_price) {
+        emit!(PriceJumpRejected {
+            order_id: ghost_order.order_id,
+            last_observed_price: ghost_order.last_observed_price,
+            current_price,
+        });
+        msg!("Price jump rejected for order {}: last={}, now={}",
+             ghost_order.order_id, ghost_order.last_observed_price, current_price);
+        return Ok(());
+    }
 
-    if ghost_order.check_trigger(current_price) {
-        ghost_order.status = OrderStatus::Triggered;
-        ghost_order.triggered_at = clock.unix_timestamp;
-        ghost_order.execution_price = current_price;
+    let fired = ghost_order.apply_trigger_check(current_price, &clock);
+    emit!(TriggerEvaluated::for_order(ghost_order, current_price, fired));
 
+    if fired {
         msg!("TRIGGER FIRED! Order {} triggered at price {}",
              ghost_order.order_id, current_price);
+    } else if ghost_order.confirmation_count > 0 {
+        msg!("Trigger confirmation {}/{}",
+             ghost_order.confirmation_count, ghost_order.required_confirmations);
+    }
+
+    if ghost_order.is_trailing {
+        msg!("Trailing stop: extreme_price={}, trigger_price={}",
+             ghost_order.extreme_price, ghost_order.trigger_price);
     }
 
+    msg!("Checked trigger: current_price={}, trigger_price={}, condition={:?}",
+         current_price, ghost_order.trigger_price, ghost_order.trigger_condition);
+
     Ok(())
 }
 
-fn read_pyth_price(price_feed: &AccountInfo) -> Result<i64> {
-    let data = price_feed.try_borrow_data()?;
-
-    if data.len() < 32 {
-        msg!("Invalid price feed data length");
-        return Ok(0);
-    }
+/// Checks up to `MAX_BATCH_ORDERS` ghost orders, passed writable in
+/// `remaining_accounts`, against a single read of `price_feed`. Inactive
+/// orders are skipped; every order checked must share the feed.
+pub fn batch_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CheckTriggersBatch<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
 
-    let price_offset = 8;
-    if data.len() < price_offset + 8 {
-        return Ok(0);
+    require_not_paused(&ctx.accounts.crank_config)?;
+    require!(
+        ctx.remaining_accounts.len() <= MAX_BATCH_ORDERS,
+        CheckTriggerError::BatchTooLarge
+    );
+
+    let mut price = None;
+    let mut fired = 0u8;
+
+    for order_info in ctx.remaining_accounts {
+        require!(order_info.is_writable, CheckTriggerError::OrderNotWritable);
+        let mut ghost_order = Account::<GhostOrder>::try_from(order_info)?;
+        require!(ghost_order.is_current_version(), MigrateError::UnsupportedAccountVersion);
+        let expected = Pubkey::create_program_address(
+            &[
+                GhostOrder::SEED_PREFIX,
+                ghost_order.owner.as_ref(),
+                &ghost_order.order_id.to_le_bytes(),
+                &[ghost_order.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(CheckTriggerError::InvalidOrderAccount))?;
+        require_keys_eq!(order_info.key(), expected, CheckTriggerError::InvalidOrderAccount);
+
+        if !ghost_order.is_active() {
+            continue;
+        }
+
+        if ghost_order.is_expired(&clock) {
+            ghost_order.cancel(OrderStatus::Expired, CancelReason::Expired);
+            emit!(GhostOrderCancelled::for_order(&ghost_order));
+            ghost_order.exit(ctx.program_id)?;
+            msg!("Order expired: id={}", ghost_order.order_id);
+            continue;
+        }
+
+        // The first order to need a price fixes the feed for the batch
+        let (source, feed_id, feed_price) = match price {
+            Some(read) => read,
+            None => {
+                let source = ghost_order.oracle_source;
+                let feed_id = ghost_order.feed_id;
+                let read = read_price(source, &ctx.accounts.price_feed, &feed_id, &clock)?;
+                price = Some((source, feed_id, read));
+                (source, feed_id, read)
+            }
+        };
+        require!(
+            ghost_order.oracle_source == source && ghost_order.feed_id == feed_id,
+            CheckTriggerError::FeedMismatch
+        );
+
+        ctx.accounts.market_registry.verify_feed(
+            ghost_order.market_type,
+            ghost_order.market_index,
+            &ghost_order.feed_id,
+        )?;
+
+        let current_price = rescale_price(feed_price, ghost_order.price_exponent)?;
+        if !ghost_order.accept_observed_price(current_price) {
+            emit!(PriceJumpRejected {
+                order_id: ghost_order.order_id,
+                last_observed_price: ghost_order.last_observed_price,
+                current_price,
+            });
+            msg!("Price jump rejected for order {}, skipping", ghost_order.order_id);
+            continue;
+        }
+        let order_fired = ghost_order.apply_trigger_check(current_price, &clock);
+        emit!(TriggerEvaluated::for_order(&ghost_order, current_price, order_fired));
+        if order_fired {
+            fired += 1;
+            msg!("TRIGGER FIRED! Order {} triggered at price {}",
+                 ghost_order.order_id, current_price);
+        }
+        ghost_order.exit(ctx.program_id)?;
     }
 
-    let price_bytes: [u8; 8] = data[price_offset..price_offset + 8]
-        .try_into()
-        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+    msg!("Batch checked: orders={}, fired={}", ctx.remaining_accounts.len(), fired);
 
-    let price = i64::from_le_bytes(price_bytes);
-
-    Ok(price)
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -60,10 +164,86 @@ pub struct CheckTrigger<'info> {
     #[account(
         mut,
         seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
-        bump = ghost_order.bump
+        bump = ghost_order.bump,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
 
-    /// CHECK: Pyth Lazer price feed account
+    /// CHECK: Price feed for `ghost_order.feed_id`; owner and layout checked
+    /// per `ghost_order.oracle_source`
+    pub price_feed: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    #[account(
+        seeds = [MarketRegistry::SEED_PREFIX],
+        bump = market_registry.bump
+    )]
+    pub market_registry: Account<'info, MarketRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct CheckTriggersBatch<'info> {
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    /// CHECK: Price feed for the `feed_id` every order in the batch shares;
+    /// owner and layout checked per the orders' `oracle_source`
     pub price_feed: AccountInfo<'info>,
+
+    #[account(
+        seeds = [MarketRegistry::SEED_PREFIX],
+        bump = market_registry.bump
+    )]
+    pub market_registry: Account<'info, MarketRegistry>,
+}
+
+/// Every accepted price read, fired or not, so an indexer can replay why an
+/// order did or did not trigger.
+#[event]
+pub struct TriggerEvaluated {
+    pub order_id: u64,
+    pub current_price: i64,
+    /// After any trailing move made by this read
+    pub trigger_price: i64,
+    pub condition: TriggerCondition,
+    pub fired: bool,
+}
+
+impl TriggerEvaluated {
+    pub fn for_order(order: &GhostOrder, current_price: i64, fired: bool) -> Self {
+        Self {
+            order_id: order.order_id,
+            current_price,
+            trigger_price: order.trigger_price,
+            condition: order.trigger_condition,
+            fired,
+        }
+    }
+}
+
+#[event]
+pub struct PriceJumpRejected {
+    pub order_id: u64,
+    pub last_observed_price: i64,
+    pub current_price: i64,
+}
+
+#[error_code]
+pub enum CheckTriggerError {
+    #[msg("Batch exceeds MAX_BATCH_ORDERS ghost orders")]
+    BatchTooLarge,
+    #[msg("Batched ghost orders must be writable")]
+    OrderNotWritable,
+    #[msg("Account is not a ghost order PDA")]
+    InvalidOrderAccount,
+    #[msg("Batched ghost orders must share the price feed")]
+    FeedMismatch,
 }
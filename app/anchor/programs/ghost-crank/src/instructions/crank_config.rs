@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use crate::program::GhostCrank;
+use crate::state::CrankConfig;
+
+pub fn init_handler(ctx: Context<InitCrankConfig>, admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.crank_config;
+    config.admin = admin;
+    config.is_paused = false;
+    config.bump = ctx.bumps.crank_config;
+
+    msg!("Crank config initialized: admin={}", admin);
+
+    Ok(())
+}
+
+pub fn set_pause_handler(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.crank_config;
+    config.is_paused = paused;
+
+    msg!("Crank {}", if paused { "paused" } else { "resumed" });
+
+    emit!(CrankPauseChanged {
+        admin: ctx.accounts.admin.key(),
+        is_paused: paused,
+    });
+
+    Ok(())
+}
+
+/// Guard shared by every instruction that checks or executes orders.
+pub fn require_not_paused(config: &CrankConfig) -> Result<()> {
+    require!(!config.is_paused, CrankConfigError::CrankPaused);
+    Ok(())
+}
+
+/// Only the program's upgrade authority may create the config and so pick
+/// the admin; anyone else could front-run the deploy and take the pause switch.
+#[derive(Accounts)]
+pub struct InitCrankConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, GhostCrank>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ CrankConfigError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = CrankConfig::LEN,
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump,
+        constraint = crank_config.is_admin(&admin.key()) @ CrankConfigError::NotAdmin
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+}
+
+#[event]
+pub struct CrankPauseChanged {
+    pub admin: Pubkey,
+    pub is_paused: bool,
+}
+
+#[error_code]
+pub enum CrankConfigError {
+    #[msg("Crank is paused")]
+    CrankPaused,
+    #[msg("Only the crank admin can change the pause state")]
+    NotAdmin,
+    #[msg("Only the program upgrade authority can initialize the crank config")]
+    NotUpgradeAuthority,
+}
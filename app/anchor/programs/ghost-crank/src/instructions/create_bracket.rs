@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::instructions::create_ghost_order::{initialize_order, CreateGhostOrderArgs};
+use crate::state::{
+    bracket_exit_conditions, min_order_size, validate_bracket_prices, GhostOrder, MarketRegistry,
+    OrderCounter, OrderIntent, OrderSide, TriggerCondition,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BracketLegArgs {
+    pub trigger_price: i64,
+    /// Commitment over the leg's params: opposite side, reduce-only, entry size
+    pub params_commitment: [u8; 32],
+    pub nonce: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateBracketArgs {
+    pub entry: CreateGhostOrderArgs,
+    pub take_profit: BracketLegArgs,
+    pub stop_loss: BracketLegArgs,
+}
+
+/// Creates an entry order plus its take-profit and stop-loss in one call.
+/// The exit legs are OCO siblings that stay `Pending` until the entry has
+/// executed; they close the entry's full size and never expire.
+pub fn handler(ctx: Context<CreateBracket>, args: CreateBracketArgs) -> Result<()> {
+    let clock = Clock::get()?;
+    let owner = ctx.accounts.owner.key();
+    let entry = &args.entry;
+
+    // Exit legs activate once, off a single entry fill
+    require!(!entry.recurring, BracketError::RecurringEntry);
+
+    validate_bracket_prices(
+        entry.order_side,
+        entry.trigger_price,
+        args.take_profit.trigger_price,
+        args.stop_loss.trigger_price,
+    )?;
+
+    let (take_profit_condition, stop_loss_condition) = bracket_exit_conditions(entry.order_side);
+    let exit_side = match entry.order_side {
+        OrderSide::Long => OrderSide::Short,
+        OrderSide::Short => OrderSide::Long,
+    };
+    let exit_args = |leg: &BracketLegArgs,
+                     trigger_condition: TriggerCondition,
+                     intent: OrderIntent| CreateGhostOrderArgs {
+        trigger_price: leg.trigger_price,
+        trigger_condition,
+        order_side: exit_side,
+        reduce_only: true,
+        expiry_seconds: 0,
+        params_commitment: leg.params_commitment,
+        nonce: leg.nonce,
+        is_trailing: false,
+        trail_offset: 0,
+        ladder_steps: 0,
+        ladder_spacing: 0,
+        // The entry's bound is on the wrong side for the closing order
+        price_bound: 0,
+        intent,
+        recurring: false,
+        remaining_runs: 0,
+        ..entry.clone()
+    };
+
+    let entry_id = ctx.accounts.order_counter.take_next_id()?;
+    let take_profit_id = ctx.accounts.order_counter.take_next_id()?;
+    let stop_loss_id = ctx.accounts.order_counter.take_next_id()?;
+    let min_base_asset_amount = min_order_size(
+        ctx.accounts.market_registry.as_deref(),
+        entry.market_type,
+        entry.market_index,
+    );
+
+    initialize_order(
+        &mut ctx.accounts.entry_order,
+        owner,
+        entry_id,
+        ctx.bumps.entry_order,
+        ctx.program_id,
+        entry,
+        min_base_asset_amount,
+        &clock,
+    )?;
+    initialize_order(
+        &mut ctx.accounts.take_profit_order,
+        owner,
+        take_profit_id,
+        ctx.bumps.take_profit_order,
+        ctx.program_id,
+        &exit_args(&args.take_profit, take_profit_condition, OrderIntent::TakeProfit),
+        min_base_asset_amount,
+        &clock,
+    )?;
+    initialize_order(
+        &mut ctx.accounts.stop_loss_order,
+        owner,
+        stop_loss_id,
+        ctx.bumps.stop_loss_order,
+        ctx.program_id,
+        &exit_args(&args.stop_loss, stop_loss_condition, OrderIntent::StopLoss),
+        min_base_asset_amount,
+        &clock,
+    )?;
+
+    let entry_key = ctx.accounts.entry_order.key();
+    let take_profit_key = ctx.accounts.take_profit_order.key();
+    let stop_loss_key = ctx.accounts.stop_loss_order.key();
+    ctx.accounts.take_profit_order.attach_to_entry(entry_key, stop_loss_key);
+    ctx.accounts.stop_loss_order.attach_to_entry(entry_key, take_profit_key);
+
+    if entry.keeper_fee_lamports > 0 {
+        for leg in [
+            ctx.accounts.entry_order.to_account_info(),
+            ctx.accounts.take_profit_order.to_account_info(),
+            ctx.accounts.stop_loss_order.to_account_info(),
+        ] {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: leg,
+                    },
+                ),
+                entry.keeper_fee_lamports,
+            )?;
+        }
+    }
+
+    emit!(BracketCreated {
+        owner,
+        entry_order_id: entry_id,
+        take_profit_order_id: take_profit_id,
+        stop_loss_order_id: stop_loss_id,
+    });
+
+    msg!(
+        "Bracket created: entry={}, take_profit={} @ {}, stop_loss={} @ {}",
+        entry_id,
+        take_profit_id,
+        args.take_profit.trigger_price,
+        stop_loss_id,
+        args.stop_loss.trigger_price
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateBracket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OrderCounter::SEED_PREFIX, owner.key().as_ref()],
+        bump = order_counter.bump
+    )]
+    pub order_counter: Account<'info, OrderCounter>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = GhostOrder::LEN,
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &order_counter.next_order_id.to_le_bytes()],
+        bump
+    )]
+    pub entry_order: Box<Account<'info, GhostOrder>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = GhostOrder::LEN,
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &order_counter.next_order_id.wrapping_add(1).to_le_bytes()],
+        bump
+    )]
+    pub take_profit_order: Box<Account<'info, GhostOrder>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = GhostOrder::LEN,
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &order_counter.next_order_id.wrapping_add(2).to_le_bytes()],
+        bump
+    )]
+    pub stop_loss_order: Box<Account<'info, GhostOrder>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Supplies the market's minimum order size; without it the default applies
+    #[account(seeds = [MarketRegistry::SEED_PREFIX], bump = market_registry.bump)]
+    pub market_registry: Option<Account<'info, MarketRegistry>>,
+}
+
+#[event]
+pub struct BracketCreated {
+    pub owner: Pubkey,
+    pub entry_order_id: u64,
+    pub take_profit_order_id: u64,
+    pub stop_loss_order_id: u64,
+}
+
+#[error_code]
+pub enum BracketError {
+    #[msg("Take-profit must be beyond the entry in the profit direction and stop-loss in the loss direction")]
+    InvalidBracketPrices,
+    #[msg("Bracket leg activation requires its entry order")]
+    ParentMissing,
+    #[msg("Provided account is not this leg's entry order")]
+    ParentMismatch,
+    #[msg("Bracket legs activate only after the entry has executed")]
+    EntryNotExecuted,
+    #[msg("A bracket entry cannot be a recurring order")]
+    RecurringEntry,
+}
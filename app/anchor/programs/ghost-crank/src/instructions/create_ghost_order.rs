@@ -1,9 +1,15 @@
 use anchor_lang::prelude::*;
-use crate::state::{GhostOrder, TriggerCondition, OrderSide, OrderStatus};
+use anchor_lang::system_program;
+use crate::account_version::ACCOUNT_VERSION;
+use crate::drift_cpi::DriftMarketType;
+use crate::oracle::{is_supported_price_exponent, OracleSource};
+use crate::state::{
+    min_order_size, validate_base_asset_amount, validate_compute_units, validate_ready_window, CancelReason,
+    GhostOrder, MarketRegistry, OrderCounter, OrderIntent, TriggerCondition, OrderSide, OrderStatus, MAX_ALLOWED_KEEPERS, MAX_LADDER_STEPS,
+};
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct CreateGhostOrderArgs {
-    pub order_id: u64,
     pub market_index: u16,
     pub trigger_price: i64,
     pub trigger_condition: TriggerCondition,
@@ -16,20 +22,137 @@ pub struct CreateGhostOrderArgs {
     pub params_commitment: [u8; 32],
     pub nonce: u64,
     pub drift_user: Pubkey,
+    /// Slots after the trigger fires during which the owner may still cancel
+    pub cancel_grace_slots: u64,
+    pub is_trailing: bool,
+    pub trail_offset: i64,
+    /// Lamports paid to the keeper that executes the order; escrowed on the order account
+    pub keeper_fee_lamports: u64,
+    /// Consecutive trigger reads required before the order fires (0 or 1 = first read)
+    pub required_confirmations: u8,
+    pub oracle_source: OracleSource,
+    /// Number of limit-order tranches to split the entry into (0 or 1 = single market order)
+    pub ladder_steps: u8,
+    /// Price gap between consecutive tranches, stepping away from the market
+    /// from the execution price: down for a long, up for a short
+    pub ladder_spacing: i64,
+    pub market_type: DriftMarketType,
+    /// Largest price move since the trigger fired tolerated at execution, in bps (0 = unbounded)
+    pub max_slippage_bps: u16,
+    /// Compute budget for the Drift call handler (0 = default)
+    pub compute_units: u32,
+    /// Decimal exponent the prices above are quoted in (e.g. -6 for micro-units)
+    pub price_exponent: i32,
+    /// Worst price the order may fill at, in the same units (0 = plain market order)
+    pub price_bound: u64,
+    /// Label for indexers and the UI; does not change how the order runs
+    pub intent: OrderIntent,
+    /// Slots a keeper gets to execute once the order is ready (0 = default)
+    pub ready_window_slots: u64,
+    /// Re-arm after each fill (dollar-cost averaging) for `remaining_runs` runs
+    pub recurring: bool,
+    pub remaining_runs: u16,
+    /// Keepers allowed to execute the order; all default keys leaves it public
+    pub keeper_allowlist: [Pubkey; MAX_ALLOWED_KEEPERS],
 }
 
 pub fn handler(ctx: Context<CreateGhostOrder>, args: CreateGhostOrderArgs) -> Result<()> {
-    let ghost_order = &mut ctx.accounts.ghost_order;
     let clock = Clock::get()?;
 
-    // Derive delegate PDA for this user
-    let (delegate_pda, delegate_bump) = GhostOrder::derive_delegate_pda(
-        &ctx.accounts.owner.key(),
+    // The id comes from the owner's counter; the PDA seeds already used it
+    let order_id = ctx.accounts.order_counter.take_next_id()?;
+    let min_base_asset_amount = min_order_size(
+        ctx.accounts.market_registry.as_deref(),
+        args.market_type,
+        args.market_index,
+    );
+
+    initialize_order(
+        &mut ctx.accounts.ghost_order,
+        ctx.accounts.owner.key(),
+        order_id,
+        ctx.bumps.ghost_order,
         ctx.program_id,
+        &args,
+        min_base_asset_amount,
+        &clock,
+    )?;
+
+    if args.keeper_fee_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.ghost_order.to_account_info(),
+                },
+            ),
+            args.keeper_fee_lamports,
+        )?;
+    }
+
+    msg!("Ghost order created: id={}, trigger_price={}, condition={:?}, commitment={:?}",
+         order_id, args.trigger_price, args.trigger_condition,
+         &args.params_commitment[..8]);
+
+    Ok(())
+}
+
+/// Validates `args` and writes a fresh `Pending` order. Shared by single
+/// orders and each leg of a bracket; escrowing the keeper fee is left to
+/// the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_order(
+    ghost_order: &mut GhostOrder,
+    owner: Pubkey,
+    order_id: u64,
+    bump: u8,
+    program_id: &Pubkey,
+    args: &CreateGhostOrderArgs,
+    min_base_asset_amount: u64,
+    clock: &Clock,
+) -> Result<()> {
+    validate_base_asset_amount(args.base_asset_amount, min_base_asset_amount)?;
+
+    if args.is_trailing {
+        let trails_its_side = match args.order_side {
+            OrderSide::Long => args.trigger_condition == TriggerCondition::Below,
+            OrderSide::Short => args.trigger_condition == TriggerCondition::Above,
+        };
+        require!(
+            args.trail_offset > 0 && trails_its_side,
+            CreateOrderError::InvalidTrailingParams
+        );
+    }
+
+    if args.ladder_steps > 1 {
+        require!(
+            args.ladder_steps <= MAX_LADDER_STEPS &&
+            args.base_asset_amount >= args.ladder_steps as u64 &&
+            args.ladder_spacing > 0,
+            CreateOrderError::InvalidLadderParams
+        );
+    }
+
+    require!(
+        is_supported_price_exponent(args.price_exponent),
+        CreateOrderError::UnsupportedPriceExponent
+    );
+
+    validate_ready_window(args.ready_window_slots)?;
+    validate_compute_units(args.compute_units)?;
+
+    require!(
+        !args.recurring || args.remaining_runs > 0,
+        CreateOrderError::InvalidRecurringRuns
     );
 
-    ghost_order.owner = ctx.accounts.owner.key();
-    ghost_order.order_id = args.order_id;
+    // Derive delegate PDA for this user
+    let (delegate_pda, delegate_bump) = GhostOrder::derive_delegate_pda(&owner, program_id);
+
+    ghost_order.version = ACCOUNT_VERSION;
+    ghost_order.owner = owner;
+    ghost_order.order_id = order_id;
     ghost_order.market_index = args.market_index;
     ghost_order.trigger_price = args.trigger_price;
     ghost_order.trigger_condition = args.trigger_condition;
@@ -48,7 +171,7 @@ pub fn handler(ctx: Context<CreateGhostOrder>, args: CreateGhostOrderArgs) -> Re
     ghost_order.feed_id = args.feed_id;
     ghost_order.crank_task_id = 0;
     ghost_order.execution_price = 0;
-    ghost_order.bump = ctx.bumps.ghost_order;
+    ghost_order.bump = bump;
 
     // Set commitment fields
     ghost_order.params_commitment = args.params_commitment;
@@ -57,28 +180,106 @@ pub fn handler(ctx: Context<CreateGhostOrder>, args: CreateGhostOrderArgs) -> Re
     ghost_order.delegate_pda = delegate_pda;
     ghost_order.delegate_bump = delegate_bump;
     ghost_order.drift_user = args.drift_user;
+    ghost_order.execution_id = 0;
+    ghost_order.cancel_grace_slots = args.cancel_grace_slots;
+    ghost_order.triggered_slot = 0;
+    ghost_order.is_trailing = args.is_trailing;
+    ghost_order.trail_offset = args.trail_offset;
+    ghost_order.extreme_price = 0;
+    ghost_order.oco_sibling = Pubkey::default();
+    ghost_order.keeper_fee_lamports = args.keeper_fee_lamports;
+    ghost_order.required_confirmations = args.required_confirmations;
+    ghost_order.confirmation_count = 0;
+    ghost_order.oracle_source = args.oracle_source;
+    ghost_order.ladder_steps = args.ladder_steps;
+    ghost_order.ladder_spacing = args.ladder_spacing;
+    ghost_order.filled_amount = 0;
+    ghost_order.market_type = args.market_type;
+    ghost_order.executed_slot = 0;
+    ghost_order.parent_order = Pubkey::default();
+    ghost_order.max_slippage_bps = args.max_slippage_bps;
+    ghost_order.compute_units = args.compute_units;
+    ghost_order.price_exponent = args.price_exponent;
+    ghost_order.price_bound = args.price_bound;
+    ghost_order.last_observed_price = 0;
+    ghost_order.rejected_jumps = 0;
+    ghost_order.intent = args.intent;
+    ghost_order.ready_window_slots = args.ready_window_slots;
+    ghost_order.recurring = args.recurring;
+    ghost_order.remaining_runs = if args.recurring { args.remaining_runs } else { 0 };
+    ghost_order.cancel_reason = CancelReason::None;
+    ghost_order.keeper_allowlist = args.keeper_allowlist;
 
-    msg!("Ghost order created: id={}, trigger_price={}, condition={:?}, commitment={:?}",
-         args.order_id, args.trigger_price, args.trigger_condition,
-         &args.params_commitment[..8]);
+    emit!(GhostOrderCreated::for_order(ghost_order));
 
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(args: CreateGhostOrderArgs)]
 pub struct CreateGhostOrder<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [OrderCounter::SEED_PREFIX, owner.key().as_ref()],
+        bump = order_counter.bump
+    )]
+    pub order_counter: Account<'info, OrderCounter>,
+
     #[account(
         init,
         payer = owner,
         space = GhostOrder::LEN,
-        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &args.order_id.to_le_bytes()],
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &order_counter.next_order_id.to_le_bytes()],
         bump
     )]
     pub ghost_order: Account<'info, GhostOrder>,
 
     pub system_program: Program<'info, System>,
+
+    /// Supplies the market's minimum order size; without it the default applies
+    #[account(seeds = [MarketRegistry::SEED_PREFIX], bump = market_registry.bump)]
+    pub market_registry: Option<Account<'info, MarketRegistry>>,
+}
+
+#[event]
+pub struct GhostOrderCreated {
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub market_index: u16,
+    pub order_side: OrderSide,
+    pub trigger_price: i64,
+    pub intent: OrderIntent,
+}
+
+impl GhostOrderCreated {
+    pub fn for_order(order: &GhostOrder) -> Self {
+        Self {
+            owner: order.owner,
+            order_id: order.order_id,
+            market_index: order.market_index,
+            order_side: order.order_side,
+            trigger_price: order.trigger_price,
+            intent: order.intent,
+        }
+    }
+}
+
+#[error_code]
+pub enum CreateOrderError {
+    #[msg("Trailing orders need a positive offset on a Long/Below or Short/Above order")]
+    InvalidTrailingParams,
+    #[msg("Ladder needs at most MAX_LADDER_STEPS steps, one unit of size per step and a positive spacing")]
+    InvalidLadderParams,
+    #[msg("Order size is below the market's minimum")]
+    OrderBelowMinSize,
+    #[msg("Price exponent must be between PRICE_TARGET_EXPONENT and 0")]
+    UnsupportedPriceExponent,
+    #[msg("Ready window must be 0 or between MIN_READY_WINDOW_SLOTS and MAX_READY_WINDOW_SLOTS")]
+    InvalidReadyWindow,
+    #[msg("Recurring orders need at least one run")]
+    InvalidRecurringRuns,
+    #[msg("Compute units exceed MAX_COMPUTE_UNITS")]
+    ComputeUnitsTooHigh,
 }
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::delegate;
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
 use crate::state::{GhostOrder, OrderStatus};
+use crate::instructions::migrate_account::MigrateError;
 
 pub fn handler(ctx: Context<DelegateOrder>) -> Result<()> {
     let owner_key = ctx.accounts.owner.key();
@@ -30,7 +31,8 @@ pub struct DelegateOrder<'info> {
 
     #[account(
         seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &ghost_order_data.order_id.to_le_bytes()],
-        bump = ghost_order_data.bump
+        bump = ghost_order_data.bump,
+        constraint = ghost_order_data.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order_data: Account<'info, GhostOrder>,
 
@@ -45,6 +47,13 @@ pub struct DelegateOrder<'info> {
 }
 
 pub fn activate_handler(ctx: Context<ActivateOrder>) -> Result<()> {
+    let parent = ctx
+        .accounts
+        .parent_order
+        .as_ref()
+        .map(|parent| (parent.key(), &**parent));
+    ctx.accounts.ghost_order.check_activation(parent)?;
+
     let ghost_order = &mut ctx.accounts.ghost_order;
     ghost_order.status = OrderStatus::Active;
 
@@ -62,7 +71,14 @@ pub struct ActivateOrder<'info> {
         seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &ghost_order.order_id.to_le_bytes()],
         bump = ghost_order.bump,
         constraint = ghost_order.owner == owner.key(),
-        constraint = ghost_order.status == OrderStatus::Pending
+        constraint = ghost_order.status == OrderStatus::Pending,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
+
+    /// Bracket entry, required to activate a take-profit/stop-loss leg
+    #[account(
+        constraint = parent_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub parent_order: Option<Account<'info, GhostOrder>>,
 }
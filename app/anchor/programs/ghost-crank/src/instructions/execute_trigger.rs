@@ -5,13 +5,19 @@ use ephemeral_rollups_sdk::ephem::{
     UndelegateType,
 };
 use ephemeral_rollups_sdk::{ActionArgs, ShortAccountMeta};
-use crate::state::{GhostOrder, OrderStatus, OrderSide};
+use crate::state::{CrankConfig, GhostOrder, OrderStatus};
+use crate::instructions::crank_config::require_not_paused;
+use crate::instructions::link_oco_orders::{cancel_sibling_status, OcoError};
+use crate::delegation::is_delegation_accounts;
+use crate::drift_cpi::{build_drift_place_perp_order, drift_call_handler};
+use crate::instructions::migrate_account::MigrateError;
 
-pub const DRIFT_PROGRAM_ID: Pubkey = pubkey!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
 pub const DELEGATION_PROGRAM_ID: Pubkey = pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
-pub const PLACE_PERP_ORDER_DISCRIMINATOR: u8 = 23;
 pub const DRIFT_EXECUTE_COMPUTE_UNITS: u32 = 200_000;
 pub const DELEGATE_COMPUTE_UNITS: u32 = 50_000;
+/// Solana's per-transaction compute limit; a larger handler budget could
+/// never be granted.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct ExecuteTriggerArgs {
@@ -19,16 +25,22 @@ pub struct ExecuteTriggerArgs {
 }
 
 pub fn handler<'info>(
-    ctx: Context<'_, '_, '_, 'info, ExecuteTrigger<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, ExecuteTrigger<'info>>,
     args: ExecuteTriggerArgs,
 ) -> Result<()> {
     let ghost_order = &mut ctx.accounts.ghost_order;
     let clock = Clock::get()?;
 
+    require_not_paused(&ctx.accounts.crank_config)?;
+
     require!(
         ghost_order.status == OrderStatus::Triggered,
         GhostCrankError::OrderNotTriggered
     );
+    ghost_order.verify_drift_accounts(
+        &ctx.accounts.drift_user.key(),
+        &ctx.accounts.drift_authority.key(),
+    )?;
 
     let order_id = ghost_order.order_id;
     let market_index = ghost_order.market_index;
@@ -38,10 +50,27 @@ pub fn handler<'info>(
     let owner = ghost_order.owner;
     let bump = ghost_order.bump;
     let execution_price = ghost_order.execution_price;
+    let compute_units = ghost_order.drift_compute_units();
 
     ghost_order.status = OrderStatus::Executed;
     ghost_order.executed_at = clock.unix_timestamp;
 
+    // One-cancels-the-other. The sibling is found by key among the remaining
+    // accounts, after any redelegation accounts, so existing account lists
+    // still parse. Its escrow stays on it: the rollup cannot credit the owner
+    if ghost_order.is_oco_linked() {
+        let sibling_info = ctx
+            .remaining_accounts
+            .iter()
+            .find(|account| account.key() == ghost_order.oco_sibling)
+            .ok_or(OcoError::SiblingMissing)?;
+        let mut sibling = Account::<GhostOrder>::try_from(sibling_info)?;
+        require!(sibling.is_current_version(), MigrateError::UnsupportedAccountVersion);
+        if cancel_sibling_status(ghost_order.key(), ghost_order, &mut sibling)? {
+            sibling.exit(&crate::ID)?;
+        }
+    }
+
     msg!(
         "Preparing Magic Action: order_id={}, market={}, side={:?}, price={}",
         order_id,
@@ -66,13 +95,12 @@ pub fn handler<'info>(
         ctx.accounts.oracle.key(),
     );
 
-    let drift_call_handler = CallHandler {
-        destination_program: DRIFT_PROGRAM_ID,
-        accounts: drift_accounts,
-        args: ActionArgs::new(drift_ix_data),
-        escrow_authority: ctx.accounts.payer.to_account_info(),
-        compute_units: DRIFT_EXECUTE_COMPUTE_UNITS,
-    };
+    let drift_call_handler = drift_call_handler(
+        ctx.accounts.payer.to_account_info(),
+        drift_accounts,
+        drift_ix_data,
+        compute_units,
+    );
 
     let call_handlers = if args.redelegate_after_execution {
         build_handlers_with_redelegation(
@@ -141,6 +169,11 @@ fn build_handlers_with_redelegation<'info>(
         &crate::ID,
     );
 
+    if !is_delegation_accounts(remaining_accounts, &ghost_order_pda, &crate::ID, &DELEGATION_PROGRAM_ID) {
+        msg!("Redelegation accounts are not the delegation PDAs of the order");
+        return Err(GhostCrankError::MagicActionFailed.into());
+    }
+
     let delegate_ix_data = build_delegate_instruction_data();
 
     let delegate_accounts = vec![
@@ -195,33 +228,6 @@ fn build_delegate_instruction_data() -> Vec<u8> {
     data
 }
 
-fn build_drift_place_perp_order(
-    market_index: u16,
-    side: OrderSide,
-    base_asset_amount: u64,
-    reduce_only: bool,
-) -> Vec<u8> {
-    let mut data = Vec::with_capacity(24);
-
-    data.push(PLACE_PERP_ORDER_DISCRIMINATOR);
-    data.push(0);
-
-    data.push(match side {
-        OrderSide::Long => 0,
-        OrderSide::Short => 1,
-    });
-
-    data.extend_from_slice(&market_index.to_le_bytes());
-    data.extend_from_slice(&base_asset_amount.to_le_bytes());
-    data.extend_from_slice(&0u64.to_le_bytes());
-
-    data.push(if reduce_only { 1 } else { 0 });
-    data.push(0);
-    data.push(0);
-
-    data
-}
-
 fn build_drift_short_account_metas(
     drift_state_key: Pubkey,
     drift_user_key: Pubkey,
@@ -268,14 +274,15 @@ pub struct ExecuteTrigger<'info> {
         mut,
         seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
         bump = ghost_order.bump,
-        constraint = ghost_order.status == OrderStatus::Triggered @ GhostCrankError::OrderNotTriggered
+        constraint = ghost_order.status == OrderStatus::Triggered @ GhostCrankError::OrderNotTriggered,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
 
     /// CHECK: Drift program state account
     pub drift_state: AccountInfo<'info>,
 
-    /// CHECK: Drift user account for the ghost order owner
+    /// CHECK: Drift user account for the ghost order owner; must match `ghost_order.drift_user`
     #[account(mut)]
     pub drift_user: AccountInfo<'info>,
 
@@ -283,7 +290,7 @@ pub struct ExecuteTrigger<'info> {
     #[account(mut)]
     pub drift_user_stats: AccountInfo<'info>,
 
-    /// CHECK: Authority for the Drift user (ghost order owner)
+    /// CHECK: Authority for the Drift user; must be the ghost order owner
     pub drift_authority: AccountInfo<'info>,
 
     /// CHECK: Perp market account for the order
@@ -298,6 +305,12 @@ pub struct ExecuteTrigger<'info> {
 
     /// CHECK: Magic program for ER operations
     pub magic_program: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
 }
 
 #[error_code]
@@ -316,4 +329,8 @@ pub enum GhostCrankError {
     MagicActionFailed,
     #[msg("Redelegation failed")]
     RedelegationFailed,
+    #[msg("Drift user account does not belong to the order")]
+    DriftUserMismatch,
+    #[msg("Drift authority is not the order owner")]
+    DriftAuthorityMismatch,
 }
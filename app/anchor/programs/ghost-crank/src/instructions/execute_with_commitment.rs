@@ -1,8 +1,18 @@
 use anchor_lang::prelude::*;
 use solana_program::hash::hash;
-use crate::state::{GhostOrder, OrderStatus, OrderSide};
-
-pub const DRIFT_PROGRAM_ID: Pubkey = pubkey!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+use crate::state::{ladder_tranches, CancelReason, CrankConfig, GhostOrder, OrderStatus, OrderSide};
+use crate::instructions::cancel_order::GhostOrderCancelled;
+use crate::instructions::crank_config::require_not_paused;
+use crate::instructions::link_oco_orders::cancel_linked_sibling;
+use crate::drift_cpi::{
+    build_drift_place_and_take_perp_order, build_drift_place_order_at_price, DriftMarketType,
+    DRIFT_PROGRAM_ID,
+};
+use crate::instructions::migrate_account::MigrateError;
+use crate::drift_user::{
+    filled_base_asset_amount, perp_base_asset_amount, reduce_only_amount, reduces_position,
+    require_delegate,
+};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct OrderParams {
@@ -10,12 +20,34 @@ pub struct OrderParams {
     pub order_side: OrderSide,
     pub base_asset_amount: u64,
     pub reduce_only: bool,
+    pub is_trailing: bool,
+    pub trail_offset: i64,
+    pub ladder_steps: u8,
+    pub ladder_spacing: i64,
+    pub market_type: DriftMarketType,
+    pub max_slippage_bps: u16,
+    /// Worst acceptable fill price at the order's `price_exponent`, placed as the Drift order price (0 = unbounded)
+    pub price_bound: u64,
+}
+
+impl OrderParams {
+    /// The value stored as `params_commitment`: sha256 over the Borsh-encoded
+    /// params followed by the little-endian nonce.
+    pub fn commitment(&self, nonce: u64) -> Result<[u8; 32]> {
+        let params_bytes = self.try_to_vec()?;
+        let mut hasher_input = Vec::with_capacity(params_bytes.len() + 8);
+        hasher_input.extend_from_slice(&params_bytes);
+        hasher_input.extend_from_slice(&nonce.to_le_bytes());
+        Ok(hash(&hasher_input).to_bytes())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ExecuteWithCommitmentArgs {
     pub order_params: OrderParams,
     pub nonce: u64,
+    /// Client-chosen id; retrying with the same id after success is a no-op
+    pub execution_id: u64,
 }
 
 pub fn handler<'info>(
@@ -25,12 +57,27 @@ pub fn handler<'info>(
     let ghost_order = &mut ctx.accounts.ghost_order;
     let clock = Clock::get()?;
 
-    // 1. Verify ready state
+    require!(args.execution_id != 0, ExecuteError::InvalidExecutionId);
+    require_not_paused(&ctx.accounts.crank_config)?;
     require!(
-        ghost_order.status == OrderStatus::ReadyToExecute,
-        ExecuteError::NotReady
+        ghost_order.is_allowed_keeper(&ctx.accounts.keeper.key()),
+        ExecuteError::KeeperNotAllowed
     );
 
+    // Acknowledge keeper retries of an execution that already landed
+    // without placing its Drift orders a second time
+    if ghost_order.is_duplicate_execution(args.execution_id, clock.slot)? {
+        msg!(
+            "Ghost order {} already executed with execution_id={}, skipping",
+            ghost_order.order_id,
+            args.execution_id
+        );
+        return Ok(());
+    }
+
+    // 1. Verify ready state; a partial fill may be re-executed for the remainder
+    require!(ghost_order.is_executable(), ExecuteError::NotReady);
+
     // 2. Verify not expired
     require!(
         (clock.slot as i64) < ghost_order.ready_expires_at,
@@ -43,32 +90,167 @@ pub fn handler<'info>(
         ExecuteError::NonceMismatch
     );
 
-    // 4. Verify commitment - this is the anti-frontrun mechanism
-    let params_bytes = args.order_params.try_to_vec()?;
-    let mut hasher_input = Vec::with_capacity(params_bytes.len() + 8);
-    hasher_input.extend_from_slice(&params_bytes);
-    hasher_input.extend_from_slice(&args.nonce.to_le_bytes());
+    // Trailing, ladder and market params are part of the commitment and must match what was stored
+    require!(
+        args.order_params.is_trailing == ghost_order.is_trailing &&
+        args.order_params.trail_offset == ghost_order.trail_offset &&
+        args.order_params.ladder_steps == ghost_order.ladder_steps &&
+        args.order_params.ladder_spacing == ghost_order.ladder_spacing &&
+        args.order_params.market_type == ghost_order.market_type &&
+        args.order_params.max_slippage_bps == ghost_order.max_slippage_bps &&
+        args.order_params.price_bound == ghost_order.price_bound,
+        ExecuteError::CommitmentMismatch
+    );
 
-    let computed_hash = hash(&hasher_input);
+    // 4. Verify commitment - this is the anti-frontrun mechanism
+    let computed_hash = args.order_params.commitment(args.nonce)?;
     require!(
-        computed_hash.to_bytes() == ghost_order.params_commitment,
+        computed_hash == ghost_order.params_commitment,
         ExecuteError::CommitmentMismatch
     );
 
+    // A market order placed after the price ran away would fill far from
+    // the trigger; put the order back under monitoring instead
+    if ghost_order.max_slippage_bps > 0 || ghost_order.price_bound > 0 {
+        let price_feed = ctx
+            .accounts
+            .price_feed
+            .as_ref()
+            .ok_or(ExecuteError::PriceFeedMissing)?;
+        let current_price = ghost_order.read_price(price_feed, &clock)?;
+
+        // The oracle is already through the worst acceptable price, so the
+        // bounded order would only rest on the book
+        if ghost_order.breaches_price_bound(current_price) {
+            emit!(PriceBoundBreached {
+                order_id: ghost_order.order_id,
+                price_bound: ghost_order.price_bound,
+                current_price,
+            });
+            msg!(
+                "Price bound {} breached for order {}: oracle at {}, re-armed",
+                ghost_order.price_bound,
+                ghost_order.order_id,
+                current_price
+            );
+            ghost_order.rearm();
+            return Ok(());
+        }
+
+        if ghost_order.exceeds_max_slippage(current_price) {
+            emit!(SlippageExceeded {
+                order_id: ghost_order.order_id,
+                execution_price: ghost_order.execution_price,
+                current_price,
+                max_slippage_bps: ghost_order.max_slippage_bps,
+            });
+            msg!(
+                "Slippage exceeded for order {}: triggered at {}, now {}, re-armed",
+                ghost_order.order_id,
+                ghost_order.execution_price,
+                current_price
+            );
+            ghost_order.rearm();
+            return Ok(());
+        }
+    }
+
     msg!(
         "Commitment verified for order {}: executing Drift CPI",
         ghost_order.order_id
     );
 
-    // 5. Build Drift place_perp_order CPI
-    let drift_ix_data = build_drift_place_perp_order(
-        args.order_params.market_index,
-        args.order_params.order_side,
-        args.order_params.base_asset_amount,
-        args.order_params.reduce_only,
+    require_keys_eq!(
+        *ctx.accounts.drift_user.owner,
+        DRIFT_PROGRAM_ID,
+        ExecuteError::DriftUserMismatch
     );
 
-    let drift_accounts = vec![
+    // Drift only honors the delegate PDA's signature if the user granted it
+    require_delegate(
+        &ctx.accounts.drift_user.try_borrow_data()?,
+        &ctx.accounts.delegate_pda.key(),
+    )?;
+
+    // Fill and reduce-only tracking read the perp position; spot orders
+    // leave reduce-only enforcement to Drift
+    let is_perp = args.order_params.market_type == DriftMarketType::Perp;
+    let position_base = if is_perp {
+        perp_base_asset_amount(
+            &ctx.accounts.drift_user.try_borrow_data()?,
+            args.order_params.market_index,
+        )?
+    } else {
+        0
+    };
+
+    // Reduce-only against a flat or same-direction position would be a no-op fill
+    if is_perp
+        && args.order_params.reduce_only
+        && !reduces_position(position_base, args.order_params.order_side)
+    {
+        ghost_order.cancel(OrderStatus::Cancelled, CancelReason::NothingToReduce);
+        emit!(GhostOrderCancelled::for_order(ghost_order));
+        cancel_linked_sibling(
+            ghost_order.key(),
+            ghost_order,
+            ctx.accounts.sibling_order.as_mut(),
+            ctx.accounts.owner.as_ref().map(|owner| owner.to_account_info()),
+        )?;
+
+        // The order never executed, so no keeper can earn its fee; a
+        // cancelled order cannot be cancelled again to reclaim it
+        if ghost_order.keeper_fee_lamports > 0 {
+            let owner = ctx.accounts.owner.as_ref().ok_or(ExecuteError::OwnerMissing)?;
+            require_keys_eq!(owner.key(), ghost_order.owner, ExecuteError::OwnerMismatch);
+            let order_info = ghost_order.to_account_info();
+            let rent_minimum = Rent::get()?.minimum_balance(order_info.data_len());
+            let refund = ghost_order.refund_keeper_fee(&order_info, owner, rent_minimum)?;
+            msg!("Keeper fee refunded: {} lamports", refund);
+        }
+
+        emit!(NothingToReduce {
+            order_id: ghost_order.order_id,
+            market_index: args.order_params.market_index,
+            order_side: args.order_params.order_side,
+            position_base_asset_amount: position_base,
+        });
+
+        msg!("Nothing to reduce for order {}, cancelled", ghost_order.order_id);
+        return Ok(());
+    }
+
+    // Reduce-only perp orders never send more than the open position
+    let remaining = ghost_order.remaining_amount();
+    let order_amount = if is_perp && args.order_params.reduce_only {
+        reduce_only_amount(remaining, position_base)
+    } else {
+        remaining
+    };
+    if order_amount < remaining {
+        msg!(
+            "Reduce-only order {} clamped to open position: {} of {}",
+            ghost_order.order_id,
+            order_amount,
+            remaining
+        );
+    }
+
+    // 5. Split into tranches; a non-laddered order is a single market order
+    let anchor_price = if ghost_order.execution_price > 0 {
+        ghost_order.execution_price
+    } else {
+        ghost_order.trigger_price
+    };
+    let tranches = ladder_tranches(
+        order_amount,
+        args.order_params.ladder_steps,
+        args.order_params.ladder_spacing,
+        anchor_price,
+        args.order_params.order_side,
+    )?;
+
+    let mut drift_accounts = vec![
         AccountMeta::new_readonly(ctx.accounts.drift_state.key(), false),
         AccountMeta::new(ctx.accounts.drift_user.key(), false),
         AccountMeta::new(ctx.accounts.drift_user_stats.key(), false),
@@ -77,91 +259,162 @@ pub fn handler<'info>(
         AccountMeta::new(ctx.accounts.perp_market.key(), false),
         AccountMeta::new_readonly(ctx.accounts.oracle.key(), false),
     ];
-
-    let drift_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id: DRIFT_PROGRAM_ID,
-        accounts: drift_accounts,
-        data: drift_ix_data,
-    };
-
-    // 6. Sign with delegate PDA via invoke_signed
-    let owner_key = ghost_order.owner;
-    let delegate_seeds = &[
-        GhostOrder::DELEGATE_SEED_PREFIX,
-        owner_key.as_ref(),
-        &[ghost_order.delegate_bump],
+    let mut drift_account_infos = vec![
+        ctx.accounts.drift_state.to_account_info(),
+        ctx.accounts.drift_user.to_account_info(),
+        ctx.accounts.drift_user_stats.to_account_info(),
+        ctx.accounts.delegate_pda.to_account_info(),
+        ctx.accounts.perp_market.to_account_info(),
+        ctx.accounts.oracle.to_account_info(),
     ];
 
-    anchor_lang::solana_program::program::invoke_signed(
-        &drift_ix,
-        &[
-            ctx.accounts.drift_state.to_account_info(),
-            ctx.accounts.drift_user.to_account_info(),
-            ctx.accounts.drift_user_stats.to_account_info(),
-            ctx.accounts.delegate_pda.to_account_info(),
-            ctx.accounts.perp_market.to_account_info(),
-            ctx.accounts.oracle.to_account_info(),
-            ctx.accounts.drift_program.to_account_info(),
-        ],
-        &[delegate_seeds],
+    // Spot orders also move the quote balance, so Drift needs the quote spot market
+    if !is_perp {
+        let quote_spot_market = ctx
+            .accounts
+            .quote_spot_market
+            .as_ref()
+            .ok_or(ExecuteError::QuoteSpotMarketMissing)?;
+        drift_accounts.push(AccountMeta::new(quote_spot_market.key(), false));
+        drift_account_infos.push(quote_spot_market.to_account_info());
+    }
+    drift_account_infos.push(ctx.accounts.drift_program.to_account_info());
+
+    // 6. Sign each Drift place order CPI with the delegate PDA. A perp
+    // market order is placed and taken in the same CPI so its fill can be
+    // measured right after it
+    let owner_key = ghost_order.owner;
+    let delegate_bump = [ghost_order.delegate_bump];
+    let delegate_seeds = GhostOrder::delegate_signer_seeds(&owner_key, &delegate_bump);
+    let take_now = tranches.len() == 1 && is_perp;
+
+    for tranche in &tranches {
+        let price = ghost_order.drift_order_price(tranche.price)?;
+        let data = if take_now {
+            build_drift_place_and_take_perp_order(
+                args.order_params.market_index,
+                args.order_params.order_side,
+                tranche.base_asset_amount,
+                price,
+                args.order_params.reduce_only,
+                ghost_order.drift_max_ts(),
+            )
+        } else {
+            build_drift_place_order_at_price(
+                args.order_params.market_type,
+                args.order_params.market_index,
+                args.order_params.order_side,
+                tranche.base_asset_amount,
+                price,
+                args.order_params.reduce_only,
+                ghost_order.drift_max_ts(),
+            )
+        };
+        let drift_ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: DRIFT_PROGRAM_ID,
+            accounts: drift_accounts.clone(),
+            data,
+        };
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &drift_ix,
+            &drift_account_infos,
+            &[&delegate_seeds],
+        )?;
+    }
+
+    // 7. Record the fill. Laddered limit tranches rest on Drift's book and
+    // fill later, so placing them completes the order; a perp market order
+    // was taken immediate-or-cancel, so how far the position moved is all it
+    // will ever fill. A clamped reduce-only order is done once the position
+    // it was sized to is closed. Spot balances are not tracked, so a spot
+    // order is only recorded as placed, never as filled.
+    let fill = if !is_perp {
+        0
+    } else if !take_now {
+        remaining
+    } else {
+        let position_after = perp_base_asset_amount(
+            &ctx.accounts.drift_user.try_borrow_data()?,
+            args.order_params.market_index,
+        )?;
+        let filled =
+            filled_base_asset_amount(position_base, position_after, args.order_params.order_side);
+        if order_amount < remaining && filled >= order_amount {
+            remaining
+        } else {
+            filled
+        }
+    };
+    let complete = if is_perp {
+        ghost_order.record_fill(fill)
+    } else {
+        ghost_order.record_placement();
+        false
+    };
+    ghost_order.executed_at = clock.unix_timestamp;
+    ghost_order.executed_slot = clock.slot;
+    ghost_order.execution_id = args.execution_id;
+
+    if complete && ghost_order.finish_run() {
+        emit!(RecurringRunCompleted {
+            order_id: ghost_order.order_id,
+            remaining_runs: ghost_order.remaining_runs,
+        });
+    }
+
+    if !is_perp {
+        emit!(SpotOrderPlaced {
+            order_id: ghost_order.order_id,
+            market_index: args.order_params.market_index,
+            base_asset_amount: order_amount,
+        });
+    } else if !complete {
+        emit!(GhostOrderPartiallyFilled {
+            order_id: ghost_order.order_id,
+            fill,
+            filled_amount: ghost_order.filled_amount,
+            remaining_amount: ghost_order.remaining_amount(),
+        });
+    }
+
+    // 8. One-cancels-the-other: the linked sibling can no longer fill
+    cancel_linked_sibling(
+        ghost_order.key(),
+        ghost_order,
+        ctx.accounts.sibling_order.as_mut(),
+        ctx.accounts.owner.as_ref().map(|owner| owner.to_account_info()),
     )?;
 
-    // 7. Mark executed
-    ghost_order.status = OrderStatus::Executed;
-    ghost_order.executed_at = clock.unix_timestamp;
+    // 9. Reward the keeper for the first fill or spot placement; the fee is
+    // escrowed once, and an underfunded order still executes
+    let order_info = ghost_order.to_account_info();
+    let rent_minimum = Rent::get()?.minimum_balance(order_info.data_len());
+    let keeper_info = ctx.accounts.keeper.to_account_info();
+    if (fill > 0 || !is_perp) && !ghost_order.pay_keeper_fee(&order_info, &keeper_info, rent_minimum)? {
+        emit!(KeeperFeeUnpaid {
+            order_id: ghost_order.order_id,
+            keeper: keeper_info.key(),
+            fee_lamports: ghost_order.keeper_fee_lamports,
+            available_lamports: order_info.lamports().saturating_sub(rent_minimum),
+        });
+    }
 
     msg!(
-        "Ghost order executed via delegate CPI: id={}, market={}, side={:?}",
+        "Ghost order executed via delegate CPI: id={}, market={}, side={:?}, tranches={}, sent={}, filled={}/{}, execution_id={}",
         ghost_order.order_id,
         args.order_params.market_index,
-        args.order_params.order_side
+        args.order_params.order_side,
+        tranches.len(),
+        order_amount,
+        ghost_order.filled_amount,
+        ghost_order.base_asset_amount,
+        args.execution_id
     );
 
     Ok(())
 }
 
-fn build_drift_place_perp_order(
-    market_index: u16,
-    side: OrderSide,
-    base_asset_amount: u64,
-    reduce_only: bool,
-) -> Vec<u8> {
-    // Drift place_perp_order discriminator: sha256("global:place_perp_order")[0..8]
-    let discriminator: [u8; 8] = [0x45, 0xa1, 0x3b, 0x69, 0x28, 0x1c, 0xfa, 0x63];
-
-    let mut data = Vec::with_capacity(32);
-    data.extend_from_slice(&discriminator);
-
-    // OrderType::Market = 0
-    data.push(0);
-
-    // Direction
-    data.push(match side {
-        OrderSide::Long => 0,
-        OrderSide::Short => 1,
-    });
-
-    // Market index (u16 LE)
-    data.extend_from_slice(&market_index.to_le_bytes());
-
-    // Base asset amount (u64 LE)
-    data.extend_from_slice(&base_asset_amount.to_le_bytes());
-
-    // Price (u64 LE) - 0 for market orders
-    data.extend_from_slice(&0u64.to_le_bytes());
-
-    // Reduce only
-    data.push(if reduce_only { 1 } else { 0 });
-
-    // Post only = false
-    data.push(0);
-
-    // Immediate or cancel = false
-    data.push(0);
-
-    data
-}
-
 #[derive(Accounts)]
 pub struct ExecuteWithCommitment<'info> {
     /// Keeper/filler - pays for tx, anyone can call
@@ -172,7 +425,12 @@ pub struct ExecuteWithCommitment<'info> {
         mut,
         seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
         bump = ghost_order.bump,
-        constraint = ghost_order.status == OrderStatus::ReadyToExecute @ ExecuteError::NotReady
+        constraint = (
+            ghost_order.is_executable() ||
+            ghost_order.status == OrderStatus::Executed ||
+            ghost_order.status == OrderStatus::Placed
+        ) @ ExecuteError::NotReady,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
 
@@ -197,7 +455,7 @@ pub struct ExecuteWithCommitment<'info> {
     #[account(mut)]
     pub drift_user_stats: AccountInfo<'info>,
 
-    /// CHECK: Perp market account
+    /// CHECK: Market account; the spot market for spot orders
     #[account(mut)]
     pub perp_market: AccountInfo<'info>,
 
@@ -207,6 +465,86 @@ pub struct ExecuteWithCommitment<'info> {
     /// CHECK: Drift program
     #[account(address = DRIFT_PROGRAM_ID)]
     pub drift_program: AccountInfo<'info>,
+
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    /// OCO sibling, required when the order is linked
+    #[account(
+        mut,
+        constraint = sibling_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub sibling_order: Option<Account<'info, GhostOrder>>,
+
+    /// CHECK: Drift quote spot market (USDC), required for spot orders
+    #[account(mut)]
+    pub quote_spot_market: Option<AccountInfo<'info>>,
+
+    /// CHECK: Price feed for `ghost_order.feed_id`; owner and layout checked per
+    /// `ghost_order.oracle_source`. Required when the order has a slippage or
+    /// price bound
+    pub price_feed: Option<AccountInfo<'info>>,
+
+    /// CHECK: The order's owner, refunded the escrow of a cancelled OCO
+    /// sibling or of this order when it has nothing to reduce; required in
+    /// those cases and checked against the order's owner
+    #[account(mut)]
+    pub owner: Option<AccountInfo<'info>>,
+}
+
+#[event]
+pub struct NothingToReduce {
+    pub order_id: u64,
+    pub market_index: u16,
+    pub order_side: OrderSide,
+    pub position_base_asset_amount: i64,
+}
+
+#[event]
+pub struct SlippageExceeded {
+    pub order_id: u64,
+    pub execution_price: i64,
+    pub current_price: i64,
+    pub max_slippage_bps: u16,
+}
+
+#[event]
+pub struct PriceBoundBreached {
+    pub order_id: u64,
+    pub price_bound: u64,
+    pub current_price: i64,
+}
+
+#[event]
+pub struct GhostOrderPartiallyFilled {
+    pub order_id: u64,
+    pub fill: u64,
+    pub filled_amount: u64,
+    pub remaining_amount: u64,
+}
+
+#[event]
+pub struct RecurringRunCompleted {
+    pub order_id: u64,
+    pub remaining_runs: u16,
+}
+
+#[event]
+pub struct SpotOrderPlaced {
+    pub order_id: u64,
+    pub market_index: u16,
+    pub base_asset_amount: u64,
+}
+
+#[event]
+pub struct KeeperFeeUnpaid {
+    pub order_id: u64,
+    pub keeper: Pubkey,
+    pub fee_lamports: u64,
+    pub available_lamports: u64,
 }
 
 #[error_code]
@@ -223,4 +561,20 @@ pub enum ExecuteError {
     DriftUserMismatch,
     #[msg("Drift CPI failed")]
     DriftCpiFailed,
+    #[msg("Order was already executed with a different execution id")]
+    AlreadyExecuted,
+    #[msg("Execution id must be non-zero")]
+    InvalidExecutionId,
+    #[msg("Ladder params produce an empty or non-positive tranche")]
+    InvalidLadder,
+    #[msg("Spot orders require the quote spot market account")]
+    QuoteSpotMarketMissing,
+    #[msg("Orders with a slippage or price bound require the price feed account")]
+    PriceFeedMissing,
+    #[msg("Keeper is not on the order's keeper allowlist")]
+    KeeperNotAllowed,
+    #[msg("Refunding the keeper fee requires the order owner account")]
+    OwnerMissing,
+    #[msg("Owner account does not match the order owner")]
+    OwnerMismatch,
 }
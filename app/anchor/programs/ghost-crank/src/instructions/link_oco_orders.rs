@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use crate::state::GhostOrder;
+use crate::instructions::cancel_order::GhostOrderCancelled;
+use crate::instructions::migrate_account::MigrateError;
+
+pub fn handler(ctx: Context<LinkOcoOrders>) -> Result<()> {
+    let order_a_key = ctx.accounts.order_a.key();
+    let order_b_key = ctx.accounts.order_b.key();
+
+    require_keys_neq!(order_a_key, order_b_key, OcoError::SelfLink);
+
+    let order_a = &mut ctx.accounts.order_a;
+    let order_b = &mut ctx.accounts.order_b;
+
+    require!(
+        order_a.can_link_oco() && order_b.can_link_oco(),
+        OcoError::OrderNotLinkable
+    );
+
+    order_a.oco_sibling = order_b_key;
+    order_b.oco_sibling = order_a_key;
+
+    msg!("OCO pair linked: {} <-> {}", order_a.order_id, order_b.order_id);
+
+    Ok(())
+}
+
+/// Cancels the OCO sibling of `order` once `order` has filled or been
+/// cancelled, and hands the sibling's escrow back to `owner`. The sibling
+/// is closed unless it already sent orders to Drift, which
+/// `cancel_drift_order` still needs it for; then only its unpaid keeper fee
+/// is returned.
+pub fn cancel_linked_sibling<'info>(
+    order_key: Pubkey,
+    order: &GhostOrder,
+    sibling: Option<&mut Account<'info, GhostOrder>>,
+    owner: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    if !order.is_oco_linked() {
+        return Ok(());
+    }
+
+    let sibling = sibling.ok_or(OcoError::SiblingMissing)?;
+    if !cancel_sibling_status(order_key, order, sibling)? {
+        return Ok(());
+    }
+
+    let owner = owner.ok_or(OcoError::OwnerMissing)?;
+    require_keys_eq!(owner.key(), sibling.owner, OcoError::OwnerMismatch);
+
+    if sibling.filled_amount == 0 {
+        sibling.close(owner)?;
+    } else {
+        let sibling_info = sibling.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(sibling_info.data_len());
+        sibling.refund_keeper_fee(&sibling_info, &owner, rent_minimum)?;
+    }
+
+    Ok(())
+}
+
+/// Marks `sibling` cancelled as `order`'s OCO sibling, leaving its escrow
+/// in place. On its own this is for the ephemeral rollup, where the owner's
+/// wallet cannot be credited. Returns whether the sibling was still open.
+pub fn cancel_sibling_status(
+    order_key: Pubkey,
+    order: &GhostOrder,
+    sibling: &mut Account<GhostOrder>,
+) -> Result<bool> {
+    require_keys_eq!(sibling.key(), order.oco_sibling, OcoError::SiblingMismatch);
+    require_keys_eq!(sibling.oco_sibling, order_key, OcoError::SiblingMismatch);
+
+    if !sibling.cancel_for_oco_sibling() {
+        return Ok(false);
+    }
+    emit!(GhostOrderCancelled::for_order(sibling));
+    msg!("OCO sibling cancelled: id={}", sibling.order_id);
+
+    Ok(true)
+}
+
+#[derive(Accounts)]
+pub struct LinkOcoOrders<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &order_a.order_id.to_le_bytes()],
+        bump = order_a.bump,
+        constraint = order_a.owner == owner.key() @ OcoError::OwnerMismatch,
+        constraint = order_a.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub order_a: Account<'info, GhostOrder>,
+
+    #[account(
+        mut,
+        constraint = order_b.owner == order_a.owner @ OcoError::OwnerMismatch,
+        constraint = order_b.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub order_b: Account<'info, GhostOrder>,
+}
+
+#[error_code]
+pub enum OcoError {
+    #[msg("An order cannot be linked to itself")]
+    SelfLink,
+    #[msg("OCO orders must share the same owner")]
+    OwnerMismatch,
+    #[msg("Order is already linked or no longer open")]
+    OrderNotLinkable,
+    #[msg("Linked OCO sibling account was not provided")]
+    SiblingMissing,
+    #[msg("Provided account is not this order's OCO sibling")]
+    SiblingMismatch,
+    #[msg("Order owner account was not provided to refund the cancelled sibling")]
+    OwnerMissing,
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::state::{GhostOrder, OrderStatus};
+use crate::instructions::migrate_account::MigrateError;
 
 /// Called inside ER action when trigger condition is met.
 /// Only writes ready flag + commitment - no plaintext order params.
@@ -12,10 +13,7 @@ pub fn handler(ctx: Context<MarkReady>, execution_price: i64) -> Result<()> {
         MarkReadyError::NotTriggered
     );
 
-    ghost_order.status = OrderStatus::ReadyToExecute;
-    ghost_order.execution_price = execution_price;
-    // 100 slots (~40 seconds) to execute before expiry
-    ghost_order.ready_expires_at = clock.slot as i64 + 100;
+    ghost_order.mark_ready(execution_price, &clock);
 
     msg!(
         "Ghost order marked ready: id={}, expires_at_slot={}",
@@ -35,7 +33,8 @@ pub struct MarkReady<'info> {
         mut,
         seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
         bump = ghost_order.bump,
-        constraint = ghost_order.status == OrderStatus::Triggered @ MarkReadyError::NotTriggered
+        constraint = ghost_order.status == OrderStatus::Triggered @ MarkReadyError::NotTriggered,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
 }
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use crate::drift_cpi::DriftMarketType;
+use crate::instructions::crank_config::CrankConfigError;
+use crate::state::{CrankConfig, MarketRegistry};
+
+pub fn init_registry_handler(ctx: Context<InitMarketRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.market_registry;
+    registry.bump = ctx.bumps.market_registry;
+    registry.markets = Vec::new();
+    registry.min_order_sizes = Vec::new();
+
+    msg!("Market registry initialized");
+
+    Ok(())
+}
+
+/// Maps `market_index` on `market_type` to the feed its orders must use.
+pub fn set_market_feed_handler(
+    ctx: Context<SetMarketFeed>,
+    market_type: DriftMarketType,
+    market_index: u16,
+    feed_id: [u8; 32],
+) -> Result<()> {
+    ctx.accounts
+        .market_registry
+        .set_feed(market_type, market_index, feed_id)?;
+
+    emit!(MarketFeedSet {
+        market_type,
+        market_index,
+        feed_id,
+    });
+
+    msg!("Market feed set: {:?} {} -> {:?}", market_type, market_index, &feed_id[..8]);
+
+    Ok(())
+}
+
+/// Sets the smallest order size, in Drift base precision, accepted on
+/// `market_index` of `market_type`.
+pub fn set_market_min_order_size_handler(
+    ctx: Context<SetMarketMinOrderSize>,
+    market_type: DriftMarketType,
+    market_index: u16,
+    min_base_asset_amount: u64,
+) -> Result<()> {
+    ctx.accounts
+        .market_registry
+        .set_min_order_size(market_type, market_index, min_base_asset_amount)?;
+
+    emit!(MarketMinOrderSizeSet {
+        market_type,
+        market_index,
+        min_base_asset_amount,
+    });
+
+    msg!(
+        "Market min order size set: {:?} {} -> {}",
+        market_type,
+        market_index,
+        min_base_asset_amount
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitMarketRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump,
+        constraint = crank_config.is_admin(&admin.key()) @ CrankConfigError::NotAdmin
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MarketRegistry::LEN,
+        seeds = [MarketRegistry::SEED_PREFIX],
+        bump
+    )]
+    pub market_registry: Account<'info, MarketRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketFeed<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump,
+        constraint = crank_config.is_admin(&admin.key()) @ CrankConfigError::NotAdmin
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    #[account(
+        mut,
+        seeds = [MarketRegistry::SEED_PREFIX],
+        bump = market_registry.bump
+    )]
+    pub market_registry: Account<'info, MarketRegistry>,
+}
+
+/// Registries created before minimum sizes existed are grown to fit them.
+#[derive(Accounts)]
+pub struct SetMarketMinOrderSize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [CrankConfig::SEED_PREFIX],
+        bump = crank_config.bump,
+        constraint = crank_config.is_admin(&admin.key()) @ CrankConfigError::NotAdmin
+    )]
+    pub crank_config: Account<'info, CrankConfig>,
+
+    #[account(
+        mut,
+        seeds = [MarketRegistry::SEED_PREFIX],
+        bump = market_registry.bump,
+        realloc = MarketRegistry::LEN,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub market_registry: Account<'info, MarketRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct MarketFeedSet {
+    pub market_type: DriftMarketType,
+    pub market_index: u16,
+    pub feed_id: [u8; 32],
+}
+
+#[event]
+pub struct MarketMinOrderSizeSet {
+    pub market_type: DriftMarketType,
+    pub market_index: u16,
+    pub min_base_asset_amount: u64,
+}
+
+#[error_code]
+pub enum MarketRegistryError {
+    #[msg("Order feed does not match the feed registered for its market")]
+    FeedMarketMismatch,
+    #[msg("Market has no registered feed")]
+    MarketNotRegistered,
+    #[msg("Market registry is full")]
+    RegistryFull,
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::account_version::{rewrite_account, ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+use crate::state::{GhostOrder, GhostOrderV1};
+
+/// Rewrites a ghost order at the current layout version, growing it to fit.
+/// Stored fields keep their values and new ones take their defaults, so
+/// anyone may pay to migrate any order; delegated orders have to be back
+/// on the base layer first.
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let ghost_order = &ctx.accounts.ghost_order;
+    let version = {
+        let data = ghost_order.try_borrow_data()?;
+        require!(
+            data.starts_with(GhostOrder::DISCRIMINATOR),
+            MigrateError::UnsupportedAccountVersion
+        );
+        GhostOrder::stored_version(&ghost_order.key(), &data)
+            .ok_or(MigrateError::UnsupportedAccountVersion)?
+    };
+
+    match version {
+        ACCOUNT_VERSION => {
+            msg!("Ghost order already at version {}", version);
+            return Ok(());
+        }
+        LEGACY_ACCOUNT_VERSION => {
+            let legacy = GhostOrderV1::from_account_data(&ghost_order.try_borrow_data()?)?;
+            rewrite_account(
+                &ghost_order.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &legacy.upgrade(),
+                GhostOrder::LEN,
+            )?;
+        }
+        _ => return err!(MigrateError::UnsupportedAccountVersion),
+    }
+
+    emit!(AccountMigrated {
+        account: ghost_order.key(),
+        from_version: version,
+        to_version: ACCOUNT_VERSION,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: A ghost order at any layout version; the handler checks its
+    /// discriminator and that its owner and id derive this address
+    #[account(mut, owner = crate::ID)]
+    pub ghost_order: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[error_code]
+pub enum MigrateError {
+    #[msg("Account is not at a layout version this program can migrate")]
+    UnsupportedAccountVersion,
+}
@@ -1,17 +1,37 @@
 pub mod create_ghost_order;
+pub mod create_bracket;
 pub mod delegate_order;
 pub mod check_trigger;
+pub mod simulate_trigger;
 pub mod execute_trigger;
 pub mod schedule_monitoring;
+pub mod cancel_monitoring;
 pub mod cancel_order;
+pub mod cancel_drift_order;
 pub mod mark_ready;
 pub mod execute_with_commitment;
+pub mod link_oco_orders;
+pub mod crank_config;
+pub mod order_counter;
+pub mod modify_ghost_order;
+pub mod market_registry;
+pub mod migrate_account;
 
 pub use create_ghost_order::*;
+pub use create_bracket::*;
 pub use delegate_order::*;
 pub use check_trigger::*;
+pub use simulate_trigger::*;
 pub use execute_trigger::*;
 pub use schedule_monitoring::*;
+pub use cancel_monitoring::*;
 pub use cancel_order::*;
+pub use cancel_drift_order::*;
 pub use mark_ready::*;
 pub use execute_with_commitment::*;
+pub use link_oco_orders::*;
+pub use crank_config::*;
+pub use order_counter::*;
+pub use modify_ghost_order::*;
+pub use market_registry::*;
+pub use migrate_account::*;
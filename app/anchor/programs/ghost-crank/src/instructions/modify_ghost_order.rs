@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use crate::state::{min_order_size, validate_base_asset_amount, GhostOrder, MarketRegistry};
+use crate::instructions::migrate_account::MigrateError;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ModifyGhostOrderArgs {
+    pub trigger_price: i64,
+    pub base_asset_amount: u64,
+    /// Seconds from now until expiry; 0 clears the expiry
+    pub expiry_seconds: i64,
+    /// Fresh nonce for `params_commitment`; must differ from the current one
+    pub nonce: u64,
+    /// Client-computed commitment to the execution params under `nonce`
+    pub params_commitment: [u8; 32],
+}
+
+pub fn handler(ctx: Context<ModifyGhostOrder>, args: ModifyGhostOrderArgs) -> Result<()> {
+    let ghost_order = &mut ctx.accounts.ghost_order;
+    let clock = Clock::get()?;
+
+    let min_base_asset_amount = min_order_size(
+        ctx.accounts.market_registry.as_deref(),
+        ghost_order.market_type,
+        ghost_order.market_index,
+    );
+    validate_base_asset_amount(args.base_asset_amount, min_base_asset_amount)?;
+
+    let expiry = if args.expiry_seconds > 0 {
+        clock.unix_timestamp + args.expiry_seconds
+    } else {
+        0
+    };
+
+    ghost_order.apply_modification(
+        args.trigger_price,
+        args.base_asset_amount,
+        expiry,
+        args.nonce,
+        args.params_commitment,
+    )?;
+
+    emit!(GhostOrderModified {
+        order_id: ghost_order.order_id,
+        owner: ghost_order.owner,
+        trigger_price: ghost_order.trigger_price,
+        base_asset_amount: ghost_order.base_asset_amount,
+        expiry: ghost_order.expiry,
+    });
+
+    msg!(
+        "Ghost order modified: id={}, trigger_price={}, amount={}, commitment={:?}",
+        ghost_order.order_id,
+        ghost_order.trigger_price,
+        ghost_order.base_asset_amount,
+        &ghost_order.params_commitment[..8]
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ModifyGhostOrder<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GhostOrder::SEED_PREFIX, owner.key().as_ref(), &ghost_order.order_id.to_le_bytes()],
+        bump = ghost_order.bump,
+        constraint = ghost_order.owner == owner.key() @ ModifyError::NotOwner,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub ghost_order: Account<'info, GhostOrder>,
+
+    /// Supplies the market's minimum order size; without it the default applies
+    #[account(seeds = [MarketRegistry::SEED_PREFIX], bump = market_registry.bump)]
+    pub market_registry: Option<Account<'info, MarketRegistry>>,
+}
+
+#[event]
+pub struct GhostOrderModified {
+    pub order_id: u64,
+    pub owner: Pubkey,
+    pub trigger_price: i64,
+    pub base_asset_amount: u64,
+    pub expiry: i64,
+}
+
+#[error_code]
+pub enum ModifyError {
+    #[msg("Only the owner can modify the order")]
+    NotOwner,
+    #[msg("Only pending or active orders, or recurring orders between runs, can be modified")]
+    OrderNotModifiable,
+    #[msg("Modification must use a fresh nonce")]
+    NonceReused,
+    #[msg("Modification must use a fresh commitment")]
+    CommitmentReused,
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::OrderCounter;
+
+pub fn handler(ctx: Context<InitOrderCounter>) -> Result<()> {
+    let counter = &mut ctx.accounts.order_counter;
+    counter.owner = ctx.accounts.owner.key();
+    counter.next_order_id = 0;
+    counter.bump = ctx.bumps.order_counter;
+
+    msg!("Order counter initialized: owner={}", counter.owner);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitOrderCounter<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = OrderCounter::LEN,
+        seeds = [OrderCounter::SEED_PREFIX, owner.key().as_ref()],
+        bump
+    )]
+    pub order_counter: Account<'info, OrderCounter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum OrderCounterError {
+    #[msg("Order counter is exhausted")]
+    CounterOverflow,
+}
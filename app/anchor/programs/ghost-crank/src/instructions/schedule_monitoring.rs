@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_lang::solana_program::program::invoke_signed;
-use crate::state::{GhostOrder, OrderStatus};
+use crate::state::{CrankConfig, GhostOrder, MarketRegistry, OrderStatus};
+use crate::instructions::migrate_account::MigrateError;
 
 pub const MAGIC_PROGRAM_ID: Pubkey = pubkey!("Magic11111111111111111111111111111111111111");
 
@@ -67,6 +68,8 @@ fn build_check_trigger_instruction(
         accounts: vec![
             AccountMeta::new(*ghost_order, false),
             AccountMeta::new_readonly(*price_feed, false),
+            AccountMeta::new_readonly(CrankConfig::pda().0, false),
+            AccountMeta::new_readonly(MarketRegistry::pda().0, false),
         ],
         data: anchor_lang::InstructionData::data(&crate::instruction::CheckTrigger {}),
     }
@@ -119,7 +122,8 @@ pub struct ScheduleMonitoring<'info> {
         mut,
         seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
         bump = ghost_order.bump,
-        constraint = ghost_order.status == OrderStatus::Active @ ScheduleError::OrderNotActive
+        constraint = ghost_order.status == OrderStatus::Active @ ScheduleError::OrderNotActive,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
     )]
     pub ghost_order: Account<'info, GhostOrder>,
 
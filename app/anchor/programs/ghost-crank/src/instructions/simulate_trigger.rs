@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::GhostOrder;
+use crate::instructions::migrate_account::MigrateError;
+
+/// Read-only twin of `check_trigger`: reports what a crank would do at the
+/// current price without changing the order.
+pub fn handler(ctx: Context<SimulateTrigger>) -> Result<()> {
+    let ghost_order = &ctx.accounts.ghost_order;
+    let clock = Clock::get()?;
+
+    let current_price = ghost_order.read_price(&ctx.accounts.price_feed, &clock)?;
+    let preview = ghost_order.preview_trigger(current_price, &clock);
+
+    emit!(TriggerSimulation {
+        order_id: ghost_order.order_id,
+        current_price,
+        trigger_price: preview.trigger_price,
+        would_trigger: preview.would_trigger,
+        would_expire: preview.would_expire,
+    });
+
+    msg!("Simulated trigger: current_price={}, trigger_price={}, would_trigger={}, would_expire={}",
+         current_price, preview.trigger_price, preview.would_trigger, preview.would_expire);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SimulateTrigger<'info> {
+    #[account(
+        seeds = [GhostOrder::SEED_PREFIX, ghost_order.owner.as_ref(), &ghost_order.order_id.to_le_bytes()],
+        bump = ghost_order.bump,
+        constraint = ghost_order.is_current_version() @ MigrateError::UnsupportedAccountVersion,
+    )]
+    pub ghost_order: Account<'info, GhostOrder>,
+
+    /// CHECK: Price feed for `ghost_order.feed_id`; owner and layout checked
+    /// per `ghost_order.oracle_source`
+    pub price_feed: AccountInfo<'info>,
+}
+
+#[event]
+pub struct TriggerSimulation {
+    pub order_id: u64,
+    pub current_price: i64,
+    pub trigger_price: i64,
+    pub would_trigger: bool,
+    pub would_expire: bool,
+}
@@ -1,8 +1,29 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::ephemeral;
 
+pub mod drift_user;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
+#[path = "../../../shared/account_version.rs"]
+pub mod account_version;
+#[path = "../../../shared/delegation.rs"]
+pub mod delegation;
+#[path = "../../../shared/drift_cpi.rs"]
+pub mod drift_cpi;
+#[path = "../../../shared/order_size.rs"]
+pub mod order_size;
+#[path = "../../../shared/pyth.rs"]
+pub mod pyth;
+#[path = "../../../shared/switchboard.rs"]
+pub mod switchboard;
+#[path = "../../../shared/time.rs"]
+pub mod time;
+#[path = "../../../shared/trigger.rs"]
+pub mod trigger;
+
+#[cfg(test)]
+mod tests;
 
 use instructions::*;
 
@@ -20,6 +41,10 @@ pub mod ghost_crank {
         instructions::create_ghost_order::handler(ctx, args)
     }
 
+    pub fn create_bracket(ctx: Context<CreateBracket>, args: CreateBracketArgs) -> Result<()> {
+        instructions::create_bracket::handler(ctx, args)
+    }
+
     pub fn delegate_order(ctx: Context<DelegateOrder>) -> Result<()> {
         instructions::delegate_order::handler(ctx)
     }
@@ -32,8 +57,18 @@ pub mod ghost_crank {
         instructions::check_trigger::handler(ctx)
     }
 
+    pub fn check_triggers_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckTriggersBatch<'info>>,
+    ) -> Result<()> {
+        instructions::check_trigger::batch_handler(ctx)
+    }
+
+    pub fn simulate_trigger(ctx: Context<SimulateTrigger>) -> Result<()> {
+        instructions::simulate_trigger::handler(ctx)
+    }
+
     pub fn execute_trigger<'info>(
-        ctx: Context<'_, '_, '_, 'info, ExecuteTrigger<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, ExecuteTrigger<'info>>,
         args: ExecuteTriggerArgs,
     ) -> Result<()> {
         instructions::execute_trigger::handler(ctx, args)
@@ -46,10 +81,25 @@ pub mod ghost_crank {
         instructions::schedule_monitoring::handler(ctx, args)
     }
 
+    pub fn cancel_monitoring(ctx: Context<CancelMonitoring>) -> Result<()> {
+        instructions::cancel_monitoring::handler(ctx)
+    }
+
     pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
         instructions::cancel_order::handler(ctx)
     }
 
+    pub fn cancel_drift_order(ctx: Context<CancelDriftOrder>, drift_order_id: u32) -> Result<()> {
+        instructions::cancel_drift_order::handler(ctx, drift_order_id)
+    }
+
+    pub fn modify_ghost_order(
+        ctx: Context<ModifyGhostOrder>,
+        args: ModifyGhostOrderArgs,
+    ) -> Result<()> {
+        instructions::modify_ghost_order::handler(ctx, args)
+    }
+
     pub fn mark_ready(ctx: Context<MarkReady>, execution_price: i64) -> Result<()> {
         instructions::mark_ready::handler(ctx, execution_price)
     }
@@ -60,4 +110,51 @@ pub mod ghost_crank {
     ) -> Result<()> {
         instructions::execute_with_commitment::handler(ctx, args)
     }
+
+    pub fn link_oco_orders(ctx: Context<LinkOcoOrders>) -> Result<()> {
+        instructions::link_oco_orders::handler(ctx)
+    }
+
+    pub fn init_order_counter(ctx: Context<InitOrderCounter>) -> Result<()> {
+        instructions::order_counter::handler(ctx)
+    }
+
+    pub fn init_crank_config(ctx: Context<InitCrankConfig>, admin: Pubkey) -> Result<()> {
+        instructions::crank_config::init_handler(ctx, admin)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+        instructions::crank_config::set_pause_handler(ctx, paused)
+    }
+
+    pub fn init_market_registry(ctx: Context<InitMarketRegistry>) -> Result<()> {
+        instructions::market_registry::init_registry_handler(ctx)
+    }
+
+    pub fn set_market_feed(
+        ctx: Context<SetMarketFeed>,
+        market_type: drift_cpi::DriftMarketType,
+        market_index: u16,
+        feed_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::market_registry::set_market_feed_handler(ctx, market_type, market_index, feed_id)
+    }
+
+    pub fn set_market_min_order_size(
+        ctx: Context<SetMarketMinOrderSize>,
+        market_type: drift_cpi::DriftMarketType,
+        market_index: u16,
+        min_base_asset_amount: u64,
+    ) -> Result<()> {
+        instructions::market_registry::set_market_min_order_size_handler(
+            ctx,
+            market_type,
+            market_index,
+            min_base_asset_amount,
+        )
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account::handler(ctx)
+    }
 }
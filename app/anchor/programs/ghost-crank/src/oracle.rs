@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+
+use crate::pyth::{read_pyth_price_for_feed, PRICE_TARGET_EXPONENT};
+use crate::switchboard::read_switchboard_price_for_feed;
+use crate::time::TimeSource;
+
+/// Errors the shared `pyth` and `switchboard` parsers raise.
+#[error_code]
+pub enum OracleError {
+    #[msg("Invalid price feed account")]
+    InvalidPriceFeed,
+    #[msg("Price feed is stale")]
+    PriceFeedStale,
+}
+
+/// Oracle network an order's feed accounts belong to. An order's `feed_id`
+/// is the Pyth feed id of its price updates, or the address of its
+/// Switchboard pull feed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum OracleSource {
+    #[default]
+    Pyth = 0,
+    Switchboard = 1,
+}
+
+/// Reads `feed` with the parser for `source`, returning a price at
+/// `PRICE_TARGET_EXPONENT`. Each parser checks the account owner, and the
+/// read is rejected unless `feed` is the one `feed_id` names.
+pub fn read_price(
+    source: OracleSource,
+    feed: &AccountInfo,
+    feed_id: &[u8; 32],
+    now: &impl TimeSource,
+) -> Result<i64> {
+    match source {
+        OracleSource::Pyth => read_pyth_price_for_feed(feed, feed_id, now),
+        OracleSource::Switchboard => read_switchboard_price_for_feed(feed, feed_id, now),
+    }
+}
+
+/// Whether an order may quote its prices at `exponent`. Oracle prices are
+/// read at `PRICE_TARGET_EXPONENT`, so finer scales would invent digits.
+pub fn is_supported_price_exponent(exponent: i32) -> bool {
+    (PRICE_TARGET_EXPONENT..=0).contains(&exponent)
+}
+
+/// Rescales a `read_price` result to `exponent` so it compares directly
+/// against prices quoted at that scale.
+pub fn rescale_price(price: i64, exponent: i32) -> Result<i64> {
+    if !is_supported_price_exponent(exponent) {
+        msg!("Unsupported price exponent: {}", exponent);
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+
+    let divisor = 10i64.pow((exponent - PRICE_TARGET_EXPONENT) as u32);
+    Ok(price / divisor)
+}
+
+/// Scales an order price quoted at `exponent` up to Drift's
+/// `PRICE_PRECISION` (1e6, the `PRICE_TARGET_EXPONENT` scale).
+pub fn drift_price(price: u64, exponent: i32) -> Result<u64> {
+    if !is_supported_price_exponent(exponent) {
+        msg!("Unsupported price exponent: {}", exponent);
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+
+    price
+        .checked_mul(10u64.pow((exponent - PRICE_TARGET_EXPONENT) as u32))
+        .ok_or(OracleError::InvalidPriceFeed.into())
+}
+
+/// Reads `feed` like `read_price`, returning the price at `exponent`.
+pub fn read_price_at(
+    source: OracleSource,
+    feed: &AccountInfo,
+    feed_id: &[u8; 32],
+    now: &impl TimeSource,
+    exponent: i32,
+) -> Result<i64> {
+    rescale_price(read_price(source, feed, feed_id, now)?, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pyth::{
+        PriceFeedMessage, PriceUpdateV2, VerificationLevel, PRICE_UPDATE_V2_DISCRIMINATOR,
+        PYTH_RECEIVER_ID,
+    };
+    use crate::switchboard::{
+        LAST_UPDATE_TIMESTAMP_OFFSET, PULL_FEED_DISCRIMINATOR, RESULT_VALUE_OFFSET,
+        SWITCHBOARD_ON_DEMAND_ID,
+    };
+    use crate::time::FixedTime;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn pull_feed(value: i128) -> Vec<u8> {
+        let mut data = vec![0u8; 3208];
+        data[..8].copy_from_slice(&PULL_FEED_DISCRIMINATOR);
+        data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&NOW.to_le_bytes());
+        data[RESULT_VALUE_OFFSET..RESULT_VALUE_OFFSET + 16].copy_from_slice(&value.to_le_bytes());
+        data
+    }
+
+    fn pyth_feed(price: i64, exponent: i32) -> Vec<u8> {
+        let update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: [7u8; 32],
+                price,
+                conf: 0,
+                exponent,
+                publish_time: NOW,
+                prev_publish_time: NOW - 1,
+                ema_price: price,
+                ema_conf: 0,
+            },
+            posted_slot: 1,
+        };
+
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        update.serialize(&mut data).unwrap();
+        data
+    }
+
+    fn read_pyth_at(data: &mut [u8], exponent: i32) -> Result<i64> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let feed = AccountInfo::new(&key, false, false, &mut lamports, data, &PYTH_RECEIVER_ID, false, 0);
+        read_price_at(OracleSource::Pyth, &feed, &[7u8; 32], &FixedTime::at(NOW), exponent)
+    }
+
+    #[test]
+    fn test_feed_exponents_compare_at_order_scale() {
+        // $150.25 published at two different feed exponents
+        let mut feed_e8 = pyth_feed(150_25000000, -8);
+        let mut feed_e6 = pyth_feed(150_250000, -6);
+
+        // An order quoting micro-units sees the same price from both
+        assert_eq!(read_pyth_at(&mut feed_e8, -6).unwrap(), 150_250000);
+        assert_eq!(read_pyth_at(&mut feed_e6, -6).unwrap(), 150_250000);
+
+        // So does an order quoting cents, whose trigger would be 15025
+        assert_eq!(read_pyth_at(&mut feed_e8, -2).unwrap(), 15025);
+        assert_eq!(read_pyth_at(&mut feed_e6, -2).unwrap(), 15025);
+    }
+
+    #[test]
+    fn test_unsupported_order_exponent_rejected() {
+        let mut feed = pyth_feed(150_25000000, -8);
+
+        assert_eq!(
+            read_pyth_at(&mut feed, -8).unwrap_err(),
+            OracleError::InvalidPriceFeed.into()
+        );
+        assert!(read_pyth_at(&mut feed, 1).is_err());
+        assert!(is_supported_price_exponent(0));
+    }
+
+    #[test]
+    fn test_order_prices_scaled_to_drift_precision() {
+        // $150.25 quoted in cents, micro-units and whole dollars
+        assert_eq!(drift_price(15025, -2).unwrap(), 150_250000);
+        assert_eq!(drift_price(150_250000, -6).unwrap(), 150_250000);
+        assert_eq!(drift_price(150, 0).unwrap(), 150_000000);
+
+        assert!(drift_price(150, -8).is_err());
+        assert!(drift_price(u64::MAX, -2).is_err());
+    }
+
+    #[test]
+    fn test_dispatch_reads_switchboard_feed() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &SWITCHBOARD_ON_DEMAND_ID, false, 0,
+        );
+
+        let now = FixedTime::at(NOW);
+
+        let price = read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &now).unwrap();
+        assert_eq!(price, 42_500000);
+    }
+
+    #[test]
+    fn test_dispatch_rejects_owner_of_other_source() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &PYTH_RECEIVER_ID, false, 0,
+        );
+
+        let now = FixedTime::at(NOW);
+
+        assert!(read_price(OracleSource::Switchboard, &feed, &key.to_bytes(), &now).is_err());
+        assert!(read_price(OracleSource::Pyth, &feed, &key.to_bytes(), &now).is_err());
+    }
+
+    #[test]
+    fn test_feed_for_another_id_rejected() {
+        let now = FixedTime::at(NOW);
+
+        // A valid Pyth update, but for a different feed than the order's
+        let mut pyth = pyth_feed(150_25000000, -8);
+        assert_eq!(read_pyth_at(&mut pyth, -6).unwrap(), 150_250000);
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let feed = AccountInfo::new(&key, false, false, &mut lamports, &mut pyth, &PYTH_RECEIVER_ID, false, 0);
+        assert_eq!(
+            read_price(OracleSource::Pyth, &feed, &[8u8; 32], &now).unwrap_err(),
+            OracleError::InvalidPriceFeed.into()
+        );
+
+        // A valid Switchboard pull feed at some other address
+        let mut data = pull_feed(42_500_000_000_000_000_000);
+        let mut lamports = 0;
+        let feed = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &SWITCHBOARD_ON_DEMAND_ID, false, 0,
+        );
+        assert_eq!(
+            read_price(OracleSource::Switchboard, &feed, &Pubkey::new_unique().to_bytes(), &now)
+                .unwrap_err(),
+            OracleError::InvalidPriceFeed.into()
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+/// Program-wide switch that lets the admin halt trigger checks and executions.
+#[account]
+pub struct CrankConfig {
+    pub admin: Pubkey,
+    pub is_paused: bool,
+    pub bump: u8,
+}
+
+impl CrankConfig {
+    pub const SEED_PREFIX: &'static [u8] = b"crank_config";
+
+    pub const LEN: usize = 8 +  // discriminator
+        32 +                    // admin
+        1 +                     // is_paused
+        1;                      // bump
+
+    pub fn pda() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX], &crate::ID)
+    }
+
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admin == *key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::crank_config::{require_not_paused, CrankConfigError};
+
+    #[test]
+    fn test_crank_config_size() {
+        assert_eq!(CrankConfig::LEN, 42);
+    }
+
+    #[test]
+    fn test_pause_blocks_execution_until_resumed() {
+        let admin = Pubkey::new_unique();
+        let mut config = CrankConfig {
+            admin,
+            is_paused: false,
+            bump: 255,
+        };
+
+        assert!(require_not_paused(&config).is_ok());
+
+        config.is_paused = true;
+        assert_eq!(
+            require_not_paused(&config).unwrap_err(),
+            CrankConfigError::CrankPaused.into()
+        );
+
+        config.is_paused = false;
+        assert!(require_not_paused(&config).is_ok());
+    }
+
+    #[test]
+    fn test_only_admin_controls_pause() {
+        let admin = Pubkey::new_unique();
+        let config = CrankConfig {
+            admin,
+            is_paused: false,
+            bump: 255,
+        };
+
+        assert!(config.is_admin(&admin));
+        assert!(!config.is_admin(&Pubkey::new_unique()));
+    }
+}
@@ -1,5 +1,19 @@
 use anchor_lang::prelude::*;
 
+use crate::account_version;
+use crate::drift_cpi::DriftMarketType;
+use crate::instructions::cancel_order::CancelError;
+use crate::instructions::create_bracket::BracketError;
+use crate::instructions::create_ghost_order::CreateOrderError;
+use crate::instructions::execute_trigger::{GhostCrankError, DRIFT_EXECUTE_COMPUTE_UNITS, MAX_COMPUTE_UNITS};
+use crate::instructions::execute_with_commitment::{ExecuteError, OrderParams};
+use crate::instructions::migrate_account::MigrateError;
+use crate::instructions::modify_ghost_order::ModifyError;
+use crate::oracle::{drift_price, read_price_at, OracleSource};
+use crate::pyth::PRICE_TARGET_EXPONENT;
+use crate::time::TimeSource;
+use crate::trigger::{accept_observed_price, evaluate_trigger, is_past_expiry};
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u8)]
 pub enum TriggerCondition {
@@ -24,6 +38,33 @@ pub enum OrderStatus {
     Executed = 4,
     Cancelled = 5,
     Expired = 6,
+    PartiallyFilled = 7,
+    /// A spot order handed to Drift, which fills it; the fill is not tracked here.
+    Placed = 8,
+}
+
+/// What the order is for, as set by its creator. A label for indexers and
+/// the UI only; nothing on-chain branches on it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum OrderIntent {
+    #[default]
+    Entry = 0,
+    TakeProfit = 1,
+    StopLoss = 2,
+    Trailing = 3,
+}
+
+/// Why an order left the book without executing; `None` until it does.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum CancelReason {
+    #[default]
+    None = 0,
+    UserRequested = 1,
+    Expired = 2,
+    SiblingFilled = 3,
+    NothingToReduce = 4,
 }
 
 impl Default for OrderStatus {
@@ -34,6 +75,8 @@ impl Default for OrderStatus {
 
 #[account]
 pub struct GhostOrder {
+    // Layout version the account was written with; see `account_version`
+    pub version: u8,
     pub owner: Pubkey,
     pub order_id: u64,
     pub market_index: u16,
@@ -61,6 +104,346 @@ pub struct GhostOrder {
     pub delegate_pda: Pubkey,
     pub delegate_bump: u8,
     pub drift_user: Pubkey,
+
+    // Keeper-supplied id of the execution that filled this order
+    pub execution_id: u64,
+
+    // Owner cancel window after a trigger fires
+    pub cancel_grace_slots: u64,
+    pub triggered_slot: u64,
+
+    // Trailing stop: trigger follows the best price seen by `trail_offset`
+    pub is_trailing: bool,
+    pub trail_offset: i64,
+    pub extreme_price: i64,
+
+    // One-cancels-the-other sibling (default pubkey when unlinked)
+    pub oco_sibling: Pubkey,
+
+    // Paid from this account to the keeper that fills the order
+    pub keeper_fee_lamports: u64,
+
+    // Consecutive crank reads that must satisfy the trigger before it fires
+    pub required_confirmations: u8,
+    pub confirmation_count: u8,
+
+    pub oracle_source: OracleSource,
+
+    // Scaled entry: split into `ladder_steps` limit orders `ladder_spacing` apart
+    pub ladder_steps: u8,
+    pub ladder_spacing: i64,
+
+    // Base amount Drift has filled so far; below `base_asset_amount` while partially filled
+    pub filled_amount: u64,
+
+    // Drift market the order trades on; `market_index` indexes into this market type
+    pub market_type: DriftMarketType,
+
+    // Slot of the last execution that placed Drift orders
+    pub executed_slot: u64,
+
+    // Bracket entry this take-profit/stop-loss leg waits on (default pubkey when standalone)
+    pub parent_order: Pubkey,
+
+    // Largest move from `execution_price` tolerated at execution (0 = unbounded)
+    pub max_slippage_bps: u16,
+
+    // Compute budget for the Drift call handler (0 = DRIFT_EXECUTE_COMPUTE_UNITS)
+    pub compute_units: u32,
+
+    // Decimal exponent `trigger_price` and the other prices are quoted in
+    pub price_exponent: i32,
+
+    // Worst price a market order may fill at, at `price_exponent`; sent as the Drift limit price (0 = unbounded)
+    pub price_bound: u64,
+
+    // Last oracle price the crank accepted, at `price_exponent` (0 = none yet)
+    pub last_observed_price: i64,
+
+    // Creator's label for the order (entry, take-profit, ...); no effect on execution
+    pub intent: OrderIntent,
+
+    // Slots a ready order stays executable (0 = DEFAULT_READY_WINDOW_SLOTS)
+    pub ready_window_slots: u64,
+
+    // DCA: a recurring order re-arms after filling until `remaining_runs` is used up
+    pub recurring: bool,
+    pub remaining_runs: u16,
+
+    // Set alongside a `Cancelled`/`Expired` status
+    pub cancel_reason: CancelReason,
+
+    // Keepers allowed to execute the order; all default keys = any keeper
+    pub keeper_allowlist: [Pubkey; MAX_ALLOWED_KEEPERS],
+
+    // Reads rejected as price jumps since `last_observed_price` was accepted
+    pub rejected_jumps: u8,
+}
+
+/// `GhostOrder` as stored at layout version 1, before the version byte and
+/// every field after `drift_user` were added.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct GhostOrderV1 {
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub market_index: u16,
+    pub trigger_price: i64,
+    pub trigger_condition: TriggerCondition,
+    pub order_side: OrderSide,
+    pub base_asset_amount: u64,
+    pub reduce_only: bool,
+    pub status: OrderStatus,
+    pub created_at: i64,
+    pub triggered_at: i64,
+    pub executed_at: i64,
+    pub expiry: i64,
+    pub feed_id: [u8; 32],
+    pub crank_task_id: u64,
+    pub execution_price: i64,
+    pub bump: u8,
+    pub params_commitment: [u8; 32],
+    pub nonce: u64,
+    pub ready_expires_at: i64,
+    pub delegate_pda: Pubkey,
+    pub delegate_bump: u8,
+    pub drift_user: Pubkey,
+}
+
+impl GhostOrderV1 {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +                     // owner
+        8 +                      // order_id
+        2 +                      // market_index
+        8 +                      // trigger_price
+        1 +                      // trigger_condition
+        1 +                      // order_side
+        8 +                      // base_asset_amount
+        1 +                      // reduce_only
+        1 +                      // status
+        8 +                      // created_at
+        8 +                      // triggered_at
+        8 +                      // executed_at
+        8 +                      // expiry
+        32 +                     // feed_id
+        8 +                      // crank_task_id
+        8 +                      // execution_price
+        1 +                      // bump
+        32 +                     // params_commitment
+        8 +                      // nonce
+        8 +                      // ready_expires_at
+        32 +                     // delegate_pda
+        1 +                      // delegate_bump
+        32;                      // drift_user
+
+    /// Reads a version 1 order from its raw account data.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(GhostOrder::DISCRIMINATOR),
+            MigrateError::UnsupportedAccountVersion
+        );
+        Self::deserialize(&mut &data[account_version::VERSION_OFFSET..])
+            .map_err(|_| error!(MigrateError::UnsupportedAccountVersion))
+    }
+
+    /// The order at the current layout. Fields version 1 stored carry over
+    /// and the rest take the values of a plain perp order on a Pyth feed,
+    /// with prices at Drift's precision as version 1 sent them.
+    pub fn upgrade(self) -> GhostOrder {
+        let cancel_reason = match self.status {
+            OrderStatus::Cancelled => CancelReason::UserRequested,
+            OrderStatus::Expired => CancelReason::Expired,
+            _ => CancelReason::None,
+        };
+        let filled_amount = if self.status == OrderStatus::Executed {
+            self.base_asset_amount
+        } else {
+            0
+        };
+
+        GhostOrder {
+            version: account_version::ACCOUNT_VERSION,
+            owner: self.owner,
+            order_id: self.order_id,
+            market_index: self.market_index,
+            trigger_price: self.trigger_price,
+            trigger_condition: self.trigger_condition,
+            order_side: self.order_side,
+            base_asset_amount: self.base_asset_amount,
+            reduce_only: self.reduce_only,
+            status: self.status,
+            created_at: self.created_at,
+            triggered_at: self.triggered_at,
+            executed_at: self.executed_at,
+            expiry: self.expiry,
+            feed_id: self.feed_id,
+            crank_task_id: self.crank_task_id,
+            execution_price: self.execution_price,
+            bump: self.bump,
+            params_commitment: self.params_commitment,
+            nonce: self.nonce,
+            ready_expires_at: self.ready_expires_at,
+            delegate_pda: self.delegate_pda,
+            delegate_bump: self.delegate_bump,
+            drift_user: self.drift_user,
+            execution_id: 0,
+            cancel_grace_slots: 0,
+            triggered_slot: 0,
+            is_trailing: false,
+            trail_offset: 0,
+            extreme_price: 0,
+            oco_sibling: Pubkey::default(),
+            keeper_fee_lamports: 0,
+            required_confirmations: 0,
+            confirmation_count: 0,
+            oracle_source: OracleSource::Pyth,
+            ladder_steps: 0,
+            ladder_spacing: 0,
+            filled_amount,
+            market_type: DriftMarketType::Perp,
+            executed_slot: 0,
+            parent_order: Pubkey::default(),
+            max_slippage_bps: 0,
+            compute_units: 0,
+            price_exponent: PRICE_TARGET_EXPONENT,
+            price_bound: 0,
+            last_observed_price: 0,
+            intent: OrderIntent::Entry,
+            ready_window_slots: 0,
+            recurring: false,
+            remaining_runs: 0,
+            cancel_reason,
+            keeper_allowlist: [Pubkey::default(); MAX_ALLOWED_KEEPERS],
+            rejected_jumps: 0,
+        }
+    }
+}
+
+/// Upper bound on tranches so a laddered execution fits in one transaction.
+pub const MAX_LADDER_STEPS: u8 = 8;
+
+/// Slots in an order's keeper allowlist.
+pub const MAX_ALLOWED_KEEPERS: usize = 4;
+
+/// Slots (~40 seconds) a ready order stays executable unless it sets its own window.
+pub const DEFAULT_READY_WINDOW_SLOTS: u64 = 100;
+
+/// Bounds on a custom ready window: long enough for a keeper to land the
+/// execution, short enough that a stale trigger cannot fill much later.
+pub const MIN_READY_WINDOW_SLOTS: u64 = 10;
+pub const MAX_READY_WINDOW_SLOTS: u64 = 1_500;
+
+/// Accepts 0 (use the default) or a window within the min/max bounds.
+pub fn validate_ready_window(ready_window_slots: u64) -> Result<()> {
+    require!(
+        ready_window_slots == 0
+            || (MIN_READY_WINDOW_SLOTS..=MAX_READY_WINDOW_SLOTS).contains(&ready_window_slots),
+        CreateOrderError::InvalidReadyWindow
+    );
+    Ok(())
+}
+
+/// Rejects dust orders that would waste compute and fail on Drift.
+pub fn validate_base_asset_amount(base_asset_amount: u64, min_base_asset_amount: u64) -> Result<()> {
+    require!(
+        base_asset_amount >= min_base_asset_amount,
+        CreateOrderError::OrderBelowMinSize
+    );
+    Ok(())
+}
+
+/// Accepts 0 (use the default) or a budget a transaction can actually grant.
+pub fn validate_compute_units(compute_units: u32) -> Result<()> {
+    require!(
+        compute_units <= MAX_COMPUTE_UNITS,
+        CreateOrderError::ComputeUnitsTooHigh
+    );
+    Ok(())
+}
+
+/// One Drift order placed for a (possibly laddered) execution. A zero
+/// price means a market order.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LadderTranche {
+    pub base_asset_amount: u64,
+    pub price: u64,
+}
+
+/// Outcome `check_trigger` would reach for a given price, computed without
+/// touching the order.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TriggerPreview {
+    pub would_trigger: bool,
+    pub would_expire: bool,
+    /// Trigger price the check compares against, after any trailing move
+    pub trigger_price: i64,
+}
+
+/// Splits `base_asset_amount` into `steps` limit orders starting at
+/// `anchor_price` and `spacing` apart, stepping away from the market on
+/// `side`: down for a long, up for a short. The last tranche absorbs the
+/// division remainder. Fewer than two steps is a single market order.
+pub fn ladder_tranches(
+    base_asset_amount: u64,
+    steps: u8,
+    spacing: i64,
+    anchor_price: i64,
+    side: OrderSide,
+) -> Result<Vec<LadderTranche>> {
+    if steps <= 1 {
+        return Ok(vec![LadderTranche { base_asset_amount, price: 0 }]);
+    }
+    require!(
+        steps <= MAX_LADDER_STEPS && base_asset_amount >= steps as u64 && spacing > 0,
+        ExecuteError::InvalidLadder
+    );
+
+    let per_step = base_asset_amount / steps as u64;
+    let remainder = base_asset_amount % steps as u64;
+
+    (0..steps)
+        .map(|step| {
+            let offset = spacing
+                .checked_mul(step as i64)
+                .ok_or(ExecuteError::InvalidLadder)?;
+            let price = match side {
+                OrderSide::Long => anchor_price.checked_sub(offset),
+                OrderSide::Short => anchor_price.checked_add(offset),
+            };
+            let price = price
+                .filter(|price| *price > 0)
+                .ok_or(ExecuteError::InvalidLadder)?;
+            let amount = if step == steps - 1 { per_step + remainder } else { per_step };
+            Ok(LadderTranche {
+                base_asset_amount: amount,
+                price: price as u64,
+            })
+        })
+        .collect()
+}
+
+/// Trigger conditions of a bracket's (take-profit, stop-loss) legs. A long
+/// entry exits upward at the take-profit and downward at the stop-loss.
+pub fn bracket_exit_conditions(entry_side: OrderSide) -> (TriggerCondition, TriggerCondition) {
+    match entry_side {
+        OrderSide::Long => (TriggerCondition::Above, TriggerCondition::Below),
+        OrderSide::Short => (TriggerCondition::Below, TriggerCondition::Above),
+    }
+}
+
+/// Take-profit must sit beyond the entry in the direction of profit and
+/// stop-loss beyond it in the direction of loss.
+pub fn validate_bracket_prices(
+    entry_side: OrderSide,
+    entry_price: i64,
+    take_profit_price: i64,
+    stop_loss_price: i64,
+) -> Result<()> {
+    let ordered = match entry_side {
+        OrderSide::Long => stop_loss_price < entry_price && entry_price < take_profit_price,
+        OrderSide::Short => take_profit_price < entry_price && entry_price < stop_loss_price,
+    };
+    require!(ordered && stop_loss_price > 0 && take_profit_price > 0, BracketError::InvalidBracketPrices);
+    Ok(())
 }
 
 impl GhostOrder {
@@ -68,6 +451,7 @@ impl GhostOrder {
     pub const DELEGATE_SEED_PREFIX: &'static [u8] = b"ghost_delegate";
 
     pub const LEN: usize = 8 +  // discriminator
+        1 +                      // version
         32 +                     // owner
         8 +                      // order_id
         2 +                      // market_index
@@ -91,7 +475,36 @@ impl GhostOrder {
         8 +                      // ready_expires_at
         32 +                     // delegate_pda
         1 +                      // delegate_bump
-        32;                      // drift_user
+        32 +                     // drift_user
+        8 +                      // execution_id
+        8 +                      // cancel_grace_slots
+        8 +                      // triggered_slot
+        1 +                      // is_trailing
+        8 +                      // trail_offset
+        8 +                      // extreme_price
+        32 +                     // oco_sibling
+        8 +                      // keeper_fee_lamports
+        1 +                      // required_confirmations
+        1 +                      // confirmation_count
+        1 +                      // oracle_source
+        1 +                      // ladder_steps
+        8 +                      // ladder_spacing
+        8 +                      // filled_amount
+        1 +                      // market_type
+        8 +                      // executed_slot
+        32 +                     // parent_order
+        2 +                      // max_slippage_bps
+        4 +                      // compute_units
+        4 +                      // price_exponent
+        8 +                      // price_bound
+        8 +                      // last_observed_price
+        1 +                      // intent
+        8 +                      // ready_window_slots
+        1 +                      // recurring
+        2 +                      // remaining_runs
+        1 +                      // cancel_reason
+        32 * MAX_ALLOWED_KEEPERS + // keeper_allowlist
+        1;                       // rejected_jumps
 
     pub fn is_active(&self) -> bool {
         self.status == OrderStatus::Active
@@ -101,25 +514,1761 @@ impl GhostOrder {
         self.status == OrderStatus::ReadyToExecute
     }
 
-    pub fn is_expired(&self, current_time: i64) -> bool {
-        self.expiry > 0 && current_time > self.expiry
+    /// Ready orders and the unfilled remainder of partial fills can be executed.
+    pub fn is_executable(&self) -> bool {
+        matches!(
+            self.status,
+            OrderStatus::ReadyToExecute | OrderStatus::PartiallyFilled
+        )
+    }
+
+    pub fn is_monitored(&self) -> bool {
+        self.crank_task_id != 0
     }
 
-    pub fn is_ready_expired(&self, current_slot: i64) -> bool {
-        self.ready_expires_at > 0 && current_slot > self.ready_expires_at
+    pub fn clear_monitoring(&mut self) {
+        self.crank_task_id = 0;
     }
 
-    pub fn check_trigger(&self, current_price: i64) -> bool {
-        match self.trigger_condition {
-            TriggerCondition::Above => current_price >= self.trigger_price,
-            TriggerCondition::Below => current_price <= self.trigger_price,
+    pub fn remaining_amount(&self) -> u64 {
+        self.base_asset_amount.saturating_sub(self.filled_amount)
+    }
+
+    /// Adds a Drift fill and settles the status: `Executed` once the whole
+    /// base amount has filled, `PartiallyFilled` otherwise. Returns whether
+    /// the order is now fully filled.
+    pub fn record_fill(&mut self, fill: u64) -> bool {
+        self.filled_amount = self
+            .filled_amount
+            .saturating_add(fill)
+            .min(self.base_asset_amount);
+
+        let complete = self.filled_amount == self.base_asset_amount;
+        self.status = if complete {
+            OrderStatus::Executed
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        complete
+    }
+
+    /// Records a spot order handed to Drift. Its fill lands in Drift's spot
+    /// balances, which are not read here, so the order is `Placed` rather
+    /// than `Executed` and its filled amount is left as it was.
+    pub fn record_placement(&mut self) {
+        self.status = OrderStatus::Placed;
+    }
+
+    pub fn is_expired(&self, now: &impl TimeSource) -> bool {
+        is_past_expiry(self.expiry, now.unix_timestamp())
+    }
+
+    /// Whether `current_price` has moved more than `max_slippage_bps` away
+    /// from the price captured when the trigger fired. Orders without a
+    /// bound, or without a captured price, never exceed it.
+    pub fn exceeds_max_slippage(&self, current_price: i64) -> bool {
+        if self.max_slippage_bps == 0 || self.execution_price <= 0 {
+            return false;
+        }
+        let moved = (current_price as i128 - self.execution_price as i128).unsigned_abs();
+        moved * 10_000 > self.execution_price as u128 * self.max_slippage_bps as u128
+    }
+
+    /// Whether `current_price` is already past `price_bound`, so the
+    /// marketable limit placed at the bound could not fill: above it for a
+    /// long, below it for a short.
+    pub fn breaches_price_bound(&self, current_price: i64) -> bool {
+        if self.price_bound == 0 {
+            return false;
+        }
+        let bound = self.price_bound as i128;
+        match self.order_side {
+            OrderSide::Long => current_price as i128 > bound,
+            OrderSide::Short => (current_price as i128) < bound,
+        }
+    }
+
+    /// Drift price for a tranche, scaled from `price_exponent` to Drift's
+    /// precision: ladder tranches keep their limit price, a market tranche
+    /// becomes a marketable limit at `price_bound`.
+    pub fn drift_order_price(&self, tranche_price: u64) -> Result<u64> {
+        let price = if tranche_price > 0 { tranche_price } else { self.price_bound };
+        drift_price(price, self.price_exponent)
+    }
+
+    /// Returns a triggered order to monitoring so it can fire again at a
+    /// fresh price. Any partial fill is kept.
+    pub fn rearm(&mut self) {
+        self.status = OrderStatus::Active;
+        self.execution_price = 0;
+        self.ready_expires_at = 0;
+        self.triggered_at = 0;
+        self.triggered_slot = 0;
+        self.confirmation_count = 0;
+    }
+
+    /// Closes out a run that just filled. The order stays `Executed`; a
+    /// recurring order with runs left waits there for its owner to commit
+    /// the next run through `modify_ghost_order`, so the params this run
+    /// revealed cannot be replayed. Returns whether another run remains.
+    pub fn finish_run(&mut self) -> bool {
+        if !self.recurring || self.remaining_runs <= 1 {
+            self.remaining_runs = 0;
+            return false;
+        }
+
+        self.remaining_runs -= 1;
+        true
+    }
+
+    /// A recurring order between runs, waiting for its next commitment.
+    pub fn awaits_next_run(&self) -> bool {
+        self.status == OrderStatus::Executed && self.recurring && self.remaining_runs > 0
+    }
+
+    /// Slots a keeper gets to execute the order once it is ready.
+    pub fn ready_window(&self) -> u64 {
+        if self.ready_window_slots > 0 {
+            self.ready_window_slots
+        } else {
+            DEFAULT_READY_WINDOW_SLOTS
+        }
+    }
+
+    /// Marks a triggered order ready to execute at `execution_price`,
+    /// executable until the end of its ready window.
+    pub fn mark_ready(&mut self, execution_price: i64, now: &impl TimeSource) {
+        self.status = OrderStatus::ReadyToExecute;
+        self.execution_price = execution_price;
+        self.ready_expires_at = now.slot() as i64 + self.ready_window() as i64;
+    }
+
+    /// Compute units the Drift call handler is given when this order executes.
+    pub fn drift_compute_units(&self) -> u32 {
+        if self.compute_units > 0 {
+            self.compute_units
+        } else {
+            DRIFT_EXECUTE_COMPUTE_UNITS
         }
     }
 
+    /// Expiry for the Drift orders this order places, so resting tranches
+    /// lapse with the ghost order instead of living on Drift's book.
+    pub fn drift_max_ts(&self) -> Option<i64> {
+        (self.expiry > 0).then_some(self.expiry)
+    }
+
+    pub fn is_ready_expired(&self, now: &impl TimeSource) -> bool {
+        self.ready_expires_at > 0 && now.slot() as i64 > self.ready_expires_at
+    }
+
+    /// Status an owner cancel moves the order to: `Cancelled` while it is
+    /// still live, `Expired` once no keeper executed it within its ready
+    /// window. Anything else, including a ready order still inside that
+    /// window, cannot be cancelled.
+    pub fn cancellation_status(&self, now: &impl TimeSource) -> Result<OrderStatus> {
+        if matches!(
+            self.status,
+            OrderStatus::Pending | OrderStatus::Active | OrderStatus::PartiallyFilled
+        ) || self.is_within_cancel_grace(now)
+        {
+            return Ok(OrderStatus::Cancelled);
+        }
+
+        if self.status == OrderStatus::ReadyToExecute && self.is_ready_expired(now) {
+            return Ok(OrderStatus::Expired);
+        }
+
+        Err(CancelError::OrderNotCancellable.into())
+    }
+
+    /// Cancels the order for its owner and returns the status it moved to.
+    /// A ready order whose window lapsed is recorded as expired, not as a
+    /// user cancel: no keeper could execute it any longer.
+    pub fn cancel_by_owner(&mut self, now: &impl TimeSource) -> Result<OrderStatus> {
+        let status = self.cancellation_status(now)?;
+        let reason = match status {
+            OrderStatus::Expired => CancelReason::Expired,
+            _ => CancelReason::UserRequested,
+        };
+        self.cancel(status, reason);
+        Ok(status)
+    }
+
+    /// Whether an execution attempt repeats one that already landed and
+    /// must be acknowledged without placing Drift orders again: the same
+    /// `execution_id` as the recorded execution, or any attempt on an
+    /// executed or placed order in the slot it executed. A different
+    /// execution of such an order in an earlier slot is an error.
+    pub fn is_duplicate_execution(&self, execution_id: u64, slot: u64) -> Result<bool> {
+        match self.status {
+            OrderStatus::Executed | OrderStatus::Placed => {
+                require!(
+                    self.execution_id == execution_id || self.executed_slot == slot,
+                    ExecuteError::AlreadyExecuted
+                );
+                Ok(true)
+            }
+            OrderStatus::PartiallyFilled => Ok(self.execution_id == execution_id),
+            _ => Ok(false),
+        }
+    }
+
+    /// Whether a triggered order can still be cancelled by its owner at `current_slot`.
+    pub fn is_within_cancel_grace(&self, now: &impl TimeSource) -> bool {
+        let triggered = matches!(
+            self.status,
+            OrderStatus::Triggered | OrderStatus::ReadyToExecute
+        );
+
+        triggered
+            && self.cancel_grace_slots > 0
+            && now.slot() <= self.triggered_slot.saturating_add(self.cancel_grace_slots)
+    }
+
+    /// Ratchets `extreme_price` to the best price seen for the side and
+    /// moves `trigger_price` to trail it by `trail_offset`: a Long tracks the
+    /// high and fires on a fall below it, a Short tracks the low and fires
+    /// on a rise above it. Returns the new trigger.
+    pub fn update_trailing_trigger(&mut self, current_price: i64) -> i64 {
+        let is_new_extreme = match self.order_side {
+            OrderSide::Long => current_price > self.extreme_price,
+            OrderSide::Short => current_price < self.extreme_price,
+        };
+        if self.extreme_price == 0 || is_new_extreme {
+            self.extreme_price = current_price;
+        }
+
+        self.trigger_price = match self.order_side {
+            OrderSide::Long => self.extreme_price.saturating_sub(self.trail_offset),
+            OrderSide::Short => self.extreme_price.saturating_add(self.trail_offset),
+        };
+        self.trigger_price
+    }
+
+    pub fn is_bracket_leg(&self) -> bool {
+        self.parent_order != Pubkey::default()
+    }
+
+    /// Makes this order a bracket leg of `entry`, one-cancels-the-other with `sibling`.
+    pub fn attach_to_entry(&mut self, entry: Pubkey, sibling: Pubkey) {
+        self.parent_order = entry;
+        self.oco_sibling = sibling;
+    }
+
+    /// Bracket legs stay inert until their entry has executed; standalone
+    /// orders activate freely.
+    pub fn check_activation(&self, parent: Option<(Pubkey, &GhostOrder)>) -> Result<()> {
+        if !self.is_bracket_leg() {
+            return Ok(());
+        }
+
+        let (parent_key, parent) = parent.ok_or(BracketError::ParentMissing)?;
+        require_keys_eq!(parent_key, self.parent_order, BracketError::ParentMismatch);
+        require!(
+            parent.status == OrderStatus::Executed,
+            BracketError::EntryNotExecuted
+        );
+        Ok(())
+    }
+
+    pub fn is_oco_linked(&self) -> bool {
+        self.oco_sibling != Pubkey::default()
+    }
+
+    pub fn can_link_oco(&self) -> bool {
+        !self.is_oco_linked()
+            && matches!(self.status, OrderStatus::Pending | OrderStatus::Active)
+    }
+
+    /// Moves the order to `status` (`Cancelled` or `Expired`) and records why.
+    pub fn cancel(&mut self, status: OrderStatus, reason: CancelReason) {
+        self.status = status;
+        self.cancel_reason = reason;
+    }
+
+    /// Cancels this order because its OCO sibling filled or was cancelled.
+    /// Returns `false` if the order had already reached a final state.
+    pub fn cancel_for_oco_sibling(&mut self) -> bool {
+        match self.status {
+            OrderStatus::Pending
+            | OrderStatus::Active
+            | OrderStatus::Triggered
+            | OrderStatus::ReadyToExecute
+            | OrderStatus::PartiallyFilled => {
+                self.cancel(OrderStatus::Cancelled, CancelReason::SiblingFilled);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Records one crank read. Returns `true` once the trigger condition has
+    /// held for `required_confirmations` consecutive reads; a failing read
+    /// resets the streak.
+    pub fn record_trigger_check(&mut self, current_price: i64) -> bool {
+        if !self.check_trigger(current_price) {
+            self.confirmation_count = 0;
+            return false;
+        }
+
+        self.confirmation_count = self.confirmation_count.saturating_add(1);
+        self.confirmation_count >= self.required_confirmations.max(1)
+    }
+
+    /// Reads `feed`, which must be this order's `feed_id`, at its
+    /// `price_exponent`, ready to compare against `trigger_price`.
+    pub fn read_price(&self, feed: &AccountInfo, now: &impl TimeSource) -> Result<i64> {
+        read_price_at(self.oracle_source, feed, &self.feed_id, now, self.price_exponent)
+    }
+
+    /// Records `current_price` as the latest read unless it jumped more than
+    /// `MAX_PRICE_JUMP_BPS` from the last accepted one, for at most
+    /// `MAX_REJECTED_JUMPS` reads in a row. Returns whether the read was
+    /// accepted; a rejected read must not be checked.
+    pub fn accept_observed_price(&mut self, current_price: i64) -> bool {
+        accept_observed_price(&mut self.last_observed_price, &mut self.rejected_jumps, current_price)
+    }
+
+    /// Applies one crank read to an active, unexpired order: moves a trailing
+    /// trigger, counts the confirmation and, once it holds, marks the order
+    /// `Triggered` at `current_price`. Returns `true` when the trigger fired.
+    pub fn apply_trigger_check(&mut self, current_price: i64, now: &impl TimeSource) -> bool {
+        if self.is_trailing {
+            self.update_trailing_trigger(current_price);
+        }
+
+        if !self.record_trigger_check(current_price) {
+            return false;
+        }
+
+        self.status = OrderStatus::Triggered;
+        self.triggered_at = now.unix_timestamp();
+        self.triggered_slot = now.slot();
+        self.execution_price = current_price;
+        true
+    }
+
+    /// Runs the same expiry, trailing and confirmation logic as `check_trigger`
+    /// on a copy of the order, so the result matches what a crank would do.
+    pub fn preview_trigger(&self, current_price: i64, now: &impl TimeSource) -> TriggerPreview {
+        let would_expire = self.is_active() && self.is_expired(now);
+        if !self.is_active() || would_expire {
+            return TriggerPreview {
+                would_trigger: false,
+                would_expire,
+                trigger_price: self.trigger_price,
+            };
+        }
+
+        let mut preview = self.clone();
+        if preview.is_trailing {
+            preview.update_trailing_trigger(current_price);
+        }
+        TriggerPreview {
+            would_trigger: preview.record_trigger_check(current_price),
+            would_expire,
+            trigger_price: preview.trigger_price,
+        }
+    }
+
+    pub fn is_keeper_restricted(&self) -> bool {
+        self.keeper_allowlist.iter().any(|keeper| *keeper != Pubkey::default())
+    }
+
+    /// Whether `keeper` may execute the order: anyone on a public order,
+    /// otherwise only a key in `keeper_allowlist`.
+    pub fn is_allowed_keeper(&self, keeper: &Pubkey) -> bool {
+        !self.is_keeper_restricted()
+            || (*keeper != Pubkey::default() && self.keeper_allowlist.contains(keeper))
+    }
+
+    /// Whether `authority` may cancel the Drift orders placed for this
+    /// order: the owner, or a keeper on its allowlist. A public order has
+    /// no keeper of its own, so only the owner can.
+    pub fn can_cancel_drift_orders(&self, authority: &Pubkey) -> bool {
+        *authority == self.owner || (self.is_keeper_restricted() && self.is_allowed_keeper(authority))
+    }
+
+    /// Moves `keeper_fee_lamports` from the order account to the keeper,
+    /// leaving at least `rent_minimum` behind. The fee is escrowed once, so
+    /// it is cleared once paid and later fills pay nothing. Returns `false`
+    /// without moving anything when the order cannot cover the fee.
+    pub fn pay_keeper_fee(
+        &mut self,
+        order_info: &AccountInfo,
+        keeper_info: &AccountInfo,
+        rent_minimum: u64,
+    ) -> Result<bool> {
+        if self.keeper_fee_lamports == 0 {
+            return Ok(true);
+        }
+
+        let available = order_info.lamports().saturating_sub(rent_minimum);
+        if available < self.keeper_fee_lamports {
+            return Ok(false);
+        }
+
+        **order_info.try_borrow_mut_lamports()? -= self.keeper_fee_lamports;
+        **keeper_info.try_borrow_mut_lamports()? += self.keeper_fee_lamports;
+        self.keeper_fee_lamports = 0;
+        Ok(true)
+    }
+
+    /// Returns an order's unpaid keeper fee to `owner_info` once no keeper
+    /// can earn it, keeping `rent_minimum` on the order account.
+    pub fn refund_keeper_fee(
+        &mut self,
+        order_info: &AccountInfo,
+        owner_info: &AccountInfo,
+        rent_minimum: u64,
+    ) -> Result<u64> {
+        let refund = order_info.lamports().saturating_sub(rent_minimum);
+        **order_info.try_borrow_mut_lamports()? -= refund;
+        **owner_info.try_borrow_mut_lamports()? += refund;
+        self.keeper_fee_lamports = 0;
+        Ok(refund)
+    }
+
+    /// The execution params this order's commitment is taken over.
+    pub fn committed_params(&self) -> OrderParams {
+        OrderParams {
+            market_index: self.market_index,
+            order_side: self.order_side,
+            base_asset_amount: self.base_asset_amount,
+            reduce_only: self.reduce_only,
+            is_trailing: self.is_trailing,
+            trail_offset: self.trail_offset,
+            ladder_steps: self.ladder_steps,
+            ladder_spacing: self.ladder_spacing,
+            market_type: self.market_type,
+            max_slippage_bps: self.max_slippage_bps,
+            price_bound: self.price_bound,
+        }
+    }
+
+    pub fn is_modifiable(&self) -> bool {
+        matches!(self.status, OrderStatus::Pending | OrderStatus::Active) || self.awaits_next_run()
+    }
+
+    /// Updates the trigger and size in place and takes the owner's fresh
+    /// `params_commitment` under `nonce`, so params committed under the
+    /// previous nonce stop verifying. A recurring order between runs is
+    /// re-armed for its next run.
+    pub fn apply_modification(
+        &mut self,
+        trigger_price: i64,
+        base_asset_amount: u64,
+        expiry: i64,
+        nonce: u64,
+        params_commitment: [u8; 32],
+    ) -> Result<()> {
+        require!(self.is_modifiable(), ModifyError::OrderNotModifiable);
+        require!(nonce != self.nonce, ModifyError::NonceReused);
+        require!(
+            params_commitment != self.params_commitment,
+            ModifyError::CommitmentReused
+        );
+
+        if self.awaits_next_run() {
+            self.filled_amount = 0;
+            self.rearm();
+        }
+        self.trigger_price = trigger_price;
+        self.base_asset_amount = base_asset_amount;
+        self.expiry = expiry;
+        self.nonce = nonce;
+        self.params_commitment = params_commitment;
+        self.confirmation_count = 0;
+        if self.is_trailing {
+            self.extreme_price = 0;
+        }
+        Ok(())
+    }
+
+    pub fn check_trigger(&self, current_price: i64) -> bool {
+        evaluate_trigger(self.trigger_condition, self.trigger_price, current_price)
+    }
+
+    /// Rejects Drift accounts that belong to anyone other than this order's owner.
+    pub fn verify_drift_accounts(&self, drift_user: &Pubkey, drift_authority: &Pubkey) -> Result<()> {
+        require_keys_eq!(*drift_authority, self.owner, GhostCrankError::DriftAuthorityMismatch);
+        require_keys_eq!(*drift_user, self.drift_user, GhostCrankError::DriftUserMismatch);
+        Ok(())
+    }
+
+    /// Whether the order was written at the layout this program reads;
+    /// older ones go through `migrate_account` first.
+    pub fn is_current_version(&self) -> bool {
+        self.version == account_version::ACCOUNT_VERSION
+    }
+
+    /// Layout version of the raw order `data` stored at `address`.
+    pub fn stored_version(address: &Pubkey, data: &[u8]) -> Option<u8> {
+        account_version::stored_version(data, |fields| {
+            // owner, then the little-endian order id
+            fields.len() >= 40
+                && Pubkey::find_program_address(
+                    &[Self::SEED_PREFIX, &fields[..32], &fields[32..40]],
+                    &crate::ID,
+                )
+                .0 == *address
+        })
+    }
+
     pub fn derive_delegate_pda(owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(
             &[Self::DELEGATE_SEED_PREFIX, owner.as_ref()],
             program_id,
         )
     }
+
+    /// Seeds the delegate PDA signs Drift CPIs with.
+    pub fn delegate_signer_seeds<'a>(owner: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+        [Self::DELEGATE_SEED_PREFIX, owner.as_ref(), bump]
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::account_version::{ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+    use crate::time::FixedTime;
+    use crate::trigger::EXPIRY_GRACE_SECONDS;
+
+    #[test]
+    fn test_delegate_signer_seeds_match_pda() {
+        let owner = Pubkey::new_unique();
+        let (delegate_pda, delegate_bump) = GhostOrder::derive_delegate_pda(&owner, &crate::ID);
+
+        let bump = [delegate_bump];
+        let seeds = GhostOrder::delegate_signer_seeds(&owner, &bump);
+        assert_eq!(
+            Pubkey::create_program_address(&seeds, &crate::ID).unwrap(),
+            delegate_pda
+        );
+    }
+
+    pub(crate) fn create_test_order() -> GhostOrder {
+        GhostOrder {
+            version: ACCOUNT_VERSION,
+            owner: Pubkey::new_unique(),
+            order_id: 1,
+            market_index: 0,
+            trigger_price: 150_000_000,
+            trigger_condition: TriggerCondition::Below,
+            order_side: OrderSide::Short,
+            base_asset_amount: 1_000_000,
+            reduce_only: true,
+            status: OrderStatus::ReadyToExecute,
+            created_at: 1_700_000_000,
+            triggered_at: 0,
+            executed_at: 0,
+            expiry: 0,
+            feed_id: [0u8; 32],
+            crank_task_id: 0,
+            execution_price: 0,
+            bump: 255,
+            params_commitment: [0u8; 32],
+            nonce: 0,
+            ready_expires_at: 0,
+            delegate_pda: Pubkey::default(),
+            delegate_bump: 255,
+            drift_user: Pubkey::default(),
+            execution_id: 0,
+            cancel_grace_slots: 0,
+            triggered_slot: 0,
+            is_trailing: false,
+            trail_offset: 0,
+            extreme_price: 0,
+            oco_sibling: Pubkey::default(),
+            keeper_fee_lamports: 0,
+            required_confirmations: 0,
+            confirmation_count: 0,
+            oracle_source: OracleSource::Pyth,
+            ladder_steps: 0,
+            ladder_spacing: 0,
+            filled_amount: 0,
+            market_type: DriftMarketType::Perp,
+            executed_slot: 0,
+            parent_order: Pubkey::default(),
+            max_slippage_bps: 0,
+            compute_units: 0,
+            price_exponent: -6,
+            price_bound: 0,
+            last_observed_price: 0,
+            intent: OrderIntent::Entry,
+            ready_window_slots: 0,
+            recurring: false,
+            remaining_runs: 0,
+            cancel_reason: CancelReason::None,
+            keeper_allowlist: [Pubkey::default(); MAX_ALLOWED_KEEPERS],
+            rejected_jumps: 0,
+        }
+    }
+
+    #[test]
+    fn test_retry_with_same_execution_id_is_noop() {
+        let mut order = create_test_order();
+        assert!(!order.is_duplicate_execution(42, 1_001).unwrap());
+
+        order.status = OrderStatus::Executed;
+        order.execution_id = 42;
+        order.executed_slot = 1_000;
+
+        assert!(order.is_duplicate_execution(42, 1_001).unwrap());
+    }
+
+    #[test]
+    fn test_reexecute_with_different_execution_id_rejected() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Executed;
+        order.execution_id = 42;
+        order.executed_slot = 1_000;
+
+        assert_eq!(
+            order.is_duplicate_execution(43, 1_001).unwrap_err(),
+            ExecuteError::AlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn test_duplicate_execution_places_once() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::ReadyToExecute;
+        let mut drift_cpis = 0;
+
+        // The keeper's first attempt lands, then it retries the same execution
+        for _ in 0..2 {
+            if !order.is_duplicate_execution(42, 1_000).unwrap() {
+                drift_cpis += 1;
+                order.record_fill(order.remaining_amount());
+                order.execution_id = 42;
+                order.executed_slot = 1_000;
+            }
+        }
+
+        assert_eq!(drift_cpis, 1);
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert_eq!(order.filled_amount, order.base_asset_amount);
+
+        // A racing keeper in the same slot is acknowledged rather than failed
+        assert!(order.is_duplicate_execution(43, 1_000).unwrap());
+        // Later, a different execution is a genuine double-execute
+        assert_eq!(
+            order.is_duplicate_execution(43, 1_001).unwrap_err(),
+            ExecuteError::AlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn test_partial_fill_retry_does_not_replace_remainder() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::ReadyToExecute;
+        order.record_fill(order.base_asset_amount / 2);
+        order.execution_id = 42;
+        order.executed_slot = 1_000;
+
+        // Retrying the landed execution is a no-op; a new one fills the rest
+        assert!(order.is_duplicate_execution(42, 1_001).unwrap());
+        assert!(!order.is_duplicate_execution(43, 1_001).unwrap());
+    }
+
+    #[test]
+    fn test_price_move_within_slippage_bound_executes() {
+        let mut order = create_test_order();
+        order.execution_price = 150_000_000;
+        order.max_slippage_bps = 50;
+
+        // 0.5% either way is exactly at the bound
+        assert!(!order.exceeds_max_slippage(150_000_000));
+        assert!(!order.exceeds_max_slippage(150_750_000));
+        assert!(!order.exceeds_max_slippage(149_250_000));
+
+        // No bound configured
+        order.max_slippage_bps = 0;
+        assert!(!order.exceeds_max_slippage(1));
+    }
+
+    #[test]
+    fn test_price_move_beyond_slippage_bound_rearms() {
+        let mut order = create_test_order();
+        order.execution_price = 150_000_000;
+        order.max_slippage_bps = 50;
+        order.ready_expires_at = 1_100;
+        order.triggered_slot = 1_000;
+        order.filled_amount = 400_000;
+
+        assert!(order.exceeds_max_slippage(150_750_001));
+        assert!(order.exceeds_max_slippage(149_249_999));
+
+        order.rearm();
+        assert!(order.is_active());
+        assert_eq!(order.execution_price, 0);
+        assert_eq!(order.ready_expires_at, 0);
+        assert_eq!(order.triggered_slot, 0);
+        assert_eq!(order.filled_amount, 400_000);
+    }
+
+    #[test]
+    fn test_slippage_read_only_accepts_the_order_feed() {
+        use crate::pyth::{
+            PriceFeedMessage, PriceUpdateV2, VerificationLevel, PRICE_UPDATE_V2_DISCRIMINATOR,
+            PYTH_RECEIVER_ID,
+        };
+
+        let now = FixedTime::at(1_700_000_000);
+        let update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: [7u8; 32],
+                price: 151_000_000,
+                conf: 0,
+                exponent: -6,
+                publish_time: now.unix_timestamp,
+                prev_publish_time: now.unix_timestamp - 1,
+                ema_price: 151_000_000,
+                ema_conf: 0,
+            },
+            posted_slot: 1,
+        };
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        update.serialize(&mut data).unwrap();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let feed = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &PYTH_RECEIVER_ID, false, 0);
+
+        let mut order = create_test_order();
+        order.execution_price = 150_000_000;
+        order.max_slippage_bps = 50;
+
+        // A fresh update of another market cannot stand in for the order's
+        order.feed_id = [9u8; 32];
+        assert!(order.read_price(&feed, &now).is_err());
+
+        order.feed_id = [7u8; 32];
+        let current_price = order.read_price(&feed, &now).unwrap();
+        assert!(order.exceeds_max_slippage(current_price));
+    }
+
+    #[test]
+    fn test_too_tight_price_bound_skips_order() {
+        let mut order = create_test_order();
+        order.order_side = OrderSide::Long;
+        order.price_bound = 151_000_000;
+
+        // Oracle still at or under the bound: the limit is marketable
+        assert!(!order.breaches_price_bound(150_000_000));
+        assert!(!order.breaches_price_bound(151_000_000));
+        // Oracle already through the bound: placing it would just rest
+        assert!(order.breaches_price_bound(151_000_001));
+
+        order.order_side = OrderSide::Short;
+        order.price_bound = 149_000_000;
+        assert!(!order.breaches_price_bound(150_000_000));
+        assert!(order.breaches_price_bound(148_999_999));
+
+        order.price_bound = 0;
+        assert!(!order.breaches_price_bound(1));
+    }
+
+    #[test]
+    fn test_price_bound_is_sent_as_drift_price() {
+        let mut order = create_test_order();
+        order.price_bound = 151_000_000;
+
+        let market_price = order.drift_order_price(0).unwrap();
+        assert_eq!(market_price, 151_000_000);
+        // Ladder tranches already carry their own limit
+        assert_eq!(order.drift_order_price(148_000_000).unwrap(), 148_000_000);
+
+        // A ladder and bound quoted in cents reach Drift in micro-units
+        order.price_exponent = -2;
+        order.price_bound = 15_100;
+        assert_eq!(order.drift_order_price(14_800).unwrap(), 148_000_000);
+        assert_eq!(order.drift_order_price(0).unwrap(), 151_000_000);
+
+        let data = crate::drift_cpi::build_drift_place_order_at_price(
+            order.market_type,
+            order.market_index,
+            order.order_side,
+            order.base_asset_amount,
+            market_price,
+            order.reduce_only,
+            None,
+        );
+        assert_eq!(data[8], 1);
+        assert_eq!(u64::from_le_bytes(data[20..28].try_into().unwrap()), 151_000_000);
+
+        let committed = order.committed_params();
+        let loosened = OrderParams {
+            price_bound: 160_000_000,
+            ..committed.clone()
+        };
+        assert_ne!(committed.commitment(7).unwrap(), loosened.commitment(7).unwrap());
+    }
+
+    #[test]
+    fn test_slippage_bound_is_committed() {
+        let mut order = create_test_order();
+        order.max_slippage_bps = 50;
+        let committed = order.committed_params();
+
+        let loosened = OrderParams {
+            max_slippage_bps: 500,
+            ..committed.clone()
+        };
+        assert_ne!(committed.commitment(7).unwrap(), loosened.commitment(7).unwrap());
+    }
+
+    #[test]
+    fn test_cancel_within_grace_window() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Triggered;
+        order.triggered_slot = 1_000;
+        order.cancel_grace_slots = 20;
+
+        assert!(order.is_within_cancel_grace(&FixedTime::at_slot(1_000)));
+        assert!(order.is_within_cancel_grace(&FixedTime::at_slot(1_020)));
+
+        order.status = OrderStatus::ReadyToExecute;
+        assert!(order.is_within_cancel_grace(&FixedTime::at_slot(1_010)));
+    }
+
+    #[test]
+    fn test_cancel_rejected_after_grace_window() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Triggered;
+        order.triggered_slot = 1_000;
+        order.cancel_grace_slots = 20;
+
+        assert!(!order.is_within_cancel_grace(&FixedTime::at_slot(1_021)));
+
+        order.cancel_grace_slots = 0;
+        assert!(!order.is_within_cancel_grace(&FixedTime::at_slot(1_000)));
+
+        order.status = OrderStatus::Executed;
+        order.cancel_grace_slots = 20;
+        assert!(!order.is_within_cancel_grace(&FixedTime::at_slot(1_000)));
+    }
+
+    #[test]
+    fn test_expiry_boundary() {
+        let mut order = create_test_order();
+        order.expiry = 1_700_000_600;
+
+        assert!(!order.is_expired(&FixedTime::at(1_700_000_600 - EXPIRY_GRACE_SECONDS)));
+        assert!(!order.is_expired(&FixedTime::at(1_700_000_600)));
+        assert!(!order.is_expired(&FixedTime::at(1_700_000_600 + EXPIRY_GRACE_SECONDS)));
+        assert!(order.is_expired(&FixedTime::at(1_700_000_601 + EXPIRY_GRACE_SECONDS)));
+
+        order.expiry = 0;
+        assert!(!order.is_expired(&FixedTime::at(i64::MAX)));
+    }
+
+    #[test]
+    fn test_drift_orders_expire_with_ghost_order() {
+        let mut order = create_test_order();
+        assert_eq!(order.drift_max_ts(), None);
+
+        order.expiry = 1_700_003_600;
+        assert_eq!(order.drift_max_ts(), Some(1_700_003_600));
+
+        let data = crate::drift_cpi::build_drift_place_order_at_price(
+            order.market_type,
+            order.market_index,
+            order.order_side,
+            order.base_asset_amount,
+            0,
+            order.reduce_only,
+            order.drift_max_ts(),
+        );
+        assert_eq!(data[33], 1);
+        assert_eq!(i64::from_le_bytes(data[34..42].try_into().unwrap()), 1_700_003_600);
+    }
+
+    #[test]
+    fn test_compute_units_capped_at_transaction_limit() {
+        let mut order = create_test_order();
+        assert_eq!(order.drift_compute_units(), DRIFT_EXECUTE_COMPUTE_UNITS);
+        order.compute_units = MAX_COMPUTE_UNITS;
+        assert_eq!(order.drift_compute_units(), MAX_COMPUTE_UNITS);
+
+        assert!(validate_compute_units(0).is_ok());
+        assert!(validate_compute_units(MAX_COMPUTE_UNITS).is_ok());
+        assert_eq!(
+            validate_compute_units(MAX_COMPUTE_UNITS + 1).unwrap_err(),
+            CreateOrderError::ComputeUnitsTooHigh.into()
+        );
+    }
+
+    #[test]
+    fn test_ready_expiry_boundary() {
+        let mut order = create_test_order();
+        order.ready_expires_at = 5_100;
+
+        assert!(!order.is_ready_expired(&FixedTime::at_slot(5_100)));
+        assert!(order.is_ready_expired(&FixedTime::at_slot(5_101)));
+    }
+
+    #[test]
+    fn test_trigger_matches_shared_table() {
+        let mut order = create_test_order();
+        for &(trigger_condition, trigger_price, current_price, fires) in crate::trigger::TRIGGER_CASES {
+            order.trigger_condition = trigger_condition;
+            order.trigger_price = trigger_price;
+            assert_eq!(order.check_trigger(current_price), fires);
+        }
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_rally_then_fires() {
+        let mut order = create_test_order();
+        order.order_side = OrderSide::Long;
+        order.trigger_condition = TriggerCondition::Below;
+        order.is_trailing = true;
+        order.trail_offset = 5_000000;
+
+        // Rally: trigger ratchets up and never fires
+        for price in [100_000000, 104_000000, 110_000000, 108_000000] {
+            order.update_trailing_trigger(price);
+            assert!(!order.check_trigger(price), "should not fire at {}", price);
+        }
+        assert_eq!(order.extreme_price, 110_000000);
+        assert_eq!(order.trigger_price, 105_000000);
+
+        // Pullback: trigger stays put until price falls through it
+        order.update_trailing_trigger(106_000000);
+        assert!(!order.check_trigger(106_000000));
+        assert_eq!(order.trigger_price, 105_000000);
+
+        order.update_trailing_trigger(105_000000);
+        assert!(order.check_trigger(105_000000));
+    }
+
+    #[test]
+    fn test_short_trailing_stop_follows_dip_then_fires() {
+        let mut order = create_test_order();
+        order.order_side = OrderSide::Short;
+        order.trigger_condition = TriggerCondition::Above;
+        order.is_trailing = true;
+        order.trail_offset = 5_000000;
+
+        // Dip: trigger ratchets down and never fires
+        for price in [100_000000, 96_000000, 90_000000, 92_000000] {
+            order.update_trailing_trigger(price);
+            assert!(!order.check_trigger(price), "should not fire at {}", price);
+        }
+        assert_eq!(order.extreme_price, 90_000000);
+        assert_eq!(order.trigger_price, 95_000000);
+
+        // Rally: trigger stays put until price rises through it
+        order.update_trailing_trigger(94_000000);
+        assert!(!order.check_trigger(94_000000));
+        assert_eq!(order.trigger_price, 95_000000);
+
+        order.update_trailing_trigger(95_000000);
+        assert!(order.check_trigger(95_000000));
+    }
+
+    #[test]
+    fn test_short_trailing_stop_fires_through_apply_trigger_check() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        order.order_side = OrderSide::Short;
+        order.trigger_condition = TriggerCondition::Above;
+        order.is_trailing = true;
+        order.trail_offset = 5_000000;
+        let now = FixedTime::at(1_700_000_100);
+
+        for price in [100_000000, 90_000000, 94_000000] {
+            assert!(!order.apply_trigger_check(price, &now));
+        }
+        assert!(order.apply_trigger_check(96_000000, &now));
+        assert_eq!(order.status, OrderStatus::Triggered);
+        assert_eq!(order.execution_price, 96_000000);
+    }
+
+    #[test]
+    fn test_min_order_size() {
+        const MIN_BASE_ASSET_AMOUNT: u64 = 1_000_000;
+
+        assert!(validate_base_asset_amount(MIN_BASE_ASSET_AMOUNT, MIN_BASE_ASSET_AMOUNT).is_ok());
+        assert_eq!(
+            validate_base_asset_amount(MIN_BASE_ASSET_AMOUNT - 1, MIN_BASE_ASSET_AMOUNT).unwrap_err(),
+            CreateOrderError::OrderBelowMinSize.into()
+        );
+    }
+
+    #[test]
+    fn test_bracket_legs_link_and_gate_on_entry() {
+        let entry_key = Pubkey::new_unique();
+        let tp_key = Pubkey::new_unique();
+        let sl_key = Pubkey::new_unique();
+
+        let mut entry = create_test_order();
+        entry.status = OrderStatus::Active;
+        let mut take_profit = create_test_order();
+        let mut stop_loss = create_test_order();
+        take_profit.status = OrderStatus::Pending;
+        stop_loss.status = OrderStatus::Pending;
+        take_profit.attach_to_entry(entry_key, sl_key);
+        stop_loss.attach_to_entry(entry_key, tp_key);
+
+        assert!(take_profit.is_bracket_leg() && stop_loss.is_bracket_leg());
+        assert_eq!(take_profit.oco_sibling, sl_key);
+        assert_eq!(stop_loss.oco_sibling, tp_key);
+
+        // Legs cannot activate before the entry fills, nor against another order
+        assert_eq!(
+            take_profit.check_activation(None).unwrap_err(),
+            BracketError::ParentMissing.into()
+        );
+        assert_eq!(
+            take_profit.check_activation(Some((entry_key, &entry))).unwrap_err(),
+            BracketError::EntryNotExecuted.into()
+        );
+        entry.status = OrderStatus::Executed;
+        assert_eq!(
+            take_profit.check_activation(Some((Pubkey::new_unique(), &entry))).unwrap_err(),
+            BracketError::ParentMismatch.into()
+        );
+        assert!(take_profit.check_activation(Some((entry_key, &entry))).is_ok());
+
+        // Once live, the legs behave as an OCO pair: the take-profit filling cancels the stop
+        stop_loss.status = OrderStatus::Active;
+        assert!(stop_loss.cancel_for_oco_sibling());
+        assert_eq!(stop_loss.status, OrderStatus::Cancelled);
+
+        // Standalone orders are not gated
+        assert!(create_test_order().check_activation(None).is_ok());
+    }
+
+    #[test]
+    fn test_bracket_price_ordering() {
+        assert!(validate_bracket_prices(OrderSide::Long, 100, 120, 90).is_ok());
+        assert!(validate_bracket_prices(OrderSide::Long, 100, 90, 120).is_err());
+        assert!(validate_bracket_prices(OrderSide::Long, 100, 100, 90).is_err());
+
+        assert!(validate_bracket_prices(OrderSide::Short, 100, 80, 110).is_ok());
+        assert!(validate_bracket_prices(OrderSide::Short, 100, 120, 90).is_err());
+
+        assert_eq!(
+            bracket_exit_conditions(OrderSide::Long),
+            (TriggerCondition::Above, TriggerCondition::Below)
+        );
+        assert_eq!(
+            bracket_exit_conditions(OrderSide::Short),
+            (TriggerCondition::Below, TriggerCondition::Above)
+        );
+    }
+
+    #[test]
+    fn test_oco_execute_cancels_sibling() {
+        let take_profit_key = Pubkey::new_unique();
+        let stop_loss_key = Pubkey::new_unique();
+
+        let mut take_profit = create_test_order();
+        let mut stop_loss = create_test_order();
+        take_profit.status = OrderStatus::Active;
+        stop_loss.status = OrderStatus::Active;
+
+        assert!(take_profit.can_link_oco() && stop_loss.can_link_oco());
+        take_profit.oco_sibling = stop_loss_key;
+        stop_loss.oco_sibling = take_profit_key;
+        assert!(!take_profit.can_link_oco());
+
+        // Take-profit fills
+        take_profit.status = OrderStatus::Executed;
+        take_profit.execution_id = 7;
+        take_profit.executed_slot = 1_000;
+        assert!(take_profit.is_duplicate_execution(7, 1_001).unwrap());
+        assert!(stop_loss.cancel_for_oco_sibling());
+
+        // Stop-loss can no longer be executed or replayed
+        assert_eq!(stop_loss.status, OrderStatus::Cancelled);
+        assert!(!stop_loss.is_ready_to_execute());
+        assert!(!stop_loss.is_duplicate_execution(7, 1_001).unwrap());
+        assert!(!stop_loss.cancel_for_oco_sibling());
+    }
+
+    #[test]
+    fn test_oco_sibling_in_final_state_untouched() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Executed;
+
+        assert!(!order.cancel_for_oco_sibling());
+        assert_eq!(order.status, OrderStatus::Executed);
+    }
+
+    fn lamport_account<'a>(
+        key: &'a Pubkey,
+        lamports: &'a mut u64,
+        data: &'a mut [u8],
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, true, lamports, data, &crate::ID, false, 0)
+    }
+
+    #[test]
+    fn test_keeper_paid_from_funded_order() {
+        let mut order = create_test_order();
+        order.keeper_fee_lamports = 5_000;
+
+        let rent_minimum = 1_000_000;
+        let (order_key, keeper_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut order_lamports, mut keeper_lamports) = (rent_minimum + 5_000, 10);
+        let (mut order_data, mut keeper_data) = ([0u8; 0], [0u8; 0]);
+        let order_info = lamport_account(&order_key, &mut order_lamports, &mut order_data);
+        let keeper_info = lamport_account(&keeper_key, &mut keeper_lamports, &mut keeper_data);
+
+        assert!(order.pay_keeper_fee(&order_info, &keeper_info, rent_minimum).unwrap());
+        assert_eq!(keeper_info.lamports(), 5_010);
+        assert_eq!(order_info.lamports(), rent_minimum);
+        assert_eq!(order.keeper_fee_lamports, 0);
+    }
+
+    #[test]
+    fn test_keeper_fee_paid_once_across_partial_fills() {
+        let mut order = create_test_order();
+        order.keeper_fee_lamports = 5_000;
+
+        // Extra lamports on the order must not fund a second payout
+        let rent_minimum = 1_000_000;
+        let (order_key, keeper_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut order_lamports, mut keeper_lamports) = (rent_minimum + 12_000, 10);
+        let (mut order_data, mut keeper_data) = ([0u8; 0], [0u8; 0]);
+        let order_info = lamport_account(&order_key, &mut order_lamports, &mut order_data);
+        let keeper_info = lamport_account(&keeper_key, &mut keeper_lamports, &mut keeper_data);
+
+        assert!(!order.record_fill(400_000));
+        assert!(order.pay_keeper_fee(&order_info, &keeper_info, rent_minimum).unwrap());
+        assert_eq!(keeper_info.lamports(), 5_010);
+
+        assert!(order.record_fill(600_000));
+        assert!(order.pay_keeper_fee(&order_info, &keeper_info, rent_minimum).unwrap());
+        assert_eq!(keeper_info.lamports(), 5_010);
+        assert_eq!(order_info.lamports(), rent_minimum + 7_000);
+        assert_eq!(order.keeper_fee_lamports, 0);
+    }
+
+    #[test]
+    fn test_keeper_fee_unpaid_when_order_underfunded() {
+        let mut order = create_test_order();
+        order.keeper_fee_lamports = 5_000;
+
+        let rent_minimum = 1_000_000;
+        let (order_key, keeper_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut order_lamports, mut keeper_lamports) = (rent_minimum + 4_999, 10);
+        let (mut order_data, mut keeper_data) = ([0u8; 0], [0u8; 0]);
+        let order_info = lamport_account(&order_key, &mut order_lamports, &mut order_data);
+        let keeper_info = lamport_account(&keeper_key, &mut keeper_lamports, &mut keeper_data);
+
+        assert!(!order.pay_keeper_fee(&order_info, &keeper_info, rent_minimum).unwrap());
+        assert_eq!(keeper_info.lamports(), 10);
+        assert_eq!(order_info.lamports(), rent_minimum + 4_999);
+    }
+
+    #[test]
+    fn test_unearned_keeper_fee_refunded_to_owner() {
+        let mut order = create_test_order();
+        order.keeper_fee_lamports = 5_000;
+
+        let rent_minimum = 1_000_000;
+        let (order_key, owner_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut order_lamports, mut owner_lamports) = (rent_minimum + 5_000, 10);
+        let (mut order_data, mut owner_data) = ([0u8; 0], [0u8; 0]);
+        let order_info = lamport_account(&order_key, &mut order_lamports, &mut order_data);
+        let owner_info = lamport_account(&owner_key, &mut owner_lamports, &mut owner_data);
+
+        assert_eq!(order.refund_keeper_fee(&order_info, &owner_info, rent_minimum).unwrap(), 5_000);
+        assert_eq!(owner_info.lamports(), 5_010);
+        assert_eq!(order_info.lamports(), rent_minimum);
+        assert_eq!(order.keeper_fee_lamports, 0);
+    }
+
+    #[test]
+    fn test_public_order_accepts_any_keeper() {
+        let order = create_test_order();
+
+        assert!(!order.is_keeper_restricted());
+        assert!(order.is_allowed_keeper(&Pubkey::new_unique()));
+        assert!(order.is_allowed_keeper(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_restricted_order_accepts_only_allowlisted_keepers() {
+        let mut order = create_test_order();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        order.keeper_allowlist[0] = first;
+        order.keeper_allowlist[2] = second;
+
+        assert!(order.is_keeper_restricted());
+        assert!(order.is_allowed_keeper(&first));
+        assert!(order.is_allowed_keeper(&second));
+        assert!(!order.is_allowed_keeper(&Pubkey::new_unique()));
+        // Empty slots do not admit the default key
+        assert!(!order.is_allowed_keeper(&Pubkey::default()));
+    }
+
+    #[test]
+    fn test_drift_order_cancel_scoped_to_owner_and_order_keepers() {
+        let mut order = create_test_order();
+        let keeper = Pubkey::new_unique();
+
+        assert!(order.can_cancel_drift_orders(&order.owner));
+        assert!(!order.can_cancel_drift_orders(&keeper));
+
+        order.keeper_allowlist[0] = keeper;
+        assert!(order.can_cancel_drift_orders(&keeper));
+        assert!(!order.can_cancel_drift_orders(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_single_confirmation_fires_immediately() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+
+        assert!(!order.record_trigger_check(150_000_001));
+        assert!(order.record_trigger_check(149_000_000));
+    }
+
+    #[test]
+    fn test_trigger_evaluated_reports_every_read() {
+        use crate::instructions::check_trigger::TriggerEvaluated;
+
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        let now = FixedTime::at(1_700_000_100);
+
+        let fired = order.apply_trigger_check(151_000_000, &now);
+        let missed = TriggerEvaluated::for_order(&order, 151_000_000, fired);
+        assert!(!missed.fired);
+        assert_eq!(missed.order_id, 1);
+        assert_eq!(missed.current_price, 151_000_000);
+        assert_eq!(missed.trigger_price, 150_000_000);
+        assert_eq!(missed.condition, TriggerCondition::Below);
+        assert_eq!(order.status, OrderStatus::Active);
+
+        let fired = order.apply_trigger_check(149_000_000, &now);
+        let hit = TriggerEvaluated::for_order(&order, 149_000_000, fired);
+        assert!(hit.fired);
+        assert_eq!(hit.current_price, 149_000_000);
+        assert_eq!(hit.trigger_price, 150_000_000);
+        assert_eq!(order.status, OrderStatus::Triggered);
+    }
+
+    #[test]
+    fn test_oscillating_price_needs_consecutive_confirmations() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        order.required_confirmations = 3;
+
+        // Below-trigger at 150; flaps across the level before settling under it
+        let series = [149_000_000, 149_500_000, 151_000_000, 149_000_000, 148_000_000];
+        for price in series {
+            assert!(!order.record_trigger_check(price));
+        }
+        assert_eq!(order.confirmation_count, 2);
+
+        assert!(order.record_trigger_check(147_000_000));
+        assert_eq!(order.confirmation_count, 3);
+    }
+
+    #[test]
+    fn test_failed_read_resets_confirmations() {
+        let mut order = create_test_order();
+        order.required_confirmations = 2;
+
+        assert!(!order.record_trigger_check(149_000_000));
+        assert_eq!(order.confirmation_count, 1);
+
+        assert!(!order.record_trigger_check(155_000_000));
+        assert_eq!(order.confirmation_count, 0);
+    }
+
+    #[test]
+    fn test_modify_recommits_under_new_nonce() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        order.nonce = 1;
+        let old_params = order.committed_params();
+        order.params_commitment = old_params.commitment(1).unwrap();
+
+        let new_params = OrderParams {
+            base_asset_amount: 2_000_000,
+            ..old_params.clone()
+        };
+        let new_commitment = new_params.commitment(2).unwrap();
+        order.apply_modification(140_000_000, 2_000_000, 0, 2, new_commitment).unwrap();
+        assert_eq!(order.trigger_price, 140_000_000);
+        assert_eq!(order.nonce, 2);
+        assert_eq!(order.params_commitment, new_commitment);
+        assert_ne!(old_params.commitment(1).unwrap(), order.params_commitment);
+    }
+
+    #[test]
+    fn test_ladder_even_split() {
+        let tranches = ladder_tranches(3_000_000, 3, 500_000, 150_000_000, OrderSide::Long).unwrap();
+
+        assert_eq!(tranches.len(), 3);
+        assert_eq!(
+            tranches,
+            vec![
+                LadderTranche { base_asset_amount: 1_000_000, price: 150_000_000 },
+                LadderTranche { base_asset_amount: 1_000_000, price: 149_500_000 },
+                LadderTranche { base_asset_amount: 1_000_000, price: 149_000_000 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ladder_remainder_on_last_tranche() {
+        let tranches = ladder_tranches(1_000_001, 4, 1_000, 100_000, OrderSide::Long).unwrap();
+
+        assert_eq!(tranches.len(), 4);
+        let amounts: Vec<u64> = tranches.iter().map(|t| t.base_asset_amount).collect();
+        assert_eq!(amounts, vec![250_000, 250_000, 250_000, 250_001]);
+        assert_eq!(amounts.iter().sum::<u64>(), 1_000_001);
+        assert_eq!(tranches[3].price, 97_000);
+    }
+
+    #[test]
+    fn test_ladder_single_step_is_market_order() {
+        for steps in [0, 1] {
+            let tranches = ladder_tranches(5, steps, 1_000, 100_000, OrderSide::Long).unwrap();
+            assert_eq!(tranches, vec![LadderTranche { base_asset_amount: 5, price: 0 }]);
+        }
+    }
+
+    #[test]
+    fn test_ladder_rejects_invalid_params() {
+        // A tranche would be priced at or below zero
+        assert!(ladder_tranches(3, 3, 50_000, 100_000, OrderSide::Long).is_err());
+        // Not enough size for every tranche to be non-zero
+        assert!(ladder_tranches(2, 3, 1, 100_000, OrderSide::Long).is_err());
+        assert!(ladder_tranches(100, MAX_LADDER_STEPS + 1, 1, 100_000, OrderSide::Long).is_err());
+        // The side sets the direction, so spacing is a positive gap
+        assert!(ladder_tranches(2, 2, 0, 100, OrderSide::Long).is_err());
+        assert!(ladder_tranches(2, 2, -10, 100, OrderSide::Short).is_err());
+    }
+
+    #[test]
+    fn test_short_ladder_steps_up() {
+        let tranches = ladder_tranches(3_000_000, 3, 500_000, 150_000_000, OrderSide::Short).unwrap();
+
+        let prices: Vec<u64> = tranches.iter().map(|t| t.price).collect();
+        assert_eq!(prices, vec![150_000_000, 150_500_000, 151_000_000]);
+    }
+
+    #[test]
+    fn test_modify_rejects_finished_orders_and_reused_nonce() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Pending;
+        assert_eq!(
+            order.apply_modification(1, 1, 0, order.nonce, [7u8; 32]).unwrap_err(),
+            ModifyError::NonceReused.into()
+        );
+        assert_eq!(
+            order.apply_modification(1, 1, 0, 99, order.params_commitment).unwrap_err(),
+            ModifyError::CommitmentReused.into()
+        );
+
+        for status in [OrderStatus::Triggered, OrderStatus::Executed, OrderStatus::Cancelled] {
+            order.status = status;
+            assert_eq!(
+                order.apply_modification(1, 1, 0, 99, [7u8; 32]).unwrap_err(),
+                ModifyError::OrderNotModifiable.into()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_drift_accounts_rejects_other_users() {
+        let mut order = create_test_order();
+        order.drift_user = Pubkey::new_unique();
+
+        assert!(order.verify_drift_accounts(&order.drift_user, &order.owner).is_ok());
+        assert_eq!(
+            order
+                .verify_drift_accounts(&Pubkey::new_unique(), &order.owner)
+                .unwrap_err(),
+            GhostCrankError::DriftUserMismatch.into()
+        );
+        assert_eq!(
+            order
+                .verify_drift_accounts(&order.drift_user, &Pubkey::new_unique())
+                .unwrap_err(),
+            GhostCrankError::DriftAuthorityMismatch.into()
+        );
+    }
+
+    #[test]
+    fn test_partial_fill_leaves_remainder_executable() {
+        let mut order = create_test_order();
+        assert!(order.is_executable());
+
+        assert!(!order.record_fill(400_000));
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.remaining_amount(), 600_000);
+        assert!(order.is_executable());
+
+        // The re-execution only places the unfilled remainder
+        let tranches = ladder_tranches(order.remaining_amount(), 0, 0, 0, order.order_side).unwrap();
+        assert_eq!(
+            tranches,
+            vec![LadderTranche { base_asset_amount: 600_000, price: 0 }]
+        );
+
+        assert!(order.record_fill(600_000));
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert_eq!(order.remaining_amount(), 0);
+        assert!(!order.is_executable());
+    }
+
+    #[test]
+    fn test_placed_spot_order_not_filled_or_replaced() {
+        let mut order = create_test_order();
+        order.market_type = DriftMarketType::Spot;
+        order.status = OrderStatus::ReadyToExecute;
+
+        order.record_placement();
+        order.execution_id = 42;
+        order.executed_slot = 1_000;
+
+        assert_eq!(order.status, OrderStatus::Placed);
+        assert_eq!(order.filled_amount, 0);
+        assert!(!order.is_executable());
+        // A keeper retry is acknowledged; a later execution is refused
+        assert!(order.is_duplicate_execution(42, 1_001).unwrap());
+        assert_eq!(
+            order.is_duplicate_execution(43, 1_001).unwrap_err(),
+            ExecuteError::AlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn test_fill_never_exceeds_base_amount() {
+        let mut order = create_test_order();
+
+        assert!(order.record_fill(5_000_000));
+        assert_eq!(order.filled_amount, order.base_asset_amount);
+    }
+
+    #[test]
+    fn test_stuck_ready_order_cancels_to_expired() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::ReadyToExecute;
+        order.triggered_slot = 1_000;
+        order.ready_expires_at = 1_150;
+
+        // A keeper could still execute it
+        assert_eq!(
+            order.cancellation_status(&FixedTime::at_slot(1_150)).unwrap_err(),
+            CancelError::OrderNotCancellable.into()
+        );
+
+        assert_eq!(
+            order.cancellation_status(&FixedTime::at_slot(1_151)).unwrap(),
+            OrderStatus::Expired
+        );
+    }
+
+    #[test]
+    fn test_live_orders_cancel_to_cancelled() {
+        let mut order = create_test_order();
+        let now = FixedTime::at_slot(1_000);
+
+        for status in [OrderStatus::Pending, OrderStatus::Active, OrderStatus::PartiallyFilled] {
+            order.status = status;
+            assert_eq!(order.cancellation_status(&now).unwrap(), OrderStatus::Cancelled);
+        }
+
+        order.status = OrderStatus::Executed;
+        assert!(order.cancellation_status(&now).is_err());
+    }
+
+    #[test]
+    fn test_ready_window_sets_expiry() {
+        let now = FixedTime::at_slot(5_000);
+
+        let mut default_window = create_test_order();
+        default_window.status = OrderStatus::Triggered;
+        default_window.mark_ready(150_000_000, &now);
+        assert_eq!(default_window.status, OrderStatus::ReadyToExecute);
+        assert_eq!(default_window.execution_price, 150_000_000);
+        assert_eq!(default_window.ready_expires_at, 5_000 + DEFAULT_READY_WINDOW_SLOTS as i64);
+
+        let mut fast = create_test_order();
+        fast.ready_window_slots = 20;
+        fast.mark_ready(150_000_000, &now);
+        assert_eq!(fast.ready_expires_at, 5_020);
+        assert!(!fast.is_ready_expired(&FixedTime::at_slot(5_020)));
+        assert!(fast.is_ready_expired(&FixedTime::at_slot(5_021)));
+
+        let mut slow = create_test_order();
+        slow.ready_window_slots = 1_000;
+        slow.mark_ready(150_000_000, &now);
+        assert_eq!(slow.ready_expires_at, 6_000);
+    }
+
+    #[test]
+    fn test_ready_window_bounds() {
+        assert!(validate_ready_window(0).is_ok());
+        assert!(validate_ready_window(MIN_READY_WINDOW_SLOTS).is_ok());
+        assert!(validate_ready_window(MAX_READY_WINDOW_SLOTS).is_ok());
+        assert_eq!(
+            validate_ready_window(MIN_READY_WINDOW_SLOTS - 1).unwrap_err(),
+            CreateOrderError::InvalidReadyWindow.into()
+        );
+        assert!(validate_ready_window(MAX_READY_WINDOW_SLOTS + 1).is_err());
+    }
+
+    #[test]
+    fn test_recurring_order_rearms_until_last_run() {
+        let now = FixedTime::at_slot(1_000);
+        let mut order = create_test_order();
+        order.recurring = true;
+        order.remaining_runs = 2;
+        order.params_commitment = order.committed_params().commitment(order.nonce).unwrap();
+        let first_nonce = order.nonce;
+        let first_commitment = order.params_commitment;
+
+        // First run fills and waits for the owner's next commitment
+        assert!(order.record_fill(order.remaining_amount()));
+        assert!(order.finish_run());
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert_eq!(order.remaining_runs, 1);
+        assert!(order.awaits_next_run());
+        assert!(!order.is_active());
+
+        // The owner re-arms it under a fresh nonce and commitment
+        let next_commitment = order.committed_params().commitment(first_nonce + 7).unwrap();
+        order
+            .apply_modification(order.trigger_price, order.base_asset_amount, 0, first_nonce + 7, next_commitment)
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Active);
+        assert_eq!(order.filled_amount, 0);
+        assert_eq!(order.params_commitment, next_commitment);
+        // The first run's reveal no longer verifies
+        assert_ne!(first_commitment, order.params_commitment);
+
+        // Second run is the last: the order stays executed for good
+        order.mark_ready(150_000_000, &now);
+        assert!(order.record_fill(order.remaining_amount()));
+        assert!(!order.finish_run());
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert_eq!(order.remaining_runs, 0);
+        assert!(!order.is_modifiable());
+    }
+
+    #[test]
+    fn test_one_shot_order_does_not_rearm() {
+        let mut order = create_test_order();
+        order.record_fill(order.remaining_amount());
+
+        assert!(!order.finish_run());
+        assert_eq!(order.status, OrderStatus::Executed);
+        assert!(!order.is_modifiable());
+    }
+
+    #[test]
+    fn test_intent_round_trips_and_is_announced() {
+        use crate::instructions::create_ghost_order::GhostOrderCreated;
+
+        let mut order = create_test_order();
+        order.intent = OrderIntent::StopLoss;
+
+        let mut buf = Vec::new();
+        order.try_serialize(&mut buf).unwrap();
+        assert_eq!(buf.len(), GhostOrder::LEN);
+        let decoded = GhostOrder::try_deserialize(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded.intent, OrderIntent::StopLoss);
+
+        let event = GhostOrderCreated::for_order(&decoded);
+        assert_eq!(event.order_id, order.order_id);
+        assert_eq!(event.intent, OrderIntent::StopLoss);
+        let event_bytes = event.try_to_vec().unwrap();
+        assert_eq!(*event_bytes.last().unwrap(), OrderIntent::StopLoss as u8);
+    }
+
+    #[test]
+    fn test_price_spike_is_ignored() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        order.trigger_price = 140_000_000;
+        order.trigger_condition = TriggerCondition::Below;
+        let now = FixedTime::at_slot(1_000);
+
+        // Normal tick: accepted, above the trigger
+        assert!(order.accept_observed_price(150_000_000));
+        assert!(!order.apply_trigger_check(150_000_000, &now));
+        assert_eq!(order.last_observed_price, 150_000_000);
+
+        // A 50% drop would fire the trigger but is dropped as bad data
+        assert!(!order.accept_observed_price(75_000_000));
+        assert_eq!(order.last_observed_price, 150_000_000);
+        assert!(order.is_active());
+
+        // A move inside the bound is accepted and fires
+        assert!(order.accept_observed_price(125_000_000));
+        assert!(order.apply_trigger_check(125_000_000, &now));
+    }
+
+    #[test]
+    fn test_one_price_read_fires_only_matching_orders() {
+        let now = FixedTime { unix_timestamp: 1_700_000_100, slot: 500 };
+        let price = 149_000_000;
+
+        let mut stop = create_test_order();
+        stop.status = OrderStatus::Active;
+
+        let mut take_profit = create_test_order();
+        take_profit.status = OrderStatus::Active;
+        take_profit.trigger_condition = TriggerCondition::Above;
+        take_profit.trigger_price = 160_000_000;
+
+        let mut confirming = create_test_order();
+        confirming.status = OrderStatus::Active;
+        confirming.required_confirmations = 2;
+
+        let mut trailing = create_test_order();
+        trailing.status = OrderStatus::Active;
+        trailing.order_side = OrderSide::Long;
+        trailing.is_trailing = true;
+        trailing.trail_offset = 5_000_000;
+        trailing.extreme_price = 155_000_000;
+
+        let mut orders = [stop, take_profit, confirming, trailing];
+        let fired: Vec<bool> = orders
+            .iter_mut()
+            .map(|order| order.apply_trigger_check(price, &now))
+            .collect();
+
+        assert_eq!(fired, vec![true, false, false, true]);
+        assert_eq!(orders[0].status, OrderStatus::Triggered);
+        assert_eq!(orders[0].execution_price, price);
+        assert_eq!(orders[0].triggered_slot, 500);
+        assert_eq!(orders[1].status, OrderStatus::Active);
+        assert_eq!(orders[1].execution_price, 0);
+        assert_eq!(orders[2].status, OrderStatus::Active);
+        assert_eq!(orders[2].confirmation_count, 1);
+        assert_eq!(orders[3].trigger_price, 150_000_000);
+        assert_eq!(orders[3].status, OrderStatus::Triggered);
+    }
+
+    #[test]
+    fn test_preview_trigger_matches_check_without_mutating() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        let now = FixedTime::at(1_700_000_100);
+
+        let preview = order.preview_trigger(149_000_000, &now);
+        assert!(preview.would_trigger);
+        assert!(!preview.would_expire);
+        assert_eq!(order.confirmation_count, 0);
+
+        let preview = order.preview_trigger(151_000_000, &now);
+        assert!(!preview.would_trigger);
+
+        // A pending confirmation streak means one read is not enough
+        order.required_confirmations = 2;
+        assert!(!order.preview_trigger(149_000_000, &now).would_trigger);
+        order.confirmation_count = 1;
+        assert!(order.preview_trigger(149_000_000, &now).would_trigger);
+    }
+
+    #[test]
+    fn test_preview_trigger_reports_expiry() {
+        let mut order = create_test_order();
+        order.status = OrderStatus::Active;
+        order.expiry = 1_700_000_050;
+
+        let preview = order.preview_trigger(149_000_000, &FixedTime::at(1_700_000_100));
+        assert!(preview.would_expire);
+        assert!(!preview.would_trigger);
+        assert_eq!(order.status, OrderStatus::Active);
+    }
+
+    #[test]
+    fn test_cancelling_monitoring_resets_task_id() {
+        let mut order = create_test_order();
+        assert!(!order.is_monitored());
+
+        // schedule_monitoring
+        order.crank_task_id = 77;
+        assert!(order.is_monitored());
+
+        // cancel_monitoring / cancel_order
+        order.clear_monitoring();
+        assert_eq!(order.crank_task_id, 0);
+        assert!(!order.is_monitored());
+    }
+
+    #[test]
+    fn test_legacy_order_detected_and_upgraded() {
+        let legacy = GhostOrderV1 {
+            owner: Pubkey::new_unique(),
+            order_id: 7,
+            market_index: 2,
+            trigger_price: 123_000_000,
+            trigger_condition: TriggerCondition::Above,
+            order_side: OrderSide::Long,
+            base_asset_amount: 5_000_000,
+            reduce_only: false,
+            status: OrderStatus::Cancelled,
+            created_at: 1_700_000_000,
+            triggered_at: 0,
+            executed_at: 0,
+            expiry: 1_700_086_400,
+            feed_id: [4u8; 32],
+            crank_task_id: 11,
+            execution_price: 0,
+            bump: 253,
+            params_commitment: [6u8; 32],
+            nonce: 99,
+            ready_expires_at: 0,
+            delegate_pda: Pubkey::new_unique(),
+            delegate_bump: 252,
+            drift_user: Pubkey::new_unique(),
+        };
+        let (address, _) = Pubkey::find_program_address(
+            &[GhostOrder::SEED_PREFIX, legacy.owner.as_ref(), &legacy.order_id.to_le_bytes()],
+            &crate::ID,
+        );
+
+        // Serialized with the version 1 layout
+        let mut data = GhostOrder::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), GhostOrderV1::LEN);
+        assert_eq!(GhostOrder::stored_version(&address, &data), Some(LEGACY_ACCOUNT_VERSION));
+
+        let mut upgraded = vec![0u8; GhostOrder::LEN];
+        GhostOrderV1::from_account_data(&data)
+            .unwrap()
+            .upgrade()
+            .try_serialize(&mut upgraded.as_mut_slice())
+            .unwrap();
+        assert_eq!(GhostOrder::stored_version(&address, &upgraded), Some(ACCOUNT_VERSION));
+        assert_eq!(GhostOrder::stored_version(&Pubkey::new_unique(), &upgraded), None);
+
+        let order = GhostOrder::try_deserialize(&mut upgraded.as_slice()).unwrap();
+        assert!(order.is_current_version());
+        assert_eq!(order.owner, legacy.owner);
+        assert_eq!(order.order_id, 7);
+        assert_eq!(order.market_index, 2);
+        assert_eq!(order.trigger_price, 123_000_000);
+        assert_eq!(order.base_asset_amount, 5_000_000);
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        assert_eq!(order.expiry, 1_700_086_400);
+        assert_eq!(order.feed_id, [4u8; 32]);
+        assert_eq!(order.params_commitment, [6u8; 32]);
+        assert_eq!(order.nonce, 99);
+        assert_eq!(order.delegate_pda, legacy.delegate_pda);
+        assert_eq!(order.drift_user, legacy.drift_user);
+        assert_eq!(order.market_type, DriftMarketType::Perp);
+        assert_eq!(order.price_exponent, PRICE_TARGET_EXPONENT);
+        assert_eq!(order.cancel_reason, CancelReason::UserRequested);
+        assert!(!order.is_oco_linked());
+    }
 }
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+
+use crate::drift_cpi::DriftMarketType;
+use crate::instructions::market_registry::MarketRegistryError;
+use crate::order_size::{min_base_asset_amount, MarketMinOrderSize, DEFAULT_MIN_BASE_ASSET_AMOUNT};
+
+/// Most markets the registry can map; sized up front so it never reallocs.
+pub const MAX_REGISTERED_MARKETS: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MarketFeed {
+    pub market_type: DriftMarketType,
+    pub market_index: u16,
+    pub feed_id: [u8; 32],
+}
+
+impl MarketFeed {
+    pub const LEN: usize = 1 + 2 + 32;
+}
+
+/// Admin-maintained map from Drift market to the oracle feed its orders
+/// must watch, so an order cannot be triggered off another market's price,
+/// and to the smallest order size Drift accepts there.
+///
+/// ghost-bridge reads this account directly; keep the layout stable.
+#[account]
+pub struct MarketRegistry {
+    pub bump: u8,
+    pub markets: Vec<MarketFeed>,
+    pub min_order_sizes: Vec<MarketMinOrderSize>,
+}
+
+impl MarketRegistry {
+    pub const SEED_PREFIX: &'static [u8] = b"market_registry";
+
+    pub const LEN: usize = 8 +                               // discriminator
+        1 +                                                  // bump
+        4 + MAX_REGISTERED_MARKETS * MarketFeed::LEN +       // markets
+        4 + MAX_REGISTERED_MARKETS * MarketMinOrderSize::LEN; // min_order_sizes
+
+    pub fn pda() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX], &crate::ID)
+    }
+
+    pub fn feed_for(&self, market_type: DriftMarketType, market_index: u16) -> Option<[u8; 32]> {
+        self.markets
+            .iter()
+            .find(|m| m.market_type == market_type && m.market_index == market_index)
+            .map(|m| m.feed_id)
+    }
+
+    /// Adds the market or replaces its feed.
+    pub fn set_feed(
+        &mut self,
+        market_type: DriftMarketType,
+        market_index: u16,
+        feed_id: [u8; 32],
+    ) -> Result<()> {
+        if let Some(entry) = self
+            .markets
+            .iter_mut()
+            .find(|m| m.market_type == market_type && m.market_index == market_index)
+        {
+            entry.feed_id = feed_id;
+            return Ok(());
+        }
+
+        require!(
+            self.markets.len() < MAX_REGISTERED_MARKETS,
+            MarketRegistryError::RegistryFull
+        );
+        self.markets.push(MarketFeed { market_type, market_index, feed_id });
+        Ok(())
+    }
+
+    /// Rejects an order whose `feed_id` is not the one registered for its market.
+    pub fn verify_feed(
+        &self,
+        market_type: DriftMarketType,
+        market_index: u16,
+        feed_id: &[u8; 32],
+    ) -> Result<()> {
+        let expected = self
+            .feed_for(market_type, market_index)
+            .ok_or(MarketRegistryError::MarketNotRegistered)?;
+        require!(expected == *feed_id, MarketRegistryError::FeedMarketMismatch);
+        Ok(())
+    }
+
+    pub fn min_base_asset_amount(&self, market_type: DriftMarketType, market_index: u16) -> u64 {
+        min_base_asset_amount(&self.min_order_sizes, market_type, market_index)
+    }
+
+    /// Sets the market's minimum order size, replacing any earlier one.
+    pub fn set_min_order_size(
+        &mut self,
+        market_type: DriftMarketType,
+        market_index: u16,
+        min_base_asset_amount: u64,
+    ) -> Result<()> {
+        if let Some(entry) = self
+            .min_order_sizes
+            .iter_mut()
+            .find(|s| s.market_type == market_type && s.market_index == market_index)
+        {
+            entry.min_base_asset_amount = min_base_asset_amount;
+            return Ok(());
+        }
+
+        require!(
+            self.min_order_sizes.len() < MAX_REGISTERED_MARKETS,
+            MarketRegistryError::RegistryFull
+        );
+        self.min_order_sizes.push(MarketMinOrderSize {
+            market_type,
+            market_index,
+            min_base_asset_amount,
+        });
+        Ok(())
+    }
+}
+
+/// Minimum order size for the market; orders created without the registry
+/// get the default.
+pub fn min_order_size(
+    registry: Option<&MarketRegistry>,
+    market_type: DriftMarketType,
+    market_index: u16,
+) -> u64 {
+    registry.map_or(DEFAULT_MIN_BASE_ASSET_AMOUNT, |registry| {
+        registry.min_base_asset_amount(market_type, market_index)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL_FEED: [u8; 32] = [1u8; 32];
+    const BTC_FEED: [u8; 32] = [2u8; 32];
+
+    fn registry() -> MarketRegistry {
+        MarketRegistry { bump: 255, markets: Vec::new(), min_order_sizes: Vec::new() }
+    }
+
+    #[test]
+    fn test_registered_feed_accepted() {
+        let mut registry = registry();
+        registry.set_feed(DriftMarketType::Perp, 0, SOL_FEED).unwrap();
+        registry.set_feed(DriftMarketType::Perp, 1, BTC_FEED).unwrap();
+
+        assert!(registry.verify_feed(DriftMarketType::Perp, 0, &SOL_FEED).is_ok());
+        assert!(registry.verify_feed(DriftMarketType::Perp, 1, &BTC_FEED).is_ok());
+    }
+
+    #[test]
+    fn test_mismatched_feed_rejected() {
+        let mut registry = registry();
+        registry.set_feed(DriftMarketType::Perp, 0, SOL_FEED).unwrap();
+
+        // A SOL-PERP order pointed at the BTC feed
+        assert_eq!(
+            registry.verify_feed(DriftMarketType::Perp, 0, &BTC_FEED).unwrap_err(),
+            MarketRegistryError::FeedMarketMismatch.into()
+        );
+        // Spot market 0 is a different market from perp market 0
+        assert_eq!(
+            registry.verify_feed(DriftMarketType::Spot, 0, &SOL_FEED).unwrap_err(),
+            MarketRegistryError::MarketNotRegistered.into()
+        );
+    }
+
+    #[test]
+    fn test_set_feed_replaces_and_fills() {
+        let mut registry = registry();
+        registry.set_feed(DriftMarketType::Perp, 0, BTC_FEED).unwrap();
+        registry.set_feed(DriftMarketType::Perp, 0, SOL_FEED).unwrap();
+        assert_eq!(registry.markets.len(), 1);
+        assert_eq!(registry.feed_for(DriftMarketType::Perp, 0), Some(SOL_FEED));
+
+        for index in 1..MAX_REGISTERED_MARKETS as u16 {
+            registry.set_feed(DriftMarketType::Perp, index, SOL_FEED).unwrap();
+        }
+        assert_eq!(
+            registry.set_feed(DriftMarketType::Spot, 0, SOL_FEED).unwrap_err(),
+            MarketRegistryError::RegistryFull.into()
+        );
+    }
+
+    #[test]
+    fn test_full_registry_fits_account() {
+        let registry = MarketRegistry {
+            bump: 255,
+            markets: vec![
+                MarketFeed { market_type: DriftMarketType::Perp, market_index: 0, feed_id: SOL_FEED };
+                MAX_REGISTERED_MARKETS
+            ],
+            min_order_sizes: vec![
+                MarketMinOrderSize {
+                    market_type: DriftMarketType::Perp,
+                    market_index: 0,
+                    min_base_asset_amount: 1,
+                };
+                MAX_REGISTERED_MARKETS
+            ],
+        };
+
+        assert_eq!(registry.try_to_vec().unwrap().len() + 8, MarketRegistry::LEN);
+    }
+
+    #[test]
+    fn test_min_order_size_per_market() {
+        let mut registry = registry();
+        registry.set_min_order_size(DriftMarketType::Perp, 1, 5_000_000).unwrap();
+        registry.set_min_order_size(DriftMarketType::Perp, 1, 1_000_000).unwrap();
+        assert_eq!(registry.min_order_sizes.len(), 1);
+
+        assert_eq!(min_order_size(Some(&registry), DriftMarketType::Perp, 1), 1_000_000);
+        assert_eq!(
+            min_order_size(Some(&registry), DriftMarketType::Perp, 0),
+            DEFAULT_MIN_BASE_ASSET_AMOUNT
+        );
+        assert_eq!(min_order_size(None, DriftMarketType::Perp, 1), DEFAULT_MIN_BASE_ASSET_AMOUNT);
+    }
+
+    #[test]
+    fn test_discriminator_matches_bridge_mirror() {
+        // ghost-bridge's MARKET_REGISTRY_DISCRIMINATOR
+        assert_eq!(MarketRegistry::DISCRIMINATOR, &[200, 118, 217, 126, 179, 169, 172, 104]);
+    }
+}
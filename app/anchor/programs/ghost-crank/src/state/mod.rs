@@ -1,3 +1,9 @@
 pub mod ghost_order;
+pub mod crank_config;
+pub mod order_counter;
+pub mod market_registry;
 
 pub use ghost_order::*;
+pub use crank_config::*;
+pub use order_counter::*;
+pub use market_registry::*;
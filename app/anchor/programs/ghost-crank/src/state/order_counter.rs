@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::order_counter::OrderCounterError;
+
+/// Per-owner sequence that hands out ghost order ids, so an id (and with it
+/// the order PDA) is never reused.
+#[account]
+pub struct OrderCounter {
+    pub owner: Pubkey,
+    pub next_order_id: u64,
+    pub bump: u8,
+}
+
+impl OrderCounter {
+    pub const SEED_PREFIX: &'static [u8] = b"order_counter";
+
+    pub const LEN: usize = 8 +  // discriminator
+        32 +                    // owner
+        8 +                     // next_order_id
+        1;                      // bump
+
+    pub fn pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, owner.as_ref()], &crate::ID)
+    }
+
+    /// Returns the id for the order being created and advances the sequence.
+    pub fn take_next_id(&mut self) -> Result<u64> {
+        let order_id = self.next_order_id;
+        self.next_order_id = order_id
+            .checked_add(1)
+            .ok_or(OrderCounterError::CounterOverflow)?;
+        Ok(order_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_counter() -> OrderCounter {
+        OrderCounter {
+            owner: Pubkey::new_unique(),
+            next_order_id: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_sequential_orders_get_incrementing_ids() {
+        let mut counter = create_test_counter();
+
+        assert_eq!(counter.take_next_id().unwrap(), 0);
+        assert_eq!(counter.take_next_id().unwrap(), 1);
+        assert_eq!(counter.next_order_id, 2);
+    }
+
+    #[test]
+    fn test_ids_are_never_reissued() {
+        let mut counter = create_test_counter();
+        counter.next_order_id = u64::MAX - 1;
+
+        assert_eq!(counter.take_next_id().unwrap(), u64::MAX - 1);
+        assert_eq!(
+            counter.take_next_id().unwrap_err(),
+            OrderCounterError::CounterOverflow.into()
+        );
+        assert_eq!(counter.next_order_id, u64::MAX);
+    }
+}
@@ -0,0 +1,158 @@
+//! The cancel reason each cancelling instruction emits.
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::cancel_order::GhostOrderCancelled;
+    use crate::state::ghost_order::tests::create_test_order;
+    use crate::state::{CancelReason, CrankConfig, GhostOrder, MarketRegistry, OrderStatus};
+    use crate::tests::svm::{
+        decode_events, fetch, ghost_order_pda, send, set_ghost_order, set_program_account, setup,
+        sighash,
+    };
+    use crate::ID as PROGRAM_ID;
+    use anchor_lang::prelude::Clock;
+    use litesvm::LiteSVM;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    };
+
+    fn owned_order(owner: &Keypair, order_id: u64, status: OrderStatus) -> GhostOrder {
+        GhostOrder {
+            owner: owner.pubkey(),
+            order_id,
+            status,
+            ..create_test_order()
+        }
+    }
+
+    fn cancel_ix(owner: &Pubkey, order: &Pubkey, sibling: Option<&Pubkey>) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(*order, false),
+                // Anchor reads the program id as an absent optional account
+                match sibling {
+                    Some(sibling) => AccountMeta::new(*sibling, false),
+                    None => AccountMeta::new_readonly(PROGRAM_ID, false),
+                },
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: sighash("cancel_order").to_vec(),
+        }
+    }
+
+    fn cancel_events(svm: &mut LiteSVM, owner: &Keypair, ix: Instruction) -> Vec<GhostOrderCancelled> {
+        let logs = send(svm, owner, ix).expect("Cancel should succeed");
+        decode_events(&logs)
+    }
+
+    #[test]
+    fn test_owner_cancel_is_user_requested() {
+        let (mut svm, owner) = setup();
+        let order = set_ghost_order(&mut svm, &mut owned_order(&owner, 1, OrderStatus::Active));
+
+        let events = cancel_events(&mut svm, &owner, cancel_ix(&owner.pubkey(), &order, None));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, OrderStatus::Cancelled);
+        assert_eq!(events[0].cancel_reason, CancelReason::UserRequested);
+    }
+
+    #[test]
+    fn test_owner_cancel_refunds_keeper_fee() {
+        let (mut svm, owner) = setup();
+        let mut active = owned_order(&owner, 1, OrderStatus::Active);
+        active.keeper_fee_lamports = 5_000;
+        let order = set_ghost_order(&mut svm, &mut active);
+
+        let mut account = svm.get_account(&order).unwrap();
+        let rent_minimum = account.lamports;
+        account.lamports += 5_000;
+        svm.set_account(order, account).unwrap();
+
+        cancel_events(&mut svm, &owner, cancel_ix(&owner.pubkey(), &order, None));
+
+        let cancelled: GhostOrder = fetch(&svm, &order);
+        assert_eq!(cancelled.status, OrderStatus::Cancelled);
+        assert_eq!(cancelled.keeper_fee_lamports, 0);
+        assert_eq!(svm.get_account(&order).unwrap().lamports, rent_minimum);
+    }
+
+    #[test]
+    fn test_owner_cancel_of_lapsed_ready_order_is_expired() {
+        let (mut svm, owner) = setup();
+        let mut ready = owned_order(&owner, 1, OrderStatus::ReadyToExecute);
+        ready.ready_expires_at = 50;
+        let order = set_ghost_order(&mut svm, &mut ready);
+        svm.warp_to_slot(100);
+
+        let events = cancel_events(&mut svm, &owner, cancel_ix(&owner.pubkey(), &order, None));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, OrderStatus::Expired);
+        assert_eq!(events[0].cancel_reason, CancelReason::Expired);
+    }
+
+    #[test]
+    fn test_oco_sibling_cancel_is_sibling_filled() {
+        let (mut svm, owner) = setup();
+        let (address_a, _) = ghost_order_pda(&owner.pubkey(), 1);
+        let (address_b, _) = ghost_order_pda(&owner.pubkey(), 2);
+
+        let mut order_a = owned_order(&owner, 1, OrderStatus::Active);
+        order_a.oco_sibling = address_b;
+        let mut order_b = owned_order(&owner, 2, OrderStatus::Active);
+        order_b.oco_sibling = address_a;
+        set_ghost_order(&mut svm, &mut order_a);
+        set_ghost_order(&mut svm, &mut order_b);
+
+        let events = cancel_events(
+            &mut svm,
+            &owner,
+            cancel_ix(&owner.pubkey(), &address_a, Some(&address_b)),
+        );
+        let reasons: Vec<(u64, CancelReason)> = events.iter().map(|e| (e.order_id, e.cancel_reason)).collect();
+        assert_eq!(
+            reasons,
+            vec![(1, CancelReason::UserRequested), (2, CancelReason::SiblingFilled)]
+        );
+    }
+
+    #[test]
+    fn test_crank_expiry_is_expired() {
+        let (mut svm, owner) = setup();
+
+        let (config_address, config_bump) = CrankConfig::pda();
+        let config = CrankConfig { admin: owner.pubkey(), is_paused: false, bump: config_bump };
+        set_program_account(&mut svm, config_address, &config, CrankConfig::LEN);
+
+        let (registry_address, registry_bump) = MarketRegistry::pda();
+        let registry = MarketRegistry { bump: registry_bump, markets: Vec::new(), min_order_sizes: Vec::new() };
+        set_program_account(&mut svm, registry_address, &registry, MarketRegistry::LEN);
+
+        let mut active = owned_order(&owner, 1, OrderStatus::Active);
+        active.expiry = 1_000;
+        let order = set_ghost_order(&mut svm, &mut active);
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp = 2_000;
+        svm.set_sysvar(&clock);
+
+        let check_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(order, false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(config_address, false),
+                AccountMeta::new_readonly(registry_address, false),
+            ],
+            data: sighash("check_trigger").to_vec(),
+        };
+        let events: Vec<GhostOrderCancelled> = decode_events(&send(&mut svm, &owner, check_ix).expect("Check should succeed"));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, OrderStatus::Expired);
+        assert_eq!(events[0].cancel_reason, CancelReason::Expired);
+    }
+}
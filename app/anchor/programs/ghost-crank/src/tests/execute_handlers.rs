@@ -0,0 +1,137 @@
+//! `execute_with_commitment` run end to end against the built program.
+
+#[cfg(test)]
+mod tests {
+    use crate::drift_cpi::DRIFT_PROGRAM_ID;
+    use crate::drift_user::{DELEGATE_OFFSET, DRIFT_USER_DISCRIMINATOR, MIN_DRIFT_USER_LEN};
+    use crate::instructions::execute_with_commitment::ExecuteWithCommitmentArgs;
+    use crate::state::ghost_order::tests::create_test_order;
+    use crate::state::{CancelReason, CrankConfig, GhostOrder, OrderStatus};
+    use crate::tests::svm::{fetch, send, set_ghost_order, set_program_account, setup, sighash};
+    use crate::ID as PROGRAM_ID;
+    use anchor_lang::AnchorSerialize;
+    use litesvm::LiteSVM;
+    use solana_sdk::{
+        account::Account,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    };
+
+    const NONCE: u64 = 7;
+
+    /// A ready reduce-only order whose owner's Drift user has granted the
+    /// delegate PDA but holds no position, so it has nothing to reduce.
+    fn setup_flat_order(keeper_fee_lamports: u64) -> (LiteSVM, Keypair, Pubkey, GhostOrder) {
+        let (mut svm, owner) = setup();
+
+        let (config_address, config_bump) = CrankConfig::pda();
+        let config = CrankConfig { admin: owner.pubkey(), is_paused: false, bump: config_bump };
+        set_program_account(&mut svm, config_address, &config, CrankConfig::LEN);
+
+        let (delegate_pda, delegate_bump) = GhostOrder::derive_delegate_pda(&owner.pubkey(), &PROGRAM_ID);
+        let drift_user = Pubkey::new_unique();
+        let mut data = vec![0u8; MIN_DRIFT_USER_LEN];
+        data[..8].copy_from_slice(&DRIFT_USER_DISCRIMINATOR);
+        data[DELEGATE_OFFSET..DELEGATE_OFFSET + 32].copy_from_slice(delegate_pda.as_ref());
+        let drift_user_account = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: DRIFT_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(drift_user, drift_user_account).unwrap();
+
+        let mut order = GhostOrder {
+            owner: owner.pubkey(),
+            delegate_pda,
+            delegate_bump,
+            drift_user,
+            nonce: NONCE,
+            ready_expires_at: i64::MAX,
+            keeper_fee_lamports,
+            ..create_test_order()
+        };
+        order.params_commitment = order.committed_params().commitment(NONCE).unwrap();
+        let address = set_ghost_order(&mut svm, &mut order);
+
+        // The escrowed fee sits on top of the order's rent
+        let mut account = svm.get_account(&address).unwrap();
+        account.lamports += keeper_fee_lamports;
+        svm.set_account(address, account).unwrap();
+
+        (svm, owner, address, order)
+    }
+
+    fn execute_ix(keeper: &Pubkey, order: &GhostOrder, address: &Pubkey, owner: Option<&Pubkey>) -> Instruction {
+        let mut data = sighash("execute_with_commitment").to_vec();
+        ExecuteWithCommitmentArgs {
+            order_params: order.committed_params(),
+            nonce: NONCE,
+            execution_id: 1,
+        }
+        .serialize(&mut data)
+        .unwrap();
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*keeper, true),
+                AccountMeta::new(*address, false),
+                AccountMeta::new_readonly(order.delegate_pda, false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new(order.drift_user, false),
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(DRIFT_PROGRAM_ID, false),
+                AccountMeta::new_readonly(CrankConfig::pda().0, false),
+                // Anchor reads the program id as an absent optional account
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                match owner {
+                    Some(owner) => AccountMeta::new(*owner, false),
+                    None => AccountMeta::new_readonly(PROGRAM_ID, false),
+                },
+            ],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_nothing_to_reduce_refunds_keeper_fee() {
+        let (mut svm, owner, address, order) = setup_flat_order(5_000);
+        let rent_minimum = svm.minimum_balance_for_rent_exemption(GhostOrder::LEN);
+        let keeper = Keypair::new();
+        svm.airdrop(&keeper.pubkey(), 1_000_000_000).unwrap();
+        let owner_before = svm.get_account(&owner.pubkey()).unwrap().lamports;
+
+        send(&mut svm, &keeper, execute_ix(&keeper.pubkey(), &order, &address, Some(&owner.pubkey())))
+            .expect("Execute should cancel the order");
+
+        let cancelled: GhostOrder = fetch(&svm, &address);
+        assert_eq!(cancelled.status, OrderStatus::Cancelled);
+        assert_eq!(cancelled.cancel_reason, CancelReason::NothingToReduce);
+        assert_eq!(cancelled.keeper_fee_lamports, 0);
+        assert_eq!(svm.get_account(&address).unwrap().lamports, rent_minimum);
+        assert_eq!(svm.get_account(&owner.pubkey()).unwrap().lamports, owner_before + 5_000);
+    }
+
+    #[test]
+    fn test_nothing_to_reduce_with_escrow_requires_owner() {
+        let (mut svm, _owner, address, order) = setup_flat_order(5_000);
+        let keeper = Keypair::new();
+        svm.airdrop(&keeper.pubkey(), 1_000_000_000).unwrap();
+
+        let logs = send(&mut svm, &keeper, execute_ix(&keeper.pubkey(), &order, &address, None)).unwrap_err();
+        assert!(logs.contains("OwnerMissing"), "The escrow must go back to the owner: {logs}");
+
+        let intruder = Pubkey::new_unique();
+        let logs = send(&mut svm, &keeper, execute_ix(&keeper.pubkey(), &order, &address, Some(&intruder)))
+            .unwrap_err();
+        assert!(logs.contains("OwnerMismatch"), "Only the owner may take the escrow: {logs}");
+    }
+}
@@ -0,0 +1,3 @@
+mod cancel_handlers;
+mod execute_handlers;
+mod svm;
@@ -0,0 +1,91 @@
+//! Helpers for running ghost-crank instructions against the built program.
+
+use crate::state::GhostOrder;
+use crate::ID as PROGRAM_ID;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator};
+use base64::Engine;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+pub fn sighash(name: &str) -> [u8; 8] {
+    let preimage = format!("global:{}", name);
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    sighash
+}
+
+/// The program loaded and a funded owner.
+pub fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    let program_bytes = include_bytes!("../../../../target/deploy/ghost_crank.so");
+    let _ = svm.add_program(PROGRAM_ID, program_bytes);
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    (svm, owner)
+}
+
+/// Sends `ix` signed by `signer`, returning the program logs either way.
+pub fn send(svm: &mut LiteSVM, signer: &Keypair, ix: Instruction) -> Result<Vec<String>, String> {
+    svm.expire_blockhash();
+    let blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+    svm.send_transaction(tx)
+        .map(|meta| meta.logs)
+        .map_err(|failed| failed.meta.logs.join("\n"))
+}
+
+/// Decodes every `emit!`ed event of type `E` from a transaction's program logs.
+pub fn decode_events<E: AnchorDeserialize + Discriminator>(logs: &[String]) -> Vec<E> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded).ok())
+        .filter(|data| data.starts_with(E::DISCRIMINATOR))
+        .map(|data| E::deserialize(&mut &data[E::DISCRIMINATOR.len()..]).expect("event should decode"))
+        .collect()
+}
+
+pub fn fetch<T: AccountDeserialize>(svm: &LiteSVM, address: &Pubkey) -> T {
+    let account = svm.get_account(address).expect("account should exist");
+    T::try_deserialize(&mut &account.data[..]).expect("account should decode")
+}
+
+/// Writes `state` as a rent-exempt account of this program at `address`.
+pub fn set_program_account(svm: &mut LiteSVM, address: Pubkey, state: &impl AccountSerialize, len: usize) {
+    let mut data = Vec::with_capacity(len);
+    state.try_serialize(&mut data).unwrap();
+    data.resize(len.max(data.len()), 0);
+
+    let account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+        data,
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(address, account).unwrap();
+}
+
+pub fn ghost_order_pda(owner: &Pubkey, order_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[GhostOrder::SEED_PREFIX, owner.as_ref(), &order_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Stores `order` at its PDA, fixing up its bump, and returns the address.
+pub fn set_ghost_order(svm: &mut LiteSVM, order: &mut GhostOrder) -> Pubkey {
+    let (address, bump) = ghost_order_pda(&order.owner, order.order_id);
+    order.bump = bump;
+    set_program_account(svm, address, order, GhostOrder::LEN);
+    address
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Anchor discriminator of Jupiter's `route` (`sha256("global:route")[..8]`).
+pub const JUPITER_ROUTE_DISCRIMINATOR: [u8; 8] = [229, 23, 203, 151, 122, 227, 173, 42];
+
+/// Anchor discriminator of Jupiter's `shared_accounts_route`
+/// (`sha256("global:shared_accounts_route")[..8]`).
+pub const JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+
+pub const DEFAULT_SESSION_DURATION_SECONDS: u32 = 86400;
+
+pub const SECONDS_PER_WEEK: i64 = 604_800;
+
+/// Delay between requesting and executing an emergency withdraw. Long enough
+/// that a lockout still means something, short enough for a real emergency.
+pub const EMERGENCY_DELAY_SECONDS: i64 = 172_800;
+
+pub const MAX_WITHDRAW_WHITELIST: usize = 4;
+
+pub const MAX_TRUSTED_FEEDS: usize = 4;
+
+/// Upper bound on a single loss-scaled cooldown.
+pub const MAX_COOLDOWN_SECONDS: i64 = 86_400;
+
+/// How long a split pre/post swap may stay open before the owner can clear it.
+pub const SWAP_TIMEOUT_SECONDS: i64 = 300;
+
+/// Each early unlock multiplies future lockouts by this unless the vault sets its own.
+pub const DEFAULT_EARLY_UNLOCK_MULTIPLIER: u8 = 2;
+
+/// Early-unlock penalties stop escalating a lockout past this length.
+pub const MAX_ESCALATED_LOCKOUT_SECONDS: u32 = 2_592_000;
+
+/// Minimum gap between `update_stats` writes, so a delegated profile is not
+/// rewritten (and committed) every slot.
+pub const DEFAULT_MIN_PROFILE_UPDATE_INTERVAL: i64 = 60;
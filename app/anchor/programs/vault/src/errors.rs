@@ -36,4 +36,42 @@ pub enum VaultError {
     SwapAlreadyInProgress,
     #[msg("No swap is currently in progress")]
     NoSwapInProgress,
+    #[msg("Invalid session duration")]
+    InvalidSessionDuration,
+    #[msg("Guardian signature required")]
+    GuardianRequired,
+    #[msg("An emergency withdraw is already pending")]
+    EmergencyWithdrawPending,
+    #[msg("No emergency withdraw is pending")]
+    NoEmergencyWithdrawPending,
+    #[msg("Emergency withdraw delay has not elapsed")]
+    EmergencyDelayNotElapsed,
+    #[msg("Weekly loss limit exceeded")]
+    WeeklyLossLimitExceeded,
+    #[msg("Withdraw whitelist is full")]
+    WithdrawWhitelistFull,
+    #[msg("Withdraw destination is not whitelisted")]
+    WithdrawDestinationNotAllowed,
+    #[msg("Pending swap has not timed out yet")]
+    SwapNotTimedOut,
+    #[msg("Vault is not in an active lockout")]
+    NotLockedOut,
+    #[msg("Profile was updated too recently")]
+    TooFrequent,
+    #[msg("Cooldown exceeds MAX_COOLDOWN_SECONDS")]
+    CooldownTooLong,
+    #[msg("Trusted feed list is full")]
+    TrustedFeedsFull,
+    #[msg("Price feed is not on the vault's trusted list")]
+    UntrustedPriceFeed,
+    #[msg("Deposit would take the vault above its max balance")]
+    DepositCapExceeded,
+    #[msg("Account is not at a layout version this program can migrate")]
+    UnsupportedAccountVersion,
+    #[msg("Early unlock penalty cannot be lowered once an early unlock was taken")]
+    EarlyUnlockPenaltyLowered,
+    #[msg("Jupiter route does not swap the enforced amount_in")]
+    RouteAmountMismatch,
+    #[msg("Vault has settled no swap since the profile last recorded one")]
+    NoUnrecordedSwap,
 }
@@ -14,6 +14,7 @@ pub struct Deposit<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 
@@ -24,6 +25,13 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     require!(amount > 0, VaultError::InvalidAmount);
 
     let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    if vault.should_reset_session(&clock) {
+        vault.reset_daily_counters(&clock);
+    }
+
+    vault.check_deposit_cap(vault.to_account_info().lamports(), amount)?;
 
     transfer(
         CpiContext::new(
@@ -40,6 +48,7 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         .total_deposited
         .checked_add(amount)
         .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.record_session_deposit(amount)?;
 
     Ok(())
 }
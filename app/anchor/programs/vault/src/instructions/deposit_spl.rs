@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::errors::VaultError;
+use crate::state::{TokenBalance, Vault};
+
+#[derive(Accounts)]
+pub struct DepositSpl<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + TokenBalance::INIT_SPACE,
+        seeds = [TokenBalance::SEED_PREFIX, vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub token_balance: Account<'info, TokenBalance>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        token::mint = mint,
+        token::authority = vault,
+        seeds = [TokenBalance::TOKEN_ACCOUNT_SEED_PREFIX, vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let token_balance = &mut ctx.accounts.token_balance;
+    token_balance.vault = ctx.accounts.vault.key();
+    token_balance.mint = ctx.accounts.mint.key();
+    token_balance.bump = ctx.bumps.token_balance;
+    token_balance.total_deposited = token_balance
+        .total_deposited
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+#[derive(Accounts)]
+pub struct RequestEmergencyWithdraw<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteEmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Receives the lamports instead of `owner`; must be on the vault's
+    /// withdraw whitelist when one is set
+    #[account(mut)]
+    pub destination: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEmergencyWithdraw<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn request_handler(ctx: Context<RequestEmergencyWithdraw>, amount: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    require!(amount > 0, VaultError::InvalidAmount);
+    require!(
+        !vault.has_pending_emergency_withdraw(),
+        VaultError::EmergencyWithdrawPending
+    );
+
+    // A reservation must be backed by funds no other request or swap holds
+    let vault_lamports = vault.to_account_info().lamports();
+    let rent = Rent::get()?.minimum_balance(8 + Vault::INIT_SPACE);
+    require!(
+        amount <= vault.withdrawable_lamports(vault_lamports, rent),
+        VaultError::InsufficientFunds
+    );
+
+    vault.emergency_withdraw_amount = amount;
+    vault.emergency_withdraw_requested_at = clock.unix_timestamp;
+    vault.reserve_lamports(amount)?;
+
+    msg!(
+        "Emergency withdraw requested: amount={}, executable_at={}",
+        amount,
+        vault.emergency_withdraw_executable_at()
+    );
+
+    Ok(())
+}
+
+/// Pays out a matured emergency request. The lockout is deliberately not
+/// checked here; the delay is what keeps this from being an instant bypass.
+pub fn execute_handler(ctx: Context<ExecuteEmergencyWithdraw>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    require!(
+        vault.has_pending_emergency_withdraw(),
+        VaultError::NoEmergencyWithdrawPending
+    );
+    require!(
+        vault.is_emergency_withdraw_ready(&clock),
+        VaultError::EmergencyDelayNotElapsed
+    );
+
+    // This request's own reservation is what it pays out from
+    let amount = vault.emergency_withdraw_amount;
+    vault.release_lamports(amount);
+    let vault_lamports = vault.to_account_info().lamports();
+    let rent = Rent::get()?.minimum_balance(8 + Vault::INIT_SPACE);
+    let available = vault.withdrawable_lamports(vault_lamports, rent);
+
+    require!(amount <= available, VaultError::InsufficientFunds);
+
+    // The delay does not relax where the funds may go
+    let destination = match ctx.accounts.destination.as_ref() {
+        Some(destination) => destination.clone(),
+        None => ctx.accounts.owner.to_account_info(),
+    };
+    require!(
+        vault.is_allowed_withdraw_destination(destination.key),
+        VaultError::WithdrawDestinationNotAllowed
+    );
+
+    vault.sub_lamports(amount)?;
+    destination.add_lamports(amount)?;
+
+    vault.total_withdrawn = vault
+        .total_withdrawn
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+    vault.clear_emergency_withdraw();
+
+    Ok(())
+}
+
+pub fn cancel_handler(ctx: Context<CancelEmergencyWithdraw>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    require!(
+        vault.has_pending_emergency_withdraw(),
+        VaultError::NoEmergencyWithdrawPending
+    );
+
+    let amount = vault.emergency_withdraw_amount;
+    vault.release_lamports(amount);
+    vault.clear_emergency_withdraw();
+
+    Ok(())
+}
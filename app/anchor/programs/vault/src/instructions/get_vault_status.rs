@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct GetVaultStatus<'info> {
+    #[account(
+        seeds = [Vault::SEED_PREFIX, vault.owner.as_ref()],
+        bump = vault.bump,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Read-only view of the vault's enforcement state at the current clock,
+/// so clients need not decode the account layout themselves.
+pub fn handler(ctx: Context<GetVaultStatus>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let clock = Clock::get()?;
+    let status = vault.enforcement_status(&clock);
+
+    emit!(VaultStatus {
+        vault: vault.key(),
+        is_locked_now: status.is_locked_now,
+        in_cooldown: status.in_cooldown,
+        trades_remaining: status.trades_remaining,
+        seconds_until_unlock: status.seconds_until_unlock,
+        seconds_until_cooldown_ends: status.seconds_until_cooldown_ends,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct VaultStatus {
+    pub vault: Pubkey,
+    pub is_locked_now: bool,
+    pub in_cooldown: bool,
+    /// `u8::MAX` when the vault sets no daily trade limit
+    pub trades_remaining: u8,
+    pub seconds_until_unlock: i64,
+    pub seconds_until_cooldown_ends: i64,
+    pub timestamp: i64,
+}
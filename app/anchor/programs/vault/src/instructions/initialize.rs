@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
 
+use crate::account_version::ACCOUNT_VERSION;
+use crate::constants::{
+    DEFAULT_EARLY_UNLOCK_MULTIPLIER, DEFAULT_SESSION_DURATION_SECONDS, MAX_TRUSTED_FEEDS,
+    MAX_WITHDRAW_WHITELIST,
+};
 use crate::state::Vault;
 
 #[derive(Accounts)]
@@ -23,6 +28,7 @@ pub fn handler(ctx: Context<Initialize>, lockout_duration: u32) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let clock = Clock::get()?;
 
+    vault.version = ACCOUNT_VERSION;
     vault.owner = ctx.accounts.owner.key();
     vault.bump = ctx.bumps.vault;
     vault.is_locked = false;
@@ -35,6 +41,33 @@ pub fn handler(ctx: Context<Initialize>, lockout_duration: u32) -> Result<()> {
     vault.session_start = clock.unix_timestamp;
     vault.total_deposited = 0;
     vault.total_withdrawn = 0;
+    vault.session_duration_seconds = DEFAULT_SESSION_DURATION_SECONDS;
+    vault.guardian = Pubkey::default();
+    vault.emergency_withdraw_amount = 0;
+    vault.emergency_withdraw_requested_at = 0;
+    vault.max_position_size = 0;
+    vault.weekly_loss_limit = 0;
+    vault.weekly_loss_so_far = 0;
+    vault.week_start = clock.unix_timestamp;
+    vault.current_loss_streak = 0;
+    vault.max_loss_streak = 0;
+    vault.withdraw_whitelist = [Pubkey::default(); MAX_WITHDRAW_WHITELIST];
+    vault.withdraw_whitelist_count = 0;
+    vault.last_loss_amount = 0;
+    vault.cooldown_until = 0;
+    vault.early_unlock_count = 0;
+    vault.early_unlock_multiplier = DEFAULT_EARLY_UNLOCK_MULTIPLIER;
+    vault.count_withdrawals_as_loss = false;
+    vault.session_deposited = 0;
+    vault.session_withdrawn = 0;
+    vault.daily_profit_target = 0;
+    vault.session_realized_pnl = 0;
+    vault.trusted_feeds = [Pubkey::default(); MAX_TRUSTED_FEEDS];
+    vault.trusted_feed_count = 0;
+    vault.max_balance = 0;
+    vault.settled_swap_count = 0;
+    vault.last_swap_pnl = 0;
+    vault.last_swap_size = 0;
 
     Ok(())
 }
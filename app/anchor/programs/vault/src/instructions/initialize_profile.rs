@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::state::TraderProfile;
+use crate::constants::DEFAULT_MIN_PROFILE_UPDATE_INTERVAL;
+use crate::state::{TraderProfile, PNL_HISTORY_LEN};
 
 pub fn handler(ctx: Context<InitializeProfile>) -> Result<()> {
     let profile = &mut ctx.accounts.profile;
@@ -17,7 +18,15 @@ pub fn handler(ctx: Context<InitializeProfile>) -> Result<()> {
     profile.total_pnl = 0;
     profile.avg_trade_size = 0;
     profile.trading_days = 0;
+    profile.recent_pnl = [0; PNL_HISTORY_LEN];
+    profile.recent_pnl_count = 0;
+    profile.recent_pnl_head = 0;
+    profile.badges = 0;
     profile.last_updated = Clock::get()?.unix_timestamp;
+    profile.month_start = profile.last_updated;
+    profile.month_pnl = 0;
+    profile.min_update_interval = DEFAULT_MIN_PROFILE_UPDATE_INTERVAL;
+    profile.recorded_swap_count = 0;
     Ok(())
 }
 
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::VaultError;
-use crate::state::Vault;
+use crate::state::{LockoutReason, Vault};
 
 #[derive(Accounts)]
 pub struct ManualLock<'info> {
@@ -12,6 +12,7 @@ pub struct ManualLock<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 }
@@ -22,15 +23,36 @@ pub fn handler(ctx: Context<ManualLock>) -> Result<()> {
 
     require!(vault.lockout_duration > 0, VaultError::InvalidLockoutDuration);
 
-    vault.is_locked = true;
-    vault.lockout_until = clock
-        .unix_timestamp
-        .checked_add(vault.lockout_duration as i64)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-    vault.lockout_count = vault
-        .lockout_count
-        .checked_add(1)
-        .ok_or(VaultError::ArithmeticOverflow)?;
-
+    lock_vault(vault, LockoutReason::Manual, &clock)?;
     Ok(())
 }
+
+/// Engages the vault's lockout and emits `VaultLocked`. Every path that
+/// locks a vault goes through here so monitoring sees each lockout. A
+/// vault without a lockout duration is not locked and nothing is emitted;
+/// returns whether it locked.
+pub fn lock_vault(vault: &mut Account<Vault>, reason: LockoutReason, now: &Clock) -> Result<bool> {
+    if !vault.engage_lockout(now)? {
+        msg!("No lockout duration set, {:?} lockout skipped", reason);
+        return Ok(false);
+    }
+
+    emit!(VaultLocked {
+        vault: vault.key(),
+        reason,
+        lockout_until: vault.lockout_until,
+        lockout_count: vault.lockout_count,
+    });
+
+    msg!("Vault locked ({:?}) until {}", reason, vault.lockout_until);
+
+    Ok(true)
+}
+
+#[event]
+pub struct VaultLocked {
+    pub vault: Pubkey,
+    pub reason: LockoutReason,
+    pub lockout_until: i64,
+    pub lockout_count: u32,
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::account_version::{rewrite_account, ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+use crate::errors::VaultError;
+use crate::state::{Vault, VaultV1};
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: A vault at any layout version; the handler checks its
+    /// discriminator and that its owner field derives this address
+    #[account(mut, owner = crate::ID)]
+    pub vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrites a vault at the current layout version, growing it to fit.
+/// Stored fields keep their values and new ones take their defaults, so
+/// anyone may pay to migrate any vault.
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let version = {
+        let data = vault.try_borrow_data()?;
+        require!(
+            data.starts_with(Vault::DISCRIMINATOR),
+            VaultError::UnsupportedAccountVersion
+        );
+        Vault::stored_version(&vault.key(), &data).ok_or(VaultError::UnsupportedAccountVersion)?
+    };
+
+    match version {
+        ACCOUNT_VERSION => {
+            msg!("Vault already at version {}", version);
+            return Ok(());
+        }
+        LEGACY_ACCOUNT_VERSION => {
+            let legacy = VaultV1::from_account_data(&vault.try_borrow_data()?)?;
+            rewrite_account(
+                &vault.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &legacy.upgrade(&Clock::get()?),
+                8 + Vault::INIT_SPACE,
+            )?;
+        }
+        _ => return err!(VaultError::UnsupportedAccountVersion),
+    }
+
+    emit!(AccountMigrated {
+        account: vault.key(),
+        from_version: version,
+        to_version: ACCOUNT_VERSION,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
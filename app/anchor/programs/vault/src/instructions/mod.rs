@@ -1,27 +1,45 @@
 pub mod initialize;
 pub mod deposit;
 pub mod withdraw;
+pub mod deposit_spl;
+pub mod withdraw_spl;
 pub mod set_rules;
 pub mod manual_lock;
 pub mod unlock;
+pub mod set_guardian;
+pub mod set_withdraw_whitelist;
+pub mod set_trusted_feeds;
+pub mod emergency_withdraw;
 pub mod swap;
 pub mod delegate;
 pub mod undelegate;
 pub mod initialize_profile;
 pub mod update_stats;
+pub mod record_trade;
 pub mod delegate_profile;
 pub mod undelegate_profile;
+pub mod get_vault_status;
+pub mod migrate_account;
 
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
+pub use deposit_spl::*;
+pub use withdraw_spl::*;
 pub use set_rules::*;
 pub use manual_lock::*;
 pub use unlock::*;
+pub use set_guardian::*;
+pub use set_withdraw_whitelist::*;
+pub use set_trusted_feeds::*;
+pub use emergency_withdraw::*;
 pub use swap::*;
 pub use delegate::*;
 pub use undelegate::*;
 pub use initialize_profile::*;
 pub use update_stats::*;
+pub use record_trade::*;
 pub use delegate_profile::*;
 pub use undelegate_profile::*;
+pub use get_vault_status::*;
+pub use migrate_account::*;
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::{TraderProfile, Vault};
+use crate::errors::VaultError;
+
+/// Records the authority's last settled vault swap on their profile. The
+/// trade's PnL and size come from the vault's settlement, never the client.
+pub fn handler(ctx: Context<RecordTrade>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &ctx.accounts.vault;
+    let survived_lockout = vault.lockout_count > 0 && !vault.is_currently_locked(&clock);
+
+    let profile = &mut ctx.accounts.profile;
+    let badges_before = profile.badges;
+    profile.record_settled_swap(vault, &clock)?;
+    profile.award_badges(survived_lockout);
+    // record_trade can award the positive-month badge itself, so diff against
+    // the badges held before the trade rather than using award_badges' result
+    let newly_earned = profile.badges & !badges_before;
+
+    emit_badges_earned(profile.authority, newly_earned, clock.unix_timestamp);
+    Ok(())
+}
+
+pub fn emit_badges_earned(authority: Pubkey, newly_earned: u64, earned_at: i64) {
+    for badge in 0..64u8 {
+        if newly_earned & (1 << badge) != 0 {
+            emit!(BadgeEarned {
+                authority,
+                badge,
+                earned_at,
+            });
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct RecordTrade<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [TraderProfile::SEED_PREFIX, authority.key().as_ref()],
+        bump = profile.bump,
+        has_one = authority,
+    )]
+    pub profile: Account<'info, TraderProfile>,
+
+    /// Authority's vault, whose last settled swap is recorded
+    #[account(
+        seeds = [Vault::SEED_PREFIX, authority.key().as_ref()],
+        bump = vault.bump,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[event]
+pub struct BadgeEarned {
+    pub authority: Pubkey,
+    pub badge: u8,
+    pub earned_at: i64,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub owner: Signer<'info>,
+
+    /// Current guardian; must co-sign once one is configured so the owner
+    /// cannot quietly remove their own accountability partner.
+    pub guardian: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetGuardian>, new_guardian: Pubkey) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    vault.require_guardian_signature(ctx.accounts.guardian.as_ref().map(|g| g.key))?;
+    require!(new_guardian != vault.owner, VaultError::Unauthorized);
+
+    vault.guardian = new_guardian;
+
+    Ok(())
+}
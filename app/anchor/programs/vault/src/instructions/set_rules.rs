@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::MAX_COOLDOWN_SECONDS;
 use crate::errors::VaultError;
 use crate::state::Vault;
 
@@ -12,21 +13,76 @@ pub struct SetRules<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 }
 
-pub fn handler(
-    ctx: Context<SetRules>,
-    daily_loss_limit: u64,
-    max_trades_per_day: u8,
-    lockout_duration: u32,
-) -> Result<()> {
+/// Its encoding is fixed so clients built against it keep working; rules
+/// added since are set through `update_rules`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SetRulesArgs {
+    pub daily_loss_limit: u64,
+    pub max_trades_per_day: u8,
+    pub lockout_duration: u32,
+    pub session_duration_seconds: u32,
+    pub max_position_size: u64,
+    pub weekly_loss_limit: u64,
+    pub max_loss_streak: u8,
+    /// Factor each early unlock applies to later lockouts (0 = default doubling)
+    pub early_unlock_multiplier: u8,
+}
+
+/// One rule outside `SetRulesArgs`. New rules are appended as variants, so
+/// updates encoded for the existing ones never change.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum RuleUpdate {
+    /// Count native withdrawals beyond the session's deposits as losses
+    CountWithdrawalsAsLoss(bool),
+    /// Post-loss cooldown, scaled by the loss's share of the daily limit
+    CooldownSeconds(u32),
+    /// Session gain that locks the vault while ahead (0 = no target)
+    DailyProfitTarget(u64),
+    /// Lamports deposits may bring the vault up to (0 = no cap)
+    MaxBalance(u64),
+}
+
+pub fn handler(ctx: Context<SetRules>, args: SetRulesArgs) -> Result<()> {
+    require!(args.session_duration_seconds > 0, VaultError::InvalidSessionDuration);
+
+    let vault = &mut ctx.accounts.vault;
+    require!(
+        vault.allows_early_unlock_multiplier(args.early_unlock_multiplier),
+        VaultError::EarlyUnlockPenaltyLowered
+    );
+
+    vault.daily_loss_limit = args.daily_loss_limit;
+    vault.max_trades_per_day = args.max_trades_per_day;
+    vault.lockout_duration = args.lockout_duration;
+    vault.session_duration_seconds = args.session_duration_seconds;
+    vault.max_position_size = args.max_position_size;
+    vault.weekly_loss_limit = args.weekly_loss_limit;
+    vault.max_loss_streak = args.max_loss_streak;
+    vault.early_unlock_multiplier = args.early_unlock_multiplier;
+
+    Ok(())
+}
+
+/// Applies `updates` in order, leaving rules not named unchanged.
+pub fn update_rules_handler(ctx: Context<SetRules>, updates: Vec<RuleUpdate>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
-    vault.daily_loss_limit = daily_loss_limit;
-    vault.max_trades_per_day = max_trades_per_day;
-    vault.lockout_duration = lockout_duration;
+    for update in updates {
+        match update {
+            RuleUpdate::CountWithdrawalsAsLoss(enabled) => vault.count_withdrawals_as_loss = enabled,
+            RuleUpdate::CooldownSeconds(seconds) => {
+                require!(seconds as i64 <= MAX_COOLDOWN_SECONDS, VaultError::CooldownTooLong);
+                vault.cooldown_seconds = seconds;
+            }
+            RuleUpdate::DailyProfitTarget(target) => vault.daily_profit_target = target,
+            RuleUpdate::MaxBalance(max_balance) => vault.max_balance = max_balance,
+        }
+    }
 
     Ok(())
 }
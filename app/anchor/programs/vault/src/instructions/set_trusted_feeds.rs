@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+#[derive(Accounts)]
+pub struct SetTrustedFeeds<'info> {
+    pub owner: Signer<'info>,
+
+    /// Configured guardian; must co-sign so a compromised owner key cannot
+    /// trust a feed it controls.
+    pub guardian: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetTrustedFeeds>, feeds: Vec<Pubkey>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    vault.require_guardian_signature(ctx.accounts.guardian.as_ref().map(|g| g.key))?;
+    vault.set_trusted_feeds(&feeds)?;
+
+    msg!("Trusted price feeds set: {} feed(s)", feeds.len());
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+#[derive(Accounts)]
+pub struct SetWithdrawWhitelist<'info> {
+    pub owner: Signer<'info>,
+
+    /// Configured guardian; must co-sign so a compromised owner key cannot
+    /// whitelist an attacker address.
+    pub guardian: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<SetWithdrawWhitelist>, destinations: Vec<Pubkey>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    vault.require_guardian_signature(ctx.accounts.guardian.as_ref().map(|g| g.key))?;
+    vault.set_withdraw_whitelist(&destinations)?;
+
+    msg!("Withdraw whitelist set: {} destination(s)", destinations.len());
+
+    Ok(())
+}
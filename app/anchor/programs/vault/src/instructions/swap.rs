@@ -1,6 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token::TokenAccount;
-use crate::state::Vault;
+use crate::constants::{
+    JUPITER_PROGRAM_ID, JUPITER_ROUTE_DISCRIMINATOR, JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR,
+};
+use crate::instructions::manual_lock::lock_vault;
+use crate::state::{LockoutReason, Vault};
 use crate::errors::VaultError;
 
 #[derive(Accounts)]
@@ -13,6 +20,7 @@ pub struct PreSwapCheck<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 
@@ -20,6 +28,10 @@ pub struct PreSwapCheck<'info> {
         constraint = destination_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount
     )]
     pub destination_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Oracle the swap is priced against; must be one of `vault.trusted_feeds`,
+    /// and passed whenever any are set
+    pub price_feed: Option<UncheckedAccount<'info>>,
 }
 
 pub fn pre_swap_check_handler(
@@ -31,20 +43,35 @@ pub fn pre_swap_check_handler(
 ) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let clock = Clock::get()?;
-    let current_time = clock.unix_timestamp;
 
-    if vault.should_reset_session(current_time) {
-        vault.reset_daily_counters(current_time);
+    if vault.should_reset_session(&clock) {
+        vault.reset_daily_counters(&clock);
+    }
+    if vault.should_reset_week(&clock) {
+        vault.reset_weekly_counters(&clock);
     }
 
-    require!(!vault.is_currently_locked(current_time), VaultError::VaultLocked);
-    require!(!vault.is_in_cooldown(current_time), VaultError::CooldownActive);
+    require!(!vault.is_currently_locked(&clock), VaultError::VaultLocked);
+    require!(!vault.is_in_cooldown(&clock), VaultError::CooldownActive);
     require!(
-        vault.trades_today < vault.max_trades_per_day,
+        !vault.has_reached_trade_limit(),
         VaultError::TradeLimitExceeded
     );
+    require!(
+        !vault.has_exceeded_loss_limit(),
+        VaultError::LossLimitExceeded
+    );
+    require!(
+        !vault.has_exceeded_weekly_loss_limit(),
+        VaultError::WeeklyLossLimitExceeded
+    );
     require!(amount_in > 0 && min_out > 0, VaultError::InvalidAmount);
+    require!(
+        !vault.exceeds_max_position(amount_in),
+        VaultError::ExceedsMaxPosition
+    );
     require!(!vault.swap_in_progress, VaultError::SwapAlreadyInProgress);
+    vault.require_trusted_feed(ctx.accounts.price_feed.as_ref().map(|feed| feed.key()))?;
 
     vault.swap_in_progress = true;
     vault.pending_swap_source_mint = source_mint;
@@ -52,6 +79,9 @@ pub fn pre_swap_check_handler(
     vault.pending_swap_amount_in = amount_in;
     vault.pending_swap_min_out = min_out;
     vault.balance_before_swap = ctx.accounts.destination_token_account.amount;
+    vault.swap_started_at = clock.unix_timestamp;
+    let swap_lamports = vault.pending_swap_lamports();
+    vault.reserve_lamports(swap_lamports)?;
 
     msg!(
         "Pre-swap check passed: {} in, min {} out, balance_before={}",
@@ -60,6 +90,14 @@ pub fn pre_swap_check_handler(
         vault.balance_before_swap
     );
 
+    emit!(SwapEnforced {
+        vault: vault.key(),
+        amount_in,
+        min_out,
+        trades_today: vault.trades_today,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
 
@@ -73,6 +111,7 @@ pub struct PostSwapUpdate<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 
@@ -89,22 +128,145 @@ pub fn post_swap_update_handler(ctx: Context<PostSwapUpdate>) -> Result<()> {
     require!(vault.swap_in_progress, VaultError::NoSwapInProgress);
 
     let balance_after = ctx.accounts.destination_token_account.amount;
+    let amount_in = vault.pending_swap_amount_in;
+    let min_out = vault.pending_swap_min_out;
+    let sol_spent = vault.pending_swap_lamports();
+    let dest_mint = ctx.accounts.destination_token_account.mint;
+    settle_swap(vault, amount_in, balance_after, min_out, sol_spent, dest_mint, &clock)?;
+    let swap_lamports = vault.pending_swap_lamports();
+    vault.release_lamports(swap_lamports);
+    vault.swap_in_progress = false;
+    vault.swap_started_at = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClearStuckSwap<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        constraint = destination_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount
+    )]
+    pub destination_token_account: Account<'info, TokenAccount>,
+}
+
+/// Releases the swap guard left set by a `pre_swap_check` whose
+/// `post_swap_update` never landed. The swap is settled from the
+/// destination balance first, so whatever it lost still counts against
+/// the session's limits.
+pub fn clear_stuck_swap_handler(ctx: Context<ClearStuckSwap>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    require!(vault.swap_in_progress, VaultError::NoSwapInProgress);
+    require!(vault.is_swap_stuck(&clock), VaultError::SwapNotTimedOut);
+
+    let balance_after = ctx.accounts.destination_token_account.amount;
+    let amount_in = vault.pending_swap_amount_in;
+    let min_out = vault.pending_swap_min_out;
+    let sol_spent = vault.pending_swap_lamports();
+    let dest_mint = ctx.accounts.destination_token_account.mint;
+    settle_swap(vault, amount_in, balance_after, min_out, sol_spent, dest_mint, &clock)?;
+
+    let started_at = vault.swap_started_at;
+    vault.clear_pending_swap();
+
+    msg!("Cleared swap stuck since {}", started_at);
+
+    emit!(StuckSwapCleared {
+        vault: vault.key(),
+        started_at,
+        cleared_at: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Records the outcome of a swap from the destination balance delta.
+/// `sol_spent` is what the swap drew from the vault's lamports; together
+/// with any SOL it paid out, it moves the session's realized PnL.
+fn settle_swap(
+    vault: &mut Account<Vault>,
+    amount_in: u64,
+    balance_after: u64,
+    min_out: u64,
+    sol_spent: u64,
+    dest_mint: Pubkey,
+    now: &Clock,
+) -> Result<()> {
     let actual_out = balance_after
         .checked_sub(vault.balance_before_swap)
         .ok_or(VaultError::ArithmeticOverflow)?;
 
-    vault.last_trade_was_loss = actual_out < vault.pending_swap_min_out;
+    let loss = min_out.saturating_sub(actual_out);
+    let sol_received = if dest_mint == native_mint::ID { actual_out } else { 0 };
+    let was_over_loss_limit = vault.has_exceeded_loss_limit();
+    vault.last_trade_was_loss = loss > 0;
+    vault.record_settled_swap(amount_in, actual_out, min_out)?;
+    vault.record_loss(loss)?;
+    let hit_profit_target = vault.record_realized_pnl(sol_spent, sol_received)?;
     vault.increment_trade()?;
-    vault.last_trade_time = clock.unix_timestamp;
-    vault.swap_in_progress = false;
+    vault.last_trade_time = now.unix_timestamp;
+    vault.start_cooldown(loss, now)?;
+    let hit_loss_streak = vault.record_trade_outcome(loss > 0);
+
+    // One lockout per settlement, for the most severe rule the swap broke
+    if vault.cooldown_escalates(loss) {
+        if lock_vault(vault, LockoutReason::CooldownEscalation, now)? {
+            msg!(
+                "Loss of {} exceeds the maximum cooldown, vault locked until {}",
+                loss,
+                vault.lockout_until
+            );
+        }
+    } else if !was_over_loss_limit && vault.has_exceeded_loss_limit() {
+        if lock_vault(vault, LockoutReason::LossLimit, now)? {
+            msg!(
+                "Daily loss limit of {} exceeded, vault locked until {}",
+                vault.daily_loss_limit,
+                vault.lockout_until
+            );
+        }
+    } else if hit_loss_streak {
+        if lock_vault(vault, LockoutReason::LossStreak, now)? {
+            msg!(
+                "Loss streak limit of {} reached, vault locked until {}",
+                vault.max_loss_streak,
+                vault.lockout_until
+            );
+        }
+    } else if hit_profit_target && lock_vault(vault, LockoutReason::ProfitTarget, now)? {
+        msg!(
+            "Daily profit target of {} reached, vault locked until {}",
+            vault.daily_profit_target,
+            vault.lockout_until
+        );
+    }
 
     msg!(
-        "Swap complete: {} out (min {}), loss={}",
+        "Swap complete: {} out (min {}), loss={}, daily_loss={}",
         actual_out,
-        vault.pending_swap_min_out,
-        vault.last_trade_was_loss
+        min_out,
+        loss,
+        vault.daily_loss_so_far
     );
 
+    emit!(SwapSettled {
+        vault: vault.key(),
+        actual_out,
+        was_loss: loss > 0,
+    });
+
     Ok(())
 }
 
@@ -118,29 +280,72 @@ pub struct SwapWithEnforcement<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        constraint = destination_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount
+    )]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Jupiter aggregator program
+    #[account(address = JUPITER_PROGRAM_ID @ VaultError::InvalidJupiterProgram)]
+    pub jupiter_program: AccountInfo<'info>,
+
+    /// CHECK: Oracle the swap is priced against; must be one of `vault.trusted_feeds`
+    /// once any are set. Always passed so it never takes a route account's slot
+    pub price_feed: UncheckedAccount<'info>,
+    // remaining_accounts: Jupiter route accounts, in route instruction order
 }
 
-pub fn handler(ctx: Context<SwapWithEnforcement>, amount_in: u64, min_out: u64) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapWithEnforcement<'info>>,
+    amount_in: u64,
+    min_out: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let clock = Clock::get()?;
-    let current_time = clock.unix_timestamp;
 
-    if vault.should_reset_session(current_time) {
-        vault.reset_daily_counters(current_time);
+    if vault.should_reset_session(&clock) {
+        vault.reset_daily_counters(&clock);
+    }
+    if vault.should_reset_week(&clock) {
+        vault.reset_weekly_counters(&clock);
     }
 
-    require!(!vault.is_currently_locked(current_time), VaultError::VaultLocked);
-    require!(!vault.is_in_cooldown(current_time), VaultError::CooldownActive);
+    require!(!vault.is_currently_locked(&clock), VaultError::VaultLocked);
+    require!(!vault.is_in_cooldown(&clock), VaultError::CooldownActive);
     require!(
-        vault.trades_today < vault.max_trades_per_day,
+        !vault.has_reached_trade_limit(),
         VaultError::TradeLimitExceeded
     );
+    require!(
+        !vault.has_exceeded_loss_limit(),
+        VaultError::LossLimitExceeded
+    );
+    require!(
+        !vault.has_exceeded_weekly_loss_limit(),
+        VaultError::WeeklyLossLimitExceeded
+    );
     require!(amount_in > 0 && min_out > 0, VaultError::InvalidAmount);
+    require!(
+        !vault.exceeds_max_position(amount_in),
+        VaultError::ExceedsMaxPosition
+    );
+    require!(!vault.swap_in_progress, VaultError::SwapAlreadyInProgress);
+    vault.require_trusted_feed(Some(ctx.accounts.price_feed.key()))?;
 
-    vault.increment_trade()?;
-    vault.last_trade_time = current_time;
+    // The limits above were checked against amount_in, so the route must
+    // swap exactly that much
+    require!(
+        route_in_amount(&route_data) == Some(amount_in),
+        VaultError::RouteAmountMismatch
+    );
+
+    vault.balance_before_swap = ctx.accounts.destination_token_account.amount;
 
     msg!(
         "Pre-trade enforcement passed: {} in, min {} out",
@@ -148,5 +353,89 @@ pub fn handler(ctx: Context<SwapWithEnforcement>, amount_in: u64, min_out: u64)
         min_out
     );
 
+    emit!(SwapEnforced {
+        vault: vault.key(),
+        amount_in,
+        min_out,
+        trades_today: vault.trades_today,
+        timestamp: clock.unix_timestamp,
+    });
+
+    // The vault PDA is the route's user authority and signs the CPI
+    let vault_key = vault.key();
+    let route_accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == vault_key,
+            is_writable: account.is_writable,
+        })
+        .collect();
+
+    let route_ix = Instruction {
+        program_id: JUPITER_PROGRAM_ID,
+        accounts: route_accounts,
+        data: route_data,
+    };
+
+    let mut route_account_infos = ctx.remaining_accounts.to_vec();
+    route_account_infos.push(ctx.accounts.jupiter_program.to_account_info());
+
+    let owner_key = ctx.accounts.owner.key();
+    let vault_seeds = &[Vault::SEED_PREFIX, owner_key.as_ref(), &[vault.bump]];
+
+    let lamports_before = vault.to_account_info().lamports();
+    invoke_signed(&route_ix, &route_account_infos, &[vault_seeds])
+        .map_err(|_| error!(VaultError::SwapFailed))?;
+    let sol_spent = lamports_before.saturating_sub(ctx.accounts.vault.to_account_info().lamports());
+
+    ctx.accounts.destination_token_account.reload()?;
+    let balance_after = ctx.accounts.destination_token_account.amount;
+    let dest_mint = ctx.accounts.destination_token_account.mint;
+    settle_swap(&mut ctx.accounts.vault, amount_in, balance_after, min_out, sol_spent, dest_mint, &clock)?;
+
     Ok(())
 }
+
+/// `in_amount` of a Jupiter `route` or `shared_accounts_route` instruction.
+/// Both end their args with `in_amount: u64, quoted_out_amount: u64,
+/// slippage_bps: u16, platform_fee_bps: u8`; any other instruction is `None`.
+fn route_in_amount(route_data: &[u8]) -> Option<u64> {
+    const ARGS_TAIL_LEN: usize = 8 + 8 + 2 + 1;
+
+    let discriminator = route_data.get(..8)?;
+    if discriminator != JUPITER_ROUTE_DISCRIMINATOR
+        && discriminator != JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR
+    {
+        return None;
+    }
+
+    let start = route_data.len().checked_sub(ARGS_TAIL_LEN).filter(|start| *start >= 8)?;
+    let in_amount = route_data[start..start + 8].try_into().ok()?;
+    Some(u64::from_le_bytes(in_amount))
+}
+
+#[event]
+pub struct SwapEnforced {
+    pub vault: Pubkey,
+    pub amount_in: u64,
+    pub min_out: u64,
+    /// Trades already taken this session, not counting this one
+    pub trades_today: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapSettled {
+    pub vault: Pubkey,
+    pub actual_out: u64,
+    pub was_loss: bool,
+}
+
+#[event]
+pub struct StuckSwapCleared {
+    pub vault: Pubkey,
+    pub started_at: i64,
+    pub cleared_at: i64,
+}
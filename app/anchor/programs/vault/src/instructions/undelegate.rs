@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::commit;
 use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
 use crate::state::Vault;
+use crate::errors::VaultError;
 
 pub fn handler(ctx: Context<Undelegate>) -> Result<()> {
     commit_and_undelegate_accounts(
@@ -22,7 +23,8 @@ pub struct Undelegate<'info> {
     #[account(
         mut,
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
-        bump = vault.bump
+        bump = vault.bump,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 }
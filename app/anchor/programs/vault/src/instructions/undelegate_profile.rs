@@ -1,15 +1,38 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::commit;
-use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
+use ephemeral_rollups_sdk::ephem::{
+    CommitAndUndelegate, CommitType, MagicAction, MagicInstructionBuilder, UndelegateType,
+};
 use crate::state::TraderProfile;
 
+/// Commits the profile's latest stats to the base layer and undelegates it
+/// in one Magic action, so trades recorded in the ER are never dropped.
 pub fn handler(ctx: Context<UndelegateProfile>) -> Result<()> {
-    commit_and_undelegate_accounts(
-        &ctx.accounts.authority,
-        vec![&ctx.accounts.profile.to_account_info()],
-        &ctx.accounts.magic_context,
-        &ctx.accounts.magic_program,
-    )?;
+    // Anchor only writes account changes back when the instruction returns;
+    // flush them now so the commit snapshots the current stats
+    ctx.accounts.profile.exit(&crate::ID)?;
+
+    let last_updated = ctx.accounts.profile.last_updated;
+    let profile_info = ctx.accounts.profile.to_account_info();
+
+    let magic_builder = MagicInstructionBuilder {
+        payer: ctx.accounts.authority.to_account_info(),
+        magic_context: ctx.accounts.magic_context.to_account_info(),
+        magic_program: ctx.accounts.magic_program.to_account_info(),
+        magic_action: MagicAction::CommitAndUndelegate(CommitAndUndelegate {
+            commit_type: CommitType::Standalone(vec![profile_info]),
+            undelegate_type: UndelegateType::Standalone,
+        }),
+    };
+
+    magic_builder.build_and_invoke()?;
+
+    msg!(
+        "TraderProfile committed and undelegated: authority={}, last_updated={}",
+        ctx.accounts.authority.key(),
+        last_updated
+    );
+
     Ok(())
 }
 
@@ -26,3 +49,67 @@ pub struct UndelegateProfile<'info> {
     )]
     pub profile: Account<'info, TraderProfile>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::trader_profile::tests::create_test_profile;
+    use crate::time::FixedTime;
+    use anchor_lang::solana_program::{bpf_loader_upgradeable, system_program};
+    use std::collections::BTreeSet;
+
+    const MAGIC_PROGRAM_ID: Pubkey = pubkey!("Magic11111111111111111111111111111111111111");
+    const MAGIC_CONTEXT_ID: Pubkey = pubkey!("MagicContext1111111111111111111111111111111");
+
+    #[test]
+    fn test_undelegate_keeps_latest_stats() {
+        let authority = Pubkey::new_unique();
+        let (profile_key, bump) =
+            Pubkey::find_program_address(&[TraderProfile::SEED_PREFIX, authority.as_ref()], &crate::ID);
+
+        let mut profile = TraderProfile { authority, bump, ..create_test_profile() };
+        profile.record_trade(100, 1_000, &FixedTime::at(1_700_000_123)).unwrap();
+        let mut profile_data = Vec::new();
+        profile.try_serialize(&mut profile_data).unwrap();
+        profile_data.resize(8 + TraderProfile::INIT_SPACE, 0);
+
+        let (mut authority_lamports, mut profile_lamports) = (1_000_000_000u64, 1_000_000_000u64);
+        let (mut context_lamports, mut program_lamports) = (0u64, 0u64);
+        let (mut authority_data, mut context_data, mut program_data) = (Vec::new(), Vec::new(), Vec::new());
+        let infos = [
+            AccountInfo::new(
+                &authority, true, true, &mut authority_lamports, &mut authority_data,
+                &system_program::ID, false, 0,
+            ),
+            AccountInfo::new(
+                &profile_key, false, true, &mut profile_lamports, &mut profile_data,
+                &crate::ID, false, 0,
+            ),
+            AccountInfo::new(
+                &MAGIC_CONTEXT_ID, false, true, &mut context_lamports, &mut context_data,
+                &MAGIC_PROGRAM_ID, false, 0,
+            ),
+            AccountInfo::new(
+                &MAGIC_PROGRAM_ID, false, false, &mut program_lamports, &mut program_data,
+                &bpf_loader_upgradeable::ID, true, 0,
+            ),
+        ];
+
+        let mut account_infos: &[AccountInfo] = &infos;
+        let mut bumps = UndelegateProfileBumps::default();
+        let mut accounts = UndelegateProfile::try_accounts(
+            &crate::ID,
+            &mut account_infos,
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        handler(Context::new(&crate::ID, &mut accounts, &[], bumps)).unwrap();
+
+        // What the commit hands back to the base layer
+        let committed = TraderProfile::try_deserialize(&mut &infos[1].try_borrow_data().unwrap()[..]).unwrap();
+        assert_eq!(committed.last_updated, 1_700_000_123);
+        assert_eq!(committed.total_trades, 1);
+    }
+}
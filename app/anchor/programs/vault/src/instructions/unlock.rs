@@ -7,11 +7,15 @@ use crate::state::Vault;
 pub struct Unlock<'info> {
     pub owner: Signer<'info>,
 
+    /// Must co-sign when the vault has a guardian configured
+    pub guardian: Option<Signer<'info>>,
+
     #[account(
         mut,
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 }
@@ -20,6 +24,8 @@ pub fn handler(ctx: Context<Unlock>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let clock = Clock::get()?;
 
+    vault.require_guardian_signature(ctx.accounts.guardian.as_ref().map(|g| g.key))?;
+
     require!(
         clock.unix_timestamp >= vault.lockout_until,
         VaultError::LockoutNotExpired
@@ -30,3 +36,21 @@ pub fn handler(ctx: Context<Unlock>) -> Result<()> {
 
     Ok(())
 }
+
+/// Releases the lockout before it expires. Allowed, but each early unlock
+/// makes future lockouts longer by the vault's `early_unlock_multiplier`.
+pub fn early_unlock_handler(ctx: Context<Unlock>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    vault.require_guardian_signature(ctx.accounts.guardian.as_ref().map(|g| g.key))?;
+    vault.early_unlock(&clock)?;
+
+    msg!(
+        "Early unlock #{}: next lockout lasts {}s",
+        vault.early_unlock_count,
+        vault.effective_lockout_duration()
+    );
+
+    Ok(())
+}
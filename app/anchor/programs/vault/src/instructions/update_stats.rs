@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::instructions::record_trade::emit_badges_earned;
 use crate::state::TraderProfile;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -19,6 +20,9 @@ pub struct UpdateStatsArgs {
 
 pub fn handler(ctx: Context<UpdateStats>, args: UpdateStatsArgs) -> Result<()> {
     let profile = &mut ctx.accounts.profile;
+    let clock = Clock::get()?;
+    profile.check_update_interval(&clock)?;
+
     profile.discipline = args.discipline.min(99);
     profile.patience = args.patience.min(99);
     profile.consistency = args.consistency.min(99);
@@ -31,7 +35,10 @@ pub fn handler(ctx: Context<UpdateStats>, args: UpdateStatsArgs) -> Result<()> {
     profile.total_pnl = args.total_pnl;
     profile.avg_trade_size = args.avg_trade_size;
     profile.trading_days = args.trading_days;
-    profile.last_updated = Clock::get()?.unix_timestamp;
+    profile.last_updated = clock.unix_timestamp;
+
+    let newly_earned = profile.award_badges(false);
+    emit_badges_earned(profile.authority, newly_earned, profile.last_updated);
     Ok(())
 }
 
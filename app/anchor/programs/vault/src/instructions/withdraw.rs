@@ -13,27 +13,61 @@ pub struct Withdraw<'info> {
         seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
         bump = vault.bump,
         has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
     )]
     pub vault: Account<'info, Vault>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Receives the lamports instead of `owner`; must be on the vault's
+    /// withdraw whitelist when one is set
+    #[account(mut)]
+    pub destination: Option<AccountInfo<'info>>,
 }
 
 pub fn handler(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let clock = Clock::get()?;
 
-    require!(!vault.is_currently_locked(clock.unix_timestamp), VaultError::VaultLocked);
+    if vault.should_reset_session(&clock) {
+        vault.reset_daily_counters(&clock);
+    }
+    if vault.should_reset_week(&clock) {
+        vault.reset_weekly_counters(&clock);
+    }
+
+    require!(!vault.is_currently_locked(&clock), VaultError::VaultLocked);
     require!(amount > 0, VaultError::InvalidAmount);
 
     let vault_lamports = vault.to_account_info().lamports();
     let rent = Rent::get()?.minimum_balance(8 + Vault::INIT_SPACE);
-    let available = vault_lamports.saturating_sub(rent);
+    let available = vault.withdrawable_lamports(vault_lamports, rent);
 
     require!(amount <= available, VaultError::InsufficientFunds);
 
+    let destination = match ctx.accounts.destination.as_ref() {
+        Some(destination) => destination.clone(),
+        None => ctx.accounts.owner.to_account_info(),
+    };
+    require!(
+        vault.is_allowed_withdraw_destination(destination.key),
+        VaultError::WithdrawDestinationNotAllowed
+    );
+
+    // Only the part past this session's deposits is held to the loss limits
+    if vault.record_withdrawal(amount)? > 0 {
+        require!(
+            !vault.has_exceeded_loss_limit(),
+            VaultError::LossLimitExceeded
+        );
+        require!(
+            !vault.has_exceeded_weekly_loss_limit(),
+            VaultError::WeeklyLossLimitExceeded
+        );
+    }
+
     vault.sub_lamports(amount)?;
-    ctx.accounts.owner.add_lamports(amount)?;
+    destination.add_lamports(amount)?;
 
     vault.total_withdrawn = vault
         .total_withdrawn
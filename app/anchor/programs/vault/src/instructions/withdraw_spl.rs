@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::errors::VaultError;
+use crate::state::{TokenBalance, Vault};
+
+#[derive(Accounts)]
+pub struct WithdrawSpl<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [Vault::SEED_PREFIX, owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner @ VaultError::Unauthorized,
+        constraint = vault.is_current_version() @ VaultError::UnsupportedAccountVersion,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [TokenBalance::SEED_PREFIX, vault.key().as_ref(), mint.key().as_ref()],
+        bump = token_balance.bump,
+    )]
+    pub token_balance: Account<'info, TokenBalance>,
+
+    /// Receives the tokens; its authority must be the owner, or on the
+    /// vault's withdraw whitelist when one is set
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = vault.is_allowed_withdraw_destination(&owner_token_account.owner)
+            @ VaultError::WithdrawDestinationNotAllowed,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault,
+        seeds = [TokenBalance::TOKEN_ACCOUNT_SEED_PREFIX, vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<WithdrawSpl>, amount: u64) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    require!(!vault.is_currently_locked(&clock), VaultError::VaultLocked);
+    require!(!vault.is_in_cooldown(&clock), VaultError::CooldownActive);
+    require!(amount > 0, VaultError::InvalidAmount);
+    require!(
+        amount <= ctx.accounts.vault_token_account.amount,
+        VaultError::InsufficientFunds
+    );
+
+    let owner_key = ctx.accounts.owner.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[Vault::SEED_PREFIX, owner_key.as_ref(), &[vault.bump]]];
+
+    transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    let token_balance = &mut ctx.accounts.token_balance;
+    token_balance.total_withdrawn = token_balance
+        .total_withdrawn
+        .checked_add(amount)
+        .ok_or(VaultError::ArithmeticOverflow)?;
+
+    Ok(())
+}
@@ -1,9 +1,14 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::ephemeral;
 
+pub mod constants;
 pub mod errors;
 pub mod instructions;
 pub mod state;
+#[path = "../../../shared/account_version.rs"]
+pub mod account_version;
+#[path = "../../../shared/time.rs"]
+pub mod time;
 
 #[cfg(test)]
 mod tests;
@@ -29,13 +34,20 @@ pub mod vault {
         instructions::withdraw::handler(ctx, amount)
     }
 
-    pub fn set_rules(
-        ctx: Context<SetRules>,
-        daily_loss_limit: u64,
-        max_trades_per_day: u8,
-        lockout_duration: u32,
-    ) -> Result<()> {
-        instructions::set_rules::handler(ctx, daily_loss_limit, max_trades_per_day, lockout_duration)
+    pub fn deposit_spl(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
+        instructions::deposit_spl::handler(ctx, amount)
+    }
+
+    pub fn withdraw_spl(ctx: Context<WithdrawSpl>, amount: u64) -> Result<()> {
+        instructions::withdraw_spl::handler(ctx, amount)
+    }
+
+    pub fn set_rules(ctx: Context<SetRules>, args: SetRulesArgs) -> Result<()> {
+        instructions::set_rules::handler(ctx, args)
+    }
+
+    pub fn update_rules(ctx: Context<SetRules>, updates: Vec<RuleUpdate>) -> Result<()> {
+        instructions::set_rules::update_rules_handler(ctx, updates)
     }
 
     pub fn manual_lock(ctx: Context<ManualLock>) -> Result<()> {
@@ -46,12 +58,47 @@ pub mod vault {
         instructions::unlock::handler(ctx)
     }
 
-    pub fn swap_with_enforcement(
-        ctx: Context<SwapWithEnforcement>,
+    pub fn early_unlock(ctx: Context<Unlock>) -> Result<()> {
+        instructions::unlock::early_unlock_handler(ctx)
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian::handler(ctx, guardian)
+    }
+
+    pub fn set_withdraw_whitelist(
+        ctx: Context<SetWithdrawWhitelist>,
+        destinations: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_withdraw_whitelist::handler(ctx, destinations)
+    }
+
+    pub fn set_trusted_feeds(ctx: Context<SetTrustedFeeds>, feeds: Vec<Pubkey>) -> Result<()> {
+        instructions::set_trusted_feeds::handler(ctx, feeds)
+    }
+
+    pub fn request_emergency_withdraw(
+        ctx: Context<RequestEmergencyWithdraw>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::emergency_withdraw::request_handler(ctx, amount)
+    }
+
+    pub fn execute_emergency_withdraw(ctx: Context<ExecuteEmergencyWithdraw>) -> Result<()> {
+        instructions::emergency_withdraw::execute_handler(ctx)
+    }
+
+    pub fn cancel_emergency_withdraw(ctx: Context<CancelEmergencyWithdraw>) -> Result<()> {
+        instructions::emergency_withdraw::cancel_handler(ctx)
+    }
+
+    pub fn swap_with_enforcement<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapWithEnforcement<'info>>,
         amount_in: u64,
         min_out: u64,
+        route_data: Vec<u8>,
     ) -> Result<()> {
-        instructions::swap::handler(ctx, amount_in, min_out)
+        instructions::swap::handler(ctx, amount_in, min_out, route_data)
     }
 
     pub fn pre_swap_check(
@@ -68,6 +115,10 @@ pub mod vault {
         instructions::swap::post_swap_update_handler(ctx)
     }
 
+    pub fn clear_stuck_swap(ctx: Context<ClearStuckSwap>) -> Result<()> {
+        instructions::swap::clear_stuck_swap_handler(ctx)
+    }
+
     pub fn delegate(ctx: Context<DelegateInput>) -> Result<()> {
         instructions::delegate::handler(ctx)
     }
@@ -84,6 +135,10 @@ pub mod vault {
         instructions::update_stats::handler(ctx, args)
     }
 
+    pub fn record_trade(ctx: Context<RecordTrade>) -> Result<()> {
+        instructions::record_trade::handler(ctx)
+    }
+
     pub fn delegate_profile(ctx: Context<DelegateProfileInput>) -> Result<()> {
         instructions::delegate_profile::handler(ctx)
     }
@@ -91,4 +146,12 @@ pub mod vault {
     pub fn undelegate_profile(ctx: Context<UndelegateProfile>) -> Result<()> {
         instructions::undelegate_profile::handler(ctx)
     }
+
+    pub fn get_vault_status(ctx: Context<GetVaultStatus>) -> Result<()> {
+        instructions::get_vault_status::handler(ctx)
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account::handler(ctx)
+    }
 }
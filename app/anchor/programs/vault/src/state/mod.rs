@@ -1,5 +1,7 @@
 pub mod vault;
 pub mod trader_profile;
+pub mod token_balance;
 
 pub use vault::*;
 pub use trader_profile::*;
+pub use token_balance::*;
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TokenBalance {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+}
+
+impl TokenBalance {
+    pub const SEED_PREFIX: &'static [u8] = b"token_balance";
+    pub const TOKEN_ACCOUNT_SEED_PREFIX: &'static [u8] = b"vault_token";
+}
@@ -1,5 +1,20 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::VaultError;
+use crate::state::Vault;
+use crate::time::TimeSource;
+
+/// Number of most recent trade PnLs kept for the consistency rating.
+pub const PNL_HISTORY_LEN: usize = 16;
+
+/// Bit indices into `TraderProfile::badges`.
+pub const BADGE_HUNDRED_TRADES: u8 = 0;
+pub const BADGE_THIRTY_DAY_STREAK: u8 = 1;
+pub const BADGE_POSITIVE_MONTH: u8 = 2;
+pub const BADGE_SURVIVED_LOCKOUT: u8 = 3;
+
+pub const SECONDS_PER_MONTH: i64 = 30 * 86_400;
+
 #[account]
 #[derive(InitSpace)]
 pub struct TraderProfile {
@@ -18,8 +33,325 @@ pub struct TraderProfile {
     pub avg_trade_size: u64,
     pub trading_days: u16,
     pub last_updated: i64,
+
+    pub recent_pnl: [i64; PNL_HISTORY_LEN],
+    pub recent_pnl_count: u8,
+    pub recent_pnl_head: u8,
+
+    pub badges: u64,
+    pub month_start: i64,
+    pub month_pnl: i64,
+
+    /// Seconds `update_stats` must leave after `last_updated`
+    pub min_update_interval: i64,
+
+    /// The vault's `settled_swap_count` when its last swap was recorded here
+    pub recorded_swap_count: u64,
 }
 
 impl TraderProfile {
     pub const SEED_PREFIX: &'static [u8] = b"trader_profile";
+
+    /// Folds a single trade into the aggregates. Everything here is derived
+    /// from the trade itself rather than taken from the client.
+    pub fn record_trade(&mut self, pnl: i64, size: u64, now: &impl TimeSource) -> Result<()> {
+        let total_trades = self
+            .total_trades
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        let running_total = (self.avg_trade_size as u128) * (self.total_trades as u128) + size as u128;
+        self.avg_trade_size = (running_total / total_trades as u128) as u64;
+        self.total_trades = total_trades;
+
+        if pnl > 0 {
+            self.total_wins = self
+                .total_wins
+                .checked_add(1)
+                .ok_or(VaultError::ArithmeticOverflow)?;
+        }
+        self.total_pnl = self
+            .total_pnl
+            .checked_add(pnl)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        self.roll_month(now);
+        self.month_pnl = self.month_pnl.saturating_add(pnl);
+
+        self.push_recent_pnl(pnl);
+        if let Some(consistency) = self.consistency_from_history() {
+            self.consistency = consistency;
+        }
+
+        self.last_updated = now.unix_timestamp();
+        Ok(())
+    }
+
+    /// Folds `vault`'s last settled swap into the aggregates, once. A swap
+    /// settled before the one before it was recorded is not counted.
+    pub fn record_settled_swap(&mut self, vault: &Vault, now: &impl TimeSource) -> Result<()> {
+        require!(
+            vault.settled_swap_count > self.recorded_swap_count,
+            VaultError::NoUnrecordedSwap
+        );
+        self.record_trade(vault.last_swap_pnl, vault.last_swap_size, now)?;
+        self.recorded_swap_count = vault.settled_swap_count;
+        Ok(())
+    }
+
+    /// Closes out the current 30-day window once it has elapsed, awarding
+    /// the positive-month badge if it ended in profit.
+    fn roll_month(&mut self, now: &impl TimeSource) {
+        if now.unix_timestamp() - self.month_start < SECONDS_PER_MONTH {
+            return;
+        }
+        if self.month_start > 0 && self.month_pnl > 0 {
+            self.badges |= 1 << BADGE_POSITIVE_MONTH;
+        }
+        self.month_start = now.unix_timestamp();
+        self.month_pnl = 0;
+    }
+
+    /// Rejects a stats write landing within `min_update_interval` of the
+    /// last one.
+    pub fn check_update_interval(&self, now: &impl TimeSource) -> Result<()> {
+        let elapsed = now.unix_timestamp().saturating_sub(self.last_updated);
+        require!(elapsed >= self.min_update_interval, VaultError::TooFrequent);
+        Ok(())
+    }
+
+    pub fn has_badge(&self, index: u8) -> bool {
+        index < 64 && self.badges & (1 << index) != 0
+    }
+
+    /// Sets every milestone bit the profile currently qualifies for and
+    /// returns only the bits that were not already set.
+    pub fn award_badges(&mut self, survived_lockout: bool) -> u64 {
+        let before = self.badges;
+
+        if self.total_trades >= 100 {
+            self.badges |= 1 << BADGE_HUNDRED_TRADES;
+        }
+        if self.trading_days >= 30 {
+            self.badges |= 1 << BADGE_THIRTY_DAY_STREAK;
+        }
+        if survived_lockout {
+            self.badges |= 1 << BADGE_SURVIVED_LOCKOUT;
+        }
+
+        self.badges & !before
+    }
+
+    fn push_recent_pnl(&mut self, pnl: i64) {
+        let head = self.recent_pnl_head as usize % PNL_HISTORY_LEN;
+        self.recent_pnl[head] = pnl;
+        self.recent_pnl_head = ((head + 1) % PNL_HISTORY_LEN) as u8;
+        if (self.recent_pnl_count as usize) < PNL_HISTORY_LEN {
+            self.recent_pnl_count += 1;
+        }
+    }
+
+    /// Scores 0-99 from how tightly recent PnLs cluster: `mean|pnl| /
+    /// (mean|pnl| + stddev)`. Needs at least two trades to say anything.
+    pub fn consistency_from_history(&self) -> Option<u8> {
+        let count = self.recent_pnl_count as usize;
+        if count < 2 {
+            return None;
+        }
+
+        let samples = &self.recent_pnl[..count];
+        let n = count as i128;
+        let mean = samples.iter().map(|&p| p as i128).sum::<i128>() / n;
+        let variance = samples
+            .iter()
+            .map(|&p| {
+                let delta = p as i128 - mean;
+                (delta * delta) as u128
+            })
+            .sum::<u128>()
+            / count as u128;
+        let std_dev = isqrt(variance);
+        let mean_abs = samples.iter().map(|&p| p.unsigned_abs() as u128).sum::<u128>() / count as u128;
+
+        if mean_abs + std_dev == 0 {
+            return Some(99);
+        }
+        Some((99 * mean_abs / (mean_abs + std_dev)) as u8)
+    }
+}
+
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::time::FixedTime;
+
+    pub(crate) fn create_test_profile() -> TraderProfile {
+        TraderProfile {
+            authority: Pubkey::new_unique(),
+            bump: 255,
+            overall_rating: 50,
+            discipline: 50,
+            patience: 50,
+            consistency: 50,
+            timing: 50,
+            risk_control: 50,
+            endurance: 50,
+            total_trades: 0,
+            total_wins: 0,
+            total_pnl: 0,
+            avg_trade_size: 0,
+            trading_days: 0,
+            last_updated: 0,
+            recent_pnl: [0; PNL_HISTORY_LEN],
+            recent_pnl_count: 0,
+            recent_pnl_head: 0,
+            badges: 0,
+            month_start: 1_700_000_000,
+            month_pnl: 0,
+            min_update_interval: 60,
+            recorded_swap_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_record_trade_aggregates() {
+        let mut profile = create_test_profile();
+        let trades: [(i64, u64); 5] = [(100, 1_000), (-50, 2_000), (200, 3_000), (0, 4_000), (-25, 5_000)];
+
+        for (i, (pnl, size)) in trades.iter().enumerate() {
+            profile.record_trade(*pnl, *size, &FixedTime::at(1_700_000_000 + i as i64)).unwrap();
+        }
+
+        assert_eq!(profile.total_trades, 5);
+        assert_eq!(profile.total_wins, 2);
+        assert_eq!(profile.total_pnl, 225);
+        assert_eq!(profile.avg_trade_size, 3_000);
+        assert_eq!(profile.recent_pnl_count, 5);
+        assert_eq!(profile.last_updated, 1_700_000_004);
+    }
+
+    #[test]
+    fn test_settled_swaps_recorded_once_each() {
+        let mut profile = create_test_profile();
+        let mut vault = crate::state::vault::tests::create_test_vault();
+        let now = FixedTime::at(1_700_000_000);
+
+        assert_eq!(
+            profile.record_settled_swap(&vault, &now).unwrap_err(),
+            VaultError::NoUnrecordedSwap.into()
+        );
+
+        vault.record_settled_swap(1_000, 1_100, 1_000).unwrap();
+        profile.record_settled_swap(&vault, &now).unwrap();
+        assert_eq!(
+            profile.record_settled_swap(&vault, &now).unwrap_err(),
+            VaultError::NoUnrecordedSwap.into()
+        );
+
+        vault.record_settled_swap(3_000, 2_700, 2_900).unwrap();
+        profile.record_settled_swap(&vault, &now).unwrap();
+
+        assert_eq!(profile.total_trades, 2);
+        assert_eq!(profile.total_wins, 1);
+        assert_eq!(profile.total_pnl, -100);
+        assert_eq!(profile.avg_trade_size, 2_000);
+        assert_eq!(profile.recorded_swap_count, 2);
+    }
+
+    #[test]
+    fn test_rapid_stats_updates_are_throttled() {
+        let mut profile = create_test_profile();
+        profile.last_updated = 1_700_000_000;
+
+        assert!(profile.check_update_interval(&FixedTime::at(1_700_000_059)).is_err());
+        assert!(profile.check_update_interval(&FixedTime::at(1_700_000_060)).is_ok());
+
+        profile.min_update_interval = 0;
+        assert!(profile.check_update_interval(&FixedTime::at(1_700_000_000)).is_ok());
+    }
+
+    #[test]
+    fn test_consistency_rewards_steady_pnl() {
+        let mut steady = create_test_profile();
+        let mut erratic = create_test_profile();
+        let now = FixedTime::at(1_700_000_000);
+
+        steady.record_trade(100, 1, &now).unwrap();
+        assert_eq!(steady.consistency, 50, "one trade is not enough history");
+
+        for _ in 0..9 {
+            steady.record_trade(100, 1, &now).unwrap();
+        }
+        for pnl in [500, -400, 300, -600, 50, 700, -350, 20, -10, 400] {
+            erratic.record_trade(pnl, 1, &now).unwrap();
+        }
+
+        assert_eq!(steady.consistency, 99);
+        assert!(erratic.consistency < steady.consistency);
+    }
+
+    #[test]
+    fn test_hundred_trades_badge_awarded_once() {
+        let mut profile = create_test_profile();
+        let now = FixedTime::at(1_700_000_000);
+
+        for _ in 0..99 {
+            profile.record_trade(1, 1, &now).unwrap();
+            assert_eq!(profile.award_badges(false), 0);
+        }
+
+        profile.record_trade(1, 1, &now).unwrap();
+        assert_eq!(profile.award_badges(false), 1 << BADGE_HUNDRED_TRADES);
+        assert!(profile.has_badge(BADGE_HUNDRED_TRADES));
+
+        profile.record_trade(1, 1, &now).unwrap();
+        assert_eq!(profile.award_badges(false), 0, "badge should only be newly earned once");
+        assert!(!profile.has_badge(BADGE_SURVIVED_LOCKOUT));
+        assert!(!profile.has_badge(64));
+    }
+
+    #[test]
+    fn test_positive_month_and_lockout_badges() {
+        let mut profile = create_test_profile();
+
+        profile.record_trade(-50, 1, &FixedTime::at(1_700_000_000)).unwrap();
+        profile.record_trade(80, 1, &FixedTime::at(1_700_100_000)).unwrap();
+        assert!(!profile.has_badge(BADGE_POSITIVE_MONTH));
+
+        profile.record_trade(-500, 1, &FixedTime::at(1_700_000_000 + SECONDS_PER_MONTH)).unwrap();
+        assert!(profile.has_badge(BADGE_POSITIVE_MONTH));
+        assert_eq!(profile.month_pnl, -500);
+
+        assert_eq!(profile.award_badges(true), 1 << BADGE_SURVIVED_LOCKOUT);
+        profile.trading_days = 30;
+        assert_eq!(profile.award_badges(true), 1 << BADGE_THIRTY_DAY_STREAK);
+    }
+
+    #[test]
+    fn test_recent_pnl_buffer_wraps() {
+        let mut profile = create_test_profile();
+        let now = FixedTime::at(1_700_000_000);
+
+        for pnl in 0..(PNL_HISTORY_LEN as i64 + 3) {
+            profile.record_trade(pnl, 1, &now).unwrap();
+        }
+
+        assert_eq!(profile.recent_pnl_count as usize, PNL_HISTORY_LEN);
+        assert_eq!(profile.recent_pnl_head, 3);
+        assert_eq!(profile.recent_pnl[0], PNL_HISTORY_LEN as i64);
+        assert_eq!(profile.total_trades as usize, PNL_HISTORY_LEN + 3);
+    }
 }
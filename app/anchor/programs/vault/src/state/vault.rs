@@ -1,8 +1,43 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::constants::{
+    DEFAULT_EARLY_UNLOCK_MULTIPLIER, DEFAULT_SESSION_DURATION_SECONDS, EMERGENCY_DELAY_SECONDS,
+    MAX_COOLDOWN_SECONDS, MAX_ESCALATED_LOCKOUT_SECONDS, MAX_TRUSTED_FEEDS,
+    MAX_WITHDRAW_WHITELIST, SECONDS_PER_WEEK, SWAP_TIMEOUT_SECONDS,
+};
+use crate::account_version;
+use crate::errors::VaultError;
+use crate::time::TimeSource;
+
+/// Why a vault entered a lockout; carried on the `VaultLocked` event.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum LockoutReason {
+    Manual = 0,
+    LossLimit = 1,
+    LossStreak = 2,
+    CooldownEscalation = 3,
+    ProfitTarget = 4,
+}
+
+/// Enforcement state of a vault at a point in time, as `pre_swap_check`
+/// would see it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EnforcementStatus {
+    pub is_locked_now: bool,
+    pub in_cooldown: bool,
+    /// `u8::MAX` when the vault sets no daily trade limit
+    pub trades_remaining: u8,
+    pub seconds_until_unlock: i64,
+    pub seconds_until_cooldown_ends: i64,
+}
 
 #[account]
 #[derive(InitSpace)]
 pub struct Vault {
+    /// Layout version the account was written with; see `account_version`
+    pub version: u8,
     pub owner: Pubkey,
     pub bump: u8,
     pub is_locked: bool,
@@ -25,32 +60,1578 @@ pub struct Vault {
     pub pending_swap_amount_in: u64,
     pub pending_swap_min_out: u64,
     pub balance_before_swap: u64,
+
+    pub daily_loss_so_far: u64,
+
+    pub session_duration_seconds: u32,
+
+    pub guardian: Pubkey,
+
+    pub emergency_withdraw_amount: u64,
+    pub emergency_withdraw_requested_at: i64,
+
+    pub max_position_size: u64,
+
+    pub weekly_loss_limit: u64,
+    pub weekly_loss_so_far: u64,
+    pub week_start: i64,
+
+    pub current_loss_streak: u8,
+    pub max_loss_streak: u8,
+
+    pub withdraw_whitelist: [Pubkey; MAX_WITHDRAW_WHITELIST],
+    pub withdraw_whitelist_count: u8,
+
+    pub last_loss_amount: u64,
+    pub cooldown_until: i64,
+
+    pub swap_started_at: i64,
+
+    pub reserved_lamports: u64,
+
+    pub early_unlock_count: u32,
+    pub early_unlock_multiplier: u8,
+
+    /// Counts native withdrawals beyond the session's deposits against the loss limits
+    pub count_withdrawals_as_loss: bool,
+    pub session_deposited: u64,
+    pub session_withdrawn: u64,
+
+    /// Session gain that locks the vault while ahead (0 = no target)
+    pub daily_profit_target: u64,
+    /// Lamports swaps paid out in SOL less those they spent from it this session
+    pub session_realized_pnl: i64,
+
+    /// Oracle accounts swap enforcement will read prices from
+    pub trusted_feeds: [Pubkey; MAX_TRUSTED_FEEDS],
+    pub trusted_feed_count: u8,
+
+    /// Lamports a deposit may bring the vault up to (0 = no cap)
+    pub max_balance: u64,
+
+    /// Swaps settled over the vault's lifetime, the last one described below
+    pub settled_swap_count: u64,
+    /// How far the last settled swap's output landed above (or below) its minimum
+    pub last_swap_pnl: i64,
+    /// `amount_in` of the last settled swap
+    pub last_swap_size: u64,
+}
+
+/// `Vault` as stored at layout version 1, before the version byte and
+/// every field after `balance_before_swap` were added.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct VaultV1 {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub is_locked: bool,
+    pub lockout_until: i64,
+    pub lockout_count: u32,
+    pub lockout_duration: u32,
+    pub daily_loss_limit: u64,
+    pub max_trades_per_day: u8,
+    pub trades_today: u8,
+    pub session_start: i64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub last_trade_was_loss: bool,
+    pub last_trade_time: i64,
+    pub cooldown_seconds: u32,
+    pub swap_in_progress: bool,
+    pub pending_swap_source_mint: Pubkey,
+    pub pending_swap_dest_mint: Pubkey,
+    pub pending_swap_amount_in: u64,
+    pub pending_swap_min_out: u64,
+    pub balance_before_swap: u64,
+}
+
+impl VaultV1 {
+    /// Reads a version 1 vault from its raw account data.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(Vault::DISCRIMINATOR),
+            VaultError::UnsupportedAccountVersion
+        );
+        Self::deserialize(&mut &data[account_version::VERSION_OFFSET..])
+            .map_err(|_| error!(VaultError::UnsupportedAccountVersion))
+    }
+
+    /// The vault at the current layout. Fields version 1 stored carry over
+    /// and the rest start as `initialize` sets them, except that a running
+    /// loss cooldown keeps its end and an open swap is reserved and timed
+    /// from `now`.
+    pub fn upgrade(self, now: &impl TimeSource) -> Vault {
+        let now = now.unix_timestamp();
+        let cooldown_until = if self.last_trade_was_loss {
+            self.last_trade_time.saturating_add(self.cooldown_seconds as i64)
+        } else {
+            0
+        };
+
+        let mut vault = Vault {
+            version: account_version::ACCOUNT_VERSION,
+            owner: self.owner,
+            bump: self.bump,
+            is_locked: self.is_locked,
+            lockout_until: self.lockout_until,
+            lockout_count: self.lockout_count,
+            lockout_duration: self.lockout_duration,
+            daily_loss_limit: self.daily_loss_limit,
+            max_trades_per_day: self.max_trades_per_day,
+            trades_today: self.trades_today,
+            session_start: self.session_start,
+            total_deposited: self.total_deposited,
+            total_withdrawn: self.total_withdrawn,
+            last_trade_was_loss: self.last_trade_was_loss,
+            last_trade_time: self.last_trade_time,
+            cooldown_seconds: self.cooldown_seconds,
+            swap_in_progress: self.swap_in_progress,
+            pending_swap_source_mint: self.pending_swap_source_mint,
+            pending_swap_dest_mint: self.pending_swap_dest_mint,
+            pending_swap_amount_in: self.pending_swap_amount_in,
+            pending_swap_min_out: self.pending_swap_min_out,
+            balance_before_swap: self.balance_before_swap,
+            daily_loss_so_far: 0,
+            session_duration_seconds: DEFAULT_SESSION_DURATION_SECONDS,
+            guardian: Pubkey::default(),
+            emergency_withdraw_amount: 0,
+            emergency_withdraw_requested_at: 0,
+            max_position_size: 0,
+            weekly_loss_limit: 0,
+            weekly_loss_so_far: 0,
+            week_start: now,
+            current_loss_streak: 0,
+            max_loss_streak: 0,
+            withdraw_whitelist: [Pubkey::default(); MAX_WITHDRAW_WHITELIST],
+            withdraw_whitelist_count: 0,
+            last_loss_amount: 0,
+            cooldown_until,
+            swap_started_at: if self.swap_in_progress { now } else { 0 },
+            reserved_lamports: 0,
+            early_unlock_count: 0,
+            early_unlock_multiplier: DEFAULT_EARLY_UNLOCK_MULTIPLIER,
+            count_withdrawals_as_loss: false,
+            session_deposited: 0,
+            session_withdrawn: 0,
+            daily_profit_target: 0,
+            session_realized_pnl: 0,
+            trusted_feeds: [Pubkey::default(); MAX_TRUSTED_FEEDS],
+            trusted_feed_count: 0,
+            max_balance: 0,
+            settled_swap_count: 0,
+            last_swap_pnl: 0,
+            last_swap_size: 0,
+        };
+        vault.reserved_lamports = vault.pending_swap_lamports();
+        vault
+    }
+}
+
+fn effective_early_unlock_multiplier(multiplier: u8) -> u32 {
+    if multiplier == 0 {
+        DEFAULT_EARLY_UNLOCK_MULTIPLIER as u32
+    } else {
+        multiplier as u32
+    }
 }
 
 impl Vault {
     pub const SEED_PREFIX: &'static [u8] = b"vault";
 
-    pub fn is_currently_locked(&self, current_time: i64) -> bool {
-        self.is_locked && current_time < self.lockout_until
+    /// Whether the vault was written at the layout this program reads;
+    /// older ones go through `migrate_account` first.
+    pub fn is_current_version(&self) -> bool {
+        self.version == account_version::ACCOUNT_VERSION
+    }
+
+    /// Layout version of the raw vault `data` stored at `address`.
+    pub fn stored_version(address: &Pubkey, data: &[u8]) -> Option<u8> {
+        account_version::stored_version(data, |fields| {
+            fields.len() >= 32
+                && Pubkey::find_program_address(&[Self::SEED_PREFIX, &fields[..32]], &crate::ID).0
+                    == *address
+        })
+    }
+
+    pub fn is_currently_locked(&self, now: &impl TimeSource) -> bool {
+        self.is_locked && now.unix_timestamp() < self.lockout_until
+    }
+
+    /// Locks the vault for its effective lockout duration and returns
+    /// whether it did; a vault without a lockout duration is left unlocked.
+    pub fn engage_lockout(&mut self, now: &impl TimeSource) -> Result<bool> {
+        if self.effective_lockout_duration() == 0 {
+            return Ok(false);
+        }
+
+        self.is_locked = true;
+        self.lockout_until = now
+            .unix_timestamp()
+            .checked_add(self.effective_lockout_duration() as i64)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.lockout_count = self
+            .lockout_count
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(true)
+    }
+
+    /// Computes lock, cooldown and trade-limit state at `now` without
+    /// mutating the vault; an expired session counts as already reset.
+    pub fn enforcement_status(&self, now: &impl TimeSource) -> EnforcementStatus {
+        let is_locked_now = self.is_currently_locked(now);
+        let in_cooldown = self.is_in_cooldown(now);
+        let trades_today = if self.should_reset_session(now) {
+            0
+        } else {
+            self.trades_today
+        };
+
+        EnforcementStatus {
+            is_locked_now,
+            in_cooldown,
+            trades_remaining: if self.max_trades_per_day == 0 {
+                u8::MAX
+            } else {
+                self.max_trades_per_day.saturating_sub(trades_today)
+            },
+            seconds_until_unlock: if is_locked_now {
+                self.lockout_until - now.unix_timestamp()
+            } else {
+                0
+            },
+            seconds_until_cooldown_ends: if in_cooldown {
+                self.cooldown_until - now.unix_timestamp()
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Penalty factor per early unlock. Zero falls back to doubling; 1
+    /// disables it.
+    pub fn early_unlock_multiplier(&self) -> u32 {
+        effective_early_unlock_multiplier(self.early_unlock_multiplier)
     }
 
-    pub fn reset_daily_counters(&mut self, current_time: i64) {
+    /// Once an early unlock has been taken its penalty stands, so rules may
+    /// only keep or raise the multiplier from then on.
+    pub fn allows_early_unlock_multiplier(&self, multiplier: u8) -> bool {
+        self.early_unlock_count == 0
+            || effective_early_unlock_multiplier(multiplier) >= self.early_unlock_multiplier()
+    }
+
+    /// `lockout_duration` escalated once per early unlock, capped at
+    /// `MAX_ESCALATED_LOCKOUT_SECONDS` (or the base duration if it is longer).
+    pub fn effective_lockout_duration(&self) -> u32 {
+        let cap = self.lockout_duration.max(MAX_ESCALATED_LOCKOUT_SECONDS) as u64;
+        let penalty = (self.early_unlock_multiplier() as u64)
+            .checked_pow(self.early_unlock_count)
+            .unwrap_or(u64::MAX);
+        (self.lockout_duration as u64).saturating_mul(penalty).min(cap) as u32
+    }
+
+    /// Breaks an active lockout before `lockout_until`. Counts as a lockout
+    /// and makes every later lockout longer.
+    pub fn early_unlock(&mut self, now: &impl TimeSource) -> Result<()> {
+        require!(
+            self.is_currently_locked(now),
+            VaultError::NotLockedOut
+        );
+
+        self.is_locked = false;
+        self.lockout_until = 0;
+        self.early_unlock_count = self
+            .early_unlock_count
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        self.lockout_count = self
+            .lockout_count
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Extends the loss streak on a loss and clears it otherwise. Returns true
+    /// when a loss leaves the streak at or past `max_loss_streak`, clearing it
+    /// so the next lock takes another full run. With `max_loss_streak` at zero
+    /// losses are still counted but never return true.
+    pub fn record_trade_outcome(&mut self, was_loss: bool) -> bool {
+        if !was_loss {
+            self.current_loss_streak = 0;
+            return false;
+        }
+
+        self.current_loss_streak = self.current_loss_streak.saturating_add(1);
+        if self.max_loss_streak > 0 && self.current_loss_streak >= self.max_loss_streak {
+            self.current_loss_streak = 0;
+            return true;
+        }
+        false
+    }
+
+    pub fn reset_daily_counters(&mut self, now: &impl TimeSource) {
         self.trades_today = 0;
-        self.session_start = current_time;
+        self.daily_loss_so_far = 0;
+        self.session_deposited = 0;
+        self.session_withdrawn = 0;
+        self.session_realized_pnl = 0;
+        self.session_start = now.unix_timestamp();
     }
 
-    pub fn should_reset_session(&self, current_time: i64) -> bool {
-        const SECONDS_PER_DAY: i64 = 86400;
-        current_time - self.session_start >= SECONDS_PER_DAY
+    /// Length of a trading session. Never zero: `initialize` and the version
+    /// 1 upgrade start it at a full day and `set_rules` rejects zero.
+    pub fn session_duration(&self) -> i64 {
+        self.session_duration_seconds as i64
+    }
+
+    pub fn should_reset_session(&self, now: &impl TimeSource) -> bool {
+        now.unix_timestamp() - self.session_start >= self.session_duration()
+    }
+
+    pub fn reset_weekly_counters(&mut self, now: &impl TimeSource) {
+        self.weekly_loss_so_far = 0;
+        self.week_start = now.unix_timestamp();
+    }
+
+    pub fn should_reset_week(&self, now: &impl TimeSource) -> bool {
+        now.unix_timestamp() - self.week_start >= SECONDS_PER_WEEK
+    }
+
+    pub fn has_guardian(&self) -> bool {
+        self.guardian != Pubkey::default()
+    }
+
+    /// Passes when no guardian is configured, otherwise requires the
+    /// configured guardian to be among the signers.
+    pub fn require_guardian_signature(&self, signer: Option<&Pubkey>) -> Result<()> {
+        if !self.has_guardian() {
+            return Ok(());
+        }
+        require!(
+            signer == Some(&self.guardian),
+            VaultError::GuardianRequired
+        );
+        Ok(())
+    }
+
+    pub fn has_pending_emergency_withdraw(&self) -> bool {
+        self.emergency_withdraw_requested_at != 0
+    }
+
+    pub fn emergency_withdraw_executable_at(&self) -> i64 {
+        self.emergency_withdraw_requested_at
+            .saturating_add(EMERGENCY_DELAY_SECONDS)
+    }
+
+    pub fn is_emergency_withdraw_ready(&self, now: &impl TimeSource) -> bool {
+        self.has_pending_emergency_withdraw()
+            && now.unix_timestamp() >= self.emergency_withdraw_executable_at()
+    }
+
+    pub fn clear_emergency_withdraw(&mut self) {
+        self.emergency_withdraw_amount = 0;
+        self.emergency_withdraw_requested_at = 0;
     }
 
     pub fn increment_trade(&mut self) -> Result<()> {
-        self.trades_today = self.trades_today.checked_add(1).ok_or(error!(crate::errors::VaultError::ArithmeticOverflow))?;
+        self.trades_today = self.trades_today.checked_add(1).ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_loss(&mut self, loss: u64) -> Result<()> {
+        self.daily_loss_so_far = self.daily_loss_so_far.checked_add(loss).ok_or(VaultError::ArithmeticOverflow)?;
+        self.weekly_loss_so_far = self.weekly_loss_so_far.checked_add(loss).ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Lamports by which this session's withdrawals exceed its deposits.
+    pub fn session_net_outflow(&self) -> u64 {
+        self.session_withdrawn.saturating_sub(self.session_deposited)
+    }
+
+    pub fn record_session_deposit(&mut self, amount: u64) -> Result<()> {
+        self.session_deposited = self
+            .session_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Tracks a native withdrawal against the session and returns the part
+    /// recorded as a loss: with `count_withdrawals_as_loss` set, whatever it
+    /// adds to the session's net outflow, otherwise nothing.
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<u64> {
+        let outflow_before = self.session_net_outflow();
+        self.session_withdrawn = self
+            .session_withdrawn
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        if !self.count_withdrawals_as_loss {
+            return Ok(0);
+        }
+        let loss = self.session_net_outflow() - outflow_before;
+        self.record_loss(loss)?;
+        Ok(loss)
+    }
+
+    /// Books a settled swap's SOL legs against the session and returns
+    /// whether realized profit reached `daily_profit_target`. Gains only
+    /// count once a position is sold back into SOL, so a fill above its
+    /// minimum out is not profit on its own.
+    pub fn record_realized_pnl(&mut self, sol_spent: u64, sol_received: u64) -> Result<bool> {
+        let pnl = i128::from(self.session_realized_pnl) + i128::from(sol_received)
+            - i128::from(sol_spent);
+        self.session_realized_pnl = i64::try_from(pnl)
+            .map_err(|_| VaultError::ArithmeticOverflow)?;
+        Ok(self.has_hit_profit_target())
+    }
+
+    /// Keeps the settled swap for `record_trade` to fold into the owner's
+    /// profile: its size and how far its output beat (or missed) `min_out`.
+    pub fn record_settled_swap(&mut self, amount_in: u64, actual_out: u64, min_out: u64) -> Result<()> {
+        self.last_swap_pnl = i64::try_from(i128::from(actual_out) - i128::from(min_out))
+            .map_err(|_| VaultError::ArithmeticOverflow)?;
+        self.last_swap_size = amount_in;
+        self.settled_swap_count = self
+            .settled_swap_count
+            .checked_add(1)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// A zero target is treated as unset.
+    pub fn has_hit_profit_target(&self) -> bool {
+        self.daily_profit_target > 0
+            && self.session_realized_pnl >= 0
+            && self.session_realized_pnl as u64 >= self.daily_profit_target
+    }
+
+    /// A zero daily trade limit is treated as unset.
+    pub fn has_reached_trade_limit(&self) -> bool {
+        self.max_trades_per_day > 0 && self.trades_today >= self.max_trades_per_day
+    }
+
+    /// A zero daily limit is treated as unset, as `scaled_cooldown_seconds` does.
+    pub fn has_exceeded_loss_limit(&self) -> bool {
+        self.daily_loss_limit > 0 && self.daily_loss_so_far > self.daily_loss_limit
+    }
+
+    /// A zero weekly limit is treated as unset, so vaults that predate the
+    /// field are only bound by their daily limit.
+    pub fn has_exceeded_weekly_loss_limit(&self) -> bool {
+        self.weekly_loss_limit > 0 && self.weekly_loss_so_far > self.weekly_loss_limit
+    }
+
+    /// A zero cap means trades are not size-limited.
+    pub fn exceeds_max_position(&self, amount_in: u64) -> bool {
+        self.max_position_size > 0 && amount_in > self.max_position_size
+    }
+
+    pub fn is_in_cooldown(&self, now: &impl TimeSource) -> bool {
+        now.unix_timestamp() < self.cooldown_until
+    }
+
+    /// Cooldown earned by a loss, proportional to its share of the daily
+    /// loss limit and capped at `MAX_COOLDOWN_SECONDS`. Without a daily
+    /// limit the flat `cooldown_seconds` applies.
+    pub fn scaled_cooldown_seconds(&self, loss: u64) -> i64 {
+        self.uncapped_cooldown_seconds(loss)
+            .min(MAX_COOLDOWN_SECONDS as u128) as i64
+    }
+
+    /// A loss whose proportional cooldown would run past
+    /// `MAX_COOLDOWN_SECONDS` escalates to a full lockout instead.
+    pub fn cooldown_escalates(&self, loss: u64) -> bool {
+        self.uncapped_cooldown_seconds(loss) > MAX_COOLDOWN_SECONDS as u128
+    }
+
+    fn uncapped_cooldown_seconds(&self, loss: u64) -> u128 {
+        if loss == 0 {
+            return 0;
+        }
+        let base = self.cooldown_seconds as u128;
+        if self.daily_loss_limit == 0 {
+            base
+        } else {
+            base * loss as u128 / self.daily_loss_limit as u128
+        }
+    }
+
+    /// Records the realized loss of the last trade and starts its cooldown.
+    /// A winning trade ends any cooldown still running.
+    pub fn start_cooldown(&mut self, loss: u64, now: &impl TimeSource) -> Result<()> {
+        self.last_loss_amount = loss;
+        self.cooldown_until = now
+            .unix_timestamp()
+            .checked_add(self.scaled_cooldown_seconds(loss))
+            .ok_or(VaultError::ArithmeticOverflow)?;
         Ok(())
     }
 
-    pub fn is_in_cooldown(&self, current_time: i64) -> bool {
-        self.last_trade_was_loss
-            && current_time < self.last_trade_time + (self.cooldown_seconds as i64)
+    /// A swap whose post-swap update never ran, e.g. because the swap
+    /// instruction between them failed, and that has been open longer
+    /// than `SWAP_TIMEOUT_SECONDS`.
+    pub fn is_swap_stuck(&self, now: &impl TimeSource) -> bool {
+        self.swap_in_progress
+            && now.unix_timestamp() >= self.swap_started_at.saturating_add(SWAP_TIMEOUT_SECONDS)
+    }
+
+    pub fn clear_pending_swap(&mut self) {
+        let swap_lamports = self.pending_swap_lamports();
+        self.release_lamports(swap_lamports);
+        self.swap_in_progress = false;
+        self.pending_swap_source_mint = Pubkey::default();
+        self.pending_swap_dest_mint = Pubkey::default();
+        self.pending_swap_amount_in = 0;
+        self.pending_swap_min_out = 0;
+        self.balance_before_swap = 0;
+        self.swap_started_at = 0;
+    }
+
+    /// Lamports an open split swap is spending; only swaps out of native
+    /// SOL draw on the vault's lamport balance.
+    pub fn pending_swap_lamports(&self) -> u64 {
+        if self.swap_in_progress && self.pending_swap_source_mint == native_mint::ID {
+            self.pending_swap_amount_in
+        } else {
+            0
+        }
+    }
+
+    /// Sets lamports aside for a pending emergency withdraw or swap so a
+    /// regular withdraw cannot spend them first.
+    pub fn reserve_lamports(&mut self, amount: u64) -> Result<()> {
+        self.reserved_lamports = self
+            .reserved_lamports
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn release_lamports(&mut self, amount: u64) {
+        self.reserved_lamports = self.reserved_lamports.saturating_sub(amount);
+    }
+
+    /// Balance a withdraw may take: everything above rent that is not reserved.
+    pub fn withdrawable_lamports(&self, balance: u64, rent_minimum: u64) -> u64 {
+        balance
+            .saturating_sub(rent_minimum)
+            .saturating_sub(self.reserved_lamports)
+    }
+
+    pub fn withdraw_whitelist(&self) -> &[Pubkey] {
+        &self.withdraw_whitelist[..self.withdraw_whitelist_count as usize]
+    }
+
+    /// With an empty whitelist only the owner may receive withdrawals;
+    /// otherwise the destination must be listed, owner included.
+    pub fn is_allowed_withdraw_destination(&self, destination: &Pubkey) -> bool {
+        if self.withdraw_whitelist_count == 0 {
+            return *destination == self.owner;
+        }
+        self.withdraw_whitelist().contains(destination)
+    }
+
+    pub fn set_withdraw_whitelist(&mut self, destinations: &[Pubkey]) -> Result<()> {
+        require!(
+            destinations.len() <= MAX_WITHDRAW_WHITELIST,
+            VaultError::WithdrawWhitelistFull
+        );
+        self.withdraw_whitelist = [Pubkey::default(); MAX_WITHDRAW_WHITELIST];
+        self.withdraw_whitelist[..destinations.len()].copy_from_slice(destinations);
+        self.withdraw_whitelist_count = destinations.len() as u8;
+        Ok(())
+    }
+
+    pub fn trusted_feeds(&self) -> &[Pubkey] {
+        &self.trusted_feeds[..self.trusted_feed_count as usize]
+    }
+
+    /// Only listed feeds are trusted; with an empty list, none are.
+    pub fn is_trusted_feed(&self, feed: &Pubkey) -> bool {
+        self.trusted_feeds().contains(feed)
+    }
+
+    /// Checks the feed a swap is priced against. Once any feed is trusted
+    /// one must be passed and listed; until then the feed is not checked.
+    pub fn require_trusted_feed(&self, feed: Option<Pubkey>) -> Result<()> {
+        if self.trusted_feed_count == 0 {
+            return Ok(());
+        }
+        require!(
+            feed.is_some_and(|feed| self.is_trusted_feed(&feed)),
+            VaultError::UntrustedPriceFeed
+        );
+        Ok(())
+    }
+
+    pub fn set_trusted_feeds(&mut self, feeds: &[Pubkey]) -> Result<()> {
+        require!(
+            feeds.len() <= MAX_TRUSTED_FEEDS,
+            VaultError::TrustedFeedsFull
+        );
+        self.trusted_feeds = [Pubkey::default(); MAX_TRUSTED_FEEDS];
+        self.trusted_feeds[..feeds.len()].copy_from_slice(feeds);
+        self.trusted_feed_count = feeds.len() as u8;
+        Ok(())
+    }
+
+    /// Rejects depositing `amount` into a vault holding `lamports` when it
+    /// would end up above `max_balance`.
+    pub fn check_deposit_cap(&self, lamports: u64, amount: u64) -> Result<()> {
+        if self.max_balance == 0 {
+            return Ok(());
+        }
+        let balance = lamports
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+        require!(
+            balance <= self.max_balance,
+            VaultError::DepositCapExceeded
+        );
+        Ok(())
+    }
+}
+
+/// Byte offsets of each `Vault` field in the serialized account, counting
+/// the 8-byte discriminator, for tests and off-chain readers that patch or
+/// read single fields. Keep in step with the struct; `END` must equal
+/// `8 + Vault::INIT_SPACE`.
+pub mod vault_layout {
+    use super::*;
+
+    macro_rules! offsets {
+        ($offset:expr;) => {
+            pub const END: usize = $offset;
+        };
+        ($offset:expr; $name:ident: $ty:ty, $($rest:tt)*) => {
+            pub const $name: usize = $offset;
+            offsets!($name + core::mem::size_of::<$ty>(); $($rest)*);
+        };
+    }
+
+    offsets! {
+        8;
+        VERSION: u8,
+        OWNER: Pubkey,
+        BUMP: u8,
+        IS_LOCKED: bool,
+        LOCKOUT_UNTIL: i64,
+        LOCKOUT_COUNT: u32,
+        LOCKOUT_DURATION: u32,
+        DAILY_LOSS_LIMIT: u64,
+        MAX_TRADES_PER_DAY: u8,
+        TRADES_TODAY: u8,
+        SESSION_START: i64,
+        TOTAL_DEPOSITED: u64,
+        TOTAL_WITHDRAWN: u64,
+        LAST_TRADE_WAS_LOSS: bool,
+        LAST_TRADE_TIME: i64,
+        COOLDOWN_SECONDS: u32,
+        SWAP_IN_PROGRESS: bool,
+        PENDING_SWAP_SOURCE_MINT: Pubkey,
+        PENDING_SWAP_DEST_MINT: Pubkey,
+        PENDING_SWAP_AMOUNT_IN: u64,
+        PENDING_SWAP_MIN_OUT: u64,
+        BALANCE_BEFORE_SWAP: u64,
+        DAILY_LOSS_SO_FAR: u64,
+        SESSION_DURATION_SECONDS: u32,
+        GUARDIAN: Pubkey,
+        EMERGENCY_WITHDRAW_AMOUNT: u64,
+        EMERGENCY_WITHDRAW_REQUESTED_AT: i64,
+        MAX_POSITION_SIZE: u64,
+        WEEKLY_LOSS_LIMIT: u64,
+        WEEKLY_LOSS_SO_FAR: u64,
+        WEEK_START: i64,
+        CURRENT_LOSS_STREAK: u8,
+        MAX_LOSS_STREAK: u8,
+        WITHDRAW_WHITELIST: [Pubkey; MAX_WITHDRAW_WHITELIST],
+        WITHDRAW_WHITELIST_COUNT: u8,
+        LAST_LOSS_AMOUNT: u64,
+        COOLDOWN_UNTIL: i64,
+        SWAP_STARTED_AT: i64,
+        RESERVED_LAMPORTS: u64,
+        EARLY_UNLOCK_COUNT: u32,
+        EARLY_UNLOCK_MULTIPLIER: u8,
+        COUNT_WITHDRAWALS_AS_LOSS: bool,
+        SESSION_DEPOSITED: u64,
+        SESSION_WITHDRAWN: u64,
+        DAILY_PROFIT_TARGET: u64,
+        SESSION_REALIZED_PNL: i64,
+        TRUSTED_FEEDS: [Pubkey; MAX_TRUSTED_FEEDS],
+        TRUSTED_FEED_COUNT: u8,
+        MAX_BALANCE: u64,
+        SETTLED_SWAP_COUNT: u64,
+        LAST_SWAP_PNL: i64,
+        LAST_SWAP_SIZE: u64,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::account_version::{ACCOUNT_VERSION, LEGACY_ACCOUNT_VERSION};
+    use crate::time::FixedTime;
+
+    pub(crate) fn create_test_vault() -> Vault {
+        Vault {
+            version: ACCOUNT_VERSION,
+            owner: Pubkey::new_unique(),
+            bump: 255,
+            is_locked: false,
+            lockout_until: 0,
+            lockout_count: 0,
+            lockout_duration: 3600,
+            daily_loss_limit: 0,
+            max_trades_per_day: 10,
+            trades_today: 0,
+            session_start: 1_700_000_000,
+            total_deposited: 0,
+            total_withdrawn: 0,
+            last_trade_was_loss: false,
+            last_trade_time: 0,
+            cooldown_seconds: 0,
+            swap_in_progress: false,
+            pending_swap_source_mint: Pubkey::default(),
+            pending_swap_dest_mint: Pubkey::default(),
+            pending_swap_amount_in: 0,
+            pending_swap_min_out: 0,
+            balance_before_swap: 0,
+            daily_loss_so_far: 0,
+            session_duration_seconds: DEFAULT_SESSION_DURATION_SECONDS,
+            guardian: Pubkey::default(),
+            emergency_withdraw_amount: 0,
+            emergency_withdraw_requested_at: 0,
+            max_position_size: 0,
+            weekly_loss_limit: 0,
+            weekly_loss_so_far: 0,
+            week_start: 1_700_000_000,
+            current_loss_streak: 0,
+            max_loss_streak: 0,
+            withdraw_whitelist: [Pubkey::default(); MAX_WITHDRAW_WHITELIST],
+            withdraw_whitelist_count: 0,
+            last_loss_amount: 0,
+            cooldown_until: 0,
+            swap_started_at: 0,
+            reserved_lamports: 0,
+            early_unlock_count: 0,
+            early_unlock_multiplier: 0,
+            count_withdrawals_as_loss: false,
+            session_deposited: 0,
+            session_withdrawn: 0,
+            daily_profit_target: 0,
+            session_realized_pnl: 0,
+            trusted_feeds: [Pubkey::default(); MAX_TRUSTED_FEEDS],
+            trusted_feed_count: 0,
+            max_balance: 0,
+            settled_swap_count: 0,
+            last_swap_pnl: 0,
+            last_swap_size: 0,
+        }
+    }
+
+    #[test]
+    fn test_lockout_boundary() {
+        let mut vault = create_test_vault();
+        vault.is_locked = true;
+        vault.lockout_until = 1_700_003_600;
+
+        assert!(vault.is_currently_locked(&FixedTime::at(1_700_003_599)));
+        assert!(!vault.is_currently_locked(&FixedTime::at(1_700_003_600)));
+    }
+
+    #[test]
+    fn test_cooldown_boundary() {
+        let mut vault = create_test_vault();
+        vault.cooldown_seconds = 300;
+        vault.start_cooldown(1_000, &FixedTime::at(1_700_000_000)).unwrap();
+
+        assert!(vault.is_in_cooldown(&FixedTime::at(1_700_000_299)));
+        assert!(!vault.is_in_cooldown(&FixedTime::at(1_700_000_300)));
+
+        vault.start_cooldown(0, &FixedTime::at(1_700_000_000)).unwrap();
+        assert!(!vault.is_in_cooldown(&FixedTime::at(1_700_000_000)));
+    }
+
+    #[test]
+    fn test_cooldown_scales_with_loss_size() {
+        let mut vault = create_test_vault();
+        vault.cooldown_seconds = 3_600;
+        vault.daily_loss_limit = 1_000;
+
+        vault.start_cooldown(100, &FixedTime::at(1_700_000_000)).unwrap();
+        let small = vault.cooldown_until - 1_700_000_000;
+        assert_eq!(small, 360);
+        assert_eq!(vault.last_loss_amount, 100);
+
+        vault.start_cooldown(800, &FixedTime::at(1_700_000_000)).unwrap();
+        let large = vault.cooldown_until - 1_700_000_000;
+        assert_eq!(large, 2_880);
+        assert!(large > small);
+
+        // A loss far beyond the limit is clamped
+        vault.start_cooldown(1_000_000, &FixedTime::at(1_700_000_000)).unwrap();
+        assert_eq!(vault.cooldown_until - 1_700_000_000, MAX_COOLDOWN_SECONDS);
+    }
+
+    #[test]
+    fn test_cooldown_escalates_past_cap() {
+        let mut vault = create_test_vault();
+        vault.cooldown_seconds = 43_200;
+        vault.daily_loss_limit = 1_000;
+
+        assert!(!vault.cooldown_escalates(0));
+        assert!(!vault.cooldown_escalates(2_000));
+        assert!(vault.cooldown_escalates(2_001));
+
+        // The flat cooldown of a vault without a daily limit never exceeds the cap
+        vault.daily_loss_limit = 0;
+        vault.cooldown_seconds = MAX_COOLDOWN_SECONDS as u32;
+        assert!(!vault.cooldown_escalates(u64::MAX));
+    }
+
+    #[test]
+    fn test_session_reset_boundary() {
+        let mut vault = create_test_vault();
+        vault.trades_today = 3;
+        vault.daily_loss_so_far = 500;
+
+        assert!(!vault.should_reset_session(&FixedTime::at(1_700_086_399)));
+
+        let next_day = FixedTime::at(1_700_086_400);
+        assert!(vault.should_reset_session(&next_day));
+
+        vault.reset_daily_counters(&next_day);
+        assert_eq!(vault.trades_today, 0);
+        assert_eq!(vault.daily_loss_so_far, 0);
+        assert_eq!(vault.session_start, 1_700_086_400);
+    }
+
+    #[test]
+    fn test_hourly_session_resets_after_an_hour() {
+        let mut vault = create_test_vault();
+        vault.session_duration_seconds = 3600;
+        vault.trades_today = 5;
+
+        assert!(!vault.should_reset_session(&FixedTime::at(1_700_003_599)));
+
+        let next_session = FixedTime::at(1_700_003_600);
+        assert!(vault.should_reset_session(&next_session));
+
+        vault.reset_daily_counters(&next_session);
+        assert_eq!(vault.trades_today, 0);
+        assert!(!vault.should_reset_session(&FixedTime::at(1_700_007_199)));
+        assert!(vault.should_reset_session(&FixedTime::at(1_700_007_200)));
+    }
+
+    #[test]
+    fn test_guardian_signature() {
+        let mut vault = create_test_vault();
+        let stranger = Pubkey::new_unique();
+
+        assert!(vault.require_guardian_signature(None).is_ok());
+
+        vault.guardian = Pubkey::new_unique();
+        let guardian = vault.guardian;
+        assert_eq!(
+            vault.require_guardian_signature(None).unwrap_err(),
+            VaultError::GuardianRequired.into()
+        );
+        assert!(vault.require_guardian_signature(Some(&stranger)).is_err());
+        assert!(vault.require_guardian_signature(Some(&guardian)).is_ok());
+    }
+
+    #[test]
+    fn test_emergency_withdraw_delay_ignores_lockout() {
+        let mut vault = create_test_vault();
+        vault.is_locked = true;
+        vault.lockout_until = i64::MAX;
+
+        assert!(!vault.is_emergency_withdraw_ready(&FixedTime::at(i64::MAX)));
+
+        vault.emergency_withdraw_amount = 1_000;
+        vault.emergency_withdraw_requested_at = 1_700_000_000;
+        let ready_at = 1_700_000_000 + EMERGENCY_DELAY_SECONDS;
+
+        assert!(!vault.is_emergency_withdraw_ready(&FixedTime::at(1_700_000_000)));
+        assert!(!vault.is_emergency_withdraw_ready(&FixedTime::at(ready_at - 1)));
+        assert!(vault.is_emergency_withdraw_ready(&FixedTime::at(ready_at)));
+        assert!(vault.is_currently_locked(&FixedTime::at(ready_at)));
+
+        vault.clear_emergency_withdraw();
+        assert!(!vault.has_pending_emergency_withdraw());
+        assert!(!vault.is_emergency_withdraw_ready(&FixedTime::at(ready_at)));
+    }
+
+    #[test]
+    fn test_max_position_size_boundary() {
+        let mut vault = create_test_vault();
+        assert!(!vault.exceeds_max_position(u64::MAX));
+
+        vault.max_position_size = 1_000;
+        assert!(!vault.exceeds_max_position(1_000));
+        assert!(vault.exceeds_max_position(1_001));
+    }
+
+    #[test]
+    fn test_weekly_limit_trips_while_each_day_stays_under() {
+        let mut vault = create_test_vault();
+        vault.daily_loss_limit = 1_000;
+        vault.weekly_loss_limit = 3_000;
+
+        for day in 0..4i64 {
+            let now = FixedTime::at(1_700_000_000 + day * 86_400);
+            if vault.should_reset_session(&now) {
+                vault.reset_daily_counters(&now);
+            }
+            assert!(!vault.should_reset_week(&now));
+
+            vault.record_loss(900).unwrap();
+            assert!(!vault.has_exceeded_loss_limit());
+        }
+
+        assert_eq!(vault.weekly_loss_so_far, 3_600);
+        assert!(vault.has_exceeded_weekly_loss_limit());
+
+        let next_week = FixedTime::at(1_700_000_000 + SECONDS_PER_WEEK);
+        assert!(vault.should_reset_week(&next_week));
+        vault.reset_weekly_counters(&next_week);
+        assert!(!vault.has_exceeded_weekly_loss_limit());
+    }
+
+    #[test]
+    fn test_zero_weekly_limit_is_unlimited() {
+        let mut vault = create_test_vault();
+        vault.daily_loss_limit = u64::MAX;
+
+        vault.record_loss(1_000_000).unwrap();
+        assert!(!vault.has_exceeded_weekly_loss_limit());
+    }
+
+    #[test]
+    fn test_loss_streak_resets_on_win() {
+        let mut vault = create_test_vault();
+        vault.max_loss_streak = 3;
+
+        assert!(!vault.record_trade_outcome(true));
+        assert!(!vault.record_trade_outcome(true));
+        assert!(!vault.record_trade_outcome(false));
+        assert_eq!(vault.current_loss_streak, 0);
+
+        assert!(!vault.record_trade_outcome(true));
+        assert!(!vault.record_trade_outcome(true));
+        assert!(vault.record_trade_outcome(true));
+        assert_eq!(vault.current_loss_streak, 0);
+
+        vault.max_loss_streak = 0;
+        for _ in 0..10 {
+            assert!(!vault.record_trade_outcome(true));
+        }
+    }
+
+    #[test]
+    fn test_loss_limit_exceeded_after_losing_swap() {
+        let mut vault = create_test_vault();
+        vault.daily_loss_limit = 1_000;
+
+        vault.record_loss(1_000).unwrap();
+        assert!(!vault.has_exceeded_loss_limit());
+
+        vault.record_loss(1).unwrap();
+        assert!(vault.has_exceeded_loss_limit());
+
+        vault.reset_daily_counters(&FixedTime::at(1_700_086_400));
+        assert!(!vault.has_exceeded_loss_limit());
+    }
+
+    #[test]
+    fn test_stuck_swap_clears_only_after_timeout() {
+        let mut vault = create_test_vault();
+        assert!(!vault.is_swap_stuck(&FixedTime::at(i64::MAX)));
+
+        vault.swap_in_progress = true;
+        vault.swap_started_at = 1_700_000_000;
+        vault.pending_swap_amount_in = 1000;
+        vault.pending_swap_min_out = 900;
+        vault.balance_before_swap = 5;
+
+        assert!(!vault.is_swap_stuck(&FixedTime::at(1_700_000_000)));
+        assert!(!vault.is_swap_stuck(&FixedTime::at(1_700_000_000 + SWAP_TIMEOUT_SECONDS - 1)));
+        assert!(vault.is_swap_stuck(&FixedTime::at(1_700_000_000 + SWAP_TIMEOUT_SECONDS)));
+
+        vault.clear_pending_swap();
+        assert!(!vault.swap_in_progress);
+        assert_eq!(vault.pending_swap_amount_in, 0);
+        assert_eq!(vault.pending_swap_min_out, 0);
+        assert_eq!(vault.balance_before_swap, 0);
+        assert_eq!(vault.swap_started_at, 0);
+        assert!(!vault.is_swap_stuck(&FixedTime::at(i64::MAX)));
+    }
+
+    #[test]
+    fn test_vault_layout_matches_serialized_fields() {
+        use anchor_lang::AccountSerialize;
+
+        let mut vault = create_test_vault();
+        vault.bump = 7;
+        vault.is_locked = true;
+        vault.lockout_until = 1_700_000_001;
+        vault.lockout_count = 3;
+        vault.lockout_duration = 3600;
+        vault.max_trades_per_day = 5;
+        vault.trades_today = 4;
+        vault.total_deposited = 11;
+        vault.total_withdrawn = 12;
+        vault.last_trade_was_loss = true;
+        vault.last_trade_time = 1_700_000_002;
+        vault.cooldown_seconds = 300;
+        vault.swap_in_progress = true;
+        vault.pending_swap_source_mint = Pubkey::new_unique();
+        vault.pending_swap_dest_mint = Pubkey::new_unique();
+        vault.pending_swap_amount_in = 13;
+        vault.pending_swap_min_out = 14;
+        vault.balance_before_swap = 15;
+        vault.daily_loss_so_far = 16;
+        vault.guardian = Pubkey::new_unique();
+        vault.emergency_withdraw_amount = 17;
+        vault.emergency_withdraw_requested_at = 1_700_000_003;
+        vault.max_position_size = 18;
+        vault.weekly_loss_limit = 19;
+        vault.weekly_loss_so_far = 20;
+        vault.week_start = 1_700_000_004;
+        vault.current_loss_streak = 2;
+        vault.max_loss_streak = 6;
+        vault.withdraw_whitelist[1] = Pubkey::new_unique();
+        vault.withdraw_whitelist_count = 2;
+        vault.last_loss_amount = 21;
+        vault.cooldown_until = 1_700_000_005;
+        vault.swap_started_at = 1_700_000_006;
+        vault.reserved_lamports = 22;
+        vault.early_unlock_count = 8;
+        vault.early_unlock_multiplier = 9;
+        vault.count_withdrawals_as_loss = true;
+        vault.session_deposited = 23;
+        vault.session_withdrawn = 24;
+        vault.daily_profit_target = 25;
+        vault.session_realized_pnl = -26;
+        vault.trusted_feeds[2] = Pubkey::new_unique();
+        vault.trusted_feed_count = 3;
+        vault.max_balance = 27;
+        vault.settled_swap_count = 28;
+        vault.last_swap_pnl = -29;
+        vault.last_swap_size = 30;
+
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), vault_layout::END);
+        assert_eq!(vault_layout::END, 8 + Vault::INIT_SPACE);
+
+        macro_rules! check {
+            ($field:ident, $offset:ident) => {
+                let expected = vault.$field.try_to_vec().unwrap();
+                assert_eq!(
+                    &data[vault_layout::$offset..vault_layout::$offset + expected.len()],
+                    expected.as_slice(),
+                    stringify!($field)
+                );
+            };
+        }
+
+        check!(version, VERSION);
+        check!(owner, OWNER);
+        check!(bump, BUMP);
+        check!(is_locked, IS_LOCKED);
+        check!(lockout_until, LOCKOUT_UNTIL);
+        check!(lockout_count, LOCKOUT_COUNT);
+        check!(lockout_duration, LOCKOUT_DURATION);
+        check!(daily_loss_limit, DAILY_LOSS_LIMIT);
+        check!(max_trades_per_day, MAX_TRADES_PER_DAY);
+        check!(trades_today, TRADES_TODAY);
+        check!(session_start, SESSION_START);
+        check!(total_deposited, TOTAL_DEPOSITED);
+        check!(total_withdrawn, TOTAL_WITHDRAWN);
+        check!(last_trade_was_loss, LAST_TRADE_WAS_LOSS);
+        check!(last_trade_time, LAST_TRADE_TIME);
+        check!(cooldown_seconds, COOLDOWN_SECONDS);
+        check!(swap_in_progress, SWAP_IN_PROGRESS);
+        check!(pending_swap_source_mint, PENDING_SWAP_SOURCE_MINT);
+        check!(pending_swap_dest_mint, PENDING_SWAP_DEST_MINT);
+        check!(pending_swap_amount_in, PENDING_SWAP_AMOUNT_IN);
+        check!(pending_swap_min_out, PENDING_SWAP_MIN_OUT);
+        check!(balance_before_swap, BALANCE_BEFORE_SWAP);
+        check!(daily_loss_so_far, DAILY_LOSS_SO_FAR);
+        check!(session_duration_seconds, SESSION_DURATION_SECONDS);
+        check!(guardian, GUARDIAN);
+        check!(emergency_withdraw_amount, EMERGENCY_WITHDRAW_AMOUNT);
+        check!(emergency_withdraw_requested_at, EMERGENCY_WITHDRAW_REQUESTED_AT);
+        check!(max_position_size, MAX_POSITION_SIZE);
+        check!(weekly_loss_limit, WEEKLY_LOSS_LIMIT);
+        check!(weekly_loss_so_far, WEEKLY_LOSS_SO_FAR);
+        check!(week_start, WEEK_START);
+        check!(current_loss_streak, CURRENT_LOSS_STREAK);
+        check!(max_loss_streak, MAX_LOSS_STREAK);
+        check!(withdraw_whitelist, WITHDRAW_WHITELIST);
+        check!(withdraw_whitelist_count, WITHDRAW_WHITELIST_COUNT);
+        check!(last_loss_amount, LAST_LOSS_AMOUNT);
+        check!(cooldown_until, COOLDOWN_UNTIL);
+        check!(swap_started_at, SWAP_STARTED_AT);
+        check!(reserved_lamports, RESERVED_LAMPORTS);
+        check!(early_unlock_count, EARLY_UNLOCK_COUNT);
+        check!(early_unlock_multiplier, EARLY_UNLOCK_MULTIPLIER);
+        check!(count_withdrawals_as_loss, COUNT_WITHDRAWALS_AS_LOSS);
+        check!(session_deposited, SESSION_DEPOSITED);
+        check!(session_withdrawn, SESSION_WITHDRAWN);
+        check!(daily_profit_target, DAILY_PROFIT_TARGET);
+        check!(session_realized_pnl, SESSION_REALIZED_PNL);
+        check!(trusted_feeds, TRUSTED_FEEDS);
+        check!(trusted_feed_count, TRUSTED_FEED_COUNT);
+        check!(max_balance, MAX_BALANCE);
+        check!(settled_swap_count, SETTLED_SWAP_COUNT);
+        check!(last_swap_pnl, LAST_SWAP_PNL);
+        check!(last_swap_size, LAST_SWAP_SIZE);
+    }
+
+    #[test]
+    fn test_settled_swap_kept_for_profile() {
+        let mut vault = create_test_vault();
+
+        vault.record_settled_swap(1_000, 850, 900).unwrap();
+        assert_eq!(vault.settled_swap_count, 1);
+        assert_eq!(vault.last_swap_pnl, -50);
+        assert_eq!(vault.last_swap_size, 1_000);
+
+        vault.record_settled_swap(2_000, u64::MAX, 0).unwrap_err();
+    }
+
+    #[test]
+    fn test_enforcement_status_when_open() {
+        let mut vault = create_test_vault();
+        vault.session_start = 1_700_000_000;
+        vault.max_trades_per_day = 5;
+        vault.trades_today = 2;
+
+        let status = vault.enforcement_status(&FixedTime::at(1_700_000_100));
+        assert_eq!(
+            status,
+            EnforcementStatus {
+                is_locked_now: false,
+                in_cooldown: false,
+                trades_remaining: 3,
+                seconds_until_unlock: 0,
+                seconds_until_cooldown_ends: 0,
+            }
+        );
+
+        // Once the session has rolled over the day's trades are available again
+        let status = vault.enforcement_status(&FixedTime::at(1_700_086_400));
+        assert_eq!(status.trades_remaining, 5);
+    }
+
+    #[test]
+    fn test_enforcement_status_when_locked() {
+        let mut vault = create_test_vault();
+        vault.session_start = 1_700_000_000;
+        vault.lockout_duration = 3600;
+        vault.engage_lockout(&FixedTime::at(1_700_000_000)).unwrap();
+
+        let status = vault.enforcement_status(&FixedTime::at(1_700_000_600));
+        assert!(status.is_locked_now);
+        assert_eq!(status.seconds_until_unlock, 3000);
+
+        let status = vault.enforcement_status(&FixedTime::at(1_700_003_600));
+        assert!(!status.is_locked_now);
+        assert_eq!(status.seconds_until_unlock, 0);
+    }
+
+    #[test]
+    fn test_enforcement_status_in_cooldown() {
+        let mut vault = create_test_vault();
+        vault.session_start = 1_700_000_000;
+        vault.max_trades_per_day = 5;
+        vault.trades_today = 5;
+        vault.cooldown_until = 1_700_000_300;
+
+        let status = vault.enforcement_status(&FixedTime::at(1_700_000_100));
+        assert!(!status.is_locked_now);
+        assert!(status.in_cooldown);
+        assert_eq!(status.seconds_until_cooldown_ends, 200);
+        assert_eq!(status.trades_remaining, 0);
+    }
+
+    #[test]
+    fn test_early_unlock_escalates_next_lockout() {
+        let mut vault = create_test_vault();
+        vault.lockout_duration = 3600;
+        let now = FixedTime::at(1_700_000_000);
+
+        vault.engage_lockout(&now).unwrap();
+        assert_eq!(vault.lockout_until, 1_700_003_600);
+
+        vault.early_unlock(&now).unwrap();
+        assert!(!vault.is_currently_locked(&now));
+        assert_eq!(vault.early_unlock_count, 1);
+        assert_eq!(vault.lockout_count, 2);
+
+        // The next lockout is twice as long
+        vault.engage_lockout(&now).unwrap();
+        assert_eq!(vault.lockout_until, 1_700_007_200);
+
+        // And breaking that one doubles it again
+        vault.early_unlock(&now).unwrap();
+        assert_eq!(vault.effective_lockout_duration(), 14_400);
+    }
+
+    #[test]
+    fn test_zero_lockout_duration_does_not_lock() {
+        let mut vault = create_test_vault();
+        vault.lockout_duration = 0;
+        let now = FixedTime::at(1_700_000_000);
+
+        assert!(!vault.engage_lockout(&now).unwrap());
+        assert!(!vault.is_locked);
+        assert_eq!(vault.lockout_until, 0);
+        assert_eq!(vault.lockout_count, 0);
+    }
+
+    #[test]
+    fn test_early_unlock_requires_active_lockout() {
+        let mut vault = create_test_vault();
+        vault.lockout_duration = 3600;
+        let now = FixedTime::at(1_700_000_000);
+
+        assert!(vault.early_unlock(&now).is_err());
+
+        vault.engage_lockout(&now).unwrap();
+        // Once the lockout has run out the regular unlock applies
+        assert!(vault.early_unlock(&FixedTime::at(1_700_003_600)).is_err());
+        assert_eq!(vault.early_unlock_count, 0);
+    }
+
+    #[test]
+    fn test_escalated_lockout_is_capped() {
+        let mut vault = create_test_vault();
+        vault.lockout_duration = 3600;
+        vault.early_unlock_multiplier = 3;
+        vault.early_unlock_count = 40;
+
+        assert_eq!(vault.effective_lockout_duration(), MAX_ESCALATED_LOCKOUT_SECONDS);
+
+        vault.early_unlock_multiplier = 1;
+        assert_eq!(vault.effective_lockout_duration(), 3600);
+    }
+
+    #[test]
+    fn test_early_unlock_penalty_cannot_be_lowered() {
+        let mut vault = create_test_vault();
+        vault.early_unlock_multiplier = 3;
+        assert!(vault.allows_early_unlock_multiplier(1));
+
+        vault.early_unlock_count = 1;
+        assert!(!vault.allows_early_unlock_multiplier(1));
+        assert!(!vault.allows_early_unlock_multiplier(0));
+        assert!(vault.allows_early_unlock_multiplier(3));
+        assert!(vault.allows_early_unlock_multiplier(4));
+    }
+
+    #[test]
+    fn test_reservations_reduce_withdrawable_balance() {
+        let mut vault = create_test_vault();
+        let rent = 1_000_000;
+        let balance = rent + 10_000_000;
+        assert_eq!(vault.withdrawable_lamports(balance, rent), 10_000_000);
+
+        // Pending emergency withdraw
+        vault.reserve_lamports(4_000_000).unwrap();
+        assert_eq!(vault.withdrawable_lamports(balance, rent), 6_000_000);
+
+        // Open swap out of native SOL
+        vault.swap_in_progress = true;
+        vault.pending_swap_source_mint = native_mint::ID;
+        vault.pending_swap_amount_in = 5_000_000;
+        let swap_lamports = vault.pending_swap_lamports();
+        vault.reserve_lamports(swap_lamports).unwrap();
+        assert_eq!(vault.withdrawable_lamports(balance, rent), 1_000_000);
+
+        // Reservations beyond the balance leave nothing, never underflow
+        vault.reserve_lamports(5_000_000).unwrap();
+        assert_eq!(vault.withdrawable_lamports(balance, rent), 0);
+        vault.release_lamports(5_000_000);
+
+        vault.clear_pending_swap();
+        assert_eq!(vault.reserved_lamports, 4_000_000);
+        assert_eq!(vault.withdrawable_lamports(balance, rent), 6_000_000);
+
+        vault.release_lamports(4_000_000);
+        assert_eq!(vault.withdrawable_lamports(balance, rent), 10_000_000);
+    }
+
+    #[test]
+    fn test_token_swap_reserves_no_lamports() {
+        let mut vault = create_test_vault();
+        vault.swap_in_progress = true;
+        vault.pending_swap_source_mint = Pubkey::new_unique();
+        vault.pending_swap_amount_in = 5_000_000;
+
+        assert_eq!(vault.pending_swap_lamports(), 0);
+    }
+
+    #[test]
+    fn test_only_listed_feeds_are_trusted() {
+        let mut vault = create_test_vault();
+        let pyth_feed = Pubkey::new_unique();
+        let spoofed_feed = Pubkey::new_unique();
+
+        assert!(!vault.is_trusted_feed(&pyth_feed));
+
+        vault.set_trusted_feeds(&[pyth_feed]).unwrap();
+        assert!(vault.is_trusted_feed(&pyth_feed));
+        assert!(!vault.is_trusted_feed(&spoofed_feed));
+        // Unused slots are zeroed, not trusted
+        assert!(!vault.is_trusted_feed(&Pubkey::default()));
+
+        assert!(vault.set_trusted_feeds(&[Pubkey::new_unique(); 5]).is_err());
+
+        vault.set_trusted_feeds(&[]).unwrap();
+        assert!(!vault.is_trusted_feed(&pyth_feed));
+    }
+
+    #[test]
+    fn test_feed_required_once_any_is_trusted() {
+        let mut vault = create_test_vault();
+        let pyth_feed = Pubkey::new_unique();
+
+        assert!(vault.require_trusted_feed(None).is_ok());
+        assert!(vault.require_trusted_feed(Some(Pubkey::new_unique())).is_ok());
+
+        vault.set_trusted_feeds(&[pyth_feed]).unwrap();
+        assert!(vault.require_trusted_feed(Some(pyth_feed)).is_ok());
+        assert!(vault.require_trusted_feed(Some(Pubkey::new_unique())).is_err());
+        assert!(vault.require_trusted_feed(None).is_err());
+    }
+
+    #[test]
+    fn test_withdraw_whitelist_replaces_owner_default() {
+        let mut vault = create_test_vault();
+        let owner = vault.owner;
+        let cold_wallet = Pubkey::new_unique();
+
+        assert!(vault.is_allowed_withdraw_destination(&owner));
+        assert!(!vault.is_allowed_withdraw_destination(&cold_wallet));
+
+        vault.set_withdraw_whitelist(&[cold_wallet]).unwrap();
+        assert!(vault.is_allowed_withdraw_destination(&cold_wallet));
+        assert!(!vault.is_allowed_withdraw_destination(&owner));
+
+        assert!(vault.set_withdraw_whitelist(&[Pubkey::new_unique(); 5]).is_err());
+
+        vault.set_withdraw_whitelist(&[]).unwrap();
+        assert!(vault.is_allowed_withdraw_destination(&owner));
+    }
+
+    #[test]
+    fn test_withdrawals_within_session_deposits_are_not_losses() {
+        let mut vault = create_test_vault();
+        vault.count_withdrawals_as_loss = true;
+        vault.record_session_deposit(1_000_000_000).unwrap();
+
+        assert_eq!(vault.record_withdrawal(600_000_000).unwrap(), 0);
+        assert_eq!(vault.record_withdrawal(400_000_000).unwrap(), 0);
+        assert_eq!(vault.daily_loss_so_far, 0);
+    }
+
+    #[test]
+    fn test_withdrawals_beyond_session_deposits_count_as_loss() {
+        let mut vault = create_test_vault();
+        vault.count_withdrawals_as_loss = true;
+        vault.daily_loss_limit = 100_000_000;
+        vault.record_session_deposit(1_000_000_000).unwrap();
+
+        // Only the part past the deposits counts
+        assert_eq!(vault.record_withdrawal(1_050_000_000).unwrap(), 50_000_000);
+        assert_eq!(vault.record_withdrawal(100_000_000).unwrap(), 100_000_000);
+        assert_eq!(vault.daily_loss_so_far, 150_000_000);
+        assert_eq!(vault.weekly_loss_so_far, 150_000_000);
+        assert!(vault.has_exceeded_loss_limit());
+    }
+
+    #[test]
+    fn test_zero_daily_limit_leaves_counted_withdrawals_unlimited() {
+        let mut vault = create_test_vault();
+        vault.count_withdrawals_as_loss = true;
+        vault.daily_loss_limit = 0;
+
+        assert_eq!(vault.record_withdrawal(500_000_000).unwrap(), 500_000_000);
+        assert_eq!(vault.daily_loss_so_far, 500_000_000);
+        assert!(!vault.has_exceeded_loss_limit());
+    }
+
+    #[test]
+    fn test_zero_limits_leave_trading_unrestricted() {
+        let mut vault = create_test_vault();
+        vault.daily_loss_limit = 0;
+        vault.max_trades_per_day = 0;
+        vault.trades_today = 50;
+
+        vault.record_loss(1_000_000).unwrap();
+        assert!(!vault.has_exceeded_loss_limit());
+        assert!(!vault.has_reached_trade_limit());
+        assert_eq!(vault.enforcement_status(&FixedTime::at(1_700_000_000)).trades_remaining, u8::MAX);
+
+        vault.max_trades_per_day = 50;
+        assert!(vault.has_reached_trade_limit());
+    }
+
+    #[test]
+    fn test_withdrawals_not_counted_when_flag_off() {
+        let mut vault = create_test_vault();
+        vault.record_session_deposit(1_000_000_000).unwrap();
+
+        assert_eq!(vault.record_withdrawal(1_500_000_000).unwrap(), 0);
+        assert_eq!(vault.daily_loss_so_far, 0);
+        assert_eq!(vault.session_net_outflow(), 500_000_000);
+        assert!(!vault.has_exceeded_loss_limit());
+    }
+
+    #[test]
+    fn test_session_reset_clears_withdrawal_tracking() {
+        let mut vault = create_test_vault();
+        vault.count_withdrawals_as_loss = true;
+        vault.record_session_deposit(1_000_000_000).unwrap();
+        vault.record_withdrawal(400_000_000).unwrap();
+
+        vault.reset_daily_counters(&FixedTime::at(1_700_086_400));
+        assert_eq!(vault.session_deposited, 0);
+        assert_eq!(vault.session_withdrawn, 0);
+
+        // Earlier sessions' deposits no longer cover withdrawals
+        assert_eq!(vault.record_withdrawal(100_000_000).unwrap(), 100_000_000);
+    }
+
+    #[test]
+    fn test_profit_target_reached_on_realized_gains() {
+        let mut vault = create_test_vault();
+        vault.daily_profit_target = 250;
+
+        // Buying in is not a gain however well it fills
+        assert!(!vault.record_realized_pnl(1_000, 0).unwrap());
+        assert!(!vault.record_realized_pnl(0, 1_200).unwrap());
+        assert!(!vault.record_realized_pnl(1_000, 0).unwrap());
+        assert!(vault.record_realized_pnl(0, 1_050).unwrap());
+        assert_eq!(vault.session_realized_pnl, 250);
+
+        vault.reset_daily_counters(&FixedTime::at(1_700_086_400));
+        assert_eq!(vault.session_realized_pnl, 0);
+        assert!(!vault.has_hit_profit_target());
+    }
+
+    #[test]
+    fn test_profit_target_unset_never_hit() {
+        let mut vault = create_test_vault();
+        assert!(!vault.record_realized_pnl(0, i64::MAX as u64).unwrap());
+    }
+
+    #[test]
+    fn test_realized_losses_never_hit_profit_target() {
+        let mut vault = create_test_vault();
+        vault.daily_profit_target = 1;
+        assert!(!vault.record_realized_pnl(1_000, 900).unwrap());
+        assert_eq!(vault.session_realized_pnl, -100);
+    }
+
+    #[test]
+    fn test_deposit_cap_allows_up_to_max_balance() {
+        let mut vault = create_test_vault();
+        vault.max_balance = 1_000;
+
+        assert!(vault.check_deposit_cap(400, 600).is_ok());
+        assert!(vault.check_deposit_cap(400, 601).is_err());
+        assert!(vault.check_deposit_cap(1_001, 0).is_err());
+    }
+
+    #[test]
+    fn test_deposit_cap_unset_allows_any_deposit() {
+        let vault = create_test_vault();
+        assert!(vault.check_deposit_cap(u64::MAX / 2, u64::MAX / 2).is_ok());
+    }
+
+    pub(crate) fn create_test_vault_v1(owner: Pubkey) -> VaultV1 {
+        VaultV1 {
+            owner,
+            bump: 254,
+            is_locked: false,
+            lockout_until: 0,
+            lockout_count: 2,
+            lockout_duration: 3600,
+            daily_loss_limit: 5_000,
+            max_trades_per_day: 10,
+            trades_today: 3,
+            session_start: 1_700_000_000,
+            total_deposited: 9_000,
+            total_withdrawn: 1_000,
+            last_trade_was_loss: true,
+            last_trade_time: 1_700_000_100,
+            cooldown_seconds: 600,
+            swap_in_progress: true,
+            pending_swap_source_mint: native_mint::ID,
+            pending_swap_dest_mint: Pubkey::new_unique(),
+            pending_swap_amount_in: 700,
+            pending_swap_min_out: 650,
+            balance_before_swap: 8_000,
+        }
+    }
+
+    /// A vault as the version 1 program serialized it.
+    pub(crate) fn v1_vault_data(legacy: &VaultV1) -> Vec<u8> {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_legacy_vault_detected_and_upgraded() {
+        let legacy = create_test_vault_v1(Pubkey::new_unique());
+        let (address, _) = Pubkey::find_program_address(&[Vault::SEED_PREFIX, legacy.owner.as_ref()], &crate::ID);
+
+        let data = v1_vault_data(&legacy);
+        assert_eq!(data.len(), 8 + VaultV1::INIT_SPACE);
+        assert_eq!(Vault::stored_version(&address, &data), Some(LEGACY_ACCOUNT_VERSION));
+        assert!(Vault::try_deserialize(&mut data.as_slice()).is_err());
+
+        let now = FixedTime::at(1_700_000_200);
+        let mut upgraded = vec![0u8; 8 + Vault::INIT_SPACE];
+        VaultV1::from_account_data(&data)
+            .unwrap()
+            .upgrade(&now)
+            .try_serialize(&mut upgraded.as_mut_slice())
+            .unwrap();
+        assert_eq!(Vault::stored_version(&address, &upgraded), Some(ACCOUNT_VERSION));
+
+        let vault = Vault::try_deserialize(&mut upgraded.as_slice()).unwrap();
+        assert!(vault.is_current_version());
+        assert_eq!(vault.owner, legacy.owner);
+        assert_eq!(vault.bump, 254);
+        assert_eq!(vault.lockout_count, 2);
+        assert_eq!(vault.daily_loss_limit, 5_000);
+        assert_eq!(vault.trades_today, 3);
+        assert_eq!(vault.total_deposited, 9_000);
+        assert_eq!(vault.balance_before_swap, 8_000);
+        assert_eq!(vault.session_duration_seconds, DEFAULT_SESSION_DURATION_SECONDS);
+        assert_eq!(vault.early_unlock_multiplier, DEFAULT_EARLY_UNLOCK_MULTIPLIER);
+        assert_eq!(vault.week_start, 1_700_000_200);
+
+        // The running cooldown and the open SOL swap carry over
+        assert_eq!(vault.cooldown_until, 1_700_000_700);
+        assert!(vault.is_in_cooldown(&now));
+        assert_eq!(vault.reserved_lamports, 700);
+        assert_eq!(vault.swap_started_at, 1_700_000_200);
+    }
+
+    #[test]
+    fn test_vault_at_another_address_has_no_version() {
+        let vault = create_test_vault();
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+
+        assert_eq!(Vault::stored_version(&Pubkey::new_unique(), &data), None);
     }
 }
@@ -1,10 +1,26 @@
 #[cfg(test)]
 mod tests {
     use anchor_lang::Space;
-    use crate::state::Vault;
+    use anchor_lang::prelude::Clock;
+    use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+    use base64::Engine;
+    use crate::account_version::ACCOUNT_VERSION;
+    use crate::constants::{
+        EMERGENCY_DELAY_SECONDS, JUPITER_PROGRAM_ID, JUPITER_ROUTE_DISCRIMINATOR, MAX_COOLDOWN_SECONDS,
+        SWAP_TIMEOUT_SECONDS,
+    };
+    use crate::instructions::get_vault_status::VaultStatus;
+    use crate::instructions::manual_lock::VaultLocked;
+    use crate::instructions::set_rules::RuleUpdate;
+    use crate::instructions::swap::{SwapEnforced, SwapSettled};
+    use crate::state::vault::tests::{create_test_vault_v1, v1_vault_data};
+    use crate::state::{vault_layout, LockoutReason, TokenBalance, Vault, VaultV1};
     use crate::ID as PROGRAM_ID;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
     use litesvm::LiteSVM;
     use solana_sdk::{
+        account::Account,
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
         signature::Keypair,
@@ -52,16 +68,50 @@ mod tests {
     }
 
     fn create_withdraw_ix(owner: &Pubkey, vault: &Pubkey, amount: u64) -> Instruction {
+        create_withdraw_to_ix(owner, vault, None, amount)
+    }
+
+    fn create_withdraw_to_ix(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: Option<&Pubkey>,
+        amount: u64,
+    ) -> Instruction {
         let discriminator = sighash("withdraw");
         let mut data = discriminator.to_vec();
         data.extend_from_slice(&amount.to_le_bytes());
 
+        let destination_meta = match destination {
+            Some(destination) => AccountMeta::new(*destination, false),
+            None => AccountMeta::new_readonly(PROGRAM_ID, false),
+        };
+
         Instruction {
             program_id: PROGRAM_ID,
             accounts: vec![
                 AccountMeta::new(*owner, true),
                 AccountMeta::new(*vault, false),
                 AccountMeta::new_readonly(system_program::ID, false),
+                destination_meta,
+            ],
+            data,
+        }
+    }
+
+    fn create_set_withdraw_whitelist_ix(owner: &Pubkey, vault: &Pubkey, destinations: &[Pubkey]) -> Instruction {
+        let discriminator = sighash("set_withdraw_whitelist");
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&(destinations.len() as u32).to_le_bytes());
+        for destination in destinations {
+            data.extend_from_slice(destination.as_ref());
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(*vault, false),
             ],
             data,
         }
@@ -80,6 +130,94 @@ mod tests {
         }
     }
 
+    fn create_unlock_ix(owner: &Pubkey, vault: &Pubkey, guardian: Option<&Pubkey>) -> Instruction {
+        create_unlock_ix_named("unlock", owner, vault, guardian)
+    }
+
+    fn create_unlock_ix_named(
+        name: &str,
+        owner: &Pubkey,
+        vault: &Pubkey,
+        guardian: Option<&Pubkey>,
+    ) -> Instruction {
+        let discriminator = sighash(name);
+        let guardian_meta = match guardian {
+            Some(guardian) => AccountMeta::new_readonly(*guardian, true),
+            None => AccountMeta::new_readonly(PROGRAM_ID, false),
+        };
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                guardian_meta,
+                AccountMeta::new(*vault, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
+    fn create_get_vault_status_ix(vault: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![AccountMeta::new_readonly(*vault, false)],
+            data: sighash("get_vault_status").to_vec(),
+        }
+    }
+
+    fn create_set_guardian_ix(owner: &Pubkey, vault: &Pubkey, new_guardian: &Pubkey) -> Instruction {
+        let discriminator = sighash("set_guardian");
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(new_guardian.as_ref());
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(*vault, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_emergency_withdraw_ix(name: &str, owner: &Pubkey, vault: &Pubkey, amount: Option<u64>) -> Instruction {
+        create_emergency_withdraw_to_ix(name, owner, vault, amount, None)
+    }
+
+    fn create_emergency_withdraw_to_ix(
+        name: &str,
+        owner: &Pubkey,
+        vault: &Pubkey,
+        amount: Option<u64>,
+        destination: Option<&Pubkey>,
+    ) -> Instruction {
+        let discriminator = sighash(name);
+        let mut data = discriminator.to_vec();
+        let mut accounts = vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*vault, false),
+        ];
+        match name {
+            "request_emergency_withdraw" => {
+                data.extend_from_slice(&amount.unwrap_or_default().to_le_bytes());
+            }
+            "execute_emergency_withdraw" => {
+                accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+                if let Some(destination) = destination {
+                    accounts.push(AccountMeta::new(*destination, false));
+                }
+            }
+            _ => {}
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        }
+    }
+
     fn create_set_rules_ix(
         owner: &Pubkey,
         vault: &Pubkey,
@@ -87,11 +225,70 @@ mod tests {
         max_trades_per_day: u8,
         lockout_duration: u32,
     ) -> Instruction {
+        create_set_rules_ix_with(
+            owner,
+            vault,
+            &TestRules {
+                daily_loss_limit,
+                max_trades_per_day,
+                lockout_duration,
+                ..TestRules::default()
+            },
+        )
+    }
+
+    /// Every `set_rules` argument, for tests that need more than the basics.
+    struct TestRules {
+        daily_loss_limit: u64,
+        max_trades_per_day: u8,
+        lockout_duration: u32,
+        session_duration_seconds: u32,
+        max_position_size: u64,
+        weekly_loss_limit: u64,
+        max_loss_streak: u8,
+        early_unlock_multiplier: u8,
+    }
+
+    impl Default for TestRules {
+        fn default() -> Self {
+            Self {
+                daily_loss_limit: 0,
+                max_trades_per_day: 0,
+                lockout_duration: 0,
+                session_duration_seconds: 86400,
+                max_position_size: 0,
+                weekly_loss_limit: 0,
+                max_loss_streak: 0,
+                early_unlock_multiplier: 0,
+            }
+        }
+    }
+
+    fn create_set_rules_ix_with(owner: &Pubkey, vault: &Pubkey, rules: &TestRules) -> Instruction {
         let discriminator = sighash("set_rules");
         let mut data = discriminator.to_vec();
-        data.extend_from_slice(&daily_loss_limit.to_le_bytes());
-        data.push(max_trades_per_day);
-        data.extend_from_slice(&lockout_duration.to_le_bytes());
+        data.extend_from_slice(&rules.daily_loss_limit.to_le_bytes());
+        data.push(rules.max_trades_per_day);
+        data.extend_from_slice(&rules.lockout_duration.to_le_bytes());
+        data.extend_from_slice(&rules.session_duration_seconds.to_le_bytes());
+        data.extend_from_slice(&rules.max_position_size.to_le_bytes());
+        data.extend_from_slice(&rules.weekly_loss_limit.to_le_bytes());
+        data.push(rules.max_loss_streak);
+        data.push(rules.early_unlock_multiplier);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(*vault, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_update_rules_ix(owner: &Pubkey, vault: &Pubkey, updates: Vec<RuleUpdate>) -> Instruction {
+        let mut data = sighash("update_rules").to_vec();
+        updates.serialize(&mut data).unwrap();
 
         Instruction {
             program_id: PROGRAM_ID,
@@ -112,6 +309,18 @@ mod tests {
         sighash
     }
 
+    /// Decodes every `emit!`ed event of type `E` from a transaction's program logs.
+    fn decode_events<E: AnchorDeserialize + Discriminator>(logs: &[String]) -> Vec<E> {
+        logs.iter()
+            .filter_map(|line| line.strip_prefix("Program data: "))
+            .filter_map(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded).ok())
+            .filter(|data| data.starts_with(E::DISCRIMINATOR))
+            .map(|data| {
+                E::deserialize(&mut &data[E::DISCRIMINATOR.len()..]).expect("event should decode")
+            })
+            .collect()
+    }
+
     fn setup_test() -> (LiteSVM, Keypair, Pubkey, u8) {
         let mut svm = LiteSVM::new();
         let program_bytes = include_bytes!("../../../target/deploy/vault.so");
@@ -204,237 +413,291 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_withdraw_blocked_when_locked() {
+    /// Deposits 1 SOL, moves to the next session and withdraws 0.05 then
+    /// 0.1 SOL against a 0.1 SOL daily loss limit; returns both outcomes.
+    fn withdraw_across_sessions(count_withdrawals_as_loss: bool) -> (bool, bool, Vault) {
         let (mut svm, user, vault_pda, _) = setup_test();
-
         initialize_vault(&mut svm, &user, &vault_pda, 3600);
 
-        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 0, 0, 3600);
-        let blockhash = svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[set_rules_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
+        let rules_ix = create_set_rules_ix_with(
+            &user.pubkey(),
+            &vault_pda,
+            &TestRules {
+                daily_loss_limit: LAMPORTS_PER_SOL / 10,
+                max_trades_per_day: 10,
+                lockout_duration: 3600,
+                ..TestRules::default()
+            },
+        );
+        let update_ix = create_update_rules_ix(
+            &user.pubkey(),
+            &vault_pda,
+            vec![RuleUpdate::CountWithdrawalsAsLoss(count_withdrawals_as_loss)],
         );
-        svm.send_transaction(tx).expect("Set rules should succeed");
-
         let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
         let blockhash = svm.latest_blockhash();
-        let deposit_tx = Transaction::new_signed_with_payer(
-            &[deposit_ix],
+        let tx = Transaction::new_signed_with_payer(
+            &[rules_ix, update_ix, deposit_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
-        svm.send_transaction(deposit_tx).expect("Deposit should succeed");
+        svm.send_transaction(tx).expect("Rules and deposit should succeed");
 
-        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
-        let blockhash = svm.latest_blockhash();
-        let lock_tx = Transaction::new_signed_with_payer(
-            &[lock_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
-        svm.send_transaction(lock_tx).expect("Manual lock should succeed");
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += 86400;
+        svm.set_sysvar(&clock);
 
-        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL / 2);
-        let blockhash = svm.latest_blockhash();
-        let withdraw_tx = Transaction::new_signed_with_payer(
-            &[withdraw_ix],
-            Some(&user.pubkey()),
-            &[&user],
-            blockhash,
-        );
+        let mut outcomes = [false; 2];
+        for (outcome, amount) in outcomes.iter_mut().zip([LAMPORTS_PER_SOL / 20, LAMPORTS_PER_SOL / 10]) {
+            let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, amount);
+            svm.expire_blockhash();
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(
+                &[withdraw_ix],
+                Some(&user.pubkey()),
+                &[&user],
+                blockhash,
+            );
+            *outcome = svm.send_transaction(tx).is_ok();
+        }
 
-        let result = svm.send_transaction(withdraw_tx);
-        assert!(result.is_err(), "Withdraw should fail when vault is locked");
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        (outcomes[0], outcomes[1], vault)
     }
 
     #[test]
-    fn test_withdraw_fails_with_insufficient_funds() {
+    fn test_withdrawals_count_against_loss_limit_when_enabled() {
+        let (first, second, vault) = withdraw_across_sessions(true);
+
+        assert!(first, "Withdrawal within the limit should succeed");
+        assert!(!second, "Withdrawal past the daily loss limit should fail");
+        assert_eq!(vault.daily_loss_so_far, LAMPORTS_PER_SOL / 20);
+        assert_eq!(vault.session_withdrawn, LAMPORTS_PER_SOL / 20);
+    }
+
+    #[test]
+    fn test_withdrawals_ignore_loss_limit_when_disabled() {
+        let (first, second, vault) = withdraw_across_sessions(false);
+
+        assert!(first && second, "Withdrawals should not be held to the loss limit");
+        assert_eq!(vault.daily_loss_so_far, 0);
+        assert_eq!(vault.session_withdrawn, LAMPORTS_PER_SOL * 3 / 20);
+    }
+
+    #[test]
+    fn test_pending_emergency_withdraw_reserves_funds() {
         let (mut svm, user, vault_pda, _) = setup_test();
 
         initialize_vault(&mut svm, &user, &vault_pda, 3600);
 
-        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, 2 * LAMPORTS_PER_SOL);
+        let request_ix = create_emergency_withdraw_ix(
+            "request_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            Some(LAMPORTS_PER_SOL),
+        );
         let blockhash = svm.latest_blockhash();
-        let deposit_tx = Transaction::new_signed_with_payer(
-            &[deposit_ix],
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix, request_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
-        svm.send_transaction(deposit_tx).expect("Deposit should succeed");
+        svm.send_transaction(tx).expect("Deposit and request should succeed");
 
-        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, 100 * LAMPORTS_PER_SOL);
+        // The reserved SOL cannot also leave through a regular withdraw
+        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL + 1);
         let blockhash = svm.latest_blockhash();
-        let withdraw_tx = Transaction::new_signed_with_payer(
+        let tx = Transaction::new_signed_with_payer(
             &[withdraw_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Withdraw should not dip into the reservation");
 
-        let result = svm.send_transaction(withdraw_tx);
-        assert!(result.is_err(), "Withdraw should fail with insufficient funds");
-    }
-
-    #[test]
-    fn test_deposit_fails_without_initialize() {
-        let (mut svm, user, vault_pda, _) = setup_test();
-
-        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
+        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
-            &[deposit_ix],
+            &[withdraw_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
+        svm.send_transaction(tx).expect("Unreserved funds should withdraw");
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += EMERGENCY_DELAY_SECONDS;
+        svm.set_sysvar(&clock);
+        svm.expire_blockhash();
+
+        let execute_ix = create_emergency_withdraw_ix(
+            "execute_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            None,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[execute_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Reserved funds should pay out the emergency withdraw");
 
-        let result = svm.send_transaction(tx);
-        assert!(result.is_err(), "Deposit should fail without initialization");
-    }
-
-    fn create_swap_ix(owner: &Pubkey, vault: &Pubkey, amount_in: u64, min_out: u64) -> Instruction {
-        let discriminator = sighash("swap_with_enforcement");
-        let mut data = discriminator.to_vec();
-        data.extend_from_slice(&amount_in.to_le_bytes());
-        data.extend_from_slice(&min_out.to_le_bytes());
-
-        Instruction {
-            program_id: PROGRAM_ID,
-            accounts: vec![
-                AccountMeta::new(*owner, true),
-                AccountMeta::new(*vault, false),
-            ],
-            data,
-        }
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert_eq!(vault.reserved_lamports, 0);
     }
 
     #[test]
-    fn test_swap_blocked_when_locked() {
+    fn test_withdraw_whitelist_restricts_destination() {
         let (mut svm, user, vault_pda, _) = setup_test();
+        let cold_wallet = Pubkey::new_unique();
+        let attacker = Pubkey::new_unique();
+        svm.airdrop(&cold_wallet, LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&attacker, LAMPORTS_PER_SOL).unwrap();
 
         initialize_vault(&mut svm, &user, &vault_pda, 3600);
 
-        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, 2 * LAMPORTS_PER_SOL);
+        let whitelist_ix = create_set_withdraw_whitelist_ix(&user.pubkey(), &vault_pda, &[cold_wallet]);
         let blockhash = svm.latest_blockhash();
-        let lock_tx = Transaction::new_signed_with_payer(
-            &[lock_ix],
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix, whitelist_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
-        svm.send_transaction(lock_tx).expect("Manual lock should succeed");
+        svm.send_transaction(tx).expect("Deposit and whitelist should succeed");
 
-        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, 1000, 900);
+        let withdraw_ix = create_withdraw_to_ix(&user.pubkey(), &vault_pda, Some(&attacker), LAMPORTS_PER_SOL);
         let blockhash = svm.latest_blockhash();
-        let swap_tx = Transaction::new_signed_with_payer(
-            &[swap_ix],
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
+        assert!(
+            svm.send_transaction(tx).is_err(),
+            "Withdraw to a non-whitelisted address should fail"
+        );
 
-        let result = svm.send_transaction(swap_tx);
-        assert!(result.is_err(), "Swap should fail when vault is locked");
-    }
-
-    #[test]
-    fn test_swap_blocked_during_cooldown() {
-        let (mut svm, user, vault_pda, _) = setup_test();
-
-        initialize_vault(&mut svm, &user, &vault_pda, 3600);
-
-        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
-            &[set_rules_ix],
+            &[withdraw_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
-        svm.send_transaction(tx).expect("Set rules should succeed");
-
-        let mut vault_account = svm.get_account(&vault_pda).unwrap();
-        let last_trade_was_loss_offset = calculate_last_trade_was_loss_offset();
-        vault_account.data[last_trade_was_loss_offset] = 1;
-
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let time_bytes = current_time.to_le_bytes();
-        let last_trade_time_offset = last_trade_was_loss_offset + 1;
-        vault_account.data[last_trade_time_offset..last_trade_time_offset + 8].copy_from_slice(&time_bytes);
-
-        svm.set_account(vault_pda, vault_account).unwrap();
+        assert!(
+            svm.send_transaction(tx).is_err(),
+            "Withdraw to the owner should fail once a whitelist is set"
+        );
 
-        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, 1000, 900);
+        let withdraw_ix = create_withdraw_to_ix(&user.pubkey(), &vault_pda, Some(&cold_wallet), LAMPORTS_PER_SOL);
         let blockhash = svm.latest_blockhash();
-        let swap_tx = Transaction::new_signed_with_payer(
-            &[swap_ix],
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
+        svm.send_transaction(tx).expect("Withdraw to a whitelisted address should succeed");
 
-        let result = svm.send_transaction(swap_tx);
-        assert!(result.is_err(), "Swap should fail during cooldown period");
+        assert_eq!(
+            svm.get_account(&cold_wallet).unwrap().lamports,
+            2 * LAMPORTS_PER_SOL,
+            "Cold wallet should receive the withdrawn lamports"
+        );
     }
 
     #[test]
-    fn test_swap_blocked_at_trade_limit() {
+    fn test_emergency_withdraw_respects_whitelist() {
         let (mut svm, user, vault_pda, _) = setup_test();
+        let cold_wallet = Pubkey::new_unique();
+        let attacker = Pubkey::new_unique();
+        svm.airdrop(&cold_wallet, LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&attacker, LAMPORTS_PER_SOL).unwrap();
 
         initialize_vault(&mut svm, &user, &vault_pda, 3600);
 
-        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 2, 3600);
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, 2 * LAMPORTS_PER_SOL);
+        let whitelist_ix = create_set_withdraw_whitelist_ix(&user.pubkey(), &vault_pda, &[cold_wallet]);
+        let request_ix = create_emergency_withdraw_ix(
+            "request_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            Some(LAMPORTS_PER_SOL),
+        );
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
-            &[set_rules_ix],
+            &[deposit_ix, whitelist_ix, request_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
-        svm.send_transaction(tx).expect("Set rules should succeed");
-
-        for i in 0..2 {
-            let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, 1000 + i, 900);
+        svm.send_transaction(tx).expect("Deposit, whitelist and request should succeed");
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += EMERGENCY_DELAY_SECONDS;
+        svm.set_sysvar(&clock);
+        svm.expire_blockhash();
+
+        for (destination, reason) in [
+            (Some(&attacker), "Emergency withdraw to a non-whitelisted address should fail"),
+            (None, "Emergency withdraw to the owner should fail once a whitelist is set"),
+        ] {
+            let execute_ix = create_emergency_withdraw_to_ix(
+                "execute_emergency_withdraw",
+                &user.pubkey(),
+                &vault_pda,
+                None,
+                destination,
+            );
             let blockhash = svm.latest_blockhash();
-            let swap_tx = Transaction::new_signed_with_payer(
-                &[swap_ix],
+            let tx = Transaction::new_signed_with_payer(
+                &[execute_ix],
                 Some(&user.pubkey()),
                 &[&user],
                 blockhash,
             );
-            svm.send_transaction(swap_tx).expect(&format!("Swap {} should succeed", i + 1));
+            assert!(svm.send_transaction(tx).is_err(), "{}", reason);
         }
 
-        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, 1002, 900);
+        let execute_ix = create_emergency_withdraw_to_ix(
+            "execute_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            None,
+            Some(&cold_wallet),
+        );
         let blockhash = svm.latest_blockhash();
-        let swap_tx = Transaction::new_signed_with_payer(
-            &[swap_ix],
+        let tx = Transaction::new_signed_with_payer(
+            &[execute_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
         );
+        svm.send_transaction(tx).expect("Emergency withdraw to a whitelisted address should succeed");
 
-        let result = svm.send_transaction(swap_tx);
-        assert!(result.is_err(), "Swap should fail when trade limit exceeded");
+        assert_eq!(svm.get_account(&cold_wallet).unwrap().lamports, 2 * LAMPORTS_PER_SOL);
     }
 
     #[test]
-    fn test_swap_success_updates_tracking() {
+    fn test_withdraw_blocked_when_locked() {
         let (mut svm, user, vault_pda, _) = setup_test();
 
         initialize_vault(&mut svm, &user, &vault_pda, 3600);
 
-        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 0, 0, 3600);
         let blockhash = svm.latest_blockhash();
         let tx = Transaction::new_signed_with_payer(
             &[set_rules_ix],
@@ -444,14 +707,1242 @@ mod tests {
         );
         svm.send_transaction(tx).expect("Set rules should succeed");
 
-        let vault_before = svm.get_account(&vault_pda).unwrap();
-        let trades_today_offset = calculate_trades_today_offset();
-        let trades_before = vault_before.data[trades_today_offset];
-
-        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, 1000, 900);
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
         let blockhash = svm.latest_blockhash();
-        let swap_tx = Transaction::new_signed_with_payer(
-            &[swap_ix],
+        let deposit_tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(deposit_tx).expect("Deposit should succeed");
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let lock_tx = Transaction::new_signed_with_payer(
+            &[lock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(lock_tx).expect("Manual lock should succeed");
+
+        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL / 2);
+        let blockhash = svm.latest_blockhash();
+        let withdraw_tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(withdraw_tx);
+        assert!(result.is_err(), "Withdraw should fail when vault is locked");
+    }
+
+    #[test]
+    fn test_withdraw_fails_with_insufficient_funds() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
+        let blockhash = svm.latest_blockhash();
+        let deposit_tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(deposit_tx).expect("Deposit should succeed");
+
+        let withdraw_ix = create_withdraw_ix(&user.pubkey(), &vault_pda, 100 * LAMPORTS_PER_SOL);
+        let blockhash = svm.latest_blockhash();
+        let withdraw_tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(withdraw_tx);
+        assert!(result.is_err(), "Withdraw should fail with insufficient funds");
+    }
+
+    #[test]
+    fn test_unlock_requires_guardian_cosign() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+        let guardian = Keypair::new();
+        svm.airdrop(&guardian.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let set_guardian_ix = create_set_guardian_ix(&user.pubkey(), &vault_pda, &guardian.pubkey());
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_guardian_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("set_guardian should succeed");
+
+        let owner_only_ix = create_unlock_ix(&user.pubkey(), &vault_pda, None);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[owner_only_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Owner-only unlock should fail when a guardian is set");
+
+        let cosigned_ix = create_unlock_ix(&user.pubkey(), &vault_pda, Some(&guardian.pubkey()));
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[cosigned_ix],
+            Some(&user.pubkey()),
+            &[&user, &guardian],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Guardian co-signed unlock should succeed");
+
+        let replace_ix = create_set_guardian_ix(&user.pubkey(), &vault_pda, &Pubkey::default());
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[replace_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Owner should not be able to remove the guardian alone");
+    }
+
+    #[test]
+    fn test_get_vault_status_reports_lockout() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1_000_000, 3, 3600);
+        let status_ix = create_get_vault_status_ix(&vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix, status_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let meta = svm.send_transaction(tx).expect("Status should be readable");
+
+        let status = decode_events::<VaultStatus>(&meta.logs);
+        assert_eq!(status.len(), 1, "Expected one VaultStatus event");
+        assert_eq!(status[0].vault, vault_pda);
+        assert!(!status[0].is_locked_now);
+        assert!(!status[0].in_cooldown);
+        assert_eq!(status[0].trades_remaining, 3);
+        assert_eq!(status[0].seconds_until_unlock, 0);
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let status_ix = create_get_vault_status_ix(&vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[lock_ix, status_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let meta = svm.send_transaction(tx).expect("Status should be readable while locked");
+
+        let status = decode_events::<VaultStatus>(&meta.logs);
+        assert!(status[0].is_locked_now);
+        assert_eq!(status[0].seconds_until_unlock, 3600);
+    }
+
+    #[test]
+    fn test_manual_lock_emits_vault_locked() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[lock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let meta = svm.send_transaction(tx).expect("Manual lock should succeed");
+
+        let clock: Clock = svm.get_sysvar();
+        let locked = decode_events::<VaultLocked>(&meta.logs);
+        assert_eq!(locked.len(), 1, "Expected one VaultLocked event");
+        assert_eq!(locked[0].vault, vault_pda);
+        assert_eq!(locked[0].reason, LockoutReason::Manual);
+        assert_eq!(locked[0].lockout_until, clock.unix_timestamp + 3600);
+        assert_eq!(locked[0].lockout_count, 1);
+    }
+
+    #[test]
+    fn test_early_unlock_lengthens_next_lockout() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let early_unlock_ix = create_unlock_ix_named("early_unlock", &user.pubkey(), &vault_pda, None);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[lock_ix, early_unlock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("First early unlock should succeed");
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[lock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Relock should succeed");
+
+        let clock: Clock = svm.get_sysvar();
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert_eq!(vault.early_unlock_count, 1);
+        assert_eq!(vault.lockout_count, 3);
+        assert_eq!(vault.lockout_until, clock.unix_timestamp + 7200);
+
+        // Without an active lockout there is nothing to break early
+        let unlock_ix = create_unlock_ix(&user.pubkey(), &vault_pda, None);
+        let early_unlock_ix = create_unlock_ix_named("early_unlock", &user.pubkey(), &vault_pda, None);
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += 7200;
+        svm.set_sysvar(&clock);
+        svm.expire_blockhash();
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[unlock_ix, early_unlock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Early unlock after expiry should fail");
+    }
+
+    #[test]
+    fn test_emergency_withdraw_honors_delay_during_lockout() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, 2 * LAMPORTS_PER_SOL);
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let request_ix = create_emergency_withdraw_ix(
+            "request_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            Some(LAMPORTS_PER_SOL),
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix, lock_ix, request_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Deposit, lock and request should succeed");
+
+        let execute_ix = create_emergency_withdraw_ix(
+            "execute_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            None,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[execute_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Emergency withdraw should not execute before the delay");
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += EMERGENCY_DELAY_SECONDS;
+        svm.set_sysvar(&clock);
+        svm.expire_blockhash();
+
+        let execute_ix = create_emergency_withdraw_ix(
+            "execute_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            None,
+        );
+        let user_balance_before = svm.get_account(&user.pubkey()).unwrap().lamports;
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[execute_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Emergency withdraw should execute after the delay");
+
+        let user_balance_after = svm.get_account(&user.pubkey()).unwrap().lamports;
+        assert!(user_balance_after > user_balance_before);
+    }
+
+    #[test]
+    fn test_emergency_withdraw_request_above_balance_rejected() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Deposit should succeed");
+
+        let request_ix = create_emergency_withdraw_ix(
+            "request_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            Some(LAMPORTS_PER_SOL + 1),
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[request_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Request above the vault balance should fail");
+
+        // Nothing was reserved, so regular withdrawals are not held up
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert_eq!(vault.reserved_lamports, 0);
+        assert!(!vault.has_pending_emergency_withdraw());
+    }
+
+    #[test]
+    fn test_emergency_withdraw_can_be_cancelled() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, 2 * LAMPORTS_PER_SOL);
+        let request_ix = create_emergency_withdraw_ix(
+            "request_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            Some(LAMPORTS_PER_SOL),
+        );
+        let cancel_ix = create_emergency_withdraw_ix(
+            "cancel_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            None,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix, request_ix, cancel_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Deposit, request then cancel should succeed");
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += EMERGENCY_DELAY_SECONDS;
+        svm.set_sysvar(&clock);
+
+        let execute_ix = create_emergency_withdraw_ix(
+            "execute_emergency_withdraw",
+            &user.pubkey(),
+            &vault_pda,
+            None,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[execute_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Cancelled request should not be executable");
+    }
+
+    #[test]
+    fn test_set_rules_rejects_zero_session_duration() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let set_rules_ix = create_set_rules_ix_with(
+            &user.pubkey(),
+            &vault_pda,
+            &TestRules {
+                daily_loss_limit: 1000,
+                max_trades_per_day: 10,
+                lockout_duration: 3600,
+                session_duration_seconds: 0,
+                ..TestRules::default()
+            },
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "set_rules should reject a zero session duration");
+    }
+
+    /// Caps a fresh vault at rent plus one SOL and deposits `amount` into it.
+    fn deposit_under_cap(amount: u64) -> (bool, u64) {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let rent = svm.get_account(&vault_pda).unwrap().lamports;
+
+        let update_ix = create_update_rules_ix(
+            &user.pubkey(),
+            &vault_pda,
+            vec![RuleUpdate::MaxBalance(rent + LAMPORTS_PER_SOL)],
+        );
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, amount);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[update_ix, deposit_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let succeeded = svm.send_transaction(tx).is_ok();
+        (succeeded, svm.get_account(&vault_pda).unwrap().lamports - rent)
+    }
+
+    #[test]
+    fn test_deposit_within_cap_succeeds() {
+        let (succeeded, deposited) = deposit_under_cap(LAMPORTS_PER_SOL);
+
+        assert!(succeeded, "Deposit up to the cap should succeed");
+        assert_eq!(deposited, LAMPORTS_PER_SOL);
+    }
+
+    #[test]
+    fn test_deposit_over_cap_rejected() {
+        let (succeeded, deposited) = deposit_under_cap(LAMPORTS_PER_SOL + 1);
+
+        assert!(!succeeded, "Deposit past the cap should fail");
+        assert_eq!(deposited, 0);
+    }
+
+    fn create_migrate_account_ix(payer: &Pubkey, vault: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*payer, true),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: sighash("migrate_account").to_vec(),
+        }
+    }
+
+    fn send_deposit(svm: &mut LiteSVM, user: &Keypair, vault_pda: &Pubkey) -> bool {
+        let deposit_ix = create_deposit_ix(&user.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+        svm.expire_blockhash();
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&user.pubkey()),
+            &[user],
+            blockhash,
+        );
+        svm.send_transaction(tx).is_ok()
+    }
+
+    #[test]
+    fn test_migrate_account_upgrades_legacy_vault() {
+        let (mut svm, user, vault_pda, bump) = setup_test();
+
+        // A vault as the version 1 program created it
+        let legacy = VaultV1 {
+            bump,
+            last_trade_was_loss: false,
+            swap_in_progress: false,
+            ..create_test_vault_v1(user.pubkey())
+        };
+        let data = v1_vault_data(&legacy);
+        let vault_account = Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        svm.set_account(vault_pda, vault_account).unwrap();
+
+        assert!(!send_deposit(&mut svm, &user, &vault_pda), "A legacy vault must be migrated first");
+
+        // Migrating twice upgrades once and then leaves the vault alone
+        for _ in 0..2 {
+            let migrate_ix = create_migrate_account_ix(&user.pubkey(), &vault_pda);
+            svm.expire_blockhash();
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(
+                &[migrate_ix],
+                Some(&user.pubkey()),
+                &[&user],
+                blockhash,
+            );
+            svm.send_transaction(tx).expect("Migration should succeed");
+        }
+
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        assert_eq!(vault_account.data.len(), 8 + Vault::INIT_SPACE);
+        assert!(vault_account.lamports >= svm.minimum_balance_for_rent_exemption(vault_account.data.len()));
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert_eq!(vault.version, ACCOUNT_VERSION);
+        assert_eq!(vault.owner, user.pubkey());
+        assert_eq!(vault.lockout_duration, 3600);
+        assert_eq!(vault.daily_loss_limit, 5_000);
+        assert_eq!(vault.total_deposited, 9_000);
+
+        assert!(send_deposit(&mut svm, &user, &vault_pda), "A migrated vault should take deposits");
+    }
+
+    #[test]
+    fn test_deposit_fails_without_initialize() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        let deposit_ix = create_deposit_ix(&user.pubkey(), &vault_pda, LAMPORTS_PER_SOL);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Deposit should fail without initialization");
+    }
+
+    fn create_swap_ix(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        amount_in: u64,
+        min_out: u64,
+    ) -> Instruction {
+        create_swap_ix_with_program(owner, vault, destination, &JUPITER_PROGRAM_ID, amount_in, min_out)
+    }
+
+    fn create_swap_ix_with_program(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        jupiter_program: &Pubkey,
+        amount_in: u64,
+        min_out: u64,
+    ) -> Instruction {
+        create_swap_ix_with_route(owner, vault, destination, jupiter_program, &PROGRAM_ID, &[], amount_in, min_out)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_swap_ix_with_route(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        jupiter_program: &Pubkey,
+        price_feed: &Pubkey,
+        route_accounts: &[AccountMeta],
+        amount_in: u64,
+        min_out: u64,
+    ) -> Instruction {
+        create_swap_ix_with_route_data(
+            owner,
+            vault,
+            destination,
+            jupiter_program,
+            price_feed,
+            route_accounts,
+            amount_in,
+            min_out,
+            &jupiter_route_data(amount_in, min_out),
+        )
+    }
+
+    /// A Jupiter `route` with an empty route plan swapping `in_amount`.
+    fn jupiter_route_data(in_amount: u64, quoted_out_amount: u64) -> Vec<u8> {
+        let mut data = JUPITER_ROUTE_DISCRIMINATOR.to_vec();
+        // route_plan: empty Vec
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&in_amount.to_le_bytes());
+        data.extend_from_slice(&quoted_out_amount.to_le_bytes());
+        // slippage_bps, platform_fee_bps
+        data.extend_from_slice(&50u16.to_le_bytes());
+        data.push(0);
+        data
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_swap_ix_with_route_data(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        jupiter_program: &Pubkey,
+        price_feed: &Pubkey,
+        route_accounts: &[AccountMeta],
+        amount_in: u64,
+        min_out: u64,
+        route_data: &[u8],
+    ) -> Instruction {
+        let discriminator = sighash("swap_with_enforcement");
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_out.to_le_bytes());
+        data.extend_from_slice(&(route_data.len() as u32).to_le_bytes());
+        data.extend_from_slice(route_data);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new_readonly(*jupiter_program, false),
+                AccountMeta::new_readonly(*price_feed, false),
+            ]
+            .into_iter()
+            .chain(route_accounts.iter().cloned())
+            .collect(),
+            data,
+        }
+    }
+
+    fn create_pre_swap_check_ix(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        amount_in: u64,
+        min_out: u64,
+    ) -> Instruction {
+        create_pre_swap_check_ix_between(
+            owner,
+            vault,
+            destination,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            amount_in,
+            min_out,
+        )
+    }
+
+    fn create_pre_swap_check_ix_between(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        source_mint: &Pubkey,
+        dest_mint: &Pubkey,
+        amount_in: u64,
+        min_out: u64,
+    ) -> Instruction {
+        let discriminator = sighash("pre_swap_check");
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(source_mint.as_ref());
+        data.extend_from_slice(dest_mint.as_ref());
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_out.to_le_bytes());
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new_readonly(*destination, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_pre_swap_check_ix_with_feed(
+        owner: &Pubkey,
+        vault: &Pubkey,
+        destination: &Pubkey,
+        price_feed: &Pubkey,
+    ) -> Instruction {
+        let mut ix = create_pre_swap_check_ix(owner, vault, destination, 1000, 900);
+        ix.accounts.push(AccountMeta::new_readonly(*price_feed, false));
+        ix
+    }
+
+    fn create_set_trusted_feeds_ix(owner: &Pubkey, vault: &Pubkey, feeds: &[Pubkey]) -> Instruction {
+        let discriminator = sighash("set_trusted_feeds");
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&(feeds.len() as u32).to_le_bytes());
+        for feed in feeds {
+            data.extend_from_slice(feed.as_ref());
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(*vault, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_post_swap_update_ix(owner: &Pubkey, vault: &Pubkey, destination: &Pubkey) -> Instruction {
+        let discriminator = sighash("post_swap_update");
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new_readonly(*destination, false),
+            ],
+            data: discriminator.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_swap_blocked_when_locked() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let lock_tx = Transaction::new_signed_with_payer(
+            &[lock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(lock_tx).expect("Manual lock should succeed");
+
+        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[swap_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(swap_tx);
+        assert!(result.is_err(), "Swap should fail when vault is locked");
+    }
+
+    #[test]
+    fn test_loss_streak_auto_locks_vault() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix_with(
+            &user.pubkey(),
+            &vault_pda,
+            &TestRules {
+                daily_loss_limit: 1_000_000,
+                max_trades_per_day: 10,
+                lockout_duration: 3600,
+                max_loss_streak: 3,
+                ..TestRules::default()
+            },
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // Nothing lands in the destination account, so every swap settles as a loss
+        for i in 0..3 {
+            let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+            let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, &destination);
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(
+                &[pre_ix, post_ix],
+                Some(&user.pubkey()),
+                &[&user],
+                blockhash,
+            );
+            let meta = svm.send_transaction(tx)
+                .unwrap_or_else(|_| panic!("Losing swap {} should settle", i + 1));
+
+            let locked = decode_events::<VaultLocked>(&meta.logs);
+            if i < 2 {
+                assert!(locked.is_empty());
+            } else {
+                assert_eq!(locked.len(), 1, "Third loss should lock the vault");
+                assert_eq!(locked[0].reason, LockoutReason::LossStreak);
+            }
+        }
+
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[pre_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Swap should be blocked after the loss streak locks the vault");
+    }
+
+    /// Settles one swap losing 900 and returns the lockouts it emitted.
+    fn settle_losing_swap(svm: &mut LiteSVM, user: &Keypair, vault_pda: &Pubkey, destination: &Pubkey) -> Vec<VaultLocked> {
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), vault_pda, destination, 1000, 900);
+        let post_ix = create_post_swap_update_ix(&user.pubkey(), vault_pda, destination);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[pre_ix, post_ix],
+            Some(&user.pubkey()),
+            &[user],
+            blockhash,
+        );
+        let meta = svm.send_transaction(tx).expect("Losing swap should settle");
+        decode_events::<VaultLocked>(&meta.logs)
+    }
+
+    #[test]
+    fn test_loss_limit_auto_locks_vault() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 500, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let locked = settle_losing_swap(&mut svm, &user, &vault_pda, &destination);
+        assert_eq!(locked.len(), 1, "Crossing the daily loss limit should lock the vault");
+        assert_eq!(locked[0].reason, LockoutReason::LossLimit);
+    }
+
+    #[test]
+    fn test_loss_limit_without_lockout_duration_does_not_lock() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 0);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 500, 10, 0);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let locked = settle_losing_swap(&mut svm, &user, &vault_pda, &destination);
+        assert!(locked.is_empty(), "Nothing is enforced, so no lockout is reported");
+
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert!(!vault.is_locked);
+        assert_eq!(vault.lockout_count, 0);
+    }
+
+    #[test]
+    fn test_cooldown_escalation_auto_locks_vault() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 500, 10, 3600);
+        let update_ix = create_update_rules_ix(
+            &user.pubkey(),
+            &vault_pda,
+            vec![RuleUpdate::CooldownSeconds(86_400)],
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix, update_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // 900 against a limit of 500 would earn well over a day of cooldown
+        let locked = settle_losing_swap(&mut svm, &user, &vault_pda, &destination);
+        assert_eq!(locked.len(), 1, "An over-cap cooldown should lock the vault once");
+        assert_eq!(locked[0].reason, LockoutReason::CooldownEscalation);
+    }
+
+    #[test]
+    fn test_swap_blocked_during_cooldown() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let mut vault_account = svm.get_account(&vault_pda).unwrap();
+        let last_trade_was_loss_offset = vault_layout::LAST_TRADE_WAS_LOSS;
+        vault_account.data[last_trade_was_loss_offset] = 1;
+
+        let now = svm.get_sysvar::<Clock>().unix_timestamp;
+        let cooldown_until_offset = vault_layout::COOLDOWN_UNTIL;
+        vault_account.data[cooldown_until_offset..cooldown_until_offset + 8]
+            .copy_from_slice(&(now + 3600).to_le_bytes());
+
+        svm.set_account(vault_pda, vault_account).unwrap();
+
+        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[swap_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(swap_tx);
+        assert!(result.is_err(), "Swap should fail during cooldown period");
+    }
+
+    #[test]
+    fn test_set_rules_cooldown_blocks_swaps_after_loss() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let cooldown_ix = |cooldown_seconds| {
+            create_update_rules_ix(&user.pubkey(), &vault_pda, vec![RuleUpdate::CooldownSeconds(cooldown_seconds)])
+        };
+        let too_long_ix = cooldown_ix(MAX_COOLDOWN_SECONDS as u32 + 1);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[too_long_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        assert!(svm.send_transaction(tx).is_err(), "Cooldown past the max should be rejected");
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 0, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix, cooldown_ix(600)],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // Simulate a losing trade: with no daily limit the flat cooldown applies
+        let clock: Clock = svm.get_sysvar();
+        let mut vault_account = svm.get_account(&vault_pda).unwrap();
+        let mut vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert_eq!(vault.cooldown_seconds, 600);
+        vault.start_cooldown(1, &clock).unwrap();
+        vault.try_serialize(&mut vault_account.data.as_mut_slice()).unwrap();
+        svm.set_account(vault_pda, vault_account).unwrap();
+
+        let swap = |svm: &mut LiteSVM| {
+            let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+            let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, &destination);
+            svm.expire_blockhash();
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(
+                &[pre_ix, post_ix],
+                Some(&user.pubkey()),
+                &[&user],
+                blockhash,
+            );
+            svm.send_transaction(tx)
+        };
+
+        assert!(swap(&mut svm).is_err(), "Swap should fail during the cooldown");
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += 600;
+        svm.set_sysvar(&clock);
+        assert!(swap(&mut svm).is_ok(), "Swap should succeed once the cooldown ends");
+    }
+
+    #[test]
+    fn test_winning_swaps_past_profit_target_lock_vault() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (token_mint, token_account) = mint_test_token(&mut svm, &vault_pda, 0);
+        let sol_account = create_token_account(&mut svm, &spl_token::native_mint::ID, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 0, 10, 3600);
+        let update_ix = create_update_rules_ix(&user.pubkey(), &vault_pda, vec![RuleUpdate::DailyProfitTarget(250)]);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix, update_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let swap = |svm: &mut LiteSVM, source_mint: &Pubkey, dest_mint: &Pubkey, destination: &Pubkey, out: u64| {
+            let pre_ix = create_pre_swap_check_ix_between(
+                &user.pubkey(),
+                &vault_pda,
+                destination,
+                source_mint,
+                dest_mint,
+                1000,
+                900,
+            );
+            svm.expire_blockhash();
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(&[pre_ix], Some(&user.pubkey()), &[&user], blockhash);
+            svm.send_transaction(tx).expect("Pre-swap check should succeed");
+
+            let mut token_account = svm.get_account(destination).unwrap();
+            let mut token = spl_token::state::Account::unpack(&token_account.data).unwrap();
+            token.amount += out;
+            token.pack_into_slice(&mut token_account.data);
+            svm.set_account(*destination, token_account).unwrap();
+
+            let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, destination);
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(&[post_ix], Some(&user.pubkey()), &[&user], blockhash);
+            svm.send_transaction(tx).expect("Post-swap update should succeed");
+
+            let vault_account = svm.get_account(&vault_pda).unwrap();
+            Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap()
+        };
+
+        // Buys 1000 lamports of the token and sells it back for 1100
+        let round_trip = |svm: &mut LiteSVM| {
+            let native = spl_token::native_mint::ID;
+            let vault = swap(svm, &native, &token_mint, &token_account, 1500);
+            assert!(!vault.is_locked, "Filling a buy above its minimum is not profit");
+            swap(svm, &token_mint, &native, &sol_account, 1100)
+        };
+
+        for expected_pnl in [100, 200] {
+            let vault = round_trip(&mut svm);
+            assert_eq!(vault.session_realized_pnl, expected_pnl);
+            assert!(!vault.is_locked, "Vault should stay open below the target");
+        }
+
+        let vault = round_trip(&mut svm);
+        assert_eq!(vault.session_realized_pnl, 300);
+        assert!(vault.is_locked, "Crossing the profit target should lock the vault");
+
+        svm.expire_blockhash();
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &token_account, 1000, 900);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(&[pre_ix], Some(&user.pubkey()), &[&user], blockhash);
+        assert!(svm.send_transaction(tx).is_err(), "Swaps should be blocked once locked");
+    }
+
+    #[test]
+    fn test_pre_swap_check_rejects_untrusted_feed() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let trusted_feed = Pubkey::new_unique();
+        let spoofed_feed = Pubkey::new_unique();
+
+        let trust_ix = create_set_trusted_feeds_ix(&user.pubkey(), &vault_pda, &[trusted_feed]);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(&[trust_ix], Some(&user.pubkey()), &[&user], blockhash);
+        svm.send_transaction(tx).expect("Setting trusted feeds should succeed");
+
+        let spoofed_ix = create_pre_swap_check_ix_with_feed(&user.pubkey(), &vault_pda, &destination, &spoofed_feed);
+        let tx = Transaction::new_signed_with_payer(&[spoofed_ix], Some(&user.pubkey()), &[&user], blockhash);
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "A feed outside the trusted list should be rejected");
+
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert!(!vault.swap_in_progress);
+
+        let trusted_ix = create_pre_swap_check_ix_with_feed(&user.pubkey(), &vault_pda, &destination, &trusted_feed);
+        let tx = Transaction::new_signed_with_payer(&[trusted_ix], Some(&user.pubkey()), &[&user], blockhash);
+        svm.send_transaction(tx).expect("A trusted feed should pass");
+
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert!(vault.swap_in_progress);
+    }
+
+    #[test]
+    fn test_swap_feed_is_not_taken_from_route_accounts() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let trusted_feed = Pubkey::new_unique();
+        let trust_ix = create_set_trusted_feeds_ix(&user.pubkey(), &vault_pda, &[trusted_feed]);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(&[trust_ix], Some(&user.pubkey()), &[&user], blockhash);
+        svm.send_transaction(tx).expect("Setting trusted feeds should succeed");
+
+        // A route whose first account is the trusted feed still needs the
+        // feed in its own slot
+        let route = [
+            AccountMeta::new_readonly(trusted_feed, false),
+            AccountMeta::new(destination, false),
+        ];
+        let swap_ix = create_swap_ix_with_route(
+            &user.pubkey(), &vault_pda, &destination, &JUPITER_PROGRAM_ID, &PROGRAM_ID, &route, 1000, 900,
+        );
+        let tx = Transaction::new_signed_with_payer(&[swap_ix], Some(&user.pubkey()), &[&user], blockhash);
+        let logs = svm.send_transaction(tx).unwrap_err().meta.logs.join("\n");
+        assert!(logs.contains("UntrustedPriceFeed"), "Missing feed should be rejected: {logs}");
+
+        // With the feed in its slot the whole route is handed to Jupiter
+        let swap_ix = create_swap_ix_with_route(
+            &user.pubkey(), &vault_pda, &destination, &JUPITER_PROGRAM_ID, &trusted_feed, &route, 1000, 900,
+        );
+        let tx = Transaction::new_signed_with_payer(&[swap_ix], Some(&user.pubkey()), &[&user], blockhash);
+        let logs = svm.send_transaction(tx).unwrap_err().meta.logs.join("\n");
+        assert!(logs.contains("Pre-trade enforcement passed"), "Trusted feed should pass: {logs}");
+    }
+
+    #[test]
+    fn test_swap_rejects_route_for_other_amount() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        // Enforcement sees 1000 in while the route would swap far more
+        for route_data in [jupiter_route_data(1_000_000, 900), Vec::new()] {
+            let swap_ix = create_swap_ix_with_route_data(
+                &user.pubkey(), &vault_pda, &destination, &JUPITER_PROGRAM_ID, &PROGRAM_ID, &[], 1000, 900,
+                &route_data,
+            );
+            let blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(&[swap_ix], Some(&user.pubkey()), &[&user], blockhash);
+            let logs = svm.send_transaction(tx).unwrap_err().meta.logs.join("\n");
+            assert!(logs.contains("RouteAmountMismatch"), "Mismatched route should be rejected: {logs}");
+        }
+    }
+
+    #[test]
+    fn test_swap_blocked_at_trade_limit() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 2, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // Two trades already taken today
+        let mut vault_account = svm.get_account(&vault_pda).unwrap();
+        vault_account.data[vault_layout::TRADES_TODAY] = 2;
+        svm.set_account(vault_pda, vault_account).unwrap();
+
+        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, &destination, 1002, 900);
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[swap_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(swap_tx);
+        assert!(result.is_err(), "Swap should fail when trade limit exceeded");
+    }
+
+    #[test]
+    fn test_swap_success_updates_tracking() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let vault_before = svm.get_account(&vault_pda).unwrap();
+        let trades_today_offset = vault_layout::TRADES_TODAY;
+        let trades_before = vault_before.data[trades_today_offset];
+
+        // Route through the split pre/post flow, since no Jupiter program is deployed here
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, &destination);
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[pre_ix, post_ix],
             Some(&user.pubkey()),
             &[&user],
             blockhash,
@@ -466,23 +1957,458 @@ mod tests {
         assert_eq!(trades_after, trades_before + 1, "trades_today should increment by 1");
     }
 
-    fn calculate_trades_today_offset() -> usize {
-        8 +  // discriminator
-        32 + // owner (Pubkey)
-        1 +  // bump (u8)
-        1 +  // is_locked (bool)
-        8 +  // lockout_until (i64)
-        4 +  // lockout_count (u32)
-        4 +  // lockout_duration (u32)
-        8 +  // daily_loss_limit (u64)
-        1    // max_trades_per_day (u8)
-    }
-
-    fn calculate_last_trade_was_loss_offset() -> usize {
-        calculate_trades_today_offset() +
-        1 +  // trades_today (u8)
-        8 +  // session_start (i64)
-        8 +  // total_deposited (u64)
-        8    // total_withdrawn (u64)
+    #[test]
+    fn test_stuck_swap_cleared_after_timeout() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // The swap between pre and post never lands, leaving the guard set
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[pre_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Pre-swap check should succeed");
+
+        let clear_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(user.pubkey(), true),
+                AccountMeta::new(vault_pda, false),
+                AccountMeta::new_readonly(destination, false),
+            ],
+            data: sighash("clear_stuck_swap").to_vec(),
+        };
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            std::slice::from_ref(&clear_ix),
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Clear should fail before the swap times out");
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp += SWAP_TIMEOUT_SECONDS;
+        svm.set_sysvar(&clock);
+        svm.expire_blockhash();
+
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[clear_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Clear should succeed after the timeout");
+
+        let vault_account = svm.get_account(&vault_pda).unwrap();
+        let vault = Vault::try_deserialize(&mut vault_account.data.as_slice()).unwrap();
+        assert!(!vault.swap_in_progress);
+        assert_eq!(vault.swap_started_at, 0);
+
+        // Nothing arrived, so the abandoned swap settles as a full loss
+        assert_eq!(vault.trades_today, 1);
+        assert_eq!(vault.daily_loss_so_far, 900);
+        assert!(vault.last_trade_was_loss);
+
+        // The guard no longer blocks a fresh swap
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, &destination);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[pre_ix, post_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Swap should proceed after clearing");
+    }
+
+    #[test]
+    fn test_swap_emits_enforced_and_settled_events() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // Nothing lands in the destination account, so the swap settles as a loss
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, &destination);
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[pre_ix, post_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let meta = svm.send_transaction(swap_tx).expect("Swap should succeed");
+
+        let enforced = decode_events::<SwapEnforced>(&meta.logs);
+        assert_eq!(enforced.len(), 1, "Expected one SwapEnforced event");
+        assert_eq!(enforced[0].vault, vault_pda);
+        assert_eq!(enforced[0].amount_in, 1000);
+        assert_eq!(enforced[0].min_out, 900);
+        assert_eq!(enforced[0].trades_today, 0);
+
+        let settled = decode_events::<SwapSettled>(&meta.logs);
+        assert_eq!(settled.len(), 1, "Expected one SwapSettled event");
+        assert_eq!(settled[0].vault, vault_pda);
+        assert_eq!(settled[0].actual_out, 0);
+        assert!(settled[0].was_loss);
+    }
+
+    #[test]
+    fn test_swap_respects_max_position_size() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix_with(
+            &user.pubkey(),
+            &vault_pda,
+            &TestRules {
+                daily_loss_limit: 1000000,
+                max_trades_per_day: 10,
+                lockout_duration: 3600,
+                max_position_size: 1000,
+                ..TestRules::default()
+            },
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let over_cap_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1001, 900);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[over_cap_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        let result = svm.send_transaction(tx);
+        assert!(result.is_err(), "Swap above max_position_size should be rejected");
+
+        let pre_ix = create_pre_swap_check_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let post_ix = create_post_swap_update_ix(&user.pubkey(), &vault_pda, &destination);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[pre_ix, post_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Swap at max_position_size should succeed");
+    }
+
+    #[test]
+    fn test_swap_rejects_wrong_jupiter_program() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000000, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        let swap_ix = create_swap_ix_with_program(
+            &user.pubkey(),
+            &vault_pda,
+            &destination,
+            &system_program::ID,
+            1000,
+            900,
+        );
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[swap_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(swap_tx);
+        assert!(result.is_err(), "Swap should fail with a non-Jupiter program");
+    }
+
+    #[test]
+    fn test_swap_blocked_after_daily_loss_limit() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+        let (_, destination) = mint_test_token(&mut svm, &vault_pda, 0);
+
+        let set_rules_ix = create_set_rules_ix(&user.pubkey(), &vault_pda, 1000, 10, 3600);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_rules_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("Set rules should succeed");
+
+        // Simulate a losing swap that left the vault 1 over its daily limit
+        let mut vault_account = svm.get_account(&vault_pda).unwrap();
+        let daily_loss_offset = vault_layout::DAILY_LOSS_SO_FAR;
+        vault_account.data[daily_loss_offset..daily_loss_offset + 8]
+            .copy_from_slice(&1001u64.to_le_bytes());
+        svm.set_account(vault_pda, vault_account).unwrap();
+
+        let swap_ix = create_swap_ix(&user.pubkey(), &vault_pda, &destination, 1000, 900);
+        let blockhash = svm.latest_blockhash();
+        let swap_tx = Transaction::new_signed_with_payer(
+            &[swap_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(swap_tx);
+        assert!(result.is_err(), "Swap should fail once daily loss limit is exceeded");
+    }
+
+    fn get_token_balance_pda(vault: &Pubkey, mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[TokenBalance::SEED_PREFIX, vault.as_ref(), mint.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0
+    }
+
+    fn get_vault_token_account_pda(vault: &Pubkey, mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[TokenBalance::TOKEN_ACCOUNT_SEED_PREFIX, vault.as_ref(), mint.as_ref()],
+            &PROGRAM_ID,
+        )
+        .0
+    }
+
+    fn create_spl_transfer_ix(
+        name: &str,
+        owner: &Pubkey,
+        vault: &Pubkey,
+        mint: &Pubkey,
+        owner_token_account: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let discriminator = sighash(name);
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+
+        let mut accounts = vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*vault, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(get_token_balance_pda(vault, mint), false),
+            AccountMeta::new(*owner_token_account, false),
+            AccountMeta::new(get_vault_token_account_pda(vault, mint), false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ];
+        if name == "deposit_spl" {
+            accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+        }
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data,
+        }
+    }
+
+    /// Creates a mint and a token account for `owner` holding `amount` tokens.
+    fn mint_test_token(svm: &mut LiteSVM, owner: &Pubkey, amount: u64) -> (Pubkey, Pubkey) {
+        let mint = Pubkey::new_unique();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: Some(*owner).into(),
+            supply: amount,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        }
+        .pack_into_slice(&mut mint_data);
+        svm.set_account(
+            mint,
+            Account {
+                lamports: svm.minimum_balance_for_rent_exemption(mint_data.len()),
+                data: mint_data,
+                owner: spl_token::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+
+        (mint, create_token_account(svm, &mint, owner, amount))
+    }
+
+    fn create_token_account(svm: &mut LiteSVM, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let token_account = Pubkey::new_unique();
+        let mut token_data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_data);
+        svm.set_account(
+            token_account,
+            Account {
+                lamports: svm.minimum_balance_for_rent_exemption(token_data.len()),
+                data: token_data,
+                owner: spl_token::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+
+        token_account
+    }
+
+    #[test]
+    fn test_spl_withdraw_blocked_when_locked() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let (mint, user_token_account) = mint_test_token(&mut svm, &user.pubkey(), 1_000_000);
+
+        let deposit_ix = create_spl_transfer_ix(
+            "deposit_spl",
+            &user.pubkey(),
+            &vault_pda,
+            &mint,
+            &user_token_account,
+            500_000,
+        );
+        let blockhash = svm.latest_blockhash();
+        let deposit_tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(deposit_tx).expect("SPL deposit should succeed");
+
+        let lock_ix = create_manual_lock_ix(&user.pubkey(), &vault_pda);
+        let blockhash = svm.latest_blockhash();
+        let lock_tx = Transaction::new_signed_with_payer(
+            &[lock_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(lock_tx).expect("Manual lock should succeed");
+
+        let withdraw_ix = create_spl_transfer_ix(
+            "withdraw_spl",
+            &user.pubkey(),
+            &vault_pda,
+            &mint,
+            &user_token_account,
+            250_000,
+        );
+        let blockhash = svm.latest_blockhash();
+        let withdraw_tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+
+        let result = svm.send_transaction(withdraw_tx);
+        assert!(result.is_err(), "SPL withdraw should fail when vault is locked");
+    }
+
+    #[test]
+    fn test_spl_withdraw_respects_whitelist() {
+        let (mut svm, user, vault_pda, _) = setup_test();
+
+        initialize_vault(&mut svm, &user, &vault_pda, 3600);
+
+        let (mint, user_token_account) = mint_test_token(&mut svm, &user.pubkey(), 1_000_000);
+
+        let deposit_ix = create_spl_transfer_ix(
+            "deposit_spl",
+            &user.pubkey(),
+            &vault_pda,
+            &mint,
+            &user_token_account,
+            500_000,
+        );
+        let whitelist_ix =
+            create_set_withdraw_whitelist_ix(&user.pubkey(), &vault_pda, &[Pubkey::new_unique()]);
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix, whitelist_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        svm.send_transaction(tx).expect("SPL deposit and whitelist should succeed");
+
+        let withdraw_ix = create_spl_transfer_ix(
+            "withdraw_spl",
+            &user.pubkey(),
+            &vault_pda,
+            &mint,
+            &user_token_account,
+            250_000,
+        );
+        let blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&user.pubkey()),
+            &[&user],
+            blockhash,
+        );
+        assert!(
+            svm.send_transaction(tx).is_err(),
+            "SPL withdraw to the owner's account should fail once a whitelist excludes it"
+        );
     }
 }
@@ -0,0 +1,107 @@
+//! Account layout versioning shared by every program. Each versioned
+//! account stores a `version` byte right after its discriminator so code
+//! reading it can tell which layout it was written with.
+//!
+//! Accounts written before that byte existed are version 1. Nothing in
+//! their bytes says so, but every versioned account is a PDA of seeds that
+//! start with its leading fields, so only one of the two layouts derives
+//! the account's own address. Each program keeps its version 1 layouts
+//! next to the current ones and upgrades them field by field.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+/// Layout of accounts created before the version byte was added.
+pub const LEGACY_ACCOUNT_VERSION: u8 = 1;
+/// Layout new accounts are created with.
+pub const ACCOUNT_VERSION: u8 = 2;
+
+/// Byte offset of `version`, right after the discriminator.
+pub const VERSION_OFFSET: usize = 8;
+
+/// Layout version of the raw account `data`, or `None` when neither layout
+/// derives its address. `derives_address` is handed the bytes the account's
+/// fields start at under each layout and checks them against the address.
+pub fn stored_version(data: &[u8], derives_address: impl Fn(&[u8]) -> bool) -> Option<u8> {
+    if data.len() <= VERSION_OFFSET {
+        return None;
+    }
+    if derives_address(&data[VERSION_OFFSET + 1..]) {
+        Some(data[VERSION_OFFSET])
+    } else if derives_address(&data[VERSION_OFFSET..]) {
+        Some(LEGACY_ACCOUNT_VERSION)
+    } else {
+        None
+    }
+}
+
+/// Writes `state` over `account`, resized to `space` bytes with `payer`
+/// covering any extra rent. Rent freed by a smaller layout stays put.
+pub fn rewrite_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    state: &T,
+    space: usize,
+) -> Result<()> {
+    let mut data = Vec::with_capacity(space);
+    state.try_serialize(&mut data)?;
+    require!(
+        data.len() <= space,
+        anchor_lang::error::ErrorCode::AccountDidNotSerialize
+    );
+    data.resize(space, 0);
+
+    let top_up = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+
+    account.resize(space)?;
+    account.try_borrow_mut_data()?.copy_from_slice(&data);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn starts_with_owner(owner: Pubkey) -> impl Fn(&[u8]) -> bool {
+        move |fields| fields.starts_with(owner.as_ref())
+    }
+
+    #[test]
+    fn test_layout_detected_from_address() {
+        let owner = Pubkey::new_unique();
+        let legacy = [&DISCRIMINATOR[..], owner.as_ref(), &[9, 9]].concat();
+        let current = [&DISCRIMINATOR[..], &[ACCOUNT_VERSION], owner.as_ref(), &[9, 9]].concat();
+
+        assert_eq!(
+            stored_version(&legacy, starts_with_owner(owner)),
+            Some(LEGACY_ACCOUNT_VERSION)
+        );
+        assert_eq!(stored_version(&current, starts_with_owner(owner)), Some(ACCOUNT_VERSION));
+    }
+
+    #[test]
+    fn test_unrelated_account_has_no_version() {
+        let owner = Pubkey::new_unique();
+        let data = [&DISCRIMINATOR[..], &[ACCOUNT_VERSION], owner.as_ref()].concat();
+
+        assert_eq!(stored_version(&data, starts_with_owner(Pubkey::new_unique())), None);
+        assert_eq!(stored_version(&DISCRIMINATOR, starts_with_owner(owner)), None);
+    }
+}
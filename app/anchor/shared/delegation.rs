@@ -0,0 +1,89 @@
+//! Delegation-program PDAs, shared by ghost-crank and ghost-bridge the same
+//! way as `trigger`. Redelegation call handlers take these accounts from the
+//! keeper, so they are re-derived here before being handed to the program.
+
+use anchor_lang::prelude::*;
+
+pub const BUFFER_SEED: &[u8] = b"buffer";
+pub const DELEGATION_RECORD_SEED: &[u8] = b"delegation";
+pub const DELEGATION_METADATA_SEED: &[u8] = b"delegation-metadata";
+
+/// Buffer, record and metadata PDAs, in that order, the delegation program
+/// expects when delegating `delegated`. The buffer belongs to the account's
+/// `owner_program`; the other two to the delegation program.
+pub fn delegation_pdas(
+    delegated: &Pubkey,
+    owner_program: &Pubkey,
+    delegation_program: &Pubkey,
+) -> [Pubkey; 3] {
+    let find = |seed: &[u8], program: &Pubkey| {
+        Pubkey::find_program_address(&[seed, delegated.as_ref()], program).0
+    };
+    [
+        find(BUFFER_SEED, owner_program),
+        find(DELEGATION_RECORD_SEED, delegation_program),
+        find(DELEGATION_METADATA_SEED, delegation_program),
+    ]
+}
+
+/// Whether the first three `accounts` are exactly `delegation_pdas`, in order.
+pub fn is_delegation_accounts(
+    accounts: &[AccountInfo],
+    delegated: &Pubkey,
+    owner_program: &Pubkey,
+    delegation_program: &Pubkey,
+) -> bool {
+    accounts.len() >= 3
+        && accounts[..3]
+            .iter()
+            .map(|account| account.key())
+            .eq(delegation_pdas(delegated, owner_program, delegation_program))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(keys: &[Pubkey], delegated: &Pubkey, delegation_program: &Pubkey) -> bool {
+        let owner = Pubkey::default();
+        let mut lamports: Vec<u64> = vec![0; keys.len()];
+        let mut data: Vec<Vec<u8>> = vec![Vec::new(); keys.len()];
+        let accounts: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, true, lamports, data, &owner, false, 0)
+            })
+            .collect();
+        is_delegation_accounts(&accounts, delegated, &crate::ID, delegation_program)
+    }
+
+    #[test]
+    fn test_expected_delegation_pdas_accepted() {
+        let delegated = Pubkey::new_unique();
+        let delegation_program = Pubkey::new_unique();
+        let pdas = delegation_pdas(&delegated, &crate::ID, &delegation_program);
+
+        assert!(check(&pdas, &delegated, &delegation_program));
+    }
+
+    #[test]
+    fn test_wrong_delegation_pdas_rejected() {
+        let delegated = Pubkey::new_unique();
+        let delegation_program = Pubkey::new_unique();
+        let [buffer, record, metadata] = delegation_pdas(&delegated, &crate::ID, &delegation_program);
+
+        // Arbitrary keeper-supplied account in any slot
+        assert!(!check(&[Pubkey::new_unique(), record, metadata], &delegated, &delegation_program));
+        assert!(!check(&[buffer, Pubkey::new_unique(), metadata], &delegated, &delegation_program));
+        assert!(!check(&[buffer, record, Pubkey::new_unique()], &delegated, &delegation_program));
+        // Right PDAs in the wrong order
+        assert!(!check(&[record, buffer, metadata], &delegated, &delegation_program));
+        // PDAs of some other delegated account
+        let other = delegation_pdas(&Pubkey::new_unique(), &crate::ID, &delegation_program);
+        assert!(!check(&other, &delegated, &delegation_program));
+        // Too few accounts
+        assert!(!check(&[buffer, record], &delegated, &delegation_program));
+    }
+}
@@ -0,0 +1,569 @@
+//! Drift instruction builders, shared by ghost-crank and ghost-bridge the
+//! same way as `trigger`: each program includes this file as its own
+//! `drift_cpi` module, so `OrderSide` resolves to the program's own type.
+
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::ephem::CallHandler;
+use ephemeral_rollups_sdk::{ActionArgs, ShortAccountMeta};
+
+use crate::state::OrderSide;
+
+pub const DRIFT_PROGRAM_ID: Pubkey = pubkey!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+
+/// 8-byte Anchor discriminator for Drift's place_perp_order instruction.
+/// Computed as: sha256("global:place_perp_order")[0..8]
+/// Verified: echo -n "global:place_perp_order" | sha256sum -> 45a15dca787e4cb9...
+pub const PLACE_PERP_ORDER_DISCRIMINATOR: [u8; 8] = [0x45, 0xa1, 0x5d, 0xca, 0x78, 0x7e, 0x4c, 0xb9];
+
+/// 8-byte Anchor discriminator for Drift's place_spot_order instruction.
+/// Verified: echo -n "global:place_spot_order" | sha256sum -> 2d4f51a0f85a5bdc...
+pub const PLACE_SPOT_ORDER_DISCRIMINATOR: [u8; 8] = [0x2d, 0x4f, 0x51, 0xa0, 0xf8, 0x5a, 0x5b, 0xdc];
+
+/// Anchor discriminator of Drift's `place_and_take_perp_order` (`sha256("global:place_and_take_perp_order")[..8]`).
+pub const PLACE_AND_TAKE_PERP_ORDER_DISCRIMINATOR: [u8; 8] = [0xd5, 0x33, 0x01, 0xbb, 0x6c, 0xdc, 0xe6, 0xe0];
+
+/// `OrderParams.bitFlags` bit that makes Drift cancel whatever part of the
+/// order did not fill within the instruction.
+pub const IMMEDIATE_OR_CANCEL_FLAG: u8 = 0b0000_0001;
+
+/// Anchor discriminator of Drift's `cancel_order` (`sha256("global:cancel_order")[..8]`).
+pub const CANCEL_ORDER_DISCRIMINATOR: [u8; 8] = [0x5f, 0x81, 0xed, 0xf0, 0x08, 0x31, 0xdf, 0x84];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum DriftOrderType {
+    Market = 0,
+    Limit = 1,
+    TriggerMarket = 2,
+    TriggerLimit = 3,
+    Oracle = 4,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum DriftMarketType {
+    Spot = 0,
+    #[default]
+    Perp = 1,
+}
+
+impl DriftMarketType {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(DriftMarketType::Spot),
+            1 => Some(DriftMarketType::Perp),
+            _ => None,
+        }
+    }
+
+    /// Discriminator of the Drift instruction that places an order on this market type.
+    pub fn place_order_discriminator(self) -> [u8; 8] {
+        match self {
+            DriftMarketType::Spot => PLACE_SPOT_ORDER_DISCRIMINATOR,
+            DriftMarketType::Perp => PLACE_PERP_ORDER_DISCRIMINATOR,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum DriftPostOnlyParam {
+    None = 0,
+    MustPostOnly = 1,
+    TryPostOnly = 2,
+    Slide = 3,
+}
+
+/// Build instruction data for Drift's place_perp_order or place_spot_order.
+/// Both take the same OrderParams; the market type picks the discriminator.
+///
+/// This constructs a properly formatted instruction according to the Drift IDL v2.150.0.
+/// The OrderParams struct uses Borsh serialization with Option types encoded as:
+/// - None: 1 byte (0x00)
+/// - Some(value): 1 byte (0x01) + value bytes
+///
+/// # Layout
+/// ```text
+/// [0-7]    discriminator (8 bytes)
+/// [8]      orderType (1 byte enum)
+/// [9]      marketType (1 byte enum: Spot=0, Perp=1)
+/// [10]     direction (1 byte enum: Long=0, Short=1)
+/// [11]     userOrderId (1 byte, 0 for auto-assign)
+/// [12-19]  baseAssetAmount (8 bytes u64 LE)
+/// [20-27]  price (8 bytes u64 LE)
+/// [28-29]  marketIndex (2 bytes u16 LE)
+/// [30]     reduceOnly (1 byte bool)
+/// [31]     postOnly (1 byte enum)
+/// [32]     bitFlags (1 byte u8 bitmask)
+/// [33]     maxTs (Option<i64>: 1 byte None, or 0x01 + 8 bytes i64 LE)
+/// [34]     triggerPrice (Option<u64>: 1 byte None)
+/// [35]     triggerCondition (1 byte enum, ignored when triggerPrice=None)
+/// [36]     oraclePriceOffset (Option<i32>: 1 byte None, or 0x01 + 4 bytes i32 LE)
+/// [37]     auctionDuration (Option<u8>: 1 byte None)
+/// [38]     auctionStartPrice (Option<i64>: 1 byte None)
+/// [39]     auctionEndPrice (Option<i64>: 1 byte None)
+/// ```
+///
+/// Each `Some` option shifts the fields after it: a `maxTs` by eight bytes,
+/// an oracle offset by four.
+pub fn build_drift_place_order(
+    market_type: DriftMarketType,
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    reduce_only: bool,
+) -> Vec<u8> {
+    build_drift_place_order_full(
+        market_type,
+        DriftOrderType::Market,
+        market_index,
+        side,
+        base_asset_amount,
+        0, // price (0 for market orders)
+        reduce_only,
+        0, // bitFlags
+        None,
+        None,
+    )
+}
+
+/// Build a market order for a perp market.
+pub fn build_drift_place_perp_order(
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    reduce_only: bool,
+) -> Vec<u8> {
+    build_drift_place_order(
+        DriftMarketType::Perp,
+        market_index,
+        side,
+        base_asset_amount,
+        reduce_only,
+    )
+}
+
+/// Build a perp oracle order that rests at the oracle price plus `offset`
+/// (in Drift price precision, 1e6), for passive fills that track the oracle.
+pub fn build_drift_oracle_order(
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    offset: i32,
+) -> Vec<u8> {
+    build_drift_place_perp_order_full(
+        DriftOrderType::Oracle,
+        market_index,
+        side,
+        base_asset_amount,
+        0, // price is taken from the oracle
+        false,
+        0,
+        None,
+        Some(offset),
+    )
+}
+
+/// Build a perp order instruction with more options.
+#[allow(clippy::too_many_arguments)]
+pub fn build_drift_place_perp_order_full(
+    order_type: DriftOrderType,
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    price: u64,
+    reduce_only: bool,
+    bit_flags: u8,
+    max_ts: Option<i64>,
+    oracle_price_offset: Option<i32>,
+) -> Vec<u8> {
+    build_drift_place_order_full(
+        DriftMarketType::Perp,
+        order_type,
+        market_index,
+        side,
+        base_asset_amount,
+        price,
+        reduce_only,
+        bit_flags,
+        max_ts,
+        oracle_price_offset,
+    )
+}
+
+/// Build an order instruction for either market type with more options.
+#[allow(clippy::too_many_arguments)]
+pub fn build_drift_place_order_full(
+    market_type: DriftMarketType,
+    order_type: DriftOrderType,
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    price: u64,
+    reduce_only: bool,
+    bit_flags: u8,
+    max_ts: Option<i64>,
+    oracle_price_offset: Option<i32>,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(52);
+
+    // 8-byte discriminator
+    data.extend_from_slice(&market_type.place_order_discriminator());
+
+    // OrderParams struct (Borsh serialized)
+    data.push(order_type as u8);
+    data.push(market_type as u8);
+
+    // direction
+    data.push(match side {
+        OrderSide::Long => 0,
+        OrderSide::Short => 1,
+    });
+
+    // userOrderId (0 = auto-assign)
+    data.push(0u8);
+
+    // baseAssetAmount (u64 LE)
+    data.extend_from_slice(&base_asset_amount.to_le_bytes());
+
+    // price (u64 LE)
+    data.extend_from_slice(&price.to_le_bytes());
+
+    // marketIndex (u16 LE)
+    data.extend_from_slice(&market_index.to_le_bytes());
+
+    // reduceOnly (bool)
+    data.push(if reduce_only { 1 } else { 0 });
+
+    // postOnly (enum)
+    data.push(DriftPostOnlyParam::None as u8);
+
+    // bitFlags (u8 bitmask)
+    data.push(bit_flags);
+
+    // Option fields - None for basic market orders
+    // Each None is encoded as a single 0x00 byte (Borsh Option discriminant)
+    match max_ts {
+        Some(ts) => {
+            data.push(1u8);
+            data.extend_from_slice(&ts.to_le_bytes());
+        }
+        None => data.push(0u8),
+    }
+    data.push(0u8); // triggerPrice: None
+    data.push(0u8); // triggerCondition: Above=0 (ignored when triggerPrice=None)
+    match oracle_price_offset {
+        Some(offset) => {
+            data.push(1u8);
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        None => data.push(0u8),
+    }
+    data.push(0u8); // auctionDuration: None
+    data.push(0u8); // auctionStartPrice: None
+    data.push(0u8); // auctionEndPrice: None
+
+    data
+}
+
+/// Build an order that goes to the book at `price`: a limit order, or a
+/// market order when `price` is zero. `max_ts` makes Drift expire the
+/// order itself at that unix timestamp.
+pub fn build_drift_place_order_at_price(
+    market_type: DriftMarketType,
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    price: u64,
+    reduce_only: bool,
+    max_ts: Option<i64>,
+) -> Vec<u8> {
+    let order_type = if price > 0 {
+        DriftOrderType::Limit
+    } else {
+        DriftOrderType::Market
+    };
+    build_drift_place_order_full(
+        market_type,
+        order_type,
+        market_index,
+        side,
+        base_asset_amount,
+        price,
+        reduce_only,
+        0,
+        max_ts,
+        None,
+    )
+}
+
+/// Instruction data for Drift's `place_and_take_perp_order`: the perp
+/// `place_perp_order` params, immediate-or-cancel, then a `None`
+/// success condition. The order fills (or not) inside the CPI and nothing
+/// rests on the book, so the position delta right after it is the fill.
+pub fn build_drift_place_and_take_perp_order(
+    market_index: u16,
+    side: OrderSide,
+    base_asset_amount: u64,
+    price: u64,
+    reduce_only: bool,
+    max_ts: Option<i64>,
+) -> Vec<u8> {
+    let order_type = if price > 0 {
+        DriftOrderType::Limit
+    } else {
+        DriftOrderType::Market
+    };
+    let mut data = build_drift_place_perp_order_full(
+        order_type,
+        market_index,
+        side,
+        base_asset_amount,
+        price,
+        reduce_only,
+        IMMEDIATE_OR_CANCEL_FLAG,
+        max_ts,
+        None,
+    );
+    data[..8].copy_from_slice(&PLACE_AND_TAKE_PERP_ORDER_DISCRIMINATOR);
+    data.push(0);
+    data
+}
+
+/// Magic Action handler that runs `data` against Drift on the base layer
+/// with `compute_units` of budget.
+pub fn drift_call_handler<'info>(
+    escrow_authority: AccountInfo<'info>,
+    accounts: Vec<ShortAccountMeta>,
+    data: Vec<u8>,
+    compute_units: u32,
+) -> CallHandler<'info> {
+    CallHandler {
+        destination_program: DRIFT_PROGRAM_ID,
+        accounts,
+        args: ActionArgs::new(data),
+        escrow_authority,
+        compute_units,
+    }
+}
+
+/// Instruction data for Drift's `cancel_order`: the discriminator followed
+/// by `order_id` as a Borsh `Option<u32>`. Drift treats `None` as "the
+/// user's last order", so the id is always sent.
+pub fn build_drift_cancel_order(order_id: u32) -> Vec<u8> {
+    let mut data = Vec::with_capacity(13);
+    data.extend_from_slice(&CANCEL_ORDER_DISCRIMINATOR);
+    data.push(1);
+    data.extend_from_slice(&order_id.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discriminator_is_correct() {
+        // This test verifies the discriminator matches sha256("global:place_perp_order")[0..8]
+        // Expected: 0x45, 0xa1, 0x5d, 0xca, 0x78, 0x7e, 0x4c, 0xb9
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[0], 0x45);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[1], 0xa1);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[2], 0x5d);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[3], 0xca);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[4], 0x78);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[5], 0x7e);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[6], 0x4c);
+        assert_eq!(PLACE_PERP_ORDER_DISCRIMINATOR[7], 0xb9);
+    }
+
+    #[test]
+    fn test_build_market_order() {
+        let data = build_drift_place_perp_order(0, OrderSide::Long, 1_000_000_000, false);
+
+        // Check discriminator (8 bytes)
+        assert_eq!(&data[0..8], &PLACE_PERP_ORDER_DISCRIMINATOR);
+
+        // Check orderType = Market (0)
+        assert_eq!(data[8], 0);
+
+        // Check marketType = Perp (1)
+        assert_eq!(data[9], 1);
+
+        // Check direction = Long (0)
+        assert_eq!(data[10], 0);
+
+        // Check userOrderId = 0
+        assert_eq!(data[11], 0);
+
+        // Check baseAssetAmount (1_000_000_000 in LE)
+        let amount = u64::from_le_bytes(data[12..20].try_into().unwrap());
+        assert_eq!(amount, 1_000_000_000);
+
+        // Check price = 0 (market order)
+        let price = u64::from_le_bytes(data[20..28].try_into().unwrap());
+        assert_eq!(price, 0);
+
+        // Check marketIndex = 0
+        let market_idx = u16::from_le_bytes(data[28..30].try_into().unwrap());
+        assert_eq!(market_idx, 0);
+
+        // Check reduceOnly = false
+        assert_eq!(data[30], 0);
+
+        // Check postOnly = None (0)
+        assert_eq!(data[31], 0);
+
+        // Check bitFlags = 0
+        assert_eq!(data[32], 0);
+
+        // Check Option fields are all None (0)
+        assert_eq!(&data[33..40], &[0u8; 7]);
+
+        // Total length should be 40 bytes
+        assert_eq!(data.len(), 40);
+    }
+
+    #[test]
+    fn test_build_short_order() {
+        let data = build_drift_place_perp_order(1, OrderSide::Short, 500_000_000, true);
+
+        // Check direction = Short (1)
+        assert_eq!(data[10], 1);
+
+        // Check marketIndex = 1
+        let market_idx = u16::from_le_bytes(data[28..30].try_into().unwrap());
+        assert_eq!(market_idx, 1);
+
+        // Check reduceOnly = true
+        assert_eq!(data[30], 1);
+    }
+
+    #[test]
+    fn test_build_spot_order() {
+        let data = build_drift_place_order(DriftMarketType::Spot, 1, OrderSide::Long, 2_000_000_000, false);
+
+        // Spot orders go to place_spot_order with marketType = Spot (0)
+        assert_eq!(&data[0..8], &PLACE_SPOT_ORDER_DISCRIMINATOR);
+        assert_eq!(data[9], 0);
+
+        // The rest of OrderParams is laid out exactly as for perps
+        let perp = build_drift_place_order(DriftMarketType::Perp, 1, OrderSide::Long, 2_000_000_000, false);
+        assert_eq!(&perp[0..8], &PLACE_PERP_ORDER_DISCRIMINATOR);
+        assert_eq!(perp[9], 1);
+        assert_eq!(&data[10..], &perp[10..]);
+        assert_eq!(data.len(), 40);
+    }
+
+    #[test]
+    fn test_build_oracle_order_with_offset() {
+        let data = build_drift_oracle_order(0, OrderSide::Long, 1_000_000_000, -250_000);
+
+        assert_eq!(&data[0..8], &PLACE_PERP_ORDER_DISCRIMINATOR);
+        assert_eq!(data[8], DriftOrderType::Oracle as u8);
+        assert_eq!(u64::from_le_bytes(data[20..28].try_into().unwrap()), 0);
+
+        // oraclePriceOffset: Some(-250_000)
+        assert_eq!(data[36], 0x01);
+        assert_eq!(i32::from_le_bytes(data[37..41].try_into().unwrap()), -250_000);
+
+        // Auction fields follow the offset, still None
+        assert_eq!(&data[41..44], &[0, 0, 0]);
+        assert_eq!(data.len(), 44);
+    }
+
+    #[test]
+    fn test_oracle_offset_none_keeps_layout() {
+        let full = build_drift_place_perp_order_full(
+            DriftOrderType::Market,
+            0,
+            OrderSide::Long,
+            1_000_000_000,
+            0,
+            false,
+            0,
+            None,
+            None,
+        );
+
+        assert_eq!(full, build_drift_place_perp_order(0, OrderSide::Long, 1_000_000_000, false));
+        assert_eq!(full[36], 0x00);
+        assert_eq!(full.len(), 40);
+    }
+
+    #[test]
+    fn test_build_order_with_max_ts() {
+        let data = build_drift_place_perp_order_full(
+            DriftOrderType::Limit,
+            0,
+            OrderSide::Short,
+            1_000_000_000,
+            150_000_000,
+            false,
+            0,
+            Some(1_700_000_600),
+            Some(100),
+        );
+
+        // maxTs: Some(1_700_000_600)
+        assert_eq!(data[33], 0x01);
+        assert_eq!(i64::from_le_bytes(data[34..42].try_into().unwrap()), 1_700_000_600);
+
+        // triggerPrice None, triggerCondition, then the oracle offset
+        assert_eq!(data[42], 0);
+        assert_eq!(data[43], 0);
+        assert_eq!(data[44], 0x01);
+        assert_eq!(i32::from_le_bytes(data[45..49].try_into().unwrap()), 100);
+        assert_eq!(data.len(), 52);
+    }
+
+    #[test]
+    fn test_market_type_from_u8() {
+        assert_eq!(DriftMarketType::from_u8(0), Some(DriftMarketType::Spot));
+        assert_eq!(DriftMarketType::from_u8(1), Some(DriftMarketType::Perp));
+        assert_eq!(DriftMarketType::from_u8(2), None);
+    }
+
+    #[test]
+    fn test_priced_order_is_limit_unless_price_zero() {
+        let limit = build_drift_place_order_at_price(
+            DriftMarketType::Spot,
+            1,
+            OrderSide::Long,
+            5_000,
+            150_000_000,
+            false,
+            None,
+        );
+        assert_eq!(&limit[0..8], &PLACE_SPOT_ORDER_DISCRIMINATOR);
+        assert_eq!(limit[8], DriftOrderType::Limit as u8);
+        assert_eq!(u64::from_le_bytes(limit[20..28].try_into().unwrap()), 150_000_000);
+
+        let market =
+            build_drift_place_order_at_price(DriftMarketType::Perp, 0, OrderSide::Short, 1_000, 0, true, None);
+        assert_eq!(market, build_drift_place_perp_order(0, OrderSide::Short, 1_000, true));
+    }
+
+    #[test]
+    fn test_place_and_take_is_immediate_or_cancel() {
+        let data = build_drift_place_and_take_perp_order(2, OrderSide::Long, 1_000, 151_000_000, false, None);
+
+        assert_eq!(&data[0..8], &PLACE_AND_TAKE_PERP_ORDER_DISCRIMINATOR);
+        assert_eq!(data[9], DriftMarketType::Perp as u8);
+        assert_eq!(u64::from_le_bytes(data[20..28].try_into().unwrap()), 151_000_000);
+        assert_eq!(data[32], IMMEDIATE_OR_CANCEL_FLAG);
+        // success_condition: None
+        assert_eq!(data[40], 0);
+        assert_eq!(data.len(), 41);
+    }
+
+    #[test]
+    fn test_cancel_order_data() {
+        let data = build_drift_cancel_order(42);
+
+        assert_eq!(&data[0..8], &CANCEL_ORDER_DISCRIMINATOR);
+        // Some(order_id)
+        assert_eq!(data[8], 1);
+        assert_eq!(u32::from_le_bytes(data[9..13].try_into().unwrap()), 42);
+        assert_eq!(data.len(), 13);
+    }
+}
@@ -0,0 +1,58 @@
+//! Minimum order sizes. Drift's smallest accepted size differs per market,
+//! so the crank admin registers one per market in ghost-crank's market
+//! registry, which both programs read when an order is created.
+
+use anchor_lang::prelude::*;
+
+use crate::drift_cpi::DriftMarketType;
+
+/// Minimum for a market with none registered, in Drift base precision
+/// (1e9): 0.01 of the base asset.
+pub const DEFAULT_MIN_BASE_ASSET_AMOUNT: u64 = 10_000_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MarketMinOrderSize {
+    pub market_type: DriftMarketType,
+    pub market_index: u16,
+    pub min_base_asset_amount: u64,
+}
+
+impl MarketMinOrderSize {
+    pub const LEN: usize = 1 + 2 + 8;
+}
+
+/// The minimum `sizes` registers for the market, or the default.
+pub fn min_base_asset_amount(
+    sizes: &[MarketMinOrderSize],
+    market_type: DriftMarketType,
+    market_index: u16,
+) -> u64 {
+    sizes
+        .iter()
+        .find(|s| s.market_type == market_type && s.market_index == market_index)
+        .map_or(DEFAULT_MIN_BASE_ASSET_AMOUNT, |s| s.min_base_asset_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_minimum_overrides_default() {
+        let sizes = [MarketMinOrderSize {
+            market_type: DriftMarketType::Perp,
+            market_index: 1,
+            min_base_asset_amount: 1_000_000,
+        }];
+
+        assert_eq!(min_base_asset_amount(&sizes, DriftMarketType::Perp, 1), 1_000_000);
+        assert_eq!(
+            min_base_asset_amount(&sizes, DriftMarketType::Spot, 1),
+            DEFAULT_MIN_BASE_ASSET_AMOUNT
+        );
+        assert_eq!(
+            min_base_asset_amount(&sizes, DriftMarketType::Perp, 0),
+            DEFAULT_MIN_BASE_ASSET_AMOUNT
+        );
+    }
+}
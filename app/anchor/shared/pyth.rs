@@ -0,0 +1,365 @@
+//! Pyth receiver `PriceUpdateV2` parsing, shared by ghost-crank and
+//! ghost-bridge the same way as `trigger`. Errors resolve to each program's
+//! `oracle::OracleError`.
+
+use anchor_lang::prelude::*;
+
+use crate::oracle::OracleError;
+use crate::time::TimeSource;
+
+pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// A Pyth price is rejected when its confidence exceeds `price / MAX_CONFIDENCE_RATIO` (2%).
+pub const MAX_CONFIDENCE_RATIO: u64 = 50;
+
+/// Pyth prices older than this many seconds are rejected as stale.
+pub const MAX_PRICE_STALENESS_SECONDS: i64 = 30;
+
+/// Exponent every oracle price is normalized to before trigger comparison (micro-units).
+pub const PRICE_TARGET_EXPONENT: i32 = -6;
+
+/// Anchor discriminator of the Pyth receiver's `PriceUpdateV2` account
+/// (`sha256("account:PriceUpdateV2")[..8]`).
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// How many Wormhole guardian signatures backed the posted update.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// Borsh layout of the Pyth receiver's `PriceUpdateV2` account, after the discriminator.
+///
+/// # Layout
+/// ```text
+/// [0-7]    discriminator
+/// [8-39]   write_authority
+/// [40]     verification_level (0 = Partial + u8 num_signatures, 1 = Full)
+/// [..]     price_message: feed_id, price, conf, exponent, publish_time,
+///          prev_publish_time, ema_price, ema_conf
+/// [..]     posted_slot (u64)
+/// ```
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PythPrice {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub confidence: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub verification_level: VerificationLevel,
+}
+
+impl PythPrice {
+    /// Rejects prices whose confidence band is wider than `price / MAX_CONFIDENCE_RATIO`.
+    pub fn validate_confidence(&self) -> Result<()> {
+        if self.price <= 0 {
+            msg!("Non-positive Pyth price: {}", self.price);
+            return Err(OracleError::InvalidPriceFeed.into());
+        }
+
+        let max_confidence = self.price as u64 / MAX_CONFIDENCE_RATIO;
+        if self.confidence > max_confidence {
+            msg!(
+                "Pyth confidence too wide: conf={}, price={}, max_conf={}",
+                self.confidence,
+                self.price,
+                max_confidence
+            );
+            return Err(OracleError::InvalidPriceFeed.into());
+        }
+
+        Ok(())
+    }
+
+    /// Rejects prices published more than `MAX_PRICE_STALENESS_SECONDS` before `now`.
+    pub fn validate_freshness(&self, now: &impl TimeSource) -> Result<()> {
+        let age = now.unix_timestamp().saturating_sub(self.publish_time);
+        if age > MAX_PRICE_STALENESS_SECONDS {
+            msg!(
+                "Pyth price is stale: publish_time={}, age={}s",
+                self.publish_time,
+                age
+            );
+            return Err(OracleError::PriceFeedStale.into());
+        }
+
+        Ok(())
+    }
+
+    /// Rescales the price to `PRICE_TARGET_EXPONENT` so callers compare like with like.
+    pub fn normalized(&self) -> Result<i64> {
+        let shift = self.exponent - PRICE_TARGET_EXPONENT;
+        let factor = 10i64
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(OracleError::InvalidPriceFeed)?;
+
+        if shift >= 0 {
+            self.price
+                .checked_mul(factor)
+                .ok_or_else(|| OracleError::InvalidPriceFeed.into())
+        } else {
+            Ok(self.price / factor)
+        }
+    }
+}
+
+/// Deserializes a `PriceUpdateV2` account into the fields the programs consume.
+pub fn parse_pyth_price(data: &[u8]) -> Result<PythPrice> {
+    if data.len() < PRICE_UPDATE_V2_DISCRIMINATOR.len()
+        || data[..PRICE_UPDATE_V2_DISCRIMINATOR.len()] != PRICE_UPDATE_V2_DISCRIMINATOR
+    {
+        msg!("Invalid price feed discriminator");
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+
+    let update = PriceUpdateV2::deserialize(&mut &data[PRICE_UPDATE_V2_DISCRIMINATOR.len()..])
+        .map_err(|_| {
+            msg!("Malformed price update: {} bytes", data.len());
+            OracleError::InvalidPriceFeed
+        })?;
+    let message = update.price_message;
+
+    Ok(PythPrice {
+        feed_id: message.feed_id,
+        price: message.price,
+        confidence: message.conf,
+        exponent: message.exponent,
+        publish_time: message.publish_time,
+        verification_level: update.verification_level,
+    })
+}
+
+/// Parses and validates raw feed data, returning the price at `PRICE_TARGET_EXPONENT`.
+pub fn load_pyth_price(data: &[u8], now: &impl TimeSource) -> Result<i64> {
+    let pyth_price = parse_pyth_price(data)?;
+
+    pyth_price.validate_freshness(now)?;
+    pyth_price.validate_confidence()?;
+    pyth_price.normalized()
+}
+
+/// Like `load_pyth_price`, but only for an update of `feed_id`.
+pub fn load_pyth_price_for_feed(data: &[u8], feed_id: &[u8; 32], now: &impl TimeSource) -> Result<i64> {
+    let pyth_price = parse_pyth_price(data)?;
+    require!(pyth_price.feed_id == *feed_id, OracleError::InvalidPriceFeed);
+
+    pyth_price.validate_freshness(now)?;
+    pyth_price.validate_confidence()?;
+    pyth_price.normalized()
+}
+
+/// Reads a Pyth price, rejecting stale updates and wide confidence bands.
+pub fn read_pyth_price(price_feed: &AccountInfo, now: &impl TimeSource) -> Result<i64> {
+    check_pyth_owner(price_feed)?;
+
+    let data = price_feed.try_borrow_data()?;
+    load_pyth_price(&data, now)
+}
+
+/// Reads a Pyth price that must come from the `feed_id` update.
+pub fn read_pyth_price_for_feed(
+    price_feed: &AccountInfo,
+    feed_id: &[u8; 32],
+    now: &impl TimeSource,
+) -> Result<i64> {
+    check_pyth_owner(price_feed)?;
+
+    let data = price_feed.try_borrow_data()?;
+    load_pyth_price_for_feed(&data, feed_id, now)
+}
+
+pub fn check_pyth_owner(price_feed: &AccountInfo) -> Result<()> {
+    if price_feed.owner != &PYTH_RECEIVER_ID {
+        msg!(
+            "Invalid price feed owner: expected {}, got {}",
+            PYTH_RECEIVER_ID,
+            price_feed.owner
+        );
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::FixedTime;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn build_feed(price: i64, confidence: u64, exponent: i32) -> Vec<u8> {
+        build_feed_at(price, confidence, exponent, NOW)
+    }
+
+    fn build_feed_at(price: i64, confidence: u64, exponent: i32, publish_time: i64) -> Vec<u8> {
+        let update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: [7u8; 32],
+                price,
+                conf: confidence,
+                exponent,
+                publish_time,
+                prev_publish_time: publish_time - 1,
+                ema_price: price,
+                ema_conf: confidence,
+            },
+            posted_slot: 1,
+        };
+
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        update.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// SOL/USD `PriceUpdateV2` account bytes as written by the receiver
+    /// program: fully verified, price 178.23456789 (expo -8), 134-byte account.
+    const SOL_USD_PRICE_UPDATE: [u8; 134] = [
+        0x22, 0xf1, 0x23, 0x63, 0x9d, 0x7e, 0xf4, 0xcd, 0x10, 0xfd, 0x87, 0x4b, 0x68, 0xda, 0xd0, 0x80,
+        0xed, 0x70, 0x67, 0x62, 0xc8, 0xe1, 0x63, 0xda, 0xbb, 0x20, 0x51, 0x4b, 0xdd, 0xae, 0x38, 0xfb,
+        0x15, 0x9c, 0x56, 0xf7, 0x14, 0xa3, 0xb1, 0x43, 0x01, 0xef, 0x0d, 0x8b, 0x6f, 0xda, 0x2c, 0xeb,
+        0xa4, 0x1d, 0xa1, 0x5d, 0x40, 0x95, 0xd1, 0xda, 0x39, 0x2a, 0x0d, 0x2f, 0x8e, 0xd0, 0xc6, 0xc7,
+        0xbc, 0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80, 0xb5, 0x6d, 0x15, 0x5e, 0x5c, 0x26, 0x04, 0x00, 0x00,
+        0x00, 0x3f, 0xb4, 0x96, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf8, 0xff, 0xff, 0xff, 0x00, 0xf1, 0x53,
+        0x65, 0x00, 0x00, 0x00, 0x00, 0xff, 0xf0, 0x53, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x72, 0xf6,
+        0x24, 0x04, 0x00, 0x00, 0x00, 0x80, 0x96, 0x98, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0xb2, 0xe6,
+        0x0e, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_parse_price_update_v2_account() {
+        let pyth_price = parse_pyth_price(&SOL_USD_PRICE_UPDATE).unwrap();
+
+        assert_eq!(pyth_price.feed_id[..4], [0xef, 0x0d, 0x8b, 0x6f]);
+        assert_eq!(pyth_price.price, 17_823_456_789);
+        assert_eq!(pyth_price.confidence, 9_876_543);
+        assert_eq!(pyth_price.exponent, -8);
+        assert_eq!(pyth_price.publish_time, 1_700_000_000);
+        assert_eq!(pyth_price.verification_level, VerificationLevel::Full);
+        assert_eq!(load_pyth_price(&SOL_USD_PRICE_UPDATE, &FixedTime::at(NOW)).unwrap(), 178_234567);
+    }
+
+    #[test]
+    fn test_partially_verified_update_parses() {
+        let mut data = SOL_USD_PRICE_UPDATE[..133].to_vec();
+        data[40] = 0;
+        data.insert(41, 3);
+
+        let pyth_price = parse_pyth_price(&data).unwrap();
+        assert_eq!(pyth_price.verification_level, VerificationLevel::Partial { num_signatures: 3 });
+        assert_eq!(pyth_price.price, 17_823_456_789);
+    }
+
+    #[test]
+    fn test_tight_confidence_accepted() {
+        let data = build_feed(180_00000000, 5_000000, -8);
+        let pyth_price = parse_pyth_price(&data).unwrap();
+
+        assert!(pyth_price.validate_confidence().is_ok());
+    }
+
+    #[test]
+    fn test_wide_confidence_rejected() {
+        let data = build_feed(180_00000000, 180_00000000 / MAX_CONFIDENCE_RATIO + 1, -8);
+        let pyth_price = parse_pyth_price(&data).unwrap();
+
+        assert!(pyth_price.validate_confidence().is_err());
+    }
+
+    #[test]
+    fn test_non_positive_price_rejected() {
+        let pyth_price = parse_pyth_price(&build_feed(0, 0, -8)).unwrap();
+        assert!(pyth_price.validate_confidence().is_err());
+    }
+
+    #[test]
+    fn test_exponent_normalization() {
+        let expo_8 = parse_pyth_price(&build_feed(180_12345678, 0, -8)).unwrap();
+        assert_eq!(expo_8.normalized().unwrap(), 180_123456);
+
+        let expo_6 = parse_pyth_price(&build_feed(180_123456, 0, -6)).unwrap();
+        assert_eq!(expo_6.normalized().unwrap(), 180_123456);
+
+        let expo_4 = parse_pyth_price(&build_feed(180_1234, 0, -4)).unwrap();
+        assert_eq!(expo_4.normalized().unwrap(), 180_123400);
+    }
+
+    #[test]
+    fn test_invalid_magic_rejected() {
+        let mut data = build_feed(180_00000000, 0, -8);
+        data[0..4].copy_from_slice(b"PYTH");
+
+        assert!(parse_pyth_price(&data).is_err());
+    }
+
+    #[test]
+    fn test_only_price_update_v2_discriminator_accepted() {
+        let data = build_feed(180_00000000, 0, -8);
+        assert!(parse_pyth_price(&data).is_ok());
+
+        // Legacy magic guesses and a near-miss discriminator
+        for magic in [*b"PYTH", [0x50, 0x32, 0x55, 0x56]] {
+            let mut spoofed = data.clone();
+            spoofed[0..4].copy_from_slice(&magic);
+            assert_eq!(parse_pyth_price(&spoofed).unwrap_err(), OracleError::InvalidPriceFeed.into());
+        }
+        let mut spoofed = data.clone();
+        spoofed[7] ^= 1;
+        assert_eq!(parse_pyth_price(&spoofed).unwrap_err(), OracleError::InvalidPriceFeed.into());
+    }
+
+    #[test]
+    fn test_update_for_other_feed_rejected() {
+        let data = build_feed(150_000_000, 1_000, -6);
+        let now = FixedTime::at(NOW);
+
+        assert_eq!(load_pyth_price_for_feed(&data, &[7u8; 32], &now).unwrap(), 150_000_000);
+        assert_eq!(
+            load_pyth_price_for_feed(&data, &[8u8; 32], &now).unwrap_err(),
+            OracleError::InvalidPriceFeed.into()
+        );
+    }
+
+    #[test]
+    fn test_short_buffer_rejected() {
+        let data = build_feed(180_00000000, 0, -8);
+        assert!(parse_pyth_price(&data[..data.len() - 9]).is_err());
+        assert!(parse_pyth_price(&data[..4]).is_err());
+    }
+
+    #[test]
+    fn test_fresh_price_loads() {
+        let data = build_feed_at(180_00000000, 5_000000, -8, NOW - MAX_PRICE_STALENESS_SECONDS);
+        assert_eq!(load_pyth_price(&data, &FixedTime::at(NOW)).unwrap(), 180_000000);
+    }
+
+    #[test]
+    fn test_stale_price_skips_trigger() {
+        let data = build_feed_at(180_00000000, 5_000000, -8, NOW - MAX_PRICE_STALENESS_SECONDS - 1);
+        let result = load_pyth_price(&data, &FixedTime::at(NOW));
+
+        assert_eq!(result.unwrap_err(), OracleError::PriceFeedStale.into());
+    }
+}
@@ -0,0 +1,199 @@
+//! Switchboard on-demand pull feed parsing, shared by ghost-crank and
+//! ghost-bridge alongside `pyth`.
+
+use anchor_lang::prelude::*;
+
+use crate::oracle::OracleError;
+use crate::pyth::{MAX_CONFIDENCE_RATIO, MAX_PRICE_STALENESS_SECONDS, PRICE_TARGET_EXPONENT};
+use crate::time::TimeSource;
+
+pub const SWITCHBOARD_ON_DEMAND_ID: Pubkey = pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+
+/// Anchor discriminator of Switchboard on-demand `PullFeedAccountData`
+/// (`sha256("account:PullFeedAccountData")[..8]`).
+pub const PULL_FEED_DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+/// Switchboard values are fixed-point with 18 decimals.
+pub const SWITCHBOARD_DECIMALS: i32 = 18;
+
+/// Byte offsets into a `PullFeedAccountData` account (zero-copy, `repr(C)`).
+///
+/// # Layout
+/// ```text
+/// [0-7]        discriminator
+/// [8-2055]     submissions ([OracleSubmission; 32], 64 bytes each)
+/// [2056-2215]  authority, queue, feed_hash, initialized_at, permissions,
+///              max_variance, min_responses, name, flags
+/// [2216-2223]  last_update_timestamp (i64 LE)
+/// [2224-2263]  lut_slot, reserved
+/// [2264-2279]  result.value (i128 LE)
+/// [2280-2295]  result.std_dev (i128 LE)
+/// ```
+pub const LAST_UPDATE_TIMESTAMP_OFFSET: usize = 2216;
+pub const RESULT_VALUE_OFFSET: usize = 2264;
+pub const RESULT_STD_DEV_OFFSET: usize = RESULT_VALUE_OFFSET + 16;
+pub const MIN_PULL_FEED_LEN: usize = RESULT_STD_DEV_OFFSET + 16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SwitchboardPrice {
+    pub value: i128,
+    pub std_dev: i128,
+    pub last_update_timestamp: i64,
+}
+
+impl SwitchboardPrice {
+    /// Applies the same `price / MAX_CONFIDENCE_RATIO` band as Pyth to the sample std-dev.
+    pub fn validate_confidence(&self) -> Result<()> {
+        if self.value <= 0 {
+            msg!("Non-positive Switchboard value: {}", self.value);
+            return Err(OracleError::InvalidPriceFeed.into());
+        }
+
+        let max_std_dev = self.value / MAX_CONFIDENCE_RATIO as i128;
+        if self.std_dev > max_std_dev {
+            msg!("Switchboard std-dev too wide: std_dev={}, value={}", self.std_dev, self.value);
+            return Err(OracleError::InvalidPriceFeed.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_freshness(&self, now: &impl TimeSource) -> Result<()> {
+        let age = now.unix_timestamp().saturating_sub(self.last_update_timestamp);
+        if age > MAX_PRICE_STALENESS_SECONDS {
+            msg!(
+                "Switchboard value is stale: updated={}, age={}s",
+                self.last_update_timestamp,
+                age
+            );
+            return Err(OracleError::PriceFeedStale.into());
+        }
+
+        Ok(())
+    }
+
+    /// Rescales the 18-decimal value to `PRICE_TARGET_EXPONENT`.
+    pub fn normalized(&self) -> Result<i64> {
+        let divisor = 10i128.pow((SWITCHBOARD_DECIMALS + PRICE_TARGET_EXPONENT) as u32);
+        i64::try_from(self.value / divisor).map_err(|_| OracleError::InvalidPriceFeed.into())
+    }
+}
+
+fn read_i128(data: &[u8], offset: usize) -> Result<i128> {
+    let bytes: [u8; 16] = data[offset..offset + 16]
+        .try_into()
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+    Ok(i128::from_le_bytes(bytes))
+}
+
+pub fn parse_switchboard_price(data: &[u8]) -> Result<SwitchboardPrice> {
+    if data.len() < MIN_PULL_FEED_LEN || data[..8] != PULL_FEED_DISCRIMINATOR {
+        msg!("Invalid Switchboard pull feed: {} bytes", data.len());
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+
+    let timestamp_bytes: [u8; 8] = data
+        [LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+        .try_into()
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+
+    Ok(SwitchboardPrice {
+        value: read_i128(data, RESULT_VALUE_OFFSET)?,
+        std_dev: read_i128(data, RESULT_STD_DEV_OFFSET)?,
+        last_update_timestamp: i64::from_le_bytes(timestamp_bytes),
+    })
+}
+
+pub fn load_switchboard_price(data: &[u8], now: &impl TimeSource) -> Result<i64> {
+    let price = parse_switchboard_price(data)?;
+
+    price.validate_freshness(now)?;
+    price.validate_confidence()?;
+    price.normalized()
+}
+
+/// Reads a Switchboard on-demand pull feed, rejecting stale or noisy results.
+pub fn read_switchboard_price(feed: &AccountInfo, now: &impl TimeSource) -> Result<i64> {
+    check_switchboard_owner(feed)?;
+
+    let data = feed.try_borrow_data()?;
+    load_switchboard_price(&data, now)
+}
+
+/// Reads a Switchboard price that must come from the pull feed `feed_id`.
+/// Switchboard feeds are accounts, so a feed id is the account address.
+pub fn read_switchboard_price_for_feed(
+    feed: &AccountInfo,
+    feed_id: &[u8; 32],
+    now: &impl TimeSource,
+) -> Result<i64> {
+    if feed.key.to_bytes() != *feed_id {
+        msg!("Switchboard feed {} does not match the expected feed id", feed.key);
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+
+    read_switchboard_price(feed, now)
+}
+
+pub fn check_switchboard_owner(feed: &AccountInfo) -> Result<()> {
+    if feed.owner != &SWITCHBOARD_ON_DEMAND_ID {
+        msg!(
+            "Invalid Switchboard feed owner: expected {}, got {}",
+            SWITCHBOARD_ON_DEMAND_ID,
+            feed.owner
+        );
+        return Err(OracleError::InvalidPriceFeed.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::FixedTime;
+
+    const NOW: i64 = 1_700_000_000;
+    const ONE: i128 = 1_000_000_000_000_000_000;
+
+    /// Synthetic aggregator buffer with only the fields we read populated.
+    fn build_pull_feed(value: i128, std_dev: i128, updated_at: i64) -> Vec<u8> {
+        let mut data = vec![0u8; 3208];
+        data[..8].copy_from_slice(&PULL_FEED_DISCRIMINATOR);
+        data[LAST_UPDATE_TIMESTAMP_OFFSET..LAST_UPDATE_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&updated_at.to_le_bytes());
+        data[RESULT_VALUE_OFFSET..RESULT_VALUE_OFFSET + 16].copy_from_slice(&value.to_le_bytes());
+        data[RESULT_STD_DEV_OFFSET..RESULT_STD_DEV_OFFSET + 16]
+            .copy_from_slice(&std_dev.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_switchboard_value_normalized() {
+        // 178.234567891 with 18 decimals
+        let data = build_pull_feed(178 * ONE + 234_567_891_000_000_000, ONE / 10, NOW);
+        assert_eq!(load_switchboard_price(&data, &FixedTime::at(NOW)).unwrap(), 178_234567);
+    }
+
+    #[test]
+    fn test_switchboard_stale_rejected() {
+        let data = build_pull_feed(178 * ONE, 0, NOW - MAX_PRICE_STALENESS_SECONDS - 1);
+        assert_eq!(
+            load_switchboard_price(&data, &FixedTime::at(NOW)).unwrap_err(),
+            OracleError::PriceFeedStale.into()
+        );
+    }
+
+    #[test]
+    fn test_switchboard_wide_std_dev_rejected() {
+        let data = build_pull_feed(100 * ONE, 3 * ONE, NOW);
+        assert!(load_switchboard_price(&data, &FixedTime::at(NOW)).is_err());
+    }
+
+    #[test]
+    fn test_switchboard_bad_discriminator_rejected() {
+        let mut data = build_pull_feed(100 * ONE, 0, NOW);
+        data[0] ^= 0xff;
+        assert!(parse_switchboard_price(&data).is_err());
+        assert!(parse_switchboard_price(&data[..MIN_PULL_FEED_LEN - 1]).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+/// Source of "now" for time-dependent state checks.
+///
+/// Handlers pass the runtime `Clock`; tests pass a `FixedTime` so boundary
+/// conditions can be exercised without a validator.
+pub trait TimeSource {
+    fn unix_timestamp(&self) -> i64;
+    fn slot(&self) -> u64;
+}
+
+impl TimeSource for Clock {
+    fn unix_timestamp(&self) -> i64 {
+        self.unix_timestamp
+    }
+
+    fn slot(&self) -> u64 {
+        self.slot
+    }
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FixedTime {
+    pub unix_timestamp: i64,
+    pub slot: u64,
+}
+
+#[cfg(test)]
+impl FixedTime {
+    pub fn at(unix_timestamp: i64) -> Self {
+        Self { unix_timestamp, slot: 0 }
+    }
+
+    pub fn at_slot(slot: u64) -> Self {
+        Self { unix_timestamp: 0, slot }
+    }
+}
+
+#[cfg(test)]
+impl TimeSource for FixedTime {
+    fn unix_timestamp(&self) -> i64 {
+        self.unix_timestamp
+    }
+
+    fn slot(&self) -> u64 {
+        self.slot
+    }
+}
@@ -0,0 +1,110 @@
+//! Trigger semantics shared by ghost-crank and ghost-bridge. Each program
+//! includes this file as its own `trigger` module, so both resolve
+//! `TriggerCondition` to their own type but compare prices identically.
+
+use crate::state::TriggerCondition;
+
+/// Seconds past `expiry` an order still counts as live, absorbing clock
+/// drift between the ephemeral rollup and the base layer.
+pub const EXPIRY_GRACE_SECONDS: i64 = 2;
+
+/// Whether an order with `expiry` (0 for none) has lapsed at `now`, grace
+/// included.
+pub fn is_past_expiry(expiry: i64, now: i64) -> bool {
+    expiry > 0 && now > expiry.saturating_add(EXPIRY_GRACE_SECONDS)
+}
+
+/// Whether `current_price` has reached `trigger_price`. Both boundaries are
+/// inclusive: an order fires at exactly its trigger price, whichever side
+/// it is watching.
+pub fn evaluate_trigger(condition: TriggerCondition, trigger_price: i64, current_price: i64) -> bool {
+    match condition {
+        TriggerCondition::Above => current_price >= trigger_price,
+        TriggerCondition::Below => current_price <= trigger_price,
+    }
+}
+
+/// Largest move between consecutive accepted reads, in bps, before a read is
+/// treated as bad oracle data.
+pub const MAX_PRICE_JUMP_BPS: u64 = 2_000;
+
+/// Jumps rejected in a row before the next out-of-band read is accepted as
+/// the new level; a move that holds across that many reads is not a bad print.
+pub const MAX_REJECTED_JUMPS: u8 = 3;
+
+/// Whether `price` moved more than `MAX_PRICE_JUMP_BPS` from `last_price`,
+/// which is more likely a bad print than a real move. Nothing to compare
+/// against until a first price has been accepted.
+pub fn is_price_jump(last_price: i64, price: i64) -> bool {
+    if last_price <= 0 {
+        return false;
+    }
+    let moved = (price as i128 - last_price as i128).unsigned_abs();
+    moved * 10_000 > last_price as u128 * MAX_PRICE_JUMP_BPS as u128
+}
+
+/// Jump filter over an order's last accepted price and the reads it has
+/// rejected since. Returns whether `price` is accepted, in which case it
+/// becomes `last_price`; a rejected read must not be checked.
+pub fn accept_observed_price(last_price: &mut i64, rejected_jumps: &mut u8, price: i64) -> bool {
+    if is_price_jump(*last_price, price) && *rejected_jumps < MAX_REJECTED_JUMPS {
+        *rejected_jumps += 1;
+        return false;
+    }
+    *last_price = price;
+    *rejected_jumps = 0;
+    true
+}
+
+/// `(condition, trigger_price, current_price, fires)` cases each program's
+/// order type is checked against, so the two cannot drift apart.
+#[cfg(test)]
+pub const TRIGGER_CASES: &[(TriggerCondition, i64, i64, bool)] = &[
+    (TriggerCondition::Above, 50_000, 49_999, false),
+    (TriggerCondition::Above, 50_000, 50_000, true),
+    (TriggerCondition::Above, 50_000, 50_001, true),
+    (TriggerCondition::Below, 50_000, 49_999, true),
+    (TriggerCondition::Below, 50_000, 50_000, true),
+    (TriggerCondition::Below, 50_000, 50_001, false),
+    (TriggerCondition::Above, -100, -100, true),
+    (TriggerCondition::Below, -100, -99, false),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_table() {
+        for &(condition, trigger_price, current_price, fires) in TRIGGER_CASES {
+            assert_eq!(
+                evaluate_trigger(condition, trigger_price, current_price),
+                fires,
+                "{:?} {} at {}",
+                condition,
+                trigger_price,
+                current_price
+            );
+        }
+    }
+
+    #[test]
+    fn test_sustained_move_accepted_after_rejected_jumps() {
+        let (mut last_price, mut rejected_jumps) = (0, 0);
+        assert!(accept_observed_price(&mut last_price, &mut rejected_jumps, 150_000_000));
+
+        // A one-off spike is dropped and the next normal read resets the count
+        assert!(!accept_observed_price(&mut last_price, &mut rejected_jumps, 300_000_000));
+        assert!(accept_observed_price(&mut last_price, &mut rejected_jumps, 151_000_000));
+        assert_eq!(rejected_jumps, 0);
+
+        // A crash that holds is taken as the new level once the filter gives up
+        for _ in 0..MAX_REJECTED_JUMPS {
+            assert!(!accept_observed_price(&mut last_price, &mut rejected_jumps, 90_000_000));
+            assert_eq!(last_price, 151_000_000);
+        }
+        assert!(accept_observed_price(&mut last_price, &mut rejected_jumps, 90_000_000));
+        assert_eq!((last_price, rejected_jumps), (90_000_000, 0));
+        assert!(accept_observed_price(&mut last_price, &mut rejected_jumps, 89_000_000));
+    }
+}
@@ -17,11 +17,14 @@ import {
   deriveExecutorAuthorityPda,
   deriveEncryptedOrderPda,
   buildInitExecutorInstruction,
+  deriveDriftUserPda,
   buildCreateEncryptedOrderInstruction,
   buildCancelEncryptedOrderInstruction,
   buildCloseEncryptedOrderInstruction,
   parseExecutorAuthorityAccount,
   parseEncryptedOrderAccount,
+  FEED_RELATION_SPREAD,
+  OracleSource,
   EncryptedOrderStatus,
 } from "../../lib/ghost-bridge-instructions";
 
@@ -352,7 +355,9 @@ describe("Ghost-Bridge: Instruction Builders", () => {
 
     expect(ix.keys[2].pubkey.equals(SystemProgram.programId)).toBe(true);
 
-    expect(ix.data.length).toBe(8);
+    expect(ix.data.length).toBe(40);
+    const [expectedDriftUser] = deriveDriftUserPda(testOwner.publicKey);
+    expect(new PublicKey(ix.data.subarray(8, 40)).equals(expectedDriftUser)).toBe(true);
   });
 
   it("should build create_encrypted_order instruction with correct encoding", async () => {
@@ -382,6 +387,7 @@ describe("Ghost-Bridge: Instruction Builders", () => {
       orderHash: orderHashBytes,
       encryptedData: encryptedDataBytes,
       feedId: feedIdBytes,
+      commitmentKey: Buffer.from(encrypted.commitmentKey, "hex"),
     });
 
     expect(ix.programId.equals(GHOST_BRIDGE_PROGRAM_ID)).toBe(true);
@@ -401,7 +407,8 @@ describe("Ghost-Bridge: Instruction Builders", () => {
 
     expect(ix.keys[3].pubkey.equals(SystemProgram.programId)).toBe(true);
 
-    expect(ix.data.length).toBe(8 + 32 + 256 + 2 + 32);
+    expect(ix.data.length).toBe(8 + 32 + 4 + encryptedDataBytes.length + 32);
+    expect(ix.data.readUInt32LE(8 + 32)).toBe(encryptedDataBytes.length);
   });
 
   it("should build cancel_encrypted_order instruction correctly", async () => {
@@ -458,7 +465,7 @@ describe("Ghost-Bridge: Account Parsing", () => {
     const orderHash1 = Buffer.alloc(32, 0xaa);
     const orderHash2 = Buffer.alloc(32, 0xbb);
 
-    const accountSize = 8 + 32 + 8 + 1 + 1 + (32 * 16) + 1;
+    const accountSize = 8 + 32 + 8 + 1 + 1 + 4 + (32 * 2) + 1 + (32 * 4) + 1 + 2;
     const data = Buffer.alloc(accountSize);
     let offset = 0;
 
@@ -478,13 +485,18 @@ describe("Ghost-Bridge: Account Parsing", () => {
     data.writeUInt8(254, offset);
     offset += 1;
 
+    data.writeUInt32LE(2, offset);
+    offset += 4;
+
     orderHash1.copy(data, offset);
     offset += 32;
     orderHash2.copy(data, offset);
     offset += 32;
-    offset += 32 * 14;
 
     data.writeUInt8(2, offset);
+    offset += 1 + (32 * 4) + 1;
+
+    data.writeUInt16LE(32, offset);
 
     const parsed = parseExecutorAuthorityAccount(data);
 
@@ -494,6 +506,7 @@ describe("Ghost-Bridge: Account Parsing", () => {
     expect(parsed.bump).toBe(254);
     expect(parsed.orderHashCount).toBe(2);
     expect(parsed.orderHashes.length).toBe(2);
+    expect(parsed.capacity).toBe(32);
     expect(Buffer.from(parsed.orderHashes[0]).equals(orderHash1)).toBe(true);
     expect(Buffer.from(parsed.orderHashes[1]).equals(orderHash2)).toBe(true);
   });
@@ -503,10 +516,11 @@ describe("Ghost-Bridge: Account Parsing", () => {
     const executorAuthority = Keypair.generate();
     const orderHash = Buffer.alloc(32, 0xcc);
     const feedId = Buffer.from(SOL_PYTH_FEED_ID, "hex");
-    const encryptedData = Buffer.alloc(256, 0xdd);
     const actualDataLen = 128;
+    const encryptedData = Buffer.alloc(actualDataLen, 0xdd);
 
-    const accountSize = 420;
+    const feedIdB = Buffer.alloc(32, 0xee);
+    const accountSize = 211 + actualDataLen;
     const data = Buffer.alloc(accountSize);
     let offset = 0;
 
@@ -523,8 +537,11 @@ describe("Ghost-Bridge: Account Parsing", () => {
     executorAuthority.publicKey.toBuffer().copy(data, offset);
     offset += 32;
 
+    data.writeUInt32LE(actualDataLen, offset);
+    offset += 4;
+
     encryptedData.copy(data, offset);
-    offset += 256;
+    offset += actualDataLen;
 
     data.writeUInt16LE(actualDataLen, offset);
     offset += 2;
@@ -544,7 +561,22 @@ describe("Ghost-Bridge: Account Parsing", () => {
     data.writeUInt8(EncryptedOrderStatus.Triggered, offset);
     offset += 1;
 
+    data.writeUInt8(1, offset);
+    offset += 1;
+
     data.writeUInt8(253, offset);
+    offset += 1;
+
+    feedIdB.copy(data, offset);
+    offset += 32;
+
+    data.writeUInt8(FEED_RELATION_SPREAD, offset);
+    offset += 1;
+
+    data.writeUInt8(OracleSource.Switchboard, offset);
+    offset += 1;
+
+    new BN(1700086400).toArrayLike(Buffer, "le", 8).copy(data, offset);
 
     const parsed = parseEncryptedOrderAccount(data);
 
@@ -558,7 +590,12 @@ describe("Ghost-Bridge: Account Parsing", () => {
     expect(parsed.triggeredAt.toNumber()).toBe(1700001000);
     expect(parsed.executionPrice.toNumber()).toBe(185000000);
     expect(parsed.status).toBe(EncryptedOrderStatus.Triggered);
+    expect(parsed.isDelegated).toBe(true);
     expect(parsed.bump).toBe(253);
+    expect(Buffer.from(parsed.feedIdB).equals(feedIdB)).toBe(true);
+    expect(parsed.relation).toBe(FEED_RELATION_SPREAD);
+    expect(parsed.oracleSource).toBe(OracleSource.Switchboard);
+    expect(parsed.expiry.toNumber()).toBe(1700086400);
   });
 });
 
@@ -568,6 +605,7 @@ describe("Ghost-Bridge: Order Status Transitions", () => {
     expect(EncryptedOrderStatus.Triggered).toBe(1);
     expect(EncryptedOrderStatus.Executed).toBe(2);
     expect(EncryptedOrderStatus.Cancelled).toBe(3);
+    expect(EncryptedOrderStatus.Committed).toBe(4);
   });
 
   it("should correctly identify valid status values", () => {
@@ -41,11 +41,24 @@ describe("Executor Authorization", () => {
       }
     );
 
-    // Data: 8 bytes discriminator + 32 bytes executor + 1 byte authorize
-    expect(instruction.data.length).toBe(41);
+    // Data: 8 bytes discriminator + 32 bytes executor + 1 byte authorize + 8 bytes expiry
+    expect(instruction.data.length).toBe(49);
     expect(instruction.data[40]).toBe(1); // authorize = true
   });
 
+  it("should encode expiry correctly", async () => {
+    const instruction = await buildAuthorizeExecutorInstruction(
+      owner.publicKey,
+      {
+        executor: teeExecutor.publicKey,
+        authorize: true,
+        expiry: 1_700_000_060,
+      }
+    );
+
+    expect(instruction.data.readBigInt64LE(41)).toBe(BigInt(1_700_000_060));
+  });
+
   it("should encode authorize=false correctly", async () => {
     const instruction = await buildAuthorizeExecutorInstruction(
       owner.publicKey,
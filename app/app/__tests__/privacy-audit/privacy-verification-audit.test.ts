@@ -43,6 +43,7 @@ interface ExecutorAuthorityState {
   bump: number;
   orderHashes: Uint8Array[];
   orderHashCount: number;
+  capacity: number;
 }
 
 function parseExecutorAuthorityAccount(data: Buffer): ExecutorAuthorityState {
@@ -60,21 +61,31 @@ function parseExecutorAuthorityAccount(data: Buffer): ExecutorAuthorityState {
   const bump = data[offset];
   offset += 1;
 
+  const hashesLen = data.readUInt32LE(offset);
+  offset += 4;
+
   const orderHashes: Uint8Array[] = [];
-  for (let i = 0; i < 16; i++) {
+  for (let i = 0; i < hashesLen; i++) {
     orderHashes.push(new Uint8Array(data.slice(offset, offset + 32)));
     offset += 32;
   }
 
   const orderHashCount = data[offset];
+  offset += 1;
+
+  // authorized_executors (4 * 32) + executor_count
+  offset += 4 * 32 + 1;
+
+  const capacity = data.readUInt16LE(offset);
 
   return {
     owner,
     orderCount,
     isDelegated,
     bump,
-    orderHashes: orderHashes.slice(0, orderHashCount),
+    orderHashes,
     orderHashCount,
+    capacity,
   };
 }
 
@@ -112,7 +123,7 @@ describe("Beneat Privacy Verification Audit", () => {
       const owner = Keypair.generate().publicKey;
       const [executorPda] = deriveExecutorAuthorityPda(owner);
 
-      const mockAccountData = Buffer.alloc(563);
+      const mockAccountData = Buffer.alloc(8 + 32 + 8 + 1 + 1 + 4 + 32 * 16 + 1 + 32 * 4 + 1 + 2);
       let offset = 0;
 
       EXECUTOR_AUTHORITY_DISCRIMINATOR.copy(mockAccountData, offset);
@@ -130,18 +141,19 @@ describe("Beneat Privacy Verification Audit", () => {
       mockAccountData[offset] = 255;
       offset += 1;
 
+      mockAccountData.writeUInt32LE(1, offset);
+      offset += 4;
+
       const orderData = createMockOrderData(owner, "180000000");
       const orderHash = await computeOrderHash(orderData);
       const hashBytes = Buffer.from(orderHash, "hex");
       hashBytes.copy(mockAccountData, offset);
       offset += 32;
 
-      for (let i = 1; i < 16; i++) {
-        mockAccountData.fill(0, offset, offset + 32);
-        offset += 32;
-      }
-
       mockAccountData[offset] = 1;
+      offset += 1 + 32 * 4 + 1;
+
+      mockAccountData.writeUInt16LE(16, offset);
 
       const parsed = parseExecutorAuthorityAccount(mockAccountData);
 
@@ -430,6 +442,7 @@ describe("Beneat Privacy Verification Audit", () => {
         bump: 255,
         orderHashes: [new Uint8Array(32).fill(1)],
         orderHashCount: 1,
+        capacity: 16,
       };
 
       const canExecuteFromUndelegated = !mockExecutorState.isDelegated;
@@ -449,6 +462,7 @@ describe("Beneat Privacy Verification Audit", () => {
         bump: 255,
         orderHashes: [new Uint8Array(hashBytes)],
         orderHashCount: 1,
+        capacity: 16,
       };
 
       const hasOrderHash = (
@@ -529,6 +543,7 @@ describe("Beneat Privacy Verification Audit", () => {
         bump: 255,
         orderHashes: [hashBytes],
         orderHashCount: 1,
+        capacity: 16,
       };
 
       expect(executor.orderHashCount).toBe(1);
@@ -336,7 +336,7 @@ export function PrivateGhostOrders({ className = "" }: PrivateGhostOrdersProps)
       <div className="p-4">
         <div className="mb-3 flex items-center justify-between">
           <span className="text-xs text-muted">
-            ACTIVE ORDERS ({executorState?.orderHashCount || 0}/16)
+            ACTIVE ORDERS ({executorState?.orderHashCount || 0}/{executorState?.capacity || 16})
           </span>
           <button
             onClick={refreshExecutorState}
@@ -90,6 +90,8 @@ export interface ExecuteTriggerParams {
   delegationBuffer?: string;
   delegationRecord?: string;
   delegationMetadata?: string;
+  /** OCO sibling, required when the order is linked */
+  siblingOrderPda?: string;
 }
 
 export interface UseMagicBlockReturn {
@@ -202,7 +204,7 @@ function decodeGhostOrder(
   data: Buffer,
   PublicKeyClass: typeof import("@solana/web3.js").PublicKey
 ): OnChainGhostOrder {
-  let offset = 8; // skip Anchor discriminator
+  let offset = 9; // skip Anchor discriminator and layout version
 
   const owner = new PublicKeyClass(data.subarray(offset, offset + 32));
   offset += 32;
@@ -723,7 +725,7 @@ export function useMagicBlock(): UseMagicBlockReturn {
       const accounts = await conn.getProgramAccounts(programId, {
         filters: [
           { dataSize: 159 },
-          { memcmp: { offset: 8, bytes: ownerPubkey.toBase58() } },
+          { memcmp: { offset: 9, bytes: ownerPubkey.toBase58() } },
         ],
       });
 
@@ -859,6 +861,10 @@ export function useMagicBlock(): UseMagicBlockReturn {
           keys: [
             { pubkey: ownerPubkey, isSigner: true, isWritable: true },
             { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
+            // No OCO sibling: the program id stands in for the optional account
+            { pubkey: programId, isSigner: false, isWritable: false },
+            // Lets the program cancel the order's crank task, if one was scheduled
+            { pubkey: new PublicKey(MAGIC_PROGRAM_ID), isSigner: false, isWritable: false },
           ],
           programId,
           data: discriminator,
@@ -1020,6 +1026,11 @@ export function useMagicBlock(): UseMagicBlockReturn {
           programId
         );
 
+        const [crankConfigPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from("crank_config")],
+          programId
+        );
+
         const driftStatePda = PublicKey.findProgramAddressSync(
           [Buffer.from("drift_state")],
           new PublicKey(DRIFT_PROGRAM_ID)
@@ -1050,6 +1061,7 @@ export function useMagicBlock(): UseMagicBlockReturn {
           { pubkey: oraclePubkey, isSigner: false, isWritable: false },
           { pubkey: magicContextPubkey, isSigner: false, isWritable: false },
           { pubkey: magicProgramPubkey, isSigner: false, isWritable: false },
+          { pubkey: crankConfigPda, isSigner: false, isWritable: false },
         ];
 
         if (redelegateAfterExecution && params.delegationBuffer && params.delegationRecord && params.delegationMetadata) {
@@ -1060,6 +1072,10 @@ export function useMagicBlock(): UseMagicBlockReturn {
           );
         }
 
+        if (params.siblingOrderPda) {
+          keys.push({ pubkey: new PublicKey(params.siblingOrderPda), isSigner: false, isWritable: true });
+        }
+
         const executeTriggerIx = new TransactionInstruction({
           keys,
           programId,
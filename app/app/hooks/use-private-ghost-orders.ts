@@ -303,6 +303,7 @@ export function usePrivateGhostOrders(): UsePrivateGhostOrdersReturn {
           orderHash: orderHashBytes,
           encryptedData: encryptedDataBytes,
           feedId: feedIdBytes,
+          commitmentKey: Buffer.from(encryptedPayload.commitmentKey, "hex"),
         };
 
         const instruction = await buildCreateEncryptedOrderInstruction(
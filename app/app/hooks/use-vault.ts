@@ -681,6 +681,8 @@ export function useVault(): UseVaultReturn {
         programAddress: VAULT_PROGRAM_ADDRESS,
         accounts: [
           { address: walletAddress, role: 2 },
+          // No guardian co-signer: the program id stands in for the optional account
+          { address: VAULT_PROGRAM_ADDRESS, role: 0 },
           { address: vaultAddress, role: 1 },
         ],
         data: getUnlockInstructionDataEncoder().encode({}),
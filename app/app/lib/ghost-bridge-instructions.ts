@@ -4,6 +4,7 @@ import {
   SystemProgram,
   SYSVAR_CLOCK_PUBKEY,
 } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { Buffer } from "buffer";
 import BN from "bn.js";
 import {
@@ -12,6 +13,7 @@ import {
   MAGIC_CONTEXT_ID,
   MAGIC_PROGRAM_ID,
 } from "./magicblock-constants";
+import { computeCiphertextCommitment, computeCommitmentKeyHash } from "./tee-encryption";
 
 export { GHOST_BRIDGE_PROGRAM_ID, DRIFT_PROGRAM_ID };
 export const MAGIC_CONTEXT = MAGIC_CONTEXT_ID;
@@ -19,6 +21,8 @@ export const MAGIC_PROGRAM = MAGIC_PROGRAM_ID;
 
 const EXECUTOR_SEED = Buffer.from("executor");
 const ENCRYPTED_ORDER_SEED = Buffer.from("encrypted_order");
+const BRIDGE_CONFIG_SEED = Buffer.from("bridge_config");
+const FEED_HEALTH_SEED = Buffer.from("feed_health");
 
 async function getDiscriminator(instructionName: string): Promise<Buffer> {
   const encoder = new TextEncoder();
@@ -34,6 +38,29 @@ export function deriveExecutorAuthorityPda(owner: PublicKey): [PublicKey, number
   );
 }
 
+export function deriveBridgeConfigPda(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([BRIDGE_CONFIG_SEED], GHOST_BRIDGE_PROGRAM_ID);
+}
+
+export function deriveFeedHealthPda(feedId: Uint8Array): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [FEED_HEALTH_SEED, Buffer.from(feedId)],
+    GHOST_BRIDGE_PROGRAM_ID
+  );
+}
+
+export function deriveDriftUserPda(
+  authority: PublicKey,
+  subAccountId = 0
+): [PublicKey, number] {
+  const subAccount = Buffer.alloc(2);
+  subAccount.writeUInt16LE(subAccountId);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("user"), authority.toBuffer(), subAccount],
+    DRIFT_PROGRAM_ID
+  );
+}
+
 export function deriveEncryptedOrderPda(
   owner: PublicKey,
   orderHash: Uint8Array
@@ -45,7 +72,8 @@ export function deriveEncryptedOrderPda(
 }
 
 export async function buildInitExecutorInstruction(
-  owner: PublicKey
+  owner: PublicKey,
+  driftUser: PublicKey = deriveDriftUserPda(owner)[0]
 ): Promise<TransactionInstruction> {
   const [executorAuthority] = deriveExecutorAuthorityPda(owner);
   const discriminator = await getDiscriminator("init_executor");
@@ -57,7 +85,52 @@ export async function buildInitExecutorInstruction(
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
     programId: GHOST_BRIDGE_PROGRAM_ID,
-    data: discriminator,
+    data: Buffer.concat([discriminator, driftUser.toBuffer()]),
+  });
+}
+
+/**
+ * Moves the owner's executor authority to `newOwner`'s PDA; both keys sign.
+ * `driftUser` is the Drift account orders trade against after the rotation.
+ */
+/** Starts confidence tracking for a feed; permissionless, `payer` funds the account. */
+export async function buildInitFeedHealthInstruction(
+  payer: PublicKey,
+  feedId: Uint8Array
+): Promise<TransactionInstruction> {
+  const [feedHealth] = deriveFeedHealthPda(feedId);
+  const discriminator = await getDiscriminator("init_feed_health");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: feedHealth, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, Buffer.from(feedId)]),
+  });
+}
+
+export async function buildRotateExecutorOwnerInstruction(
+  owner: PublicKey,
+  newOwner: PublicKey,
+  driftUser: PublicKey = deriveDriftUserPda(newOwner)[0]
+): Promise<TransactionInstruction> {
+  const [oldExecutorAuthority] = deriveExecutorAuthorityPda(owner);
+  const [newExecutorAuthority] = deriveExecutorAuthorityPda(newOwner);
+  const discriminator = await getDiscriminator("rotate_executor_owner");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: newOwner, isSigner: true, isWritable: false },
+      { pubkey: oldExecutorAuthority, isSigner: false, isWritable: true },
+      { pubkey: newExecutorAuthority, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, driftUser.toBuffer()]),
   });
 }
 
@@ -113,6 +186,8 @@ export async function buildUndelegateExecutorInstruction(
 export interface CreateCompressedOrderArgs {
   orderId: BN;
   marketIndex: number;
+  /** Drift market type: 0 = spot, 1 = perp (default) */
+  marketType?: number;
   triggerPrice: BN;
   triggerCondition: "above" | "below";
   orderSide: "long" | "short";
@@ -123,7 +198,7 @@ export interface CreateCompressedOrderArgs {
 }
 
 function encodeCreateCompressedOrderArgs(args: CreateCompressedOrderArgs): Buffer {
-  const buf = Buffer.alloc(8 + 2 + 8 + 1 + 1 + 8 + 1 + 8 + 32);
+  const buf = Buffer.alloc(8 + 2 + 1 + 8 + 1 + 1 + 8 + 1 + 8 + 32);
   let offset = 0;
 
   args.orderId.toArrayLike(Buffer, "le", 8).copy(buf, offset);
@@ -132,6 +207,9 @@ function encodeCreateCompressedOrderArgs(args: CreateCompressedOrderArgs): Buffe
   buf.writeUInt16LE(args.marketIndex, offset);
   offset += 2;
 
+  buf.writeUInt8(args.marketType ?? 1, offset);
+  offset += 1;
+
   args.triggerPrice.toArrayLike(Buffer, "le", 8).copy(buf, offset);
   offset += 8;
 
@@ -177,6 +255,8 @@ export async function buildCreateCompressedOrderInstruction(
 export interface ConsumeAndExecuteArgs {
   orderId: BN;
   marketIndex: number;
+  /** Drift market type: 0 = spot, 1 = perp (default) */
+  marketType?: number;
   triggerPrice: BN;
   triggerCondition: "above" | "below";
   orderSide: "long" | "short";
@@ -187,6 +267,8 @@ export interface ConsumeAndExecuteArgs {
   salt: Uint8Array;
   currentPrice: BN;
   keepDelegated: boolean;
+  /** Network the price feed belongs to; defaults to Pyth */
+  oracleSource?: OracleSource;
 }
 
 export interface ConsumeAndExecuteAccounts {
@@ -198,10 +280,14 @@ export interface ConsumeAndExecuteAccounts {
   oracle: PublicKey;
   magicContext: PublicKey;
   magicProgram: PublicKey;
+  /** Drift quote spot market, required for spot orders */
+  quoteSpotMarket?: PublicKey;
+  /** Price feed for the order's feedId; required unless the owner executes */
+  priceFeed?: PublicKey;
 }
 
 function encodeConsumeAndExecuteArgs(args: ConsumeAndExecuteArgs): Buffer {
-  const buf = Buffer.alloc(8 + 2 + 8 + 1 + 1 + 8 + 1 + 8 + 32 + 16 + 8 + 1);
+  const buf = Buffer.alloc(8 + 2 + 1 + 8 + 1 + 1 + 8 + 1 + 8 + 32 + 16 + 8 + 1 + 1);
   let offset = 0;
 
   args.orderId.toArrayLike(Buffer, "le", 8).copy(buf, offset);
@@ -210,6 +296,9 @@ function encodeConsumeAndExecuteArgs(args: ConsumeAndExecuteArgs): Buffer {
   buf.writeUInt16LE(args.marketIndex, offset);
   offset += 2;
 
+  buf.writeUInt8(args.marketType ?? 1, offset);
+  offset += 1;
+
   args.triggerPrice.toArrayLike(Buffer, "le", 8).copy(buf, offset);
   offset += 8;
 
@@ -238,6 +327,9 @@ function encodeConsumeAndExecuteArgs(args: ConsumeAndExecuteArgs): Buffer {
   offset += 8;
 
   buf.writeUInt8(args.keepDelegated ? 1 : 0, offset);
+  offset += 1;
+
+  buf.writeUInt8(args.oracleSource ?? OracleSource.Pyth, offset);
 
   return buf;
 }
@@ -264,12 +356,134 @@ export async function buildConsumeAndExecuteInstruction(
       { pubkey: accounts.oracle, isSigner: false, isWritable: false },
       { pubkey: accounts.magicContext, isSigner: false, isWritable: false },
       { pubkey: accounts.magicProgram, isSigner: false, isWritable: false },
+      // Anchor reads the program id as "absent" for a skipped optional account
+      {
+        pubkey: accounts.quoteSpotMarket ?? GHOST_BRIDGE_PROGRAM_ID,
+        isSigner: false,
+        isWritable: !!accounts.quoteSpotMarket,
+      },
+      { pubkey: deriveBridgeConfigPda()[0], isSigner: false, isWritable: false },
+      {
+        pubkey: accounts.priceFeed ?? GHOST_BRIDGE_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      },
+      { pubkey: deriveFeedHealthPda(args.feedId)[0], isSigner: false, isWritable: false },
     ],
     programId: GHOST_BRIDGE_PROGRAM_ID,
     data: Buffer.concat([discriminator, argsData]),
   });
 }
 
+export interface CompressedOrderParams {
+  orderId: BN;
+  marketIndex: number;
+  /** Drift market type: 0 = spot, 1 = perp (default) */
+  marketType?: number;
+  triggerPrice: BN;
+  triggerCondition: "above" | "below";
+  orderSide: "long" | "short";
+  baseAssetAmount: BN;
+  reduceOnly: boolean;
+  expiry: BN;
+  feedId: Uint8Array;
+  salt: Uint8Array;
+}
+
+function encodeCompressedOrderParams(params: CompressedOrderParams): Buffer {
+  const buf = Buffer.alloc(8 + 2 + 1 + 8 + 1 + 1 + 8 + 1 + 8 + 32 + 16);
+  let offset = 0;
+
+  params.orderId.toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt16LE(params.marketIndex, offset);
+  offset += 2;
+
+  buf.writeUInt8(params.marketType ?? 1, offset);
+  offset += 1;
+
+  params.triggerPrice.toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(params.triggerCondition === "above" ? 0 : 1, offset);
+  offset += 1;
+
+  buf.writeUInt8(params.orderSide === "long" ? 0 : 1, offset);
+  offset += 1;
+
+  params.baseAssetAmount.toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(params.reduceOnly ? 1 : 0, offset);
+  offset += 1;
+
+  params.expiry.toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  Buffer.from(params.feedId).copy(buf, offset, 0, 32);
+  offset += 32;
+
+  Buffer.from(params.salt).copy(buf, offset, 0, 16);
+
+  return buf;
+}
+
+export async function buildCancelCompressedOrderInstruction(
+  owner: PublicKey,
+  params: CompressedOrderParams
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("cancel_compressed_order");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, encodeCompressedOrderParams(params)]),
+  });
+}
+
+export async function buildCancelAllOrdersInstruction(
+  owner: PublicKey,
+  params: CompressedOrderParams[]
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("cancel_all_orders");
+
+  const count = Buffer.alloc(4);
+  count.writeUInt32LE(params.length, 0);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, count, ...params.map(encodeCompressedOrderParams)]),
+  });
+}
+
+export async function buildExpireCompressedOrderInstruction(
+  payer: PublicKey,
+  owner: PublicKey,
+  params: CompressedOrderParams
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("expire_compressed_order");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: false },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, encodeCompressedOrderParams(params)]),
+  });
+}
+
 export interface ExecutorAuthorityState {
   owner: PublicKey;
   orderCount: BN;
@@ -277,10 +491,11 @@ export interface ExecutorAuthorityState {
   bump: number;
   orderHashes: Uint8Array[];
   orderHashCount: number;
+  capacity: number;
 }
 
 export function parseExecutorAuthorityAccount(data: Buffer): ExecutorAuthorityState {
-  let offset = 8;
+  let offset = 9; // discriminator + layout version
 
   const owner = new PublicKey(data.slice(offset, offset + 32));
   offset += 32;
@@ -294,21 +509,31 @@ export function parseExecutorAuthorityAccount(data: Buffer): ExecutorAuthoritySt
   const bump = data[offset];
   offset += 1;
 
+  const hashesLen = data.readUInt32LE(offset);
+  offset += 4;
+
   const orderHashes: Uint8Array[] = [];
-  for (let i = 0; i < 16; i++) {
+  for (let i = 0; i < hashesLen; i++) {
     orderHashes.push(new Uint8Array(data.slice(offset, offset + 32)));
     offset += 32;
   }
 
   const orderHashCount = data[offset];
+  offset += 1;
+
+  // authorized_executors (4 * 32) + executor_count
+  offset += 4 * 32 + 1;
+
+  const capacity = data.readUInt16LE(offset);
 
   return {
     owner,
     orderCount,
     isDelegated,
     bump,
-    orderHashes: orderHashes.slice(0, orderHashCount),
+    orderHashes,
     orderHashCount,
+    capacity,
   };
 }
 
@@ -316,8 +541,30 @@ export interface CreateEncryptedOrderArgs {
   orderHash: Uint8Array;
   encryptedData: Uint8Array;
   feedId: Uint8Array;
+  /** Secondary feed for spread orders; omit for single-feed orders. */
+  feedIdB?: Uint8Array;
+  relation?: number;
+  oracleSource?: OracleSource;
+  /** Unix timestamp after which monitoring cancels the order; 0 or omitted never expires */
+  expiry?: number;
+  /** Sender's X25519 ephemeral public key used to encrypt `encryptedData` */
+  ephemeralPubkey?: Uint8Array;
+  /** 12-byte AEAD nonce used to encrypt `encryptedData` */
+  nonce?: Uint8Array;
+  /** Secret sealed in `encryptedData`; only its hash goes on-chain */
+  commitmentKey: Uint8Array;
+  /** Compute budget for the Drift call handler; 0 or omitted uses the program default */
+  computeUnits?: number;
+}
+
+export enum OracleSource {
+  Pyth = 0,
+  Switchboard = 1,
 }
 
+export const FEED_RELATION_SINGLE = 0;
+export const FEED_RELATION_SPREAD = 1;
+
 export async function buildCreateEncryptedOrderInstruction(
   owner: PublicKey,
   args: CreateEncryptedOrderArgs
@@ -326,23 +573,94 @@ export async function buildCreateEncryptedOrderInstruction(
   const [encryptedOrder] = deriveEncryptedOrderPda(owner, args.orderHash);
   const discriminator = await getDiscriminator("create_encrypted_order");
 
-  const MAX_ENCRYPTED_DATA_LEN = 256;
-  const encryptedDataPadded = new Uint8Array(MAX_ENCRYPTED_DATA_LEN);
-  encryptedDataPadded.set(args.encryptedData.slice(0, MAX_ENCRYPTED_DATA_LEN));
+  const ciphertextCommitment = await computeCiphertextCommitment(
+    args.encryptedData,
+    args.orderHash,
+    args.commitmentKey
+  );
 
-  const buf = Buffer.alloc(32 + MAX_ENCRYPTED_DATA_LEN + 2 + 32);
+  const buf = Buffer.alloc(32 + 4 + args.encryptedData.length + 32 + 32 + 1 + 1 + 8 + 32 + 12 + 32 + 4);
   let offset = 0;
 
   Buffer.from(args.orderHash).copy(buf, offset);
   offset += 32;
 
-  Buffer.from(encryptedDataPadded).copy(buf, offset);
-  offset += MAX_ENCRYPTED_DATA_LEN;
+  buf.writeUInt32LE(args.encryptedData.length, offset);
+  offset += 4;
 
-  buf.writeUInt16LE(args.encryptedData.length, offset);
-  offset += 2;
+  Buffer.from(args.encryptedData).copy(buf, offset);
+  offset += args.encryptedData.length;
 
   Buffer.from(args.feedId).copy(buf, offset);
+  offset += 32;
+
+  if (args.feedIdB) {
+    Buffer.from(args.feedIdB).copy(buf, offset);
+  }
+  offset += 32;
+
+  buf.writeUInt8(
+    args.relation ?? (args.feedIdB ? FEED_RELATION_SPREAD : FEED_RELATION_SINGLE),
+    offset
+  );
+  offset += 1;
+
+  buf.writeUInt8(args.oracleSource ?? OracleSource.Pyth, offset);
+  offset += 1;
+
+  buf.writeBigInt64LE(BigInt(args.expiry ?? 0), offset);
+  offset += 8;
+
+  if (args.ephemeralPubkey) {
+    Buffer.from(args.ephemeralPubkey).copy(buf, offset);
+  }
+  offset += 32;
+
+  if (args.nonce) {
+    Buffer.from(args.nonce).copy(buf, offset);
+  }
+  offset += 12;
+
+  Buffer.from(ciphertextCommitment).copy(buf, offset);
+  offset += 32;
+
+  buf.writeUInt32LE(args.computeUnits ?? 0, offset);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+      { pubkey: encryptedOrder, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, buf]),
+  });
+}
+
+/**
+ * First half of a two-phase creation: registers only the order hash and the
+ * commitment to its future ciphertext.
+ */
+export async function buildCommitEncryptedOrderInstruction(
+  owner: PublicKey,
+  orderHash: Uint8Array,
+  encryptedData: Uint8Array,
+  commitmentKey: Uint8Array
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const [encryptedOrder] = deriveEncryptedOrderPda(owner, orderHash);
+  const discriminator = await getDiscriminator("commit_encrypted_order");
+
+  const ciphertextCommitment = await computeCiphertextCommitment(
+    encryptedData,
+    orderHash,
+    commitmentKey
+  );
+
+  const buf = Buffer.alloc(32 + 32);
+  Buffer.from(orderHash).copy(buf, 0);
+  Buffer.from(ciphertextCommitment).copy(buf, 32);
 
   return new TransactionInstruction({
     keys: [
@@ -356,6 +674,69 @@ export async function buildCreateEncryptedOrderInstruction(
   });
 }
 
+/** Attaches the ciphertext committed by `buildCommitEncryptedOrderInstruction`. */
+export async function buildRevealEncryptedOrderInstruction(
+  owner: PublicKey,
+  args: CreateEncryptedOrderArgs
+): Promise<TransactionInstruction> {
+  const [encryptedOrder] = deriveEncryptedOrderPda(owner, args.orderHash);
+  const discriminator = await getDiscriminator("reveal_encrypted_order");
+
+  const buf = Buffer.alloc(4 + args.encryptedData.length + 32 + 32 + 1 + 1 + 8 + 32 + 12 + 4 + 32);
+  let offset = 0;
+
+  buf.writeUInt32LE(args.encryptedData.length, offset);
+  offset += 4;
+
+  Buffer.from(args.encryptedData).copy(buf, offset);
+  offset += args.encryptedData.length;
+
+  Buffer.from(args.feedId).copy(buf, offset);
+  offset += 32;
+
+  if (args.feedIdB) {
+    Buffer.from(args.feedIdB).copy(buf, offset);
+  }
+  offset += 32;
+
+  buf.writeUInt8(
+    args.relation ?? (args.feedIdB ? FEED_RELATION_SPREAD : FEED_RELATION_SINGLE),
+    offset
+  );
+  offset += 1;
+
+  buf.writeUInt8(args.oracleSource ?? OracleSource.Pyth, offset);
+  offset += 1;
+
+  buf.writeBigInt64LE(BigInt(args.expiry ?? 0), offset);
+  offset += 8;
+
+  if (args.ephemeralPubkey) {
+    Buffer.from(args.ephemeralPubkey).copy(buf, offset);
+  }
+  offset += 32;
+
+  if (args.nonce) {
+    Buffer.from(args.nonce).copy(buf, offset);
+  }
+  offset += 12;
+
+  buf.writeUInt32LE(args.computeUnits ?? 0, offset);
+  offset += 4;
+
+  Buffer.from(await computeCommitmentKeyHash(args.commitmentKey)).copy(buf, offset);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: encryptedOrder, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, buf]),
+  });
+}
+
 export interface DelegateEncryptedOrderAccounts {
   delegationBuffer: PublicKey;
   delegationRecord: PublicKey;
@@ -408,10 +789,29 @@ export async function buildCancelEncryptedOrderInstruction(
   });
 }
 
+/** Emits the order's ciphertext and key material in an `EncryptedOrderData` event. */
+export async function buildRevealEncryptedDataInstruction(
+  owner: PublicKey,
+  orderHash: Uint8Array
+): Promise<TransactionInstruction> {
+  const [encryptedOrder] = deriveEncryptedOrderPda(owner, orderHash);
+  const discriminator = await getDiscriminator("reveal_encrypted_data");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: encryptedOrder, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: discriminator,
+  });
+}
+
 export async function buildCloseEncryptedOrderInstruction(
   owner: PublicKey,
   orderHash: Uint8Array
 ): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
   const [encryptedOrder] = deriveEncryptedOrderPda(owner, orderHash);
   const discriminator = await getDiscriminator("close_encrypted_order");
 
@@ -419,6 +819,7 @@ export async function buildCloseEncryptedOrderInstruction(
     keys: [
       { pubkey: owner, isSigner: true, isWritable: true },
       { pubkey: encryptedOrder, isSigner: false, isWritable: true },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
     ],
     programId: GHOST_BRIDGE_PROGRAM_ID,
     data: discriminator,
@@ -430,6 +831,7 @@ export enum EncryptedOrderStatus {
   Triggered = 1,
   Executed = 2,
   Cancelled = 3,
+  Committed = 4,
 }
 
 export interface EncryptedOrderState {
@@ -445,11 +847,17 @@ export interface EncryptedOrderState {
   status: EncryptedOrderStatus;
   isDelegated: boolean;
   bump: number;
+  feedIdB: Uint8Array;
+  relation: number;
+  oracleSource: OracleSource;
+  expiry: BN;
 }
 
 export interface AuthorizeExecutorArgs {
   executor: PublicKey;
   authorize: boolean;
+  /** Unix timestamp the grant expires at; 0 for no expiry */
+  expiry?: number;
 }
 
 export async function buildAuthorizeExecutorInstruction(
@@ -459,9 +867,111 @@ export async function buildAuthorizeExecutorInstruction(
   const [executorAuthority] = deriveExecutorAuthorityPda(owner);
   const discriminator = await getDiscriminator("authorize_executor");
 
-  const buf = Buffer.alloc(32 + 1);
+  const buf = Buffer.alloc(32 + 1 + 8);
   args.executor.toBuffer().copy(buf, 0);
   buf.writeUInt8(args.authorize ? 1 : 0, 32);
+  buf.writeBigInt64LE(BigInt(args.expiry ?? 0), 33);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, buf]),
+  });
+}
+
+export interface AllowedMarket {
+  /** Drift market type: 0 = spot, 1 = perp */
+  marketType: number;
+  marketIndex: number;
+}
+
+/** Restricts executors to `markets`; an empty list allows every market. */
+export async function buildSetAllowedMarketsInstruction(
+  owner: PublicKey,
+  markets: AllowedMarket[]
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("set_allowed_markets");
+
+  const buf = Buffer.alloc(4 + markets.length * 3);
+  buf.writeUInt32LE(markets.length, 0);
+  markets.forEach((market, i) => {
+    buf.writeUInt8(market.marketType, 4 + i * 3);
+    buf.writeUInt16LE(market.marketIndex, 5 + i * 3);
+  });
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, buf]),
+  });
+}
+
+/** Caps any single executed order's notional, in quote micro-units; 0 removes the cap. */
+export async function buildSetMaxOrderNotionalInstruction(
+  owner: PublicKey,
+  maxOrderNotional: BN
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("set_max_order_notional");
+
+  const buf = Buffer.alloc(8);
+  buf.writeBigUInt64LE(BigInt(maxOrderNotional.toString()), 0);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: Buffer.concat([discriminator, buf]),
+  });
+}
+
+/** Pays the protocol fees accrued on the owner's authority into the config's fee vault. */
+export async function buildSettleProtocolFeesInstruction(
+  owner: PublicKey,
+  ownerQuoteAccount: PublicKey,
+  feeVault: PublicKey
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("settle_protocol_fees");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: executorAuthority, isSigner: false, isWritable: true },
+      { pubkey: deriveBridgeConfigPda()[0], isSigner: false, isWritable: false },
+      { pubkey: ownerQuoteAccount, isSigner: false, isWritable: true },
+      { pubkey: feeVault, isSigner: false, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: discriminator,
+  });
+}
+
+export async function buildAuthorizeExecutorsBatchInstruction(
+  owner: PublicKey,
+  entries: AuthorizeExecutorArgs[]
+): Promise<TransactionInstruction> {
+  const [executorAuthority] = deriveExecutorAuthorityPda(owner);
+  const discriminator = await getDiscriminator("authorize_executors_batch");
+
+  const buf = Buffer.alloc(4 + entries.length * (32 + 1 + 8));
+  buf.writeUInt32LE(entries.length, 0);
+  entries.forEach((entry, i) => {
+    const offset = 4 + i * 41;
+    entry.executor.toBuffer().copy(buf, offset);
+    buf.writeUInt8(entry.authorize ? 1 : 0, offset + 32);
+    buf.writeBigInt64LE(BigInt(entry.expiry ?? 0), offset + 33);
+  });
 
   return new TransactionInstruction({
     keys: [
@@ -473,9 +983,29 @@ export async function buildAuthorizeExecutorInstruction(
   });
 }
 
+/**
+ * Rewrites an encrypted order or executor authority written before accounts
+ * carried a layout version byte. A no-op for accounts already current.
+ */
+export async function buildMigrateAccountInstruction(
+  payer: PublicKey,
+  account: PublicKey
+): Promise<TransactionInstruction> {
+  const discriminator = await getDiscriminator("migrate_account");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: account, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_BRIDGE_PROGRAM_ID,
+    data: discriminator,
+  });
+}
+
 export function parseEncryptedOrderAccount(data: Buffer): EncryptedOrderState {
-  const MAX_ENCRYPTED_DATA_LEN = 256;
-  let offset = 8;
+  let offset = 9; // discriminator + layout version
 
   const owner = new PublicKey(data.slice(offset, offset + 32));
   offset += 32;
@@ -486,14 +1016,15 @@ export function parseEncryptedOrderAccount(data: Buffer): EncryptedOrderState {
   const executorAuthority = new PublicKey(data.slice(offset, offset + 32));
   offset += 32;
 
-  const encryptedDataRaw = new Uint8Array(data.slice(offset, offset + MAX_ENCRYPTED_DATA_LEN));
-  offset += MAX_ENCRYPTED_DATA_LEN;
+  const encryptedDataLen = data.readUInt32LE(offset);
+  offset += 4;
+
+  const encryptedData = new Uint8Array(data.slice(offset, offset + encryptedDataLen));
+  offset += encryptedDataLen;
 
   const dataLen = data.readUInt16LE(offset);
   offset += 2;
 
-  const encryptedData = encryptedDataRaw.slice(0, dataLen);
-
   const feedId = new Uint8Array(data.slice(offset, offset + 32));
   offset += 32;
 
@@ -513,6 +1044,18 @@ export function parseEncryptedOrderAccount(data: Buffer): EncryptedOrderState {
   offset += 1;
 
   const bump = data[offset];
+  offset += 1;
+
+  const feedIdB = new Uint8Array(data.slice(offset, offset + 32));
+  offset += 32;
+
+  const relation = data[offset];
+  offset += 1;
+
+  const oracleSource = data[offset] as OracleSource;
+  offset += 1;
+
+  const expiry = new BN(data.slice(offset, offset + 8), "le");
 
   return {
     owner,
@@ -527,5 +1070,9 @@ export function parseEncryptedOrderAccount(data: Buffer): EncryptedOrderState {
     status,
     isDelegated,
     bump,
+    feedIdB,
+    relation,
+    oracleSource,
+    expiry,
   };
 }
@@ -8,6 +8,8 @@ import { BN } from "@coral-xyz/anchor";
 import { submitJitoBundle } from "./jito-bundles";
 import {
   OrderParams,
+  generateNonce,
+  serializeOrderParams,
   verifyCommitment,
 } from "./ghost-order-commitment";
 import {
@@ -26,6 +28,7 @@ const GHOST_CRANK_PROGRAM_ID = new PublicKey(
 
 const GHOST_ORDER_SEED = Buffer.from("ghost_order");
 const DELEGATE_SEED = Buffer.from("ghost_delegate");
+const CRANK_CONFIG_SEED = Buffer.from("crank_config");
 
 export interface ReadyGhostOrder {
   pubkey: PublicKey;
@@ -164,7 +167,7 @@ export class GhostKeeperService {
           { dataSize: 330 },
           {
             memcmp: {
-              offset: 8 + 32 + 8 + 2 + 8 + 1 + 1 + 8 + 1,
+              offset: 8 + 1 + 32 + 8 + 2 + 8 + 1 + 1 + 8 + 1,
               bytes: "4",
             },
           },
@@ -194,7 +197,7 @@ export class GhostKeeperService {
   ): ReadyGhostOrder | null {
     if (data.length < 330) return null;
 
-    let offset = 8;
+    let offset = 9; // discriminator + layout version
 
     const owner = new PublicKey(data.subarray(offset, offset + 32));
     offset += 32;
@@ -262,7 +265,14 @@ export class GhostKeeperService {
     this.log(`[Keeper] Executing order ${order.pubkey.toBase58()}`);
 
     try {
-      const executeIx = this.buildExecuteInstruction(order, params, nonce);
+      // Stable per-attempt id so a resubmitted bundle is a no-op on-chain
+      const executionId = generateNonce() || BigInt(1);
+      const executeIx = this.buildExecuteInstruction(
+        order,
+        params,
+        nonce,
+        executionId
+      );
 
       const bundleId = await submitJitoBundle(
         this.connection,
@@ -298,27 +308,18 @@ export class GhostKeeperService {
   private buildExecuteInstruction(
     order: ReadyGhostOrder,
     params: OrderParams,
-    nonce: bigint
+    nonce: bigint,
+    executionId: bigint
   ): TransactionInstruction {
     const discriminator = getExecuteWithCommitmentDiscriminator();
 
-    const argsBuffer = Buffer.alloc(2 + 1 + 8 + 1 + 8);
-    let offset = 0;
-
-    argsBuffer.writeUInt16LE(params.marketIndex, offset);
-    offset += 2;
-    argsBuffer.writeUInt8(params.orderSide === "long" ? 0 : 1, offset);
-    offset += 1;
-    params.baseAssetAmount.toArrayLike(Buffer, "le", 8).copy(argsBuffer, offset);
-    offset += 8;
-    argsBuffer.writeUInt8(params.reduceOnly ? 1 : 0, offset);
-    offset += 1;
+    const paramsBuffer = serializeOrderParams(params);
 
-    const nonceBuf = Buffer.alloc(8);
-    nonceBuf.writeBigUInt64LE(nonce);
-    nonceBuf.copy(argsBuffer, offset);
+    const tailBuffer = Buffer.alloc(8 + 8);
+    tailBuffer.writeBigUInt64LE(nonce, 0);
+    tailBuffer.writeBigUInt64LE(executionId, 8);
 
-    const data = Buffer.concat([discriminator, argsBuffer]);
+    const data = Buffer.concat([discriminator, paramsBuffer, tailBuffer]);
 
     const driftState = deriveStatePDA();
     const driftUserStats = deriveUserStatsPDA(order.owner);
@@ -338,6 +339,14 @@ export class GhostKeeperService {
         { pubkey: perpMarket, isSigner: false, isWritable: true },
         { pubkey: oracle, isSigner: false, isWritable: false },
         { pubkey: DRIFT_PROGRAM_ID, isSigner: false, isWritable: false },
+        {
+          pubkey: PublicKey.findProgramAddressSync(
+            [CRANK_CONFIG_SEED],
+            GHOST_CRANK_PROGRAM_ID
+          )[0],
+          isSigner: false,
+          isWritable: false,
+        },
       ],
       data,
     });
@@ -6,6 +6,18 @@ export interface OrderParams {
   orderSide: "long" | "short";
   baseAssetAmount: BN;
   reduceOnly: boolean;
+  isTrailing: boolean;
+  trailOffset: BN;
+  /** Limit-order tranches to split the entry into; 0 or 1 = single market order */
+  ladderSteps?: number;
+  /** Positive price gap between tranches, stepping down from the execution price for a long and up for a short */
+  ladderSpacing?: BN;
+  /** Drift market type: 0 = spot, 1 = perp (default) */
+  marketType?: number;
+  /** Largest price move since the trigger tolerated at execution; 0 = unbounded */
+  maxSlippageBps?: number;
+  /** Worst fill price, sent to Drift as the order's limit price; 0 = unbounded */
+  priceBound?: BN;
 }
 
 /**
@@ -18,37 +30,23 @@ export interface OrderParams {
  * - order_side: u8 (0 = long, 1 = short)
  * - base_asset_amount: u64 LE
  * - reduce_only: u8 (0 = false, 1 = true)
+ * - is_trailing: u8 (0 = false, 1 = true)
+ * - trail_offset: i64 LE
+ * - ladder_steps: u8
+ * - ladder_spacing: i64 LE
+ * - market_type: u8 (0 = spot, 1 = perp)
+ * - max_slippage_bps: u16 LE
+ * - price_bound: u64 LE
  * - nonce: u64 LE
  */
 export function computeOrderCommitment(
   params: OrderParams,
   nonce: bigint
 ): Uint8Array {
-  // Serialize params in the same format as Rust
-  const buffer = Buffer.alloc(2 + 1 + 8 + 1 + 8);
-  let offset = 0;
-
-  // market_index: u16 LE
-  buffer.writeUInt16LE(params.marketIndex, offset);
-  offset += 2;
-
-  // order_side: u8 (0 = long, 1 = short)
-  buffer.writeUInt8(params.orderSide === "long" ? 0 : 1, offset);
-  offset += 1;
-
-  // base_asset_amount: u64 LE
-  const amountBuf = params.baseAssetAmount.toArrayLike(Buffer, "le", 8);
-  amountBuf.copy(buffer, offset);
-  offset += 8;
-
-  // reduce_only: u8
-  buffer.writeUInt8(params.reduceOnly ? 1 : 0, offset);
-  offset += 1;
-
-  // nonce: u64 LE
+  // nonce: u64 LE, appended to the serialized params
   const nonceBuf = Buffer.alloc(8);
   nonceBuf.writeBigUInt64LE(nonce);
-  nonceBuf.copy(buffer, offset);
+  const buffer = Buffer.concat([serializeOrderParams(params), nonceBuf]);
 
   // SHA256 hash
   const hash = createHash("sha256").update(buffer).digest();
@@ -84,7 +82,7 @@ export function verifyCommitment(
  * Convert OrderParams to the format expected by the program instruction
  */
 export function serializeOrderParams(params: OrderParams): Buffer {
-  const buffer = Buffer.alloc(2 + 1 + 8 + 1);
+  const buffer = Buffer.alloc(2 + 1 + 8 + 1 + 1 + 8 + 1 + 8 + 1 + 2 + 8);
   let offset = 0;
 
   buffer.writeUInt16LE(params.marketIndex, offset);
@@ -97,6 +95,27 @@ export function serializeOrderParams(params: OrderParams): Buffer {
   offset += 8;
 
   buffer.writeUInt8(params.reduceOnly ? 1 : 0, offset);
+  offset += 1;
+
+  buffer.writeUInt8(params.isTrailing ? 1 : 0, offset);
+  offset += 1;
+
+  params.trailOffset.toTwos(64).toArrayLike(Buffer, "le", 8).copy(buffer, offset);
+  offset += 8;
+
+  buffer.writeUInt8(params.ladderSteps ?? 0, offset);
+  offset += 1;
+
+  (params.ladderSpacing ?? new BN(0)).toTwos(64).toArrayLike(Buffer, "le", 8).copy(buffer, offset);
+  offset += 8;
+
+  buffer.writeUInt8(params.marketType ?? 1, offset);
+  offset += 1;
+
+  buffer.writeUInt16LE(params.maxSlippageBps ?? 0, offset);
+  offset += 2;
+
+  (params.priceBound ?? new BN(0)).toArrayLike(Buffer, "le", 8).copy(buffer, offset);
 
   return buffer;
 }
@@ -55,6 +55,8 @@ export const SOL_PYTH_LAZER_FEED = new PublicKey(
 
 export const STATE_PROPAGATION_DELAY_MS = 3000;
 export const COMMIT_FREQUENCY_MS = 30000;
+/** Slots in a ghost order's keeper allowlist (`MAX_ALLOWED_KEEPERS`) */
+export const MAX_ALLOWED_KEEPERS = 4;
 export const DEFAULT_ESCROW_LAMPORTS = 0.01 * LAMPORTS_PER_SOL;
 
 export interface DelegationPDAs {
@@ -146,6 +148,30 @@ export function deriveDelegationPDAs(
   };
 }
 
+export function deriveCrankConfigPDA(): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("crank_config")],
+    GHOST_CRANK_PROGRAM_ID
+  );
+  return pda;
+}
+
+export function deriveMarketRegistryPDA(): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("market_registry")],
+    GHOST_CRANK_PROGRAM_ID
+  );
+  return pda;
+}
+
+export function deriveOrderCounterPDA(owner: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("order_counter"), owner.toBuffer()],
+    GHOST_CRANK_PROGRAM_ID
+  );
+  return pda;
+}
+
 export function deriveGhostOrderPDA(owner: PublicKey, orderId: BN): PublicKey {
   const [pda] = PublicKey.findProgramAddressSync(
     [
@@ -247,7 +273,7 @@ export async function fetchGhostOrderState(
     const data = accountInfo.data;
     if (data.length < 151) return null;
 
-    let offset = 8;
+    let offset = 9; // discriminator + layout version
 
     const owner = new PublicKey(data.subarray(offset, offset + 32));
     offset += 32;
@@ -364,7 +390,6 @@ export async function verifyPrivacy(
 }
 
 export function encodeCreateGhostOrderArgs(args: {
-  orderId: BN;
   marketIndex: number;
   triggerPrice: BN;
   triggerCondition: number;
@@ -372,16 +397,50 @@ export function encodeCreateGhostOrderArgs(args: {
   baseAssetAmount: BN;
   reduceOnly: boolean;
   expirySeconds: BN;
+  /** Pyth feed id, or the Switchboard pull feed's address */
   feedId: number[];
   paramsCommitment: number[];
   nonce: BN;
   driftUser: PublicKey;
+  cancelGraceSlots?: BN;
+  isTrailing?: boolean;
+  trailOffset?: BN;
+  keeperFeeLamports?: BN;
+  requiredConfirmations?: number;
+  /** 0 = Pyth, 1 = Switchboard */
+  oracleSource?: number;
+  ladderSteps?: number;
+  ladderSpacing?: BN;
+  /** 0 = spot, 1 = perp (default) */
+  marketType?: number;
+  /** Largest price move since the trigger tolerated at execution; 0 = unbounded */
+  maxSlippageBps?: number;
+  /** Compute budget for the Drift call handler; 0 = program default */
+  computeUnits?: number;
+  /** Decimal exponent `triggerPrice` is expressed in; -6 (micro-units) by default */
+  priceExponent?: number;
+  /** Worst fill price in `priceExponent` units; 0 = plain market order */
+  priceBound?: BN;
+  /** UI label: 0 = entry (default), 1 = take-profit, 2 = stop-loss, 3 = trailing */
+  intent?: number;
+  /** Slots a keeper gets to execute once ready; 0 = program default (100) */
+  readyWindowSlots?: BN;
+  /** Re-arm after each fill (DCA) for `remainingRuns` runs */
+  recurring?: boolean;
+  remainingRuns?: number;
+  /** Only these keepers may execute the order (up to 4); empty = any keeper */
+  keeperAllowlist?: PublicKey[];
 }): Buffer {
-  const buf = Buffer.alloc(8 + 2 + 8 + 1 + 1 + 8 + 1 + 8 + 32 + 32 + 8 + 32);
-  let offset = 0;
+  const keeperAllowlist = args.keeperAllowlist ?? [];
+  if (keeperAllowlist.length > MAX_ALLOWED_KEEPERS) {
+    throw new Error(`At most ${MAX_ALLOWED_KEEPERS} allowlisted keepers`);
+  }
 
-  args.orderId.toArrayLike(Buffer, "le", 8).copy(buf, offset);
-  offset += 8;
+  const buf = Buffer.alloc(
+    2 + 8 + 1 + 1 + 8 + 1 + 8 + 32 + 32 + 8 + 32 + 8 + 1 + 8 + 8 + 1 + 1 + 1 + 8 + 1 + 2 + 4 + 4 + 8 + 1 + 8 + 1 + 2 +
+      32 * MAX_ALLOWED_KEEPERS
+  );
+  let offset = 0;
 
   buf.writeUInt16LE(args.marketIndex, offset);
   offset += 2;
@@ -414,15 +473,87 @@ export function encodeCreateGhostOrderArgs(args: {
   offset += 8;
 
   args.driftUser.toBuffer().copy(buf, offset);
+  offset += 32;
+
+  (args.cancelGraceSlots ?? new BN(0)).toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(args.isTrailing ? 1 : 0, offset);
+  offset += 1;
+
+  (args.trailOffset ?? new BN(0)).toTwos(64).toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  (args.keeperFeeLamports ?? new BN(0)).toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(args.requiredConfirmations ?? 0, offset);
+  offset += 1;
+
+  buf.writeUInt8(args.oracleSource ?? 0, offset);
+  offset += 1;
+
+  buf.writeUInt8(args.ladderSteps ?? 0, offset);
+  offset += 1;
+
+  (args.ladderSpacing ?? new BN(0)).toTwos(64).toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(args.marketType ?? 1, offset);
+  offset += 1;
+
+  buf.writeUInt16LE(args.maxSlippageBps ?? 0, offset);
+  offset += 2;
+
+  buf.writeUInt32LE(args.computeUnits ?? 0, offset);
+  offset += 4;
+
+  buf.writeInt32LE(args.priceExponent ?? -6, offset);
+  offset += 4;
+
+  (args.priceBound ?? new BN(0)).toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(args.intent ?? 0, offset);
+  offset += 1;
+
+  (args.readyWindowSlots ?? new BN(0)).toArrayLike(Buffer, "le", 8).copy(buf, offset);
+  offset += 8;
+
+  buf.writeUInt8(args.recurring ? 1 : 0, offset);
+  offset += 1;
+
+  buf.writeUInt16LE(args.remainingRuns ?? 0, offset);
+  offset += 2;
+
+  // Unused slots stay zeroed, which the program reads as empty
+  keeperAllowlist.forEach((keeper, i) => keeper.toBuffer().copy(buf, offset + 32 * i));
 
   return buf;
 }
 
+export function buildInitOrderCounterInstruction(owner: PublicKey): TransactionInstruction {
+  const { SystemProgram } = require("@solana/web3.js");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: deriveOrderCounterPDA(owner), isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_CRANK_PROGRAM_ID,
+    data: anchorDiscriminator("init_order_counter"),
+  });
+}
+
+/**
+ * `ghostOrderPda` must be derived from the owner's current
+ * `OrderCounter.next_order_id`, which the program assigns as the order id.
+ */
 export function buildCreateGhostOrderInstruction(
   owner: PublicKey,
   ghostOrderPda: PublicKey,
   args: {
-    orderId: BN;
     marketIndex: number;
     triggerPrice: BN;
     triggerCondition: number;
@@ -434,6 +565,24 @@ export function buildCreateGhostOrderInstruction(
     paramsCommitment: number[];
     nonce: BN;
     driftUser: PublicKey;
+    cancelGraceSlots?: BN;
+    isTrailing?: boolean;
+    trailOffset?: BN;
+    keeperFeeLamports?: BN;
+    requiredConfirmations?: number;
+    oracleSource?: number;
+    ladderSteps?: number;
+    ladderSpacing?: BN;
+    marketType?: number;
+    maxSlippageBps?: number;
+    computeUnits?: number;
+    priceExponent?: number;
+    priceBound?: BN;
+    intent?: number;
+    readyWindowSlots?: BN;
+    recurring?: boolean;
+    remainingRuns?: number;
+    keeperAllowlist?: PublicKey[];
   }
 ): TransactionInstruction {
   const { SystemProgram } = require("@solana/web3.js");
@@ -445,6 +594,7 @@ export function buildCreateGhostOrderInstruction(
   return new TransactionInstruction({
     keys: [
       { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: deriveOrderCounterPDA(owner), isSigner: false, isWritable: true },
       { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
@@ -453,9 +603,117 @@ export function buildCreateGhostOrderInstruction(
   });
 }
 
-export function buildActivateOrderInstruction(
+/**
+ * Updates a pending or active order, or re-arms a recurring order between
+ * runs. `paramsCommitment` is the execution params committed under the new
+ * `nonce`, computed client-side like at creation.
+ */
+export function buildModifyGhostOrderInstruction(
   owner: PublicKey,
+  ghostOrderPda: PublicKey,
+  args: {
+    triggerPrice: BN;
+    baseAssetAmount: BN;
+    expirySeconds: BN;
+    nonce: BN;
+    paramsCommitment: number[];
+  }
+): TransactionInstruction {
+  const discriminator = anchorDiscriminator("modify_ghost_order");
+
+  const argsData = Buffer.alloc(64);
+  args.triggerPrice.toTwos(64).toArrayLike(Buffer, "le", 8).copy(argsData, 0);
+  args.baseAssetAmount.toArrayLike(Buffer, "le", 8).copy(argsData, 8);
+  args.expirySeconds.toTwos(64).toArrayLike(Buffer, "le", 8).copy(argsData, 16);
+  args.nonce.toArrayLike(Buffer, "le", 8).copy(argsData, 24);
+  Buffer.from(args.paramsCommitment).copy(argsData, 32);
+  const data = Buffer.concat([discriminator, argsData]);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: false },
+      { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
+    ],
+    programId: GHOST_CRANK_PROGRAM_ID,
+    data,
+  });
+}
+
+/**
+ * Rewrites a ghost order written before accounts carried a layout version
+ * byte. A no-op for orders already current; delegated orders must be
+ * undelegated first.
+ */
+export function buildMigrateGhostOrderInstruction(
+  payer: PublicKey,
   ghostOrderPda: PublicKey
+): TransactionInstruction {
+  const { SystemProgram } = require("@solana/web3.js");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_CRANK_PROGRAM_ID,
+    data: anchorDiscriminator("migrate_account"),
+  });
+}
+
+export interface BracketLegArgs {
+  triggerPrice: BN;
+  paramsCommitment: number[];
+  nonce: BN;
+}
+
+function encodeBracketLegArgs(leg: BracketLegArgs): Buffer {
+  const buf = Buffer.alloc(8 + 32 + 8);
+  leg.triggerPrice.toTwos(64).toArrayLike(Buffer, "le", 8).copy(buf, 0);
+  Buffer.from(leg.paramsCommitment).copy(buf, 8);
+  leg.nonce.toArrayLike(Buffer, "le", 8).copy(buf, 40);
+  return buf;
+}
+
+/**
+ * Creates an entry with its take-profit and stop-loss legs. The three PDAs
+ * must be derived from the owner's `next_order_id`, `+1` and `+2`.
+ */
+export function buildCreateBracketInstruction(
+  owner: PublicKey,
+  orderPdas: { entry: PublicKey; takeProfit: PublicKey; stopLoss: PublicKey },
+  entry: Parameters<typeof encodeCreateGhostOrderArgs>[0],
+  takeProfit: BracketLegArgs,
+  stopLoss: BracketLegArgs
+): TransactionInstruction {
+  const { SystemProgram } = require("@solana/web3.js");
+
+  const data = Buffer.concat([
+    anchorDiscriminator("create_bracket"),
+    encodeCreateGhostOrderArgs(entry),
+    encodeBracketLegArgs(takeProfit),
+    encodeBracketLegArgs(stopLoss),
+  ]);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      { pubkey: deriveOrderCounterPDA(owner), isSigner: false, isWritable: true },
+      { pubkey: orderPdas.entry, isSigner: false, isWritable: true },
+      { pubkey: orderPdas.takeProfit, isSigner: false, isWritable: true },
+      { pubkey: orderPdas.stopLoss, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_CRANK_PROGRAM_ID,
+    data,
+  });
+}
+
+/** Bracket legs must pass their executed entry as `parentOrderPda`. */
+export function buildActivateOrderInstruction(
+  owner: PublicKey,
+  ghostOrderPda: PublicKey,
+  parentOrderPda?: PublicKey
 ): TransactionInstruction {
   const discriminator = anchorDiscriminator("activate_order");
 
@@ -463,6 +721,9 @@ export function buildActivateOrderInstruction(
     keys: [
       { pubkey: owner, isSigner: true, isWritable: true },
       { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
+      ...(parentOrderPda
+        ? [{ pubkey: parentOrderPda, isSigner: false, isWritable: false }]
+        : []),
     ],
     programId: GHOST_CRANK_PROGRAM_ID,
     data: discriminator,
@@ -479,12 +740,64 @@ export function buildCheckTriggerInstruction(
     keys: [
       { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
       { pubkey: priceFeed, isSigner: false, isWritable: false },
+      { pubkey: deriveCrankConfigPDA(), isSigner: false, isWritable: false },
+      { pubkey: deriveMarketRegistryPDA(), isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_CRANK_PROGRAM_ID,
+    data: discriminator,
+  });
+}
+
+export function buildSimulateTriggerInstruction(
+  ghostOrderPda: PublicKey,
+  priceFeed: PublicKey
+): TransactionInstruction {
+  const discriminator = anchorDiscriminator("simulate_trigger");
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: ghostOrderPda, isSigner: false, isWritable: false },
+      { pubkey: priceFeed, isSigner: false, isWritable: false },
     ],
     programId: GHOST_CRANK_PROGRAM_ID,
     data: discriminator,
   });
 }
 
+/**
+ * Cancels a Drift order the ghost order's delegate PDA placed. Callable by the
+ * order owner or a keeper on the order's allowlist; `driftOrderId` is the id
+ * Drift assigned.
+ */
+export function buildCancelDriftOrderInstruction(
+  authority: PublicKey,
+  ghostOrderPda: PublicKey,
+  owner: PublicKey,
+  driftUser: PublicKey,
+  driftOrderId: number
+): TransactionInstruction {
+  const [delegatePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("ghost_delegate"), owner.toBuffer()],
+    GHOST_CRANK_PROGRAM_ID
+  );
+  const data = Buffer.alloc(8 + 4);
+  anchorDiscriminator("cancel_drift_order").copy(data, 0);
+  data.writeUInt32LE(driftOrderId, 8);
+
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: authority, isSigner: true, isWritable: false },
+      { pubkey: ghostOrderPda, isSigner: false, isWritable: false },
+      { pubkey: delegatePda, isSigner: false, isWritable: false },
+      { pubkey: deriveDriftStatePDA(), isSigner: false, isWritable: false },
+      { pubkey: driftUser, isSigner: false, isWritable: true },
+      { pubkey: DRIFT_PROGRAM_ID, isSigner: false, isWritable: false },
+    ],
+    programId: GHOST_CRANK_PROGRAM_ID,
+    data,
+  });
+}
+
 export interface ExecuteTriggerAccounts {
   payer: PublicKey;
   ghostOrderPda: PublicKey;
@@ -494,6 +807,8 @@ export interface ExecuteTriggerAccounts {
   driftAuthority: PublicKey;
   perpMarketPda: PublicKey;
   oraclePda: PublicKey;
+  /** OCO sibling, required when the order is linked */
+  siblingOrderPda?: PublicKey;
 }
 
 export function buildExecuteTriggerInstruction(
@@ -518,6 +833,10 @@ export function buildExecuteTriggerInstruction(
       { pubkey: accounts.oraclePda, isSigner: false, isWritable: false },
       { pubkey: MAGIC_CONTEXT_ID, isSigner: false, isWritable: false },
       { pubkey: MAGIC_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: deriveCrankConfigPDA(), isSigner: false, isWritable: false },
+      ...(accounts.siblingOrderPda
+        ? [{ pubkey: accounts.siblingOrderPda, isSigner: false, isWritable: true }]
+        : []),
     ],
     programId: GHOST_CRANK_PROGRAM_ID,
     data,
@@ -13,11 +13,15 @@ export interface CompressedGhostOrderData {
   expiry: number;
   feedId: string;
   salt?: string;
+  /** Hex secret the executor reveals to prove it decrypted the order */
+  commitmentKey?: string;
 }
 
 export interface EncryptedOrderPayload {
   encryptedData: string;
   orderHash: string;
+  /** Hex key sealed in `encryptedData`; the creator commits to its hash */
+  commitmentKey: string;
   version: number;
 }
 
@@ -41,6 +45,12 @@ export function generateOrderSalt(): string {
   return Buffer.from(salt).toString("hex");
 }
 
+export function generateCommitmentKey(): string {
+  const key = new Uint8Array(32);
+  crypto.getRandomValues(key);
+  return Buffer.from(key).toString("hex");
+}
+
 export async function encryptOrderForTEE(
   orderData: CompressedGhostOrderData,
   teePublicKey: string
@@ -50,6 +60,7 @@ export async function encryptOrderForTEE(
   const orderWithSalt: CompressedGhostOrderData = {
     ...orderData,
     salt: orderData.salt || generateOrderSalt(),
+    commitmentKey: orderData.commitmentKey || generateCommitmentKey(),
   };
 
   const message = new TextEncoder().encode(JSON.stringify(orderWithSalt));
@@ -62,6 +73,7 @@ export async function encryptOrderForTEE(
   return {
     encryptedData: encrypted.toString("base64"),
     orderHash,
+    commitmentKey: orderWithSalt.commitmentKey!,
     version: 1,
   };
 }
@@ -127,6 +139,32 @@ export async function computeOrderHash(
   return Buffer.from(hash).toString("hex");
 }
 
+export async function computeCommitmentKeyHash(
+  commitmentKey: Uint8Array
+): Promise<Uint8Array> {
+  const { blake3 } = await import("@noble/hashes/blake3");
+  return blake3(commitmentKey.slice(0, 32));
+}
+
+/**
+ * blake3(encryptedData || orderHash || blake3(commitmentKey)). Stored at
+ * creation; the executor reveals `commitmentKey`, which only the
+ * ciphertext carries, to prove it decrypted the order.
+ */
+export async function computeCiphertextCommitment(
+  encryptedData: Uint8Array,
+  orderHash: Uint8Array,
+  commitmentKey: Uint8Array
+): Promise<Uint8Array> {
+  const data = new Uint8Array(encryptedData.length + 64);
+  data.set(encryptedData, 0);
+  data.set(orderHash.slice(0, 32), encryptedData.length);
+  data.set(await computeCommitmentKeyHash(commitmentKey), encryptedData.length + 32);
+
+  const { blake3 } = await import("@noble/hashes/blake3");
+  return blake3(data);
+}
+
 export function validateTeePublicKey(publicKey: string): boolean {
   try {
     const buffer = Buffer.from(publicKey, "hex");
@@ -23,11 +23,35 @@ const GHOST_CRANK_PROGRAM_ID = new PublicKey(
   "7VvD7j99AE7q9PC9atpJeMEUeEzZ5ZYH7WqSzGdmvsqv"
 );
 
+// ghost-crank's feed-per-market map, checked before an order triggers
+const [MARKET_REGISTRY] = PublicKey.findProgramAddressSync(
+  [Buffer.from("market_registry")],
+  GHOST_CRANK_PROGRAM_ID
+);
+
+// Protocol fee settings read at execution
+const [BRIDGE_CONFIG] = PublicKey.findProgramAddressSync(
+  [Buffer.from("bridge_config")],
+  GHOST_BRIDGE_PROGRAM_ID
+);
+
+// Per-feed kill switch; executions refuse while the feed is degraded
+function deriveFeedHealthPda(feedId: Uint8Array): PublicKey {
+  const [feedHealth] = PublicKey.findProgramAddressSync(
+    [Buffer.from("feed_health"), Buffer.from(feedId)],
+    GHOST_BRIDGE_PROGRAM_ID
+  );
+  return feedHealth;
+}
+
 const MAGIC_CONTEXT = MAGIC_CONTEXT_ID;
 const MAGIC_PROGRAM = MAGIC_PROGRAM_ID;
 const PYTH_HERMES_URL = "https://hermes.pyth.network/v2/updates/price/latest";
 const DEFAULT_POLL_INTERVAL_MS = 1000;
-const MAX_ENCRYPTED_DATA_LEN = 256;
+// Size of an EncryptedOrder account with an empty ciphertext
+const ENCRYPTED_ORDER_HEADER_LEN = 301;
+// sha256("account:EncryptedOrder")[..8]
+const ENCRYPTED_ORDER_DISCRIMINATOR = Buffer.from([82, 52, 93, 72, 209, 212, 50, 250]);
 
 export interface MonitoredOrder {
   pubkey: PublicKey;
@@ -36,6 +60,11 @@ export interface MonitoredOrder {
   executorAuthority: PublicKey;
   encryptedData: Uint8Array;
   feedId: Uint8Array;
+  /** All zeros unless the order triggers on a two-feed spread. */
+  feedIdB: Uint8Array;
+  /** Ephemeral public key and nonce the ciphertext was sealed with. */
+  ephemeralPubkey: Uint8Array;
+  nonce: Uint8Array;
   createdAt: number;
   status: EncryptedOrderStatus;
   isDelegated: boolean;
@@ -60,6 +89,8 @@ export interface TriggerAndExecuteArgs {
   reduceOnly: boolean;
   expiry: BN;
   redelegateAfter: boolean;
+  /** Secret from the decrypted order proving the executor decrypted it */
+  commitmentKey: Uint8Array;
 }
 
 export interface DriftAccounts {
@@ -122,6 +153,8 @@ export const GHOST_CRANK_ORDER_STATUS = {
   Executed: 4,
   Cancelled: 5,
   Expired: 6,
+  PartiallyFilled: 7,
+  Placed: 8,
 } as const;
 
 interface PythPriceData {
@@ -149,11 +182,14 @@ function parseEncryptedOrderAccount(
   pubkey: PublicKey,
   data: Buffer
 ): MonitoredOrder | null {
-  if (data.length < 421) {
+  if (
+    data.length < ENCRYPTED_ORDER_HEADER_LEN ||
+    !data.subarray(0, 8).equals(ENCRYPTED_ORDER_DISCRIMINATOR)
+  ) {
     return null;
   }
 
-  let offset = 8;
+  let offset = 9; // discriminator + layout version
 
   const owner = new PublicKey(data.subarray(offset, offset + 32));
   offset += 32;
@@ -164,12 +200,15 @@ function parseEncryptedOrderAccount(
   const executorAuthority = new PublicKey(data.subarray(offset, offset + 32));
   offset += 32;
 
+  const encryptedDataLen = data.readUInt32LE(offset);
+  offset += 4;
+
   const encryptedData = new Uint8Array(
-    data.subarray(offset, offset + MAX_ENCRYPTED_DATA_LEN)
+    data.subarray(offset, offset + encryptedDataLen)
   );
-  offset += MAX_ENCRYPTED_DATA_LEN;
+  offset += encryptedDataLen;
 
-  const dataLen = data.readUInt16LE(offset);
+  // data_len mirrors the vec length
   offset += 2;
 
   const feedId = new Uint8Array(data.subarray(offset, offset + 32));
@@ -186,14 +225,32 @@ function parseEncryptedOrderAccount(
   offset += 1;
 
   const isDelegated = data.readUInt8(offset) === 1;
+  offset += 1;
+
+  // bump
+  offset += 1;
+
+  const feedIdB = new Uint8Array(data.subarray(offset, offset + 32));
+  offset += 32;
+
+  // relation, oracle_source, expiry
+  offset += 1 + 1 + 8;
+
+  const ephemeralPubkey = new Uint8Array(data.subarray(offset, offset + 32));
+  offset += 32;
+
+  const nonce = new Uint8Array(data.subarray(offset, offset + 12));
 
   return {
     pubkey,
     owner,
     orderHash,
     executorAuthority,
-    encryptedData: encryptedData.slice(0, dataLen),
+    encryptedData,
     feedId,
+    feedIdB,
+    ephemeralPubkey,
+    nonce,
     createdAt,
     status,
     isDelegated,
@@ -206,7 +263,7 @@ function parseGhostCrankOrder(
 ): GhostCrankOrder | null {
   if (data.length < 330) return null;
 
-  let offset = 8;
+  let offset = 9; // discriminator + layout version
 
   const owner = new PublicKey(data.subarray(offset, offset + 32));
   offset += 32;
@@ -305,7 +362,7 @@ function parseGhostCrankOrder(
 }
 
 function encodeTriggerAndExecuteArgs(args: TriggerAndExecuteArgs): Buffer {
-  const buf = Buffer.alloc(16 + 8 + 2 + 8 + 1 + 1 + 8 + 1 + 8 + 1);
+  const buf = Buffer.alloc(16 + 8 + 2 + 8 + 1 + 1 + 8 + 1 + 8 + 1 + 32);
   let offset = 0;
 
   Buffer.from(args.salt).copy(buf, offset);
@@ -336,6 +393,9 @@ function encodeTriggerAndExecuteArgs(args: TriggerAndExecuteArgs): Buffer {
   offset += 8;
 
   buf.writeUInt8(args.redelegateAfter ? 1 : 0, offset);
+  offset += 1;
+
+  Buffer.from(args.commitmentKey).copy(buf, offset);
 
   return buf;
 }
@@ -543,10 +603,7 @@ export class TeeMonitoringService {
 
     try {
       const accounts = await this.connection.getProgramAccounts(
-        GHOST_BRIDGE_PROGRAM_ID,
-        {
-          filters: [{ dataSize: 420 }],
-        }
+        GHOST_BRIDGE_PROGRAM_ID
       );
 
       let addedCount = 0;
@@ -860,14 +917,22 @@ export class TeeMonitoringService {
         reduceOnly: decrypted.reduceOnly,
         expiry: new BN(decrypted.expiry),
         redelegateAfter: false,
+        commitmentKey: decrypted.commitmentKey
+          ? Buffer.from(decrypted.commitmentKey, "hex")
+          : new Uint8Array(32),
       };
 
       const priceFeed = this.derivePriceFeedPda(order.feedId);
+      const priceFeedB = order.feedIdB.some((b) => b !== 0)
+        ? this.derivePriceFeedPda(order.feedIdB)
+        : null;
 
       const instruction = await this.buildTriggerAndExecuteInstruction(
         order.pubkey,
         order.executorAuthority,
         priceFeed,
+        priceFeedB,
+        deriveFeedHealthPda(order.feedId),
         driftAccounts,
         args
       );
@@ -986,6 +1051,8 @@ export class TeeMonitoringService {
     encryptedOrder: PublicKey,
     executorAuthority: PublicKey,
     priceFeed: PublicKey,
+    priceFeedB: PublicKey | null,
+    feedHealth: PublicKey,
     driftAccounts: DriftAccounts,
     args: TriggerAndExecuteArgs
   ): Promise<TransactionInstruction> {
@@ -1003,6 +1070,13 @@ export class TeeMonitoringService {
         { pubkey: encryptedOrder, isSigner: false, isWritable: true },
         { pubkey: executorAuthority, isSigner: false, isWritable: true },
         { pubkey: priceFeed, isSigner: false, isWritable: false },
+        // Optional secondary feed; the program id marks the slot as unused.
+        {
+          pubkey: priceFeedB ?? GHOST_BRIDGE_PROGRAM_ID,
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: MARKET_REGISTRY, isSigner: false, isWritable: false },
         {
           pubkey: driftAccounts.driftState,
           isSigner: false,
@@ -1027,6 +1101,8 @@ export class TeeMonitoringService {
         { pubkey: driftAccounts.oracle, isSigner: false, isWritable: false },
         { pubkey: MAGIC_CONTEXT, isSigner: false, isWritable: false },
         { pubkey: MAGIC_PROGRAM, isSigner: false, isWritable: false },
+        { pubkey: BRIDGE_CONFIG, isSigner: false, isWritable: false },
+        { pubkey: feedHealth, isSigner: false, isWritable: false },
       ],
       programId: GHOST_BRIDGE_PROGRAM_ID,
       data,
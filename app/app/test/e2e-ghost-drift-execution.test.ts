@@ -39,6 +39,10 @@ const ER_RPC = "https://devnet.magicblock.app";
 const GHOST_CRANK_PROGRAM_ID = new PublicKey("7VvD7j99AE7q9PC9atpJeMEUeEzZ5ZYH7WqSzGdmvsqv");
 const MAGIC_PROGRAM_ID = new PublicKey("Magic11111111111111111111111111111111111111");
 const MAGIC_CONTEXT_ID = new PublicKey("MagicContext1111111111111111111111111111111");
+const [crankConfigPda] = PublicKey.findProgramAddressSync(
+  [Buffer.from("crank_config")],
+  GHOST_CRANK_PROGRAM_ID
+);
 
 interface DriftInitResult {
   wasInitialized: boolean;
@@ -258,7 +262,7 @@ async function fetchGhostOrder(
     if (!accountInfo) return null;
 
     const data = accountInfo.data;
-    let offset = 8;
+    let offset = 9; // discriminator + layout version
 
     const owner = new PublicKey(data.subarray(offset, offset + 32));
     offset += 32;
@@ -466,12 +470,13 @@ describe("E2E: Ghost Order → Drift Execution", () => {
         keys: [
           { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
           { pubkey: oraclePda, isSigner: false, isWritable: false },
+          { pubkey: crankConfigPda, isSigner: false, isWritable: false },
         ],
         programId: GHOST_CRANK_PROGRAM_ID,
         data: discriminator,
       });
 
-      expect(ix.keys.length).toBe(2);
+      expect(ix.keys.length).toBe(3);
     });
 
     it("should build execute_trigger instruction with Magic Action accounts", async () => {
@@ -495,12 +500,13 @@ describe("E2E: Ghost Order → Drift Execution", () => {
           { pubkey: driftPdas.oraclePda!, isSigner: false, isWritable: false },
           { pubkey: MAGIC_CONTEXT_ID, isSigner: false, isWritable: false },
           { pubkey: MAGIC_PROGRAM_ID, isSigner: false, isWritable: false },
+          { pubkey: crankConfigPda, isSigner: false, isWritable: false },
         ],
         programId: GHOST_CRANK_PROGRAM_ID,
         data: discriminator,
       });
 
-      expect(ix.keys.length).toBe(10);
+      expect(ix.keys.length).toBe(11);
       expect(ix.programId.equals(GHOST_CRANK_PROGRAM_ID)).toBe(true);
     });
   });
@@ -644,7 +650,7 @@ describe("E2E: Ghost Order → Drift Execution", () => {
 });
 
 async function parseGhostOrderFromBuffer(data: Buffer): Promise<GhostOrderState> {
-  let offset = 8;
+  let offset = 9; // discriminator + layout version
 
   const owner = new PublicKey(data.subarray(offset, offset + 32));
   offset += 32;
@@ -771,6 +777,7 @@ describe("E2E: Full Flow Simulation (Dry Run)", () => {
       keys: [
         { pubkey: ghostOrderPda, isSigner: false, isWritable: true },
         { pubkey: oraclePda, isSigner: false, isWritable: false },
+        { pubkey: crankConfigPda, isSigner: false, isWritable: false },
       ],
       programId: GHOST_CRANK_PROGRAM_ID,
       data: checkTriggerDisc,
@@ -799,6 +806,7 @@ describe("E2E: Full Flow Simulation (Dry Run)", () => {
         { pubkey: driftPdas.oraclePda!, isSigner: false, isWritable: false },
         { pubkey: MAGIC_CONTEXT_ID, isSigner: false, isWritable: false },
         { pubkey: MAGIC_PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: crankConfigPda, isSigner: false, isWritable: false },
       ],
       programId: GHOST_CRANK_PROGRAM_ID,
       data: executeTriggerDisc,
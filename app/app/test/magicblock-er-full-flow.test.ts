@@ -262,7 +262,6 @@ describe("MagicBlock ER Full Flow Test", () => {
         testWallet.publicKey,
         ghostOrderPda,
         {
-          orderId,
           marketIndex: SOL_PERP_MARKET_INDEX,
           triggerPrice,
           triggerCondition: TRIGGER_CONDITION.Below,
@@ -373,7 +372,6 @@ describe("MagicBlock ER Full Flow Test", () => {
         testWallet.publicKey,
         ghostOrderPda,
         {
-          orderId,
           marketIndex: SOL_PERP_MARKET_INDEX,
           triggerPrice,
           triggerCondition: TRIGGER_CONDITION.Below,
@@ -546,7 +544,6 @@ describe("MagicBlock ER Full Flow Test", () => {
         ctx.testWallet.publicKey,
         ctx.ghostOrderPda,
         {
-          orderId: ctx.orderId,
           marketIndex: SOL_PERP_MARKET_INDEX,
           triggerPrice,
           triggerCondition: TRIGGER_CONDITION.Below,
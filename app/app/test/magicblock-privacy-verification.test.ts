@@ -149,7 +149,6 @@ describe("Privacy Verification: Account Data Visibility While Delegated", () =>
       const baseAmount = new BN(2).mul(BASE_PRECISION);
 
       const createIx = buildCreateGhostOrderInstruction(testWallet.publicKey, ghostOrderPda, {
-        orderId,
         marketIndex: SOL_PERP_MARKET_INDEX,
         triggerPrice,
         triggerCondition: TRIGGER_CONDITION.Above,